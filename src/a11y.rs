@@ -0,0 +1,320 @@
+//! Accessibility utilities.
+//!
+//! [`set_high_contrast`] snaps every color cod emits to black or white, so
+//! that each foreground/background pair has the maximum possible contrast.
+//! Foregrounds are chosen against the background that's active when they're
+//! set, so set the background first. While no background is set, the
+//! terminal's own background could be light or dark, so foregrounds are left
+//! at the terminal's default instead.
+//!
+//! [`set_reduced_motion`] is a hint for anything that animates: spinners,
+//! marquees, tweens, and the like should check [`reduced_motion`] and skip or
+//! simplify their effects. cod's own animations already do:
+//! [`Timeline`](crate::timeline::Timeline)s and
+//! [`Animation`](crate::animation::Animation)s jump straight to their end,
+//! [`ScrollView`](crate::scroll::ScrollView)s start without inertia, and
+//! [`Interpolator`](crate::interpolate::Interpolator)s draw sprites where
+//! they are rather than moving between updates.
+//!
+//! [`set_linear_output`] stops cod from positioning anything on the screen.
+//! Instead, everything drawn is collected until the next [`crate::flush`],
+//! then printed as plain lines of text in reading order (top-to-bottom,
+//! left-to-right). Each cell is only printed once per frame, with later draws
+//! replacing earlier ones, and a frame identical to the last one isn't printed
+//! at all. This makes cod apps usable through screen readers and text logs.
+//! Escape sequences (colors, styles, cursor movement) are dropped entirely.
+//!
+//! [`simulate`] makes every color cod emits pass through a color vision
+//! deficiency filter, so that you can check whether your UI is still legible
+//! for color-blind users. Note that while simulating, the 16 basic colors are
+//! emitted as their nearest 256-color equivalents (using xterm's defaults),
+//! since their actual values depend on the terminal.
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::must_use_candidate
+)]
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Mutex;
+
+static SIMULATION: AtomicU8 = AtomicU8::new(0);
+static HIGH_CONTRAST: AtomicBool = AtomicBool::new(false);
+/// The active background in high-contrast mode: [`NO_BACKGROUND`],
+/// [`DARK_BACKGROUND`], or [`LIGHT_BACKGROUND`].
+static BACKGROUND: AtomicU8 = AtomicU8::new(NO_BACKGROUND);
+const NO_BACKGROUND: u8 = 0;
+const DARK_BACKGROUND: u8 = 1;
+const LIGHT_BACKGROUND: u8 = 2;
+static REDUCED_MOTION: AtomicBool = AtomicBool::new(false);
+static LINEAR_OUTPUT: AtomicBool = AtomicBool::new(false);
+static FRAME: Mutex<Frame> = Mutex::new(Frame {
+    cells: BTreeMap::new(),
+    last: String::new(),
+});
+
+/// The cells drawn in linear output mode since the last flush.
+struct Frame {
+    /// Keyed by `(y, x)`, so that iteration follows reading order.
+    cells: BTreeMap<(u32, u32), char>,
+    last: String,
+}
+
+/// A type of color vision deficiency, used with [`simulate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Deficiency {
+    /// Lack of red cones (red-blindness).
+    Protanopia,
+    /// Lack of green cones (green-blindness).
+    Deuteranopia,
+    /// Lack of blue cones (blue-blindness).
+    Tritanopia,
+}
+
+impl Deficiency {
+    /// Transform an RGB color into how it would appear with this deficiency.
+    ///
+    /// Uses the matrices from Machado, Oliveira, and Fernandes (2009), at full
+    /// severity.
+    pub fn simulate(self, r: u8, g: u8, b: u8) -> (u8, u8, u8) {
+        let matrix = match self {
+            Self::Protanopia => [
+                [0.152_286, 1.052_583, -0.204_868],
+                [0.114_503, 0.786_281, 0.099_216],
+                [-0.003_882, -0.048_116, 1.051_998],
+            ],
+            Self::Deuteranopia => [
+                [0.367_322, 0.860_646, -0.227_968],
+                [0.280_085, 0.672_501, 0.047_413],
+                [-0.011_820, 0.042_940, 0.968_881],
+            ],
+            Self::Tritanopia => [
+                [1.255_528, -0.076_749, -0.178_779],
+                [-0.078_411, 0.930_809, 0.147_602],
+                [0.004_733, 0.691_367, 0.303_900],
+            ],
+        };
+
+        let linear = [to_linear(r), to_linear(g), to_linear(b)];
+        let [r, g, b] = matrix
+            .map(|row| from_linear(row[0] * linear[0] + row[1] * linear[1] + row[2] * linear[2]));
+        (r, g, b)
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::Protanopia => 1,
+            Self::Deuteranopia => 2,
+            Self::Tritanopia => 3,
+        }
+    }
+
+    fn from_u8(x: u8) -> Option<Self> {
+        match x {
+            1 => Some(Self::Protanopia),
+            2 => Some(Self::Deuteranopia),
+            3 => Some(Self::Tritanopia),
+            _ => None,
+        }
+    }
+}
+
+fn to_linear(c: u8) -> f32 {
+    let c = f32::from(c) / 255.0;
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn from_linear(c: f32) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let c = if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+
+    (c * 255.0).round() as u8
+}
+
+/// Simulate a color vision deficiency for all colors emitted from now on, or
+/// stop simulating with `None`.
+///
+/// Colors that are already on the screen aren't affected.
+pub fn simulate(deficiency: Option<Deficiency>) {
+    SIMULATION.store(deficiency.map_or(0, Deficiency::to_u8), Ordering::Relaxed);
+}
+
+/// Returns the color vision deficiency currently being simulated, if any.
+pub fn simulated() -> Option<Deficiency> {
+    Deficiency::from_u8(SIMULATION.load(Ordering::Relaxed))
+}
+
+/// Enable or disable high-contrast mode for all colors emitted from now on.
+///
+/// See the module documentation for details.
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// use cod::color::{Color, ColorDepth};
+/// # color::set_depth(ColorDepth::TrueColor);
+/// # compat::set_enabled(false);
+///
+/// cod::a11y::set_high_contrast(true);
+///
+/// // no background yet, so the terminal's default foreground
+/// let out = target::capture(|| color::fg(Color::Red));
+/// assert_eq!(out, "\x1b[39m");
+///
+/// // black on a light background
+/// let out = target::capture(|| {
+///     color::bg(Color::Rgb(250, 250, 250));
+///     color::fg(Color::Red);
+/// });
+/// assert_eq!(out, "\x1b[48;5;231m\x1b[38;5;16m");
+/// ```
+pub fn set_high_contrast(enabled: bool) {
+    HIGH_CONTRAST.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns true if high-contrast mode is enabled.
+pub fn is_high_contrast() -> bool {
+    HIGH_CONTRAST.load(Ordering::Relaxed)
+}
+
+/// Enable or disable the reduced-motion hint.
+pub fn set_reduced_motion(enabled: bool) {
+    REDUCED_MOTION.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns true if animations should be skipped or simplified.
+pub fn reduced_motion() -> bool {
+    REDUCED_MOTION.load(Ordering::Relaxed)
+}
+
+/// The relative luminance of a color, as defined by WCAG 2.
+fn luminance(r: u8, g: u8, b: u8) -> f32 {
+    0.2126 * to_linear(r) + 0.7152 * to_linear(g) + 0.0722 * to_linear(b)
+}
+
+/// Picks the 256-color black or white to replace a color with in
+/// high-contrast mode. Returns `None` for a foreground while no background
+/// is set, since the terminal's own background is unknown.
+pub(crate) fn contrast_color(background: bool, r: u8, g: u8, b: u8) -> Option<u8> {
+    const BLACK: u8 = 16;
+    const WHITE: u8 = 231;
+
+    let light = if background {
+        // above this, black text has more contrast than white text
+        let light = luminance(r, g, b) > 0.179;
+        let active = if light {
+            LIGHT_BACKGROUND
+        } else {
+            DARK_BACKGROUND
+        };
+        BACKGROUND.store(active, Ordering::Relaxed);
+        light
+    } else {
+        match BACKGROUND.load(Ordering::Relaxed) {
+            NO_BACKGROUND => return None,
+            active => active == DARK_BACKGROUND,
+        }
+    };
+
+    Some(if light { WHITE } else { BLACK })
+}
+
+/// Forgets the active background, e.g. after it's reset to the default.
+pub(crate) fn reset_background() {
+    BACKGROUND.store(NO_BACKGROUND, Ordering::Relaxed);
+}
+
+/// Enable or disable linear output mode.
+///
+/// See the module documentation for details.
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// use cod::canvas::Canvas;
+///
+/// a11y::set_linear_output(true);
+///
+/// let mut canvas = Canvas::new(12, 2);
+/// canvas.text("日本 ok", 0, 0);
+/// canvas.text("status: on", 0, 1);
+/// let frame = target::capture(|| {
+///     canvas.present(0, 0);
+///     cod::flush();
+/// });
+/// assert_eq!(frame, "日本 ok\nstatus: on\n");
+///
+/// // rows that changed are printed whole
+/// canvas.text("off", 8, 1);
+/// let frame = target::capture(|| {
+///     canvas.present(0, 0);
+///     cod::flush();
+/// });
+/// assert_eq!(frame, "status: off\n");
+/// ```
+pub fn set_linear_output(enabled: bool) {
+    LINEAR_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns true if linear output mode is enabled.
+pub fn is_linear_output() -> bool {
+    LINEAR_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Records a character for the current frame in linear output mode.
+pub(crate) fn record(c: char, x: u32, y: u32) {
+    FRAME.lock().unwrap().cells.insert((y, x), c);
+}
+
+/// Prints the current frame in linear output mode, unless it's identical to
+/// the previous one.
+pub(crate) fn present_linear() {
+    let mut frame = FRAME.lock().unwrap();
+    let cells = std::mem::take(&mut frame.cells);
+
+    let mut text = String::new();
+    let mut row = String::new();
+    // the row, and the column just past the last character in it
+    let mut pos = None;
+    for ((y, x), c) in cells {
+        // the right half of a wide character, which is already in the row
+        if c == crate::canvas::WIDE_TAIL {
+            continue;
+        }
+
+        match pos {
+            Some((py, end)) if py == y => {
+                row.extend(std::iter::repeat_n(' ', x.saturating_sub(end) as usize));
+            }
+            Some(_) => {
+                push_row(&mut text, &row);
+                row.clear();
+            }
+            None => {}
+        }
+
+        row.push(c);
+        pos = Some((y, x + crate::width::columns(c)));
+    }
+    push_row(&mut text, &row);
+
+    if text != frame.last {
+        crate::target::write(format_args!("{text}"));
+        frame.last = text;
+    }
+}
+
+fn push_row(text: &mut String, row: &str) {
+    let row = row.trim();
+    if !row.is_empty() {
+        text.push_str(row);
+        text.push('\n');
+    }
+}
@@ -0,0 +1,193 @@
+//! Sprites that change over time, frame by frame.
+//!
+//! An [`Animation`] is a sequence of [`Sprite`]s, each shown for its own
+//! length of time. Advance it with [`Animation::advance`], then draw its
+//! [current frame](Animation::current_frame).
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use std::time::Duration;
+//!
+//! use cod::animation::Animation;
+//! use cod::sprite::Sprite;
+//!
+//! let ms = Duration::from_millis;
+//! let mut walk = Animation::new();
+//! walk.frame(Sprite::new(" o\n/|\\\n/ \\").unwrap(), ms(200))
+//!     .frame(Sprite::new(" o\n/|\\\n | ").unwrap(), ms(100));
+//!
+//! walk.advance(ms(250));
+//! assert_eq!(walk.current_index(), Some(1));
+//!
+//! loop {
+//!     if walk.advance(ms(50)) {
+//!         walk.draw_transparent(' ', 10, 5);
+//!         cod::flush();
+//!     }
+//! #   break;
+//! }
+//! ```
+
+use std::time::Duration;
+
+use crate::canvas::Canvas;
+use crate::sprite::Sprite;
+
+/// A sequence of sprites, each shown for some time. See the
+/// [module docs](self).
+///
+/// Animations loop by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Animation {
+    frames: Vec<(Sprite, Duration)>,
+    elapsed: Duration,
+    looping: bool,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Self {
+            frames: Vec::new(),
+            elapsed: Duration::ZERO,
+            looping: true,
+        }
+    }
+}
+
+impl Animation {
+    /// Create an animation with no frames, at its start.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an animation from frames that are each shown for the same
+    /// time.
+    #[must_use]
+    pub fn uniform<I: IntoIterator<Item = Sprite>>(frames: I, duration: Duration) -> Self {
+        Self {
+            frames: frames.into_iter().map(|frame| (frame, duration)).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Add a frame to the end, shown for the given time.
+    pub fn frame(&mut self, sprite: Sprite, duration: Duration) -> &mut Self {
+        self.frames.push((sprite, duration));
+        self
+    }
+
+    /// Set whether the animation starts over once it's finished. If it
+    /// doesn't, it stays on the last frame.
+    pub fn looping(&mut self, looping: bool) -> &mut Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Returns the number of frames.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns true if there are no frames.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Returns the total time of every frame.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.frames.iter().map(|&(_, duration)| duration).sum()
+    }
+
+    /// Returns how far into the animation it is.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns true if the animation is past its last frame. Looping
+    /// animations never finish.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.elapsed >= self.duration()
+    }
+
+    /// Jump to a point in the animation.
+    pub fn seek(&mut self, time: Duration) {
+        self.elapsed = time;
+        let duration = self.duration();
+        if self.looping && !duration.is_zero() {
+            let wrapped = self.elapsed.as_nanos() % duration.as_nanos();
+            self.elapsed = Duration::from_nanos(u64::try_from(wrapped).unwrap_or(0));
+        }
+    }
+
+    /// Go back to the first frame.
+    pub fn reset(&mut self) {
+        self.elapsed = Duration::ZERO;
+    }
+
+    /// Advance the animation, returning true if the frame changed.
+    ///
+    /// With [reduced motion](crate::a11y::reduced_motion) on, jumps straight
+    /// to the last frame instead, and stays there even if looping.
+    pub fn advance(&mut self, dt: Duration) -> bool {
+        let before = self.current_index();
+        if crate::a11y::reduced_motion() {
+            self.elapsed = self.duration();
+        } else {
+            self.seek(self.elapsed.saturating_add(dt));
+        }
+        self.current_index() != before
+    }
+
+    /// Returns the index of the current frame, or `None` if there are no
+    /// frames.
+    #[must_use]
+    pub fn current_index(&self) -> Option<usize> {
+        let mut end = Duration::ZERO;
+        for (i, &(_, duration)) in self.frames.iter().enumerate() {
+            end += duration;
+            if self.elapsed < end {
+                return Some(i);
+            }
+        }
+
+        self.frames.len().checked_sub(1)
+    }
+
+    /// Returns the current frame, or `None` if there are no frames.
+    #[must_use]
+    pub fn current_frame(&self) -> Option<&Sprite> {
+        self.current_index().map(|i| &self.frames[i].0)
+    }
+
+    /// Draw the current frame onto the screen. See [`Sprite::draw`].
+    pub fn draw(&self, x: u32, y: u32) {
+        profile!();
+        if let Some(frame) = self.current_frame() {
+            frame.draw(x, y);
+        }
+    }
+
+    /// Draw the current frame onto the screen, skipping over spaces. See
+    /// [`Sprite::draw_transparent`].
+    pub fn draw_transparent(&self, blank: char, x: u32, y: u32) {
+        profile!();
+        if let Some(frame) = self.current_frame() {
+            frame.draw_transparent(blank, x, y);
+        }
+    }
+
+    /// Draw the current frame onto a canvas. See [`Sprite::draw_on`].
+    pub fn draw_on(&self, canvas: &mut Canvas, x: u32, y: u32) {
+        if let Some(frame) = self.current_frame() {
+            frame.draw_on(canvas, x, y);
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Large banner text, e.g. for splash screens and scores in games.
+//!
+//! Text is drawn in one of two built-in block fonts, [`Font::Small`] (3x5)
+//! and [`Font::Large`] (5x7), with each dot of the font drawn as a
+//! `scale` by `scale` square of the given character. Lowercase letters are
+//! drawn as uppercase, and characters the font doesn't have are left blank.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use cod::bigtext::{self, Font};
+//!
+//! bigtext::text('█', "GAME OVER", 2, 1, Font::Large, 1);
+//!
+//! let score = 1200.to_string();
+//! let (width, _) = bigtext::size(&score, Font::Small, 2);
+//! bigtext::text('█', &score, 80 - width, 10, Font::Small, 2);
+//! ```
+
+use crate::canvas::Canvas;
+
+/// One of the built-in fonts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Font {
+    /// 3 dots wide and 5 tall.
+    #[default]
+    Small,
+    /// 5 dots wide and 7 tall.
+    Large,
+}
+
+impl Font {
+    /// Returns the width of each character, in dots.
+    #[must_use]
+    pub const fn width(self) -> u32 {
+        match self {
+            Self::Small => 3,
+            Self::Large => 5,
+        }
+    }
+
+    /// Returns the height of each character, in dots.
+    #[must_use]
+    pub const fn height(self) -> u32 {
+        match self {
+            Self::Small => 5,
+            Self::Large => 7,
+        }
+    }
+
+    /// Returns the rows of a character, each a bitmask with the leftmost
+    /// dot in the highest bit, or `None` if the font doesn't have it.
+    fn glyph(self, c: char) -> Option<&'static [u8]> {
+        let c = c.to_ascii_uppercase();
+        match self {
+            Self::Small => find(SMALL, c),
+            Self::Large => find(LARGE, c),
+        }
+    }
+}
+
+/// The 3x5 font: each character, and its rows.
+#[rustfmt::skip]
+const SMALL: &[(char, [u8; 5])] = &[
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b001, 0b001, 0b001]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    ('A', [0b010, 0b101, 0b111, 0b101, 0b101]),
+    ('B', [0b110, 0b101, 0b110, 0b101, 0b110]),
+    ('C', [0b011, 0b100, 0b100, 0b100, 0b011]),
+    ('D', [0b110, 0b101, 0b101, 0b101, 0b110]),
+    ('E', [0b111, 0b100, 0b110, 0b100, 0b111]),
+    ('F', [0b111, 0b100, 0b110, 0b100, 0b100]),
+    ('G', [0b011, 0b100, 0b101, 0b101, 0b011]),
+    ('H', [0b101, 0b101, 0b111, 0b101, 0b101]),
+    ('I', [0b111, 0b010, 0b010, 0b010, 0b111]),
+    ('J', [0b001, 0b001, 0b001, 0b101, 0b010]),
+    ('K', [0b101, 0b101, 0b110, 0b101, 0b101]),
+    ('L', [0b100, 0b100, 0b100, 0b100, 0b111]),
+    ('M', [0b101, 0b111, 0b111, 0b101, 0b101]),
+    ('N', [0b110, 0b101, 0b101, 0b101, 0b101]),
+    ('O', [0b010, 0b101, 0b101, 0b101, 0b010]),
+    ('P', [0b110, 0b101, 0b110, 0b100, 0b100]),
+    ('Q', [0b010, 0b101, 0b101, 0b110, 0b011]),
+    ('R', [0b110, 0b101, 0b110, 0b101, 0b101]),
+    ('S', [0b011, 0b100, 0b010, 0b001, 0b110]),
+    ('T', [0b111, 0b010, 0b010, 0b010, 0b010]),
+    ('U', [0b101, 0b101, 0b101, 0b101, 0b111]),
+    ('V', [0b101, 0b101, 0b101, 0b101, 0b010]),
+    ('W', [0b101, 0b101, 0b111, 0b111, 0b101]),
+    ('X', [0b101, 0b101, 0b010, 0b101, 0b101]),
+    ('Y', [0b101, 0b101, 0b010, 0b010, 0b010]),
+    ('Z', [0b111, 0b001, 0b010, 0b100, 0b111]),
+    (' ', [0b000, 0b000, 0b000, 0b000, 0b000]),
+    ('.', [0b000, 0b000, 0b000, 0b000, 0b010]),
+    (',', [0b000, 0b000, 0b000, 0b010, 0b100]),
+    ('!', [0b010, 0b010, 0b010, 0b000, 0b010]),
+    ('?', [0b111, 0b001, 0b010, 0b000, 0b010]),
+    (':', [0b000, 0b010, 0b000, 0b010, 0b000]),
+    ('-', [0b000, 0b000, 0b111, 0b000, 0b000]),
+    ('+', [0b000, 0b010, 0b111, 0b010, 0b000]),
+    ('=', [0b000, 0b111, 0b000, 0b111, 0b000]),
+    ('_', [0b000, 0b000, 0b000, 0b000, 0b111]),
+    ('/', [0b001, 0b001, 0b010, 0b100, 0b100]),
+    ('%', [0b101, 0b001, 0b010, 0b100, 0b101]),
+    ('#', [0b101, 0b111, 0b101, 0b111, 0b101]),
+    ('\'', [0b010, 0b010, 0b000, 0b000, 0b000]),
+    ('"', [0b101, 0b101, 0b000, 0b000, 0b000]),
+    ('(', [0b001, 0b010, 0b010, 0b010, 0b001]),
+    (')', [0b100, 0b010, 0b010, 0b010, 0b100]),
+    ('<', [0b001, 0b010, 0b100, 0b010, 0b001]),
+    ('>', [0b100, 0b010, 0b001, 0b010, 0b100]),
+];
+
+/// The 5x7 font: each character, and its rows.
+#[rustfmt::skip]
+const LARGE: &[(char, [u8; 7])] = &[
+    ('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+    ('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+    ('3', [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110]),
+    ('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+    ('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+    ('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+    ('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+    ('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+    ('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+    ('A', [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('B', [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110]),
+    ('C', [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110]),
+    ('D', [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100]),
+    ('E', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111]),
+    ('F', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('G', [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111]),
+    ('H', [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('I', [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('J', [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100]),
+    ('K', [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001]),
+    ('L', [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111]),
+    ('M', [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001]),
+    ('N', [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001]),
+    ('O', [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('P', [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('Q', [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101]),
+    ('R', [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001]),
+    ('S', [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110]),
+    ('T', [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('U', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('V', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100]),
+    ('W', [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010]),
+    ('X', [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001]),
+    ('Y', [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100]),
+    ('Z', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111]),
+    (' ', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('.', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100]),
+    (',', [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000]),
+    ('!', [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100]),
+    ('?', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100]),
+    (':', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000]),
+    ('-', [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000]),
+    ('+', [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000]),
+    ('=', [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000]),
+    ('_', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111]),
+    ('/', [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000]),
+    ('%', [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011]),
+    ('#', [0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010]),
+    ('\'', [0b01100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('"', [0b01010, 0b01010, 0b01010, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('(', [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010]),
+    (')', [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000]),
+    ('<', [0b00010, 0b00100, 0b01000, 0b10000, 0b01000, 0b00100, 0b00010]),
+    ('>', [0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000]),
+];
+
+/// Returns the rows of a character in a font's table.
+fn find<const N: usize>(table: &'static [(char, [u8; N])], c: char) -> Option<&'static [u8]> {
+    table
+        .iter()
+        .find(|&&(glyph, _)| glyph == c)
+        .map(|(_, rows)| rows.as_slice())
+}
+
+/// Returns the width and height text takes up in a font, at a scale.
+/// Characters are a dot apart, and so are lines.
+///
+/// ```
+/// # use cod::bigtext::{self, Font};
+/// assert_eq!(bigtext::size("42", Font::Small, 1), (7, 5));
+/// assert_eq!(bigtext::size("42\nOK", Font::Large, 2), (22, 30));
+/// ```
+#[must_use]
+pub fn size(s: &str, font: Font, scale: u32) -> (u32, u32) {
+    let (mut lines, mut widest) = (0, 0);
+    for line in s.split('\n') {
+        let chars = u32::try_from(line.chars().count()).unwrap_or(u32::MAX);
+        widest = widest.max(chars.saturating_mul(font.width() + 1).saturating_sub(1));
+        lines += 1;
+    }
+
+    (widest * scale, (lines * (font.height() + 1) - 1) * scale)
+}
+
+/// Returns every filled row of text in a font, at a scale: each as its row,
+/// and the columns where runs of dots start and end (exclusive), relative
+/// to the top-left corner.
+fn runs(s: &str, font: Font, scale: u32) -> Vec<(u32, Vec<(u32, u32)>)> {
+    let (w, h) = (font.width(), font.height());
+    let mut rows = Vec::new();
+    for (line, text) in (0..).zip(s.split('\n')) {
+        for dy in 0..h {
+            let mut runs: Vec<(u32, u32)> = Vec::new();
+            for (i, c) in (0..).zip(text.chars()) {
+                let Some(glyph) = font.glyph(c) else {
+                    continue;
+                };
+
+                let bits = glyph[dy as usize];
+                for dx in 0..w {
+                    if bits & (1 << (w - 1 - dx)) == 0 {
+                        continue;
+                    }
+
+                    let start = (i * (w + 1) + dx) * scale;
+                    match runs.last_mut() {
+                        Some((_, end)) if *end == start => *end += scale,
+                        _ => runs.push((start, start + scale)),
+                    }
+                }
+            }
+
+            if runs.is_empty() {
+                continue;
+            }
+            let top = (line * (h + 1) + dy) * scale;
+            for row in top..top + scale {
+                rows.push((row, runs.clone()));
+            }
+        }
+    }
+
+    rows
+}
+
+/// Draw text onto the screen in a font, with its top-left corner at
+/// (x, y), and each dot drawn as a `scale` by `scale` square of `c`.
+/// Respects linebreaks. Only the dots are drawn; the space between them is
+/// left as it is.
+pub fn text(c: char, s: &str, x: u32, y: u32, font: Font, scale: u32) {
+    profile!();
+    for (row, runs) in runs(s, font, scale) {
+        for (start, end) in runs {
+            let run: String = std::iter::repeat_n(c, (end - start) as usize).collect();
+            crate::run(&run, x + start, y + row);
+        }
+    }
+}
+
+/// Draw text onto a canvas in a font. See [`text`].
+pub fn text_on(canvas: &mut Canvas, c: char, s: &str, x: u32, y: u32, font: Font, scale: u32) {
+    for (row, runs) in runs(s, font, scale) {
+        for (start, end) in runs {
+            for col in start..end {
+                canvas.pixel(c, x + col, y + row);
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Drawing with braille dots, at several times the resolution of cells.
+//!
+//! Each braille character (`⠀` to `⣿`, U+2800 to U+28FF) is a grid of dots
+//! two wide and four high, any of which can be raised. A braille [`Canvas`]
+//! is a grid of these dots: set and clear them one by one, or draw lines
+//! through them, then draw the whole thing, which turns each cell's dots
+//! into the right character. It's well suited to plots and smooth curves.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use cod::braille::Canvas;
+//!
+//! // a sine wave, 80 dots wide and 20 high
+//! let mut plot = Canvas::new(40, 5);
+//! for x in 0..plot.dot_width() {
+//!     let y = 9.5 - 9.5 * (f64::from(x) / 8.0).sin();
+//!     plot.set(x, y.round() as u32);
+//! }
+//!
+//! plot.line(0, 19, 79, 19);
+//! plot.draw(0, 0);
+//! cod::flush();
+//! ```
+
+use crate::line;
+
+/// The dots of a cell, indexed by row then column, as bits of a braille
+/// character.
+const DOTS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// A grid of braille dots, two wide and four high per cell. See the
+/// [module docs](self).
+///
+/// Dots outside of the canvas are ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    /// The raised dots of each cell, as bits.
+    cells: Vec<u8>,
+}
+
+impl Canvas {
+    /// Create a canvas `width` cells wide and `height` cells high, with no
+    /// dots set.
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![0; width as usize * height as usize],
+        }
+    }
+
+    /// The width of the canvas, in cells.
+    #[must_use]
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the canvas, in cells.
+    #[must_use]
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The width of the canvas, in dots.
+    #[must_use]
+    pub const fn dot_width(&self) -> u32 {
+        self.width * 2
+    }
+
+    /// The height of the canvas, in dots.
+    #[must_use]
+    pub const fn dot_height(&self) -> u32 {
+        self.height * 4
+    }
+
+    /// Returns the index of the cell a dot is in, and its bit.
+    fn dot(&self, x: u32, y: u32) -> Option<(usize, u8)> {
+        if x >= self.dot_width() || y >= self.dot_height() {
+            return None;
+        }
+
+        let cell = (y / 4) as usize * self.width as usize + (x / 2) as usize;
+        Some((cell, DOTS[(y % 4) as usize][(x % 2) as usize]))
+    }
+
+    /// Raise a dot.
+    pub fn set(&mut self, x: u32, y: u32) {
+        if let Some((cell, bit)) = self.dot(x, y) {
+            self.cells[cell] |= bit;
+        }
+    }
+
+    /// Lower a dot.
+    pub fn unset(&mut self, x: u32, y: u32) {
+        if let Some((cell, bit)) = self.dot(x, y) {
+            self.cells[cell] &= !bit;
+        }
+    }
+
+    /// Raise a dot if it's lowered, or lower it if it's raised.
+    pub fn toggle(&mut self, x: u32, y: u32) {
+        if let Some((cell, bit)) = self.dot(x, y) {
+            self.cells[cell] ^= bit;
+        }
+    }
+
+    /// Returns true if a dot is raised.
+    #[must_use]
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        self.dot(x, y)
+            .is_some_and(|(cell, bit)| self.cells[cell] & bit != 0)
+    }
+
+    /// Lower every dot.
+    pub fn clear(&mut self) {
+        self.cells.fill(0);
+    }
+
+    /// Raise every dot on a line between two dots.
+    pub fn line(&mut self, x1: u32, y1: u32, x2: u32, y2: u32) {
+        for (x, y) in line::Iter::new(x1, y1, x2, y2) {
+            self.set(x, y);
+        }
+    }
+
+    /// Returns the braille character for a cell, or `None` if it's outside
+    /// of the canvas.
+    ///
+    /// ```rust
+    /// # use cod::braille::Canvas;
+    /// let mut canvas = Canvas::new(1, 1);
+    /// canvas.line(0, 0, 1, 3);
+    /// assert_eq!(canvas.char_at(0, 0), Some('⢣'));
+    /// ```
+    #[must_use]
+    pub fn char_at(&self, x: u32, y: u32) -> Option<char> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let dots = self.cells[y as usize * self.width as usize + x as usize];
+        char::from_u32(0x2800 + u32::from(dots))
+    }
+
+    /// Returns each row of the canvas as text, with cells without any dots
+    /// raised as spaces.
+    fn rows(&self) -> impl Iterator<Item = String> + '_ {
+        (0..self.height).map(|y| {
+            (0..self.width)
+                .map(|x| match self.char_at(x, y) {
+                    Some('\u{2800}') | None => ' ',
+                    Some(c) => c,
+                })
+                .collect()
+        })
+    }
+
+    /// Draw the canvas onto the screen, with its top-left corner at the
+    /// given position. Cells with no dots raised are drawn as spaces.
+    pub fn draw(&self, x: u32, y: u32) {
+        profile!();
+        for (y, row) in (y..).zip(self.rows()) {
+            crate::run(&row, x, y);
+        }
+    }
+
+    /// Draw the canvas onto a (regular) canvas. See [`Canvas::draw`].
+    pub fn draw_on(&self, canvas: &mut crate::canvas::Canvas, x: u32, y: u32) {
+        for (y, row) in (y..).zip(self.rows()) {
+            canvas.text(row, x, y);
+        }
+    }
+}
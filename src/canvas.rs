@@ -0,0 +1,1349 @@
+//! An off-screen buffer of styled characters, drawn to the screen all at
+//! once.
+//!
+//! Drawing into a [`Canvas`] doesn't touch the terminal at all; nothing is
+//! printed until you call [`Canvas::draw`] or [`Canvas::present`]. Anything
+//! drawn outside of the canvas' bounds is silently discarded.
+//!
+//! Each cell holds a character and a [`Style`]. Everything drawn onto the
+//! canvas uses its current style, set with [`Canvas::set_style`], much like
+//! the colors and styles set for drawing straight onto the screen.
+//!
+//! For animation, draw each frame onto the same canvas and call
+//! [`Canvas::present`]: only the cells that changed since the last frame are
+//! printed, which is much faster and doesn't flicker.
+//!
+//! Regions of a canvas can also be made into hyperlinks with
+//! [`Canvas::link`], which terminals that support OSC 8 let you click.
+//!
+//! With [journaling](Canvas::set_journaling) on, a canvas remembers every
+//! change to its cells, so that they can be [undone](Canvas::undo) and
+//! [redone](Canvas::redo).
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! # use cod::canvas::Canvas;
+//! # use cod::color::Color;
+//! # use cod::rect::Rect;
+//! # use cod::style::Style;
+//! let mut canvas = Canvas::new(30, 2);
+//! canvas.text("cod on GitHub", 0, 0);
+//! canvas.link(Rect::new(0, 0, 13, 1), "https://github.com/Kyllingene/cod");
+//! assert_eq!(canvas.link_at(4, 0), Some("https://github.com/Kyllingene/cod"));
+//!
+//! for frame in 0..3 {
+//!     canvas.set_style(Style {
+//!         fg: Some(Color::Ansi(1)),
+//!         ..Style::default()
+//!     });
+//!     canvas.text(frame.to_string(), 0, 1);
+//!
+//!     // only the changed digit is printed after the first frame
+//!     canvas.present(0, 0);
+//!     cod::flush();
+//! }
+//! ```
+
+use crate::color::Color;
+use crate::line;
+use crate::rect::{Align, Rect};
+use crate::style::{Style, Tracker};
+
+/// What the cell to the right of a wide character (see [`crate::width`])
+/// holds, since the wide character covers it.
+pub const WIDE_TAIL: char = '\0';
+
+/// A single cell of a [`Canvas`]: a character, and the style it's drawn in.
+///
+/// A wide character takes up its cell and the one to its right, which
+/// holds [`WIDE_TAIL`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cell {
+    /// The character in the cell.
+    pub c: char,
+    /// The style the character is drawn in.
+    pub style: Style,
+}
+
+impl Cell {
+    /// Create a cell.
+    #[must_use]
+    pub const fn new(c: char, style: Style) -> Self {
+        Self { c, style }
+    }
+}
+
+impl Default for Cell {
+    /// A space, in the default style.
+    fn default() -> Self {
+        Self::new(' ', Style::default())
+    }
+}
+
+/// A rectangular piece of a [`Canvas`], captured by [`Canvas::snapshot`].
+///
+/// Remembers where it was captured from, so that it can be put back with
+/// [`Canvas::restore`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Texture {
+    origin: (u32, u32),
+    width: u32,
+    height: u32,
+    cells: Vec<Cell>,
+}
+
+impl Texture {
+    /// The width of the texture, in columns.
+    #[must_use]
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the texture, in rows.
+    #[must_use]
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The position on the canvas the texture was captured from.
+    #[must_use]
+    pub const fn origin(&self) -> (u32, u32) {
+        self.origin
+    }
+}
+
+/// A cell changed while journaling: its index, and what it was before and
+/// after.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Change {
+    index: usize,
+    before: Cell,
+    after: Cell,
+}
+
+/// The changes recorded while journaling, grouped into steps that are
+/// undone and redone together.
+#[derive(Debug, Clone, Default)]
+struct Journal {
+    undo: Vec<Vec<Change>>,
+    redo: Vec<Vec<Change>>,
+    /// Changes since the last step ended.
+    pending: Vec<Change>,
+    /// How many transactions are open.
+    depth: usize,
+}
+
+impl Journal {
+    /// End the current step, if anything changed.
+    fn seal(&mut self) {
+        if !self.pending.is_empty() {
+            self.undo.push(std::mem::take(&mut self.pending));
+        }
+    }
+}
+
+/// What was last printed by [`Canvas::present`].
+#[derive(Debug, Clone)]
+struct Shown {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    cells: Vec<Cell>,
+    links: Vec<Option<String>>,
+}
+
+/// A grid of styled characters, held in memory rather than drawn
+/// immediately.
+///
+/// Two canvases are equal if they hold the same cells and links, regardless
+/// of their current style or what they last presented.
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    cells: Vec<Cell>,
+    style: Style,
+
+    /// For each cell, 0 if it isn't linked, otherwise the index into `urls`
+    /// plus one.
+    links: Vec<usize>,
+    urls: Vec<String>,
+    /// For each url, how many cells are linked to it.
+    uses: Vec<usize>,
+
+    shown: Option<Shown>,
+    journal: Option<Journal>,
+    parking: bool,
+}
+
+impl PartialEq for Canvas {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.cells == other.cells
+            && (0..self.links.len()).all(|i| self.url(i) == other.url(i))
+    }
+}
+
+impl Eq for Canvas {}
+
+impl std::hash::Hash for Canvas {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.width.hash(state);
+        self.height.hash(state);
+        self.cells.hash(state);
+        for i in 0..self.links.len() {
+            self.url(i).hash(state);
+        }
+    }
+}
+
+impl Canvas {
+    /// Create a new canvas filled with spaces.
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+            style: Style::default(),
+            links: vec![0; width as usize * height as usize],
+            urls: Vec::new(),
+            uses: Vec::new(),
+            shown: None,
+            journal: None,
+            parking: false,
+        }
+    }
+
+    /// The width of the canvas, in columns.
+    #[must_use]
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the canvas, in rows.
+    #[must_use]
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The area covered by the canvas, with its top-left corner at (0, 0).
+    #[must_use]
+    pub const fn bounds(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    /// Change the cell at an index, recording the change if journaling.
+    fn set(&mut self, index: usize, cell: Cell) {
+        let before = self.cells[index];
+        if before == cell {
+            return;
+        }
+
+        self.cells[index] = cell;
+        if let Some(journal) = &mut self.journal {
+            journal.redo.clear();
+            journal.pending.push(Change {
+                index,
+                before,
+                after: cell,
+            });
+        }
+    }
+
+    /// Returns the hyperlink target of the cell at an index, if any.
+    fn url(&self, i: usize) -> Option<&str> {
+        self.links[i].checked_sub(1).map(|i| self.urls[i].as_str())
+    }
+
+    /// Set the style everything is drawn in from now on.
+    pub fn set_style(&mut self, style: Style) {
+        self.style = style;
+    }
+
+    /// Returns the style everything is currently drawn in.
+    #[must_use]
+    pub const fn style(&self) -> Style {
+        self.style
+    }
+
+    /// Fill the entire canvas with spaces in the default style, and remove
+    /// all links.
+    pub fn clear(&mut self) {
+        self.fill_cells(Cell::default(), self.bounds());
+        self.links.fill(0);
+        self.urls.clear();
+        self.uses.clear();
+    }
+
+    /// Fill a region of the canvas with spaces in the default style,
+    /// regardless of the current style. Links are kept.
+    ///
+    /// Wide characters cut in half by the region's edges are replaced by
+    /// spaces, as with [`Canvas::fill`].
+    ///
+    /// ```
+    /// # use cod::canvas::Canvas;
+    /// # use cod::rect::Rect;
+    /// let mut canvas = Canvas::new(6, 1);
+    /// canvas.text("日本語", 0, 0);
+    /// canvas.erase(Rect::new(1, 0, 4, 1));
+    /// assert!(canvas.row(0).unwrap().iter().all(|cell| cell.c == ' '));
+    /// ```
+    pub fn erase(&mut self, rect: Rect) {
+        self.fill_cells(Cell::default(), rect);
+    }
+
+    /// Draw a single character onto the canvas.
+    ///
+    /// A wide character also covers the cell to its right, or is drawn as a
+    /// space if it's at the right edge.
+    pub fn pixel(&mut self, c: char, x: u32, y: u32) {
+        if !self.bounds().contains(x, y) {
+            return;
+        }
+
+        let wide = crate::width::columns(c) == 2;
+        let c = if wide && x + 1 == self.width { ' ' } else { c };
+
+        self.split_wide(x, y);
+        let i = self.index(x, y);
+        self.set(i, Cell::new(c, self.style));
+        if wide && c != ' ' {
+            self.split_wide(x + 1, y);
+            self.set(i + 1, Cell::new(WIDE_TAIL, self.style));
+        }
+    }
+
+    /// Blank out the other half of the wide character a cell is part of,
+    /// if any, before the cell is drawn over.
+    fn split_wide(&mut self, x: u32, y: u32) {
+        let i = self.index(x, y);
+        let other = if self.cells[i].c == WIDE_TAIL {
+            (x > 0).then(|| i - 1)
+        } else if crate::width::columns(self.cells[i].c) == 2 && x + 1 < self.width {
+            Some(i + 1)
+        } else {
+            None
+        };
+
+        if let Some(other) = other {
+            let style = self.cells[other].style;
+            self.set(other, Cell::new(' ', style));
+        }
+    }
+
+    /// Copy cells onto a row of the canvas, starting at a position they fit
+    /// after. Wide characters cut in half by either end, on the canvas or
+    /// among the cells, are replaced by spaces.
+    fn put_row(&mut self, x: u32, y: u32, cells: &[Cell]) {
+        let Some(last) = cells.len().checked_sub(1) else {
+            return;
+        };
+
+        self.split_wide(x, y);
+        self.split_wide(x + u32::try_from(last).unwrap_or(u32::MAX), y);
+
+        let start = self.index(x, y);
+        for (k, &cell) in cells.iter().enumerate() {
+            let cut = (k == 0 && cell.c == WIDE_TAIL)
+                || (k == last && crate::width::columns(cell.c) == 2);
+            let c = if cut { ' ' } else { cell.c };
+            self.set(start + k, Cell::new(c, cell.style));
+        }
+    }
+
+    /// Draw text onto the canvas (non-wrapping, but respects linebreaks).
+    ///
+    /// A cell only holds one character, so each grapheme cluster (see
+    /// [`crate::width::graphemes`]) is drawn as its first character that
+    /// takes up any columns; combining marks and the like are left out.
+    pub fn text<S: AsRef<str>>(&mut self, s: S, x: u32, mut y: u32) {
+        for line in s.as_ref().split('\n') {
+            let mut nx = x;
+            for g in crate::width::graphemes(line) {
+                if let Some(c) = g.chars().find(|&c| crate::width::columns(c) > 0) {
+                    self.pixel(c, nx, y);
+                    nx = nx.saturating_add(crate::width::columns(c));
+                }
+            }
+            y += 1;
+        }
+    }
+
+    /// Draw text onto the canvas, aligned within a rectangle. See
+    /// [`crate::text_aligned`].
+    ///
+    /// ```
+    /// # use cod::canvas::Canvas;
+    /// # use cod::rect::{Align, Rect};
+    /// let mut canvas = Canvas::new(10, 3);
+    /// canvas.text_aligned("hi", Rect::new(0, 1, 10, 1), Align::Right);
+    /// assert_eq!(canvas.get(9, 1).unwrap().c, 'i');
+    ///
+    /// // entirely off the canvas
+    /// canvas.text_aligned("hi", Rect::new(u32::MAX - 1, u32::MAX - 1, 10, 10), Align::Center);
+    /// ```
+    pub fn text_aligned<S: AsRef<str>>(&mut self, s: S, rect: Rect, align: Align) {
+        let mut lines = s.as_ref().split('\n').peekable();
+        for y in rect.y..rect.y.saturating_add(rect.height).min(self.height) {
+            let Some(line) = lines.next() else {
+                break;
+            };
+
+            let last = lines.peek().is_none_or(|next| next.trim().is_empty());
+            let (text, offset) = crate::rect::align_line(line, rect.width, align, last);
+            self.text(text, rect.x.saturating_add(offset), y);
+        }
+    }
+
+    /// Draw a line onto the canvas, between two (inclusive) points.
+    pub fn line(&mut self, c: char, x1: u32, y1: u32, x2: u32, y2: u32) {
+        for (x, y) in line::Iter::new(x1, y1, x2, y2) {
+            self.pixel(c, x, y);
+        }
+    }
+
+    /// Draw the outline of a rectangle onto the canvas.
+    pub fn rect(&mut self, c: char, rect: Rect) {
+        if rect.is_empty() {
+            return;
+        }
+
+        let (right, bottom) = (rect.x + rect.width - 1, rect.y + rect.height - 1);
+        self.line(c, rect.x, rect.y, right, rect.y);
+        self.line(c, rect.x, bottom, right, bottom);
+        self.line(c, rect.x, rect.y, rect.x, bottom);
+        self.line(c, right, rect.y, right, bottom);
+    }
+
+    /// Draw the outline of a triangle onto the canvas.
+    #[allow(clippy::too_many_arguments)]
+    pub fn triangle(&mut self, c: char, x1: u32, y1: u32, x2: u32, y2: u32, x3: u32, y3: u32) {
+        self.line(c, x1, y1, x2, y2);
+        self.line(c, x2, y2, x3, y3);
+        self.line(c, x1, y1, x3, y3);
+    }
+
+    /// Fill a region of the canvas with a single character. Wide characters
+    /// cut in half by the region's edges are replaced by spaces.
+    pub fn fill(&mut self, c: char, rect: Rect) {
+        self.fill_cells(Cell::new(c, self.style), rect);
+    }
+
+    fn fill_cells(&mut self, cell: Cell, rect: Rect) {
+        let rect = rect.intersect(self.bounds());
+        if rect.is_empty() {
+            return;
+        }
+
+        for y in rect.y..rect.y + rect.height {
+            self.split_wide(rect.x, y);
+            self.split_wide(rect.x + rect.width - 1, y);
+
+            let start = self.index(rect.x, y);
+            for i in start..start + rect.width as usize {
+                self.set(i, cell);
+            }
+        }
+    }
+
+    /// Make a region of the canvas into a hyperlink to `url`, replacing any
+    /// link it had before. Whatever is drawn there stays linked until the
+    /// region is [unlinked](Canvas::unlink).
+    ///
+    /// Only the urls some cell still links to are kept, so relinking every
+    /// frame doesn't pile them up.
+    ///
+    /// ```
+    /// # use cod::canvas::Canvas;
+    /// # use cod::rect::Rect;
+    /// let mut canvas = Canvas::new(20, 2);
+    /// canvas.link(Rect::new(0, 0, 20, 1), "https://example.com");
+    /// for frame in 0..100 {
+    ///     canvas.link(Rect::new(0, 1, 20, 1), format!("https://example.com/{frame}"));
+    /// }
+    ///
+    /// assert_eq!(canvas.urls().count(), 2);
+    /// assert_eq!(canvas.link_at(0, 1), Some("https://example.com/99"));
+    /// ```
+    pub fn link<S: AsRef<str>>(&mut self, rect: Rect, url: S) {
+        let link = self.url_link(url.as_ref());
+        self.set_link(rect, link);
+    }
+
+    /// Returns every url some cell on the canvas is linked to, each once.
+    pub fn urls(&self) -> impl Iterator<Item = &str> {
+        self.urls.iter().map(String::as_str)
+    }
+
+    /// Returns what to put in `links` to link a cell to `url`, adding it to
+    /// `urls` if it isn't there yet. A url no cell ends up linked to is
+    /// forgotten by the next [`Canvas::prune_urls`].
+    fn url_link(&mut self, url: &str) -> usize {
+        if let Some(i) = self.urls.iter().position(|u| u == url) {
+            i + 1
+        } else {
+            self.urls.push(url.to_string());
+            self.uses.push(0);
+            self.urls.len()
+        }
+    }
+
+    /// Link the cell at an index, keeping count of each url's uses.
+    fn relink(&mut self, index: usize, link: usize) {
+        let before = std::mem::replace(&mut self.links[index], link);
+        if let Some(i) = before.checked_sub(1) {
+            self.uses[i] -= 1;
+        }
+        if let Some(i) = link.checked_sub(1) {
+            self.uses[i] += 1;
+        }
+    }
+
+    /// Forget the urls no cell is linked to any more, renumbering the links
+    /// to the rest.
+    fn prune_urls(&mut self) {
+        if !self.uses.contains(&0) {
+            return;
+        }
+
+        let mut remap = vec![0; self.urls.len()];
+        let mut kept = 0;
+        for (new, &uses) in remap.iter_mut().zip(&self.uses) {
+            if uses > 0 {
+                kept += 1;
+                *new = kept;
+            }
+        }
+
+        let mut uses = self.uses.iter();
+        self.urls.retain(|_| uses.next().is_some_and(|&n| n > 0));
+        self.uses.retain(|&n| n > 0);
+        for link in &mut self.links {
+            if let Some(i) = link.checked_sub(1) {
+                *link = remap[i];
+            }
+        }
+    }
+
+    /// Remove any hyperlinks from a region of the canvas.
+    pub fn unlink(&mut self, rect: Rect) {
+        self.set_link(rect, 0);
+    }
+
+    fn set_link(&mut self, rect: Rect, link: usize) {
+        let rect = rect.intersect(self.bounds());
+        for y in rect.y..rect.y + rect.height {
+            let start = self.index(rect.x, y);
+            for i in start..start + rect.width as usize {
+                self.relink(i, link);
+            }
+        }
+
+        self.prune_urls();
+    }
+
+    /// Returns the cell at a position, or `None` if it's off the canvas.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// # use cod::canvas::Canvas;
+    /// # use cod::rect::Rect;
+    /// let mut canvas = Canvas::new(10, 5);
+    /// canvas.rect('#', Rect::new(0, 0, 10, 5));
+    ///
+    /// // can't walk through walls
+    /// let blocked = |x, y| canvas.get(x, y).is_none_or(|cell| cell.c == '#');
+    /// assert!(blocked(0, 2));
+    /// assert!(!blocked(4, 2));
+    /// assert!(blocked(10, 2));
+    /// ```
+    #[must_use]
+    pub fn get(&self, x: u32, y: u32) -> Option<Cell> {
+        self.bounds()
+            .contains(x, y)
+            .then(|| self.cells[self.index(x, y)])
+    }
+
+    /// Returns a row of cells, or `None` if it's off the canvas.
+    #[must_use]
+    pub fn row(&self, y: u32) -> Option<&[Cell]> {
+        let start = self.index(0, y);
+        (y < self.height).then(|| &self.cells[start..start + self.width as usize])
+    }
+
+    /// Returns an iterator over the rows of cells, from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.cells.chunks(self.width.max(1) as usize)
+    }
+
+    /// Returns an iterator over the cells in a region, with their
+    /// positions, row by row. The region is clipped to the canvas' bounds.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// # use cod::canvas::Canvas;
+    /// # use cod::rect::Rect;
+    /// let mut canvas = Canvas::new(10, 5);
+    /// canvas.text("hi", 3, 1);
+    ///
+    /// let text: Vec<_> = canvas
+    ///     .region(Rect::new(0, 1, 10, 1))
+    ///     .filter(|&(_, _, cell)| cell.c != ' ')
+    ///     .collect();
+    /// assert_eq!(text.len(), 2);
+    /// assert_eq!((text[0].0, text[0].2.c), (3, 'h'));
+    /// ```
+    pub fn region(&self, rect: Rect) -> impl Iterator<Item = (u32, u32, Cell)> + '_ {
+        let rect = rect.intersect(self.bounds());
+        (rect.y..rect.y + rect.height).flat_map(move |y| {
+            (rect.x..rect.x + rect.width).map(move |x| (x, y, self.cells[self.index(x, y)]))
+        })
+    }
+
+    /// Returns the hyperlink target of a cell, if it has one.
+    #[must_use]
+    pub fn link_at(&self, x: u32, y: u32) -> Option<&str> {
+        if !self.bounds().contains(x, y) {
+            return None;
+        }
+
+        self.url(self.index(x, y))
+    }
+
+    /// Copy a region of the canvas so that its top-left corner lands on
+    /// `dst`. The source and destination may overlap. Links are copied
+    /// along with the characters.
+    ///
+    /// Whatever part of the region falls outside of the canvas, at either
+    /// the source or the destination, is skipped. Wide characters cut in
+    /// half, at either the source or the destination, are replaced by spaces.
+    ///
+    /// ```
+    /// # use cod::canvas::{Canvas, WIDE_TAIL};
+    /// # use cod::rect::Rect;
+    /// let mut canvas = Canvas::new(6, 2);
+    /// canvas.text("日本語", 0, 0);
+    /// canvas.text("語語語", 0, 1);
+    ///
+    /// // cuts 日 and 語 on the top row, and 語 on the bottom one
+    /// canvas.copy_region(Rect::new(1, 0, 4, 1), (1, 1));
+    ///
+    /// let text = |y| -> String {
+    ///     let cells = canvas.row(y).unwrap().iter();
+    ///     cells.map(|cell| cell.c).filter(|&c| c != WIDE_TAIL).collect()
+    /// };
+    /// assert_eq!(text(1), "  本  ");
+    /// ```
+    pub fn copy_region(&mut self, src: Rect, dst: (u32, u32)) {
+        let src = src.intersect(self.bounds());
+        let (dx, dy) = dst;
+        if src.is_empty() || dx >= self.width || dy >= self.height {
+            return;
+        }
+
+        let width = src.width.min(self.width - dx) as usize;
+        let height = src.height.min(self.height - dy);
+
+        let copy_row = |canvas: &mut Self, row: u32| {
+            let from = canvas.index(src.x, src.y + row);
+            let to = canvas.index(dx, dy + row);
+            let cells = canvas.cells[from..from + width].to_vec();
+            canvas.put_row(dx, dy + row, &cells);
+            for (i, link) in (to..).zip(canvas.links[from..from + width].to_vec()) {
+                canvas.relink(i, link);
+            }
+        };
+
+        // when moving down, copy from the bottom up so that rows aren't
+        // overwritten before they're read
+        if dy > src.y {
+            for row in (0..height).rev() {
+                copy_row(self, row);
+            }
+        } else {
+            for row in 0..height {
+                copy_row(self, row);
+            }
+        }
+
+        self.prune_urls();
+    }
+
+    /// Capture a region of the canvas, e.g. to restore it after drawing a
+    /// popup over it.
+    ///
+    /// The region is clipped to the canvas' bounds. Only cells are captured,
+    /// not links.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// # use cod::{canvas::Canvas, rect::Rect};
+    /// let mut canvas = Canvas::new(20, 10);
+    /// canvas.text("some background", 0, 4);
+    ///
+    /// let under = canvas.snapshot(Rect::new(2, 3, 10, 3));
+    /// canvas.fill('#', Rect::new(2, 3, 10, 3));
+    /// canvas.restore(&under);
+    /// # assert_eq!(canvas, {
+    /// #     let mut c = Canvas::new(20, 10);
+    /// #     c.text("some background", 0, 4);
+    /// #     c
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn snapshot(&self, rect: Rect) -> Texture {
+        let rect = rect.intersect(self.bounds());
+        let mut cells = Vec::with_capacity(rect.width as usize * rect.height as usize);
+        for y in rect.y..rect.y + rect.height {
+            let start = self.index(rect.x, y);
+            cells.extend_from_slice(&self.cells[start..start + rect.width as usize]);
+        }
+
+        Texture {
+            origin: (rect.x, rect.y),
+            width: rect.width,
+            height: rect.height,
+            cells,
+        }
+    }
+
+    /// Shrink the canvas into a texture `factor` times smaller each way, e.g.
+    /// to draw a minimap of a game's world.
+    ///
+    /// Each block of `factor` by `factor` cells becomes one cell. If most of
+    /// the block is one (non-blank) character, the cell shows it; otherwise,
+    /// it shows a shade (`░`, `▒`, or `▓`) by how much of the block isn't
+    /// blank, so even a lone character still shows up. Its
+    /// colors are the averages of the block's colors. Blocks along the right
+    /// and bottom edges may be cut short.
+    ///
+    /// ```
+    /// # use cod::canvas::Canvas;
+    /// # use cod::rect::Rect;
+    /// let mut world = Canvas::new(40, 20);
+    /// world.fill('~', Rect::new(0, 0, 20, 20));
+    /// world.text("#.#.", 20, 0);
+    ///
+    /// let minimap = world.downsample(4);
+    /// assert_eq!((minimap.width(), minimap.height()), (10, 5));
+    ///
+    /// let mut screen = Canvas::new(10, 5);
+    /// screen.paste(&minimap, 0, 0);
+    /// assert_eq!(screen.get(0, 0).unwrap().c, '~');
+    /// assert_eq!(screen.get(5, 0).unwrap().c, '░');
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `factor` is zero, panics.
+    #[must_use]
+    pub fn downsample(&self, factor: u32) -> Texture {
+        assert!(factor != 0, "can't downsample by a factor of 0");
+
+        let width = self.width.div_ceil(factor);
+        let height = self.height.div_ceil(factor);
+        let mut cells = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let block = Rect::new(x * factor, y * factor, factor, factor);
+                cells.push(self.summarize(block.intersect(self.bounds())));
+            }
+        }
+
+        Texture {
+            origin: (0, 0),
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Returns a single cell standing in for a (non-empty) block of cells,
+    /// for [`Canvas::downsample`].
+    fn summarize(&self, block: Rect) -> Cell {
+        let cells: Vec<Cell> = self.region(block).map(|(_, _, cell)| cell).collect();
+        let total = cells.len();
+
+        let mut counts: Vec<(char, usize)> = Vec::new();
+        for cell in &cells {
+            match counts.iter_mut().find(|(c, _)| *c == cell.c) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((cell.c, 1)),
+            }
+        }
+        let (common, most) =
+            counts.iter().copied().fold(
+                (' ', 0),
+                |best, next| if next.1 > best.1 { next } else { best },
+            );
+
+        let c = if common != ' ' && most * 2 > total && crate::width::columns(common) == 1 {
+            common
+        } else {
+            let filled = cells.iter().filter(|cell| cell.c != ' ').count();
+            match filled * 4 / total {
+                _ if filled == 0 => ' ',
+                0 | 1 => '\u{2591}',
+                2 => '\u{2592}',
+                _ => '\u{2593}',
+            }
+        };
+
+        let style = Style {
+            fg: average(
+                cells
+                    .iter()
+                    .filter(|cell| cell.c != ' ')
+                    .map(|cell| cell.style.fg),
+            ),
+            bg: average(cells.iter().map(|cell| cell.style.bg)),
+            ..Style::default()
+        };
+
+        Cell::new(c, style)
+    }
+
+    /// Draw a texture onto the canvas, with its top-left corner at the given
+    /// position. The texture keeps its own styles.
+    ///
+    /// Wide characters cut in half, either on the canvas by the texture's
+    /// edges or in the texture by its own edges or the canvas', are replaced
+    /// by spaces.
+    ///
+    /// ```
+    /// # use cod::canvas::{Canvas, WIDE_TAIL};
+    /// # use cod::rect::Rect;
+    /// let mut canvas = Canvas::new(6, 1);
+    /// canvas.text("日本語", 0, 0);
+    ///
+    /// // only the right half of 日, and the left half of 語
+    /// let texture = canvas.snapshot(Rect::new(1, 0, 4, 1));
+    /// let mut other = Canvas::new(6, 1);
+    /// other.text("語語語", 0, 0);
+    /// other.paste(&texture, 1, 0);
+    ///
+    /// let cells = other.row(0).unwrap().iter();
+    /// let text: String = cells.map(|cell| cell.c).filter(|&c| c != WIDE_TAIL).collect();
+    /// assert_eq!(text, "  本  ");
+    /// ```
+    pub fn paste(&mut self, texture: &Texture, x: u32, y: u32) {
+        let area = Rect::new(x, y, texture.width, texture.height).intersect(self.bounds());
+        if area.is_empty() {
+            return;
+        }
+
+        let len = area.width as usize;
+        for row in area.y..area.y + area.height {
+            let from = (row - y) as usize * texture.width as usize + (area.x - x) as usize;
+            self.put_row(area.x, row, &texture.cells[from..from + len]);
+        }
+    }
+
+    /// Draw another canvas onto this one, with its top-left corner at the
+    /// given position, keeping its styles and links. Whatever falls outside
+    /// of this canvas is discarded.
+    ///
+    /// Wide characters cut in half, either on this canvas by the stamp's
+    /// edges or on the stamp by this canvas' edge, are replaced by spaces.
+    ///
+    /// Useful for drawing something once, e.g. an icon or a panel's
+    /// decorations, then stamping it wherever it's needed every frame.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// # use cod::canvas::{Canvas, WIDE_TAIL};
+    /// # use cod::rect::Rect;
+    /// let mut icon = Canvas::new(3, 3);
+    /// icon.rect('*', icon.bounds());
+    ///
+    /// let mut screen = Canvas::new(20, 5);
+    /// for x in [0, 5, 10] {
+    ///     screen.stamp(&icon, x, 1);
+    /// }
+    /// # let mut copy = Canvas::new(3, 3);
+    /// # copy.paste(&screen.snapshot(Rect::new(10, 1, 3, 3)), 0, 0);
+    /// # assert_eq!(copy, icon);
+    ///
+    /// let mut wide = Canvas::new(4, 1);
+    /// wide.text("日本", 0, 0);
+    /// let mut screen = Canvas::new(5, 1);
+    /// screen.text("語語", 0, 0);
+    ///
+    /// let text = |canvas: &Canvas| -> String {
+    ///     let cells = canvas.row(0).unwrap().iter();
+    ///     cells.map(|cell| cell.c).filter(|&c| c != WIDE_TAIL).collect()
+    /// };
+    /// screen.stamp(&wide, 1, 0);
+    /// assert_eq!(text(&screen), " 日本");
+    /// screen.stamp(&wide, 2, 0);
+    /// assert_eq!(text(&screen), "  日 ");
+    /// ```
+    pub fn stamp(&mut self, other: &Canvas, x: u32, y: u32) {
+        let area = Rect::new(x, y, other.width, other.height).intersect(self.bounds());
+        if area.is_empty() {
+            return;
+        }
+
+        // the other canvas' links, as links on this one
+        let remap: Vec<usize> = other.urls.iter().map(|url| self.url_link(url)).collect();
+
+        let len = area.width as usize;
+        for row in area.y..area.y + area.height {
+            let from = other.index(area.x - x, row - y);
+            let to = self.index(area.x, row);
+            self.put_row(area.x, row, &other.cells[from..from + len]);
+
+            for (k, &link) in other.links[from..from + len].iter().enumerate() {
+                self.relink(to + k, link.checked_sub(1).map_or(0, |i| remap[i]));
+            }
+        }
+
+        self.prune_urls();
+    }
+
+    /// Put a texture back where it was captured from. Like
+    /// [`Canvas::paste`], wide characters cut in half are replaced by spaces.
+    pub fn restore(&mut self, texture: &Texture) {
+        let (x, y) = texture.origin;
+        self.paste(texture, x, y);
+    }
+
+    /// Turn journaling on or off. While it's on, every change to the cells
+    /// is recorded, to be [undone](Canvas::undo) later. Turning it off
+    /// forgets everything recorded.
+    ///
+    /// Changes are grouped into steps by [transactions](Canvas::begin);
+    /// changes made outside of a transaction are grouped together until the
+    /// next one begins. Only cells are journaled, not links.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// # use cod::canvas::Canvas;
+    /// # use cod::rect::Rect;
+    /// let mut canvas = Canvas::new(20, 5);
+    /// canvas.set_journaling(true);
+    ///
+    /// canvas.begin();
+    /// canvas.rect('#', Rect::new(0, 0, 10, 3));
+    /// canvas.text("hi", 1, 1);
+    /// canvas.commit();
+    ///
+    /// canvas.begin();
+    /// canvas.fill('.', canvas.bounds());
+    /// canvas.commit();
+    ///
+    /// assert!(canvas.undo());
+    /// assert!(canvas.undo());
+    /// assert_eq!(canvas, Canvas::new(20, 5));
+    ///
+    /// assert!(canvas.redo());
+    /// assert!(canvas.can_redo());
+    /// ```
+    pub fn set_journaling(&mut self, on: bool) {
+        match (on, &self.journal) {
+            (true, None) => self.journal = Some(Journal::default()),
+            (false, Some(_)) => self.journal = None,
+            _ => {}
+        }
+    }
+
+    /// Returns true if journaling is on.
+    #[must_use]
+    pub const fn is_journaling(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    /// Begin a transaction: everything changed until the matching
+    /// [`Canvas::commit`] is undone and redone as one step. Transactions
+    /// can be nested; only the outermost one makes a step.
+    ///
+    /// Does nothing unless journaling.
+    pub fn begin(&mut self) {
+        if let Some(journal) = &mut self.journal {
+            if journal.depth == 0 {
+                journal.seal();
+            }
+            journal.depth += 1;
+        }
+    }
+
+    /// End a transaction started by [`Canvas::begin`].
+    ///
+    /// Does nothing unless journaling.
+    pub fn commit(&mut self) {
+        if let Some(journal) = &mut self.journal {
+            journal.depth = journal.depth.saturating_sub(1);
+            if journal.depth == 0 {
+                journal.seal();
+            }
+        }
+    }
+
+    /// Returns true if there are changes to undo.
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        self.journal
+            .as_ref()
+            .is_some_and(|j| !j.undo.is_empty() || !j.pending.is_empty())
+    }
+
+    /// Returns true if there are changes to redo.
+    #[must_use]
+    pub fn can_redo(&self) -> bool {
+        self.journal.as_ref().is_some_and(|j| !j.redo.is_empty())
+    }
+
+    /// Undo the last step of changes, returning true if there was one.
+    /// Closes any open transactions first.
+    ///
+    /// Does nothing unless journaling.
+    pub fn undo(&mut self) -> bool {
+        let Some(journal) = &mut self.journal else {
+            return false;
+        };
+
+        journal.depth = 0;
+        journal.seal();
+        let Some(step) = journal.undo.pop() else {
+            return false;
+        };
+
+        for change in step.iter().rev() {
+            self.cells[change.index] = change.before;
+        }
+        journal.redo.push(step);
+        true
+    }
+
+    /// Redo the last step of changes undone, returning true if there was
+    /// one. Making any other change forgets what could be redone.
+    ///
+    /// Does nothing unless journaling.
+    pub fn redo(&mut self) -> bool {
+        let Some(journal) = &mut self.journal else {
+            return false;
+        };
+
+        journal.depth = 0;
+        journal.seal();
+        let Some(step) = journal.redo.pop() else {
+            return false;
+        };
+
+        for change in &step {
+            self.cells[change.index] = change.after;
+        }
+        journal.undo.push(step);
+        true
+    }
+
+    /// Scroll the contents of a region vertically by `dy` rows; positive
+    /// values move the contents down, negative values move them up.
+    ///
+    /// Rows scrolled out of the region are discarded, and rows scrolled in
+    /// are [erased](Canvas::erase). Links scroll along with the contents.
+    pub fn scroll_region(&mut self, rect: Rect, dy: i32) {
+        let rect = rect.intersect(self.bounds());
+        let shift = dy.unsigned_abs();
+        if rect.is_empty() || shift == 0 {
+            return;
+        }
+
+        if shift >= rect.height {
+            self.erase(rect);
+            self.unlink(rect);
+            return;
+        }
+
+        let kept = rect.height - shift;
+        let cleared = if dy > 0 {
+            self.copy_region(
+                Rect::new(rect.x, rect.y, rect.width, kept),
+                (rect.x, rect.y + shift),
+            );
+            Rect::new(rect.x, rect.y, rect.width, shift)
+        } else {
+            self.copy_region(
+                Rect::new(rect.x, rect.y + shift, rect.width, kept),
+                (rect.x, rect.y),
+            );
+            Rect::new(rect.x, rect.y + kept, rect.width, shift)
+        };
+
+        self.erase(cleared);
+        self.unlink(cleared);
+    }
+
+    /// Draw the entire canvas to the screen, with its top-left corner at the
+    /// given position.
+    ///
+    /// Like [`Spans::draw`](crate::spans::Spans::draw), styling is reset
+    /// afterwards if any cells were styled.
+    pub fn draw(&self, x: u32, y: u32) {
+        self.draw_region(self.bounds(), x, y);
+    }
+
+    /// Draw part of the canvas to the screen, where the canvas' top-left
+    /// corner would be at the given position. Useful to redraw only what
+    /// changed.
+    ///
+    /// Each linked run of cells opens and closes its own link, so a link
+    /// that's only partly redrawn still works, and nothing drawn afterwards
+    /// is linked by accident.
+    ///
+    /// Wide characters are always drawn whole, in the style and link of
+    /// their left half, even if the region starts on their right half.
+    ///
+    /// ```rust
+    /// # use cod::prelude::*;
+    /// use cod::canvas::{Canvas, WIDE_TAIL};
+    /// use cod::color::Color;
+    /// use cod::rect::Rect;
+    /// use cod::style::Style;
+    ///
+    /// let red = Style {
+    ///     fg: Some(Color::Red),
+    ///     ..Style::default()
+    /// };
+    /// let mut canvas = Canvas::new(6, 1);
+    /// canvas.set_style(red);
+    /// canvas.text("日x", 0, 0);
+    /// canvas.set_style(Style {
+    ///     fg: Some(Color::Blue),
+    ///     ..red
+    /// });
+    /// canvas.text("本", 3, 0);
+    /// canvas.link(Rect::new(1, 0, 2, 1), "https://example.com");
+    ///
+    /// // starts on the right half of 日
+    /// let out = cod::target::capture(|| canvas.draw_region(Rect::new(1, 0, 5, 1), 0, 0));
+    /// let shown = cod::vt::render(&out, 6, 1);
+    /// let row: String = shown.row(0).unwrap().iter().map(|cell| cell.c).collect();
+    /// assert_eq!(row.replace(WIDE_TAIL, ""), "日x本 ");
+    /// ```
+    pub fn draw_region(&self, rect: Rect, x: u32, y: u32) {
+        profile!();
+        let rect = rect.intersect(self.bounds());
+        let mut tracker = Tracker::new();
+        for row in rect.y..rect.y + rect.height {
+            self.draw_run(row, rect.x, rect.x + rect.width, (x, y), &mut tracker);
+        }
+
+        tracker.reset();
+    }
+
+    /// Draw the canvas to the screen, with its top-left corner at the given
+    /// position, printing only the cells that changed since it was last
+    /// presented.
+    ///
+    /// Everything is printed if this is the first time, or if the canvas'
+    /// size or position changed. If something else drew over the canvas on
+    /// the screen in the meantime, call [`Canvas::invalidate`] first.
+    ///
+    /// The output only depends on the canvas, what it last presented, and
+    /// the colors set outside of it: changed cells are always printed row
+    /// by row from the top, left to right, in runs of the same style and
+    /// link. So the same frames always make the same output, e.g. for
+    /// comparing against a file in tests (see [`Canvas::present_to`]).
+    pub fn present(&mut self, x: u32, y: u32) {
+        profile!();
+        let shown = self
+            .shown
+            .take()
+            .filter(|s| (s.x, s.y, s.width, s.height) == (x, y, self.width, self.height));
+
+        let Some(shown) = shown else {
+            self.draw(x, y);
+            self.shown = Some(self.to_shown(x, y));
+            if self.parking {
+                crate::goto::park();
+            }
+            return;
+        };
+
+        let mut tracker = Tracker::new();
+        for row in 0..self.height {
+            let start = self.index(0, row);
+            let changed = |col: u32| {
+                let i = start + col as usize;
+                self.cells[i] != shown.cells[i] || self.url(i) != shown.links[i].as_deref()
+            };
+
+            // linear output reads rows out whole, so a changed row is
+            // printed whole too
+            if crate::a11y::is_linear_output() {
+                if (0..self.width).any(changed) {
+                    self.draw_run(row, 0, self.width, (x, y), &mut tracker);
+                }
+                continue;
+            }
+
+            let mut col = 0;
+            while col < self.width {
+                if !changed(col) {
+                    col += 1;
+                    continue;
+                }
+
+                let from = col;
+                while col < self.width && changed(col) {
+                    col += 1;
+                }
+                self.draw_run(row, from, col, (x, y), &mut tracker);
+            }
+        }
+
+        tracker.reset();
+        self.shown = Some(self.to_shown(x, y));
+        if self.parking {
+            crate::goto::park();
+        }
+    }
+
+    /// Set whether [`Canvas::present`] parks the cursor afterwards (see
+    /// [`crate::goto::park`]), so it doesn't sit wherever the last change
+    /// was printed. Off by default.
+    pub fn set_parking(&mut self, on: bool) {
+        self.parking = on;
+    }
+
+    /// Like [`Canvas::present`], but writes the output to `w` instead of the
+    /// [target](crate::target).
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// # use cod::canvas::Canvas;
+    /// let mut canvas = Canvas::new(10, 2);
+    /// canvas.text("ab", 0, 0);
+    /// canvas.text("c", 4, 1);
+    ///
+    /// let mut frame = Vec::new();
+    /// canvas.present_to(&mut frame, 0, 0).unwrap();
+    /// # assert!(String::from_utf8(frame).unwrap().starts_with("\x1b[1;1Hab        \x1b[2;1H    c"));
+    ///
+    /// // only the changes, in order
+    /// canvas.text("X", 7, 1);
+    /// canvas.text("Y", 1, 0);
+    /// let mut frame = Vec::new();
+    /// canvas.present_to(&mut frame, 0, 0).unwrap();
+    /// assert_eq!(frame, b"\x1b[1;2HY\x1b[2;8HX");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If writing to `w` fails, returns the error. Since what was written
+    /// isn't known, the canvas is [invalidated](Canvas::invalidate), so the
+    /// next present prints everything.
+    pub fn present_to<W: std::io::Write>(
+        &mut self,
+        w: &mut W,
+        x: u32,
+        y: u32,
+    ) -> std::io::Result<()> {
+        let output = crate::target::capture(|| self.present(x, y));
+        let result = w.write_all(output.as_bytes());
+        if result.is_err() {
+            self.invalidate();
+        }
+
+        result
+    }
+
+    /// Forget what was last presented, so that the next
+    /// [`Canvas::present`] prints everything.
+    pub fn invalidate(&mut self) {
+        self.shown = None;
+    }
+
+    fn to_shown(&self, x: u32, y: u32) -> Shown {
+        Shown {
+            x,
+            y,
+            width: self.width,
+            height: self.height,
+            cells: self.cells.clone(),
+            links: (0..self.links.len())
+                .map(|i| self.url(i).map(str::to_string))
+                .collect(),
+        }
+    }
+
+    /// Draw the cells of a row from column `from` up to `to`, where the
+    /// canvas' top-left corner would be at `origin`. Styles are switched
+    /// with `tracker`, which keeps up with the style on the terminal.
+    ///
+    /// A wide character is always printed whole, in its own style and link:
+    /// if `from` is on its right half, the run starts at its left half
+    /// instead.
+    fn draw_run(&self, row: u32, from: u32, to: u32, origin: (u32, u32), tracker: &mut Tracker) {
+        let mut start = self.index(from, row);
+        let mut col = from;
+        if from < to && col > 0 && self.cells[start].c == WIDE_TAIL {
+            start -= 1;
+            col -= 1;
+        }
+
+        // the right half of a wide character goes with its left half
+        let end = self.index(to, row);
+        let mut cells = Vec::with_capacity(end - start);
+        for (cell, &link) in self.cells[start..end].iter().zip(&self.links[start..end]) {
+            match cells.last() {
+                Some(&(_, style, head)) if cell.c == WIDE_TAIL => cells.push((cell.c, style, head)),
+                _ => cells.push((cell.c, cell.style, link)),
+            }
+        }
+
+        for run in cells.chunk_by(|a, b| (a.1, a.2) == (b.1, b.2)) {
+            let (_, style, link) = run[0];
+            tracker.set(style);
+
+            let url = link.checked_sub(1).map(|i| &self.urls[i]);
+            if let Some(url) = url {
+                crate::osc(format_args!("8;;{url}"));
+            }
+
+            let text: String = run
+                .iter()
+                .map(|&(c, ..)| c)
+                .filter(|&c| c != WIDE_TAIL)
+                .collect();
+            crate::run(&text, origin.0 + col, origin.1 + row);
+
+            if url.is_some() {
+                crate::osc("8;;");
+            }
+
+            col += u32::try_from(run.len()).unwrap_or(u32::MAX);
+        }
+    }
+}
+
+/// Returns the average of the colors given, or `None` if there are none.
+fn average(colors: impl Iterator<Item = Option<Color>>) -> Option<Color> {
+    let (mut r, mut g, mut b, mut n) = (0_u32, 0_u32, 0_u32, 0_u32);
+    for (cr, cg, cb) in colors.flatten().map(Color::rgb) {
+        r += u32::from(cr);
+        g += u32::from(cg);
+        b += u32::from(cb);
+        n += 1;
+    }
+
+    let channel = |sum: u32| u8::try_from(sum / n).unwrap_or(u8::MAX);
+    (n > 0).then(|| Color::Rgb(channel(r), channel(g), channel(b)))
+}
@@ -4,11 +4,13 @@ use crate::{escape, NonOrthogonal};
 
 /// Clear the screen (full clear, not scroll).
 pub fn all() {
+    profile!();
     escape("2J");
 }
 
 /// Clear the current line.
 pub fn line() {
+    profile!();
     escape("2K");
 }
 
@@ -18,5 +20,6 @@ pub fn line() {
 ///
 /// If the rectangle was not orthogonal, returns `false`.
 pub fn rect(x1: u32, y1: u32, x2: u32, y2: u32) -> Result<(), NonOrthogonal> {
+    profile!();
     crate::rect::line(' ', x1, y1, x2, y2)
 }
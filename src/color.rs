@@ -13,6 +13,24 @@
 //! Without `color_stack`, the functions in [`with`] don't nest well: when the
 //! inner one exits, the color will be reset to normal, rather than continue
 //! the color that the outer function set.
+//!
+//! The stacks are shared by every thread, so threads drawing at once mix
+//! up each other's colors. With the feature `thread_local_stack`, each
+//! thread gets its own stacks instead, starting out empty.
+//!
+//! To make sure a pushed color is popped, even on an early return, hold on
+//! to a guard from [`fg_guard`] or [`bg_guard`] while drawing.
+//!
+//! Not every terminal supports true-color, or any color at all. Setting a
+//! [`ColorDepth`] (e.g. the one [detected](ColorDepth::detect) from the
+//! environment) with [`set_depth`] makes every color quantize to what the
+//! terminal supports, so the same code draws correctly everywhere.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+static DEPTH: AtomicU8 = AtomicU8::new(ColorDepth::TrueColor as u8);
 
 #[cfg(feature = "color_stack")]
 pub use stack::{
@@ -23,132 +41,593 @@ pub use stack::{
 #[cfg(feature = "color_stack")]
 #[allow(clippy::missing_panics_doc)]
 mod stack {
-    use super::raw::{bg, fg, tc_bg, tc_fg};
-    use std::sync::Mutex;
+    use super::Color;
 
-    fn init_stack<T>() -> Mutex<Vec<T>> {
-        Mutex::new(Vec::new())
-    }
+    #[cfg(not(feature = "thread_local_stack"))]
+    static FG_COLOR_STACK: std::sync::Mutex<Vec<Option<Color>>> = std::sync::Mutex::new(Vec::new());
+    #[cfg(not(feature = "thread_local_stack"))]
+    static BG_COLOR_STACK: std::sync::Mutex<Vec<Option<Color>>> = std::sync::Mutex::new(Vec::new());
 
-    #[derive(Clone, Copy)]
-    enum Color {
-        C(u8),
-        Rgb(u8, u8, u8),
+    #[cfg(feature = "thread_local_stack")]
+    thread_local! {
+        static FG_COLOR_STACK: std::cell::RefCell<Vec<Option<Color>>> =
+            const { std::cell::RefCell::new(Vec::new()) };
+        static BG_COLOR_STACK: std::cell::RefCell<Vec<Option<Color>>> =
+            const { std::cell::RefCell::new(Vec::new()) };
     }
 
-    impl Color {
-        fn fg(self) {
-            match self {
-                Self::C(x) => fg(x),
-                Self::Rgb(r, g, b) => tc_fg(r, g, b),
-            }
-        }
+    /// Runs a function on the foreground or background color stack: the
+    /// global one, or this thread's own with `thread_local_stack`.
+    fn with_stack<T>(background: bool, f: impl FnOnce(&mut Vec<Option<Color>>) -> T) -> T {
+        let stack = if background {
+            &BG_COLOR_STACK
+        } else {
+            &FG_COLOR_STACK
+        };
 
-        fn bg(self) {
-            match self {
-                Self::C(x) => bg(x),
-                Self::Rgb(r, g, b) => tc_bg(r, g, b),
-            }
-        }
+        #[cfg(not(feature = "thread_local_stack"))]
+        let result = f(&mut stack.lock().unwrap());
+
+        #[cfg(feature = "thread_local_stack")]
+        let result = stack.with(|stack| f(&mut stack.borrow_mut()));
+
+        result
     }
 
     pub mod fg {
-        use std::sync::{Mutex, OnceLock};
-        static FG_COLOR_STACK: OnceLock<Mutex<Vec<Color>>> = OnceLock::new();
-
-        use super::{init_stack, Color};
+        use super::{with_stack, Color};
 
         pub mod push {
-            use crate::color::stack::{init_stack, Color};
-
-            use super::FG_COLOR_STACK;
+            use crate::color::stack::{with_stack, Color};
 
             /// Pushes a color onto the foreground color stack.
-            pub fn fg(c: u8) {
-                crate::color::raw::fg(c);
-                FG_COLOR_STACK
-                    .get_or_init(init_stack)
-                    .lock()
-                    .unwrap()
-                    .push(Color::C(c));
+            pub fn fg(c: impl Into<Color>) {
+                profile!();
+                color(Some(c.into()));
             }
 
             /// Pushes an RGB color onto the foreground color stack.
             pub fn tc_fg(r: u8, g: u8, b: u8) {
-                crate::color::raw::tc_fg(r, g, b);
-                FG_COLOR_STACK
-                    .get_or_init(init_stack)
-                    .lock()
-                    .unwrap()
-                    .push(Color::Rgb(r, g, b));
+                profile!();
+                color(Some(Color::Rgb(r, g, b)));
+            }
+
+            /// Pushes a color, or the default color, onto the foreground color
+            /// stack.
+            pub(crate) fn color(c: Option<Color>) {
+                match c {
+                    Some(c) => crate::color::raw::color_fg(c),
+                    None => crate::color::raw::reset_fg(),
+                }
+                with_stack(false, |stack| stack.push(c));
             }
         }
 
         /// Pops a color off of the foreground color stack.
         pub fn pop() {
-            let mut stack = FG_COLOR_STACK.get_or_init(init_stack).lock().unwrap();
+            profile!();
+            with_stack(false, Vec::pop);
+            restore();
+        }
+
+        /// Returns the color on top of the stack, if it isn't the default.
+        pub(crate) fn top() -> Option<Color> {
+            with_stack(false, |stack| stack.last().copied().flatten())
+        }
 
-            stack.pop();
-            if let Some(c) = stack.last() {
-                c.fg();
+        /// Re-emits the color on top of the stack, or resets the color if
+        /// it's empty.
+        pub(crate) fn restore() {
+            if let Some(Some(c)) = with_stack(false, |stack| stack.last().copied()) {
+                crate::color::raw::color_fg(c);
             } else {
-                crate::escape("39m");
+                crate::color::raw::reset_fg();
             }
         }
     }
 
     pub mod bg {
-        use std::sync::{Mutex, OnceLock};
-        static BG_COLOR_STACK: OnceLock<Mutex<Vec<Color>>> = OnceLock::new();
-
-        use super::{init_stack, Color};
+        use super::{with_stack, Color};
 
         pub mod push {
-            use crate::color::stack::{init_stack, Color};
-
-            use super::BG_COLOR_STACK;
+            use crate::color::stack::{with_stack, Color};
 
             /// Pushes a color onto the background color stack.
-            pub fn bg(c: u8) {
-                crate::color::raw::bg(c);
-                BG_COLOR_STACK
-                    .get_or_init(init_stack)
-                    .lock()
-                    .unwrap()
-                    .push(Color::C(c));
+            pub fn bg(c: impl Into<Color>) {
+                profile!();
+                color(Some(c.into()));
             }
 
             /// Pushes an RGB color onto the background color stack.
             pub fn tc_bg(r: u8, g: u8, b: u8) {
-                crate::color::raw::tc_bg(r, g, b);
-                BG_COLOR_STACK
-                    .get_or_init(init_stack)
-                    .lock()
-                    .unwrap()
-                    .push(Color::Rgb(r, g, b));
+                profile!();
+                color(Some(Color::Rgb(r, g, b)));
+            }
+
+            /// Pushes a color, or the default color, onto the background color
+            /// stack.
+            pub(crate) fn color(c: Option<Color>) {
+                match c {
+                    Some(c) => crate::color::raw::color_bg(c),
+                    None => crate::color::raw::reset_bg(),
+                }
+                with_stack(true, |stack| stack.push(c));
             }
         }
 
         /// Pops a color off of the background color stack.
         pub fn pop() {
-            let mut stack = BG_COLOR_STACK.get_or_init(init_stack).lock().unwrap();
+            profile!();
+            with_stack(true, Vec::pop);
+            restore();
+        }
 
-            stack.pop();
-            if let Some(c) = stack.last() {
-                c.bg();
+        /// Returns the color on top of the stack, if it isn't the default.
+        pub(crate) fn top() -> Option<Color> {
+            with_stack(true, |stack| stack.last().copied().flatten())
+        }
+
+        /// Re-emits the color on top of the stack, or resets the color if
+        /// it's empty.
+        pub(crate) fn restore() {
+            if let Some(Some(c)) = with_stack(true, |stack| stack.last().copied()) {
+                crate::color::raw::color_bg(c);
             } else {
-                crate::escape("49m");
+                crate::color::raw::reset_bg();
+            }
+        }
+    }
+}
+
+/// A color, either one of the 16 basic colors, from the 256-color palette,
+/// or true-color.
+///
+/// The basic colors are defined by the terminal, so e.g. [`Color::Red`] is
+/// the same as `Color::Ansi(1)`, and they compare equal.
+///
+/// Can be parsed from, and displayed as, a name (`"bright_blue"`), a palette
+/// index (`"12"`), or a hex code (`"#ff8800"`). Names ignore case, and may
+/// be written with dashes, underscores, or nothing between words.
+///
+/// Anything taking `impl Into<Color>` also accepts a palette index or an
+/// RGB tuple:
+///
+/// ```
+/// # use cod::prelude::*;
+/// use cod::color::Color;
+///
+/// color::fg(Color::BrightBlue);
+/// color::bg(236);
+/// color::fg((255, 136, 0));
+/// color::de::all();
+///
+/// assert_eq!(Color::Red, Color::Ansi(1));
+/// assert_eq!("Bright-Blue".parse(), Ok(Color::BrightBlue));
+/// assert_eq!(Color::BrightBlue.to_string(), "bright_blue");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub enum Color {
+    /// Palette color 0.
+    Black,
+    /// Palette color 1.
+    Red,
+    /// Palette color 2.
+    Green,
+    /// Palette color 3.
+    Yellow,
+    /// Palette color 4.
+    Blue,
+    /// Palette color 5.
+    Magenta,
+    /// Palette color 6.
+    Cyan,
+    /// Palette color 7.
+    White,
+    /// Palette color 8.
+    BrightBlack,
+    /// Palette color 9.
+    BrightRed,
+    /// Palette color 10.
+    BrightGreen,
+    /// Palette color 11.
+    BrightYellow,
+    /// Palette color 12.
+    BrightBlue,
+    /// Palette color 13.
+    BrightMagenta,
+    /// Palette color 14.
+    BrightCyan,
+    /// Palette color 15.
+    BrightWhite,
+    /// A color from the 256-color palette. The first 16 are defined by the
+    /// terminal.
+    Ansi(u8),
+    /// A true-color RGB value.
+    Rgb(u8, u8, u8),
+}
+
+/// The basic colors, in palette order, with their names.
+const NAMED: [(Color, &str); 16] = [
+    (Color::Black, "black"),
+    (Color::Red, "red"),
+    (Color::Green, "green"),
+    (Color::Yellow, "yellow"),
+    (Color::Blue, "blue"),
+    (Color::Magenta, "magenta"),
+    (Color::Cyan, "cyan"),
+    (Color::White, "white"),
+    (Color::BrightBlack, "bright_black"),
+    (Color::BrightRed, "bright_red"),
+    (Color::BrightGreen, "bright_green"),
+    (Color::BrightYellow, "bright_yellow"),
+    (Color::BrightBlue, "bright_blue"),
+    (Color::BrightMagenta, "bright_magenta"),
+    (Color::BrightCyan, "bright_cyan"),
+    (Color::BrightWhite, "bright_white"),
+];
+
+impl Color {
+    /// Set the foreground color to this color.
+    pub fn fg(self) {
+        fg(self);
+    }
+
+    /// Set the background color to this color.
+    pub fn bg(self) {
+        bg(self);
+    }
+
+    /// Returns the color's index in the 256-color palette, or `None` for a
+    /// true-color.
+    #[must_use]
+    pub const fn index(self) -> Option<u8> {
+        match self {
+            Self::Black => Some(0),
+            Self::Red => Some(1),
+            Self::Green => Some(2),
+            Self::Yellow => Some(3),
+            Self::Blue => Some(4),
+            Self::Magenta => Some(5),
+            Self::Cyan => Some(6),
+            Self::White => Some(7),
+            Self::BrightBlack => Some(8),
+            Self::BrightRed => Some(9),
+            Self::BrightGreen => Some(10),
+            Self::BrightYellow => Some(11),
+            Self::BrightBlue => Some(12),
+            Self::BrightMagenta => Some(13),
+            Self::BrightCyan => Some(14),
+            Self::BrightWhite => Some(15),
+            Self::Ansi(c) => Some(c),
+            Self::Rgb(..) => None,
+        }
+    }
+
+    /// Returns the color as either [`Color::Ansi`] or [`Color::Rgb`],
+    /// turning the basic colors into their palette index.
+    #[must_use]
+    pub const fn normalize(self) -> Self {
+        match self.index() {
+            Some(c) => Self::Ansi(c),
+            None => self,
+        }
+    }
+
+    /// Returns the (approximate) RGB value of the color.
+    ///
+    /// For the first 16 palette colors, this uses xterm's defaults.
+    #[must_use]
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self.normalize() {
+            Self::Rgb(r, g, b) => (r, g, b),
+            other => convert::ansi_to_rgb(other.index().unwrap_or(0)),
+        }
+    }
+
+    /// Returns the true-color a fraction `t` (from 0 to 1, clamped) of the
+    /// way from this color to another, mixing their RGB values.
+    ///
+    /// ```
+    /// # use cod::prelude::*;
+    /// use cod::color::Color;
+    ///
+    /// let gray = Color::Rgb(0, 0, 0).mix(Color::Rgb(255, 255, 255), 0.5);
+    /// assert_eq!(gray, Color::Rgb(128, 128, 128));
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn mix(self, other: Self, t: f64) -> Self {
+        if self == other {
+            return self;
+        }
+
+        let t = t.clamp(0.0, 1.0);
+        let ((r1, g1, b1), (r2, g2, b2)) = (self.rgb(), other.rgb());
+        let channel = |a: u8, b: u8| {
+            let (a, b) = (f64::from(a), f64::from(b));
+            (a + (b - a) * t).round().clamp(0.0, 255.0) as u8
+        };
+        Self::Rgb(channel(r1, r2), channel(g1, g2), channel(b1, b2))
+    }
+}
+
+impl PartialEq for Color {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.index(), other.index()) {
+            (Some(a), Some(b)) => a == b,
+            (None, None) => self.rgb() == other.rgb(),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Color {}
+
+impl std::hash::Hash for Color {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.index().hash(state);
+        if self.index().is_none() {
+            self.rgb().hash(state);
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ansi(c) => write!(f, "{c}"),
+            Self::Rgb(r, g, b) => write!(f, "#{r:02x}{g:02x}{b:02x}"),
+            named => {
+                let i = usize::from(named.index().unwrap_or(0));
+                write!(f, "{}", NAMED[i].1)
+            }
+        }
+    }
+}
+
+impl From<u8> for Color {
+    fn from(c: u8) -> Self {
+        Self::Ansi(c)
+    }
+}
+
+impl From<(u8, u8, u8)> for Color {
+    fn from((r, g, b): (u8, u8, u8)) -> Self {
+        Self::Rgb(r, g, b)
+    }
+}
+
+/// How many colors the terminal can show. See [`set_depth`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColorDepth {
+    /// No colors at all; every color is left out.
+    NoColor,
+    /// The 16 basic colors, using the original SGR codes (e.g. `31` for
+    /// red).
+    Colors16,
+    /// The 256-color palette.
+    Colors256,
+    /// 24-bit true-color.
+    #[default]
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// Guess the color depth of the terminal from the environment.
+    ///
+    /// [`NO_COLOR`](https://no-color.org) (set to anything) and `TERM=dumb`
+    /// mean no color. Otherwise, `COLORTERM=truecolor` (or `24bit`) means
+    /// true-color, and a `TERM` ending in `256color` means the 256-color
+    /// palette. Anything else is assumed to support the basic 16 colors.
+    #[must_use]
+    pub fn detect() -> Self {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        let term = var("TERM");
+
+        if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) || term == "dumb" {
+            Self::NoColor
+        } else if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit") {
+            Self::TrueColor
+        } else if term.ends_with("256color") {
+            Self::Colors256
+        } else {
+            Self::Colors16
+        }
+    }
+
+    fn from_u8(x: u8) -> Self {
+        match x {
+            0 => Self::NoColor,
+            1 => Self::Colors16,
+            2 => Self::Colors256,
+            _ => Self::TrueColor,
+        }
+    }
+}
+
+/// Set how many colors the terminal can show. Every color set from now on
+/// is quantized to the closest one available, or left out entirely with
+/// [`ColorDepth::NoColor`].
+///
+/// Defaults to [`ColorDepth::TrueColor`], i.e. colors are sent as they are.
+///
+/// Example:
+///
+/// ```
+/// # use cod::prelude::*;
+/// use cod::color::{self, ColorDepth};
+///
+/// color::set_depth(ColorDepth::detect());
+///
+/// // sent as-is, as the closest palette color, as bright yellow, or not
+/// // at all, depending on the terminal
+/// color::tc_fg(255, 200, 0);
+/// cod::text("warning", 0, 0);
+/// color::de::fg();
+/// # color::set_depth(ColorDepth::TrueColor);
+/// ```
+pub fn set_depth(depth: ColorDepth) {
+    DEPTH.store(depth as u8, Ordering::Relaxed);
+}
+
+/// Returns how many colors the terminal is assumed to show. See
+/// [`set_depth`].
+#[must_use]
+pub fn depth() -> ColorDepth {
+    ColorDepth::from_u8(DEPTH.load(Ordering::Relaxed))
+}
+
+/// Returns the closest color in the 256-color palette to an RGB color, as
+/// used when downgrading to [`ColorDepth::Colors256`].
+///
+/// Only the color cube and grayscale ramp are considered, since the first
+/// 16 colors vary between terminals.
+///
+/// Example:
+///
+/// ```
+/// # use cod::prelude::*;
+/// assert_eq!(color::approximate_256(255, 0, 0), 196);
+/// assert_eq!(color::approximate_256(128, 128, 128), 244);
+/// ```
+#[must_use]
+pub fn approximate_256(r: u8, g: u8, b: u8) -> u8 {
+    convert::rgb_to_256(r, g, b)
+}
+
+/// Returns the closest of the 16 basic colors to an RGB color, as used when
+/// downgrading to [`ColorDepth::Colors16`]. Assumes xterm's default values
+/// for the basic colors.
+///
+/// Example:
+///
+/// ```
+/// # use cod::prelude::*;
+/// use cod::color::Color;
+///
+/// assert_eq!(color::approximate_16(250, 10, 10), 9);
+/// assert_eq!(Color::Ansi(color::approximate_16(0, 0, 0)), Color::Black);
+/// ```
+#[must_use]
+pub fn approximate_16(r: u8, g: u8, b: u8) -> u8 {
+    convert::rgb_to_16(r, g, b)
+}
+
+/// Returns a true-color from a hue (in degrees, wrapping around), saturation,
+/// and value (each from 0 to 1, clamped).
+///
+/// Example:
+///
+/// ```
+/// # use cod::prelude::*;
+/// use cod::color::{self, Color};
+///
+/// assert_eq!(color::hsv(0.0, 1.0, 1.0), Color::Rgb(255, 0, 0));
+/// assert_eq!(color::hsv(480.0, 1.0, 0.5), Color::Rgb(0, 128, 0));
+///
+/// // a rainbow
+/// for x in 0..36 {
+///     color::fg(color::hsv(f64::from(x) * 10.0, 1.0, 1.0));
+///     cod::pixel('#', x, 0);
+///     color::de::fg();
+/// }
+/// ```
+#[must_use]
+pub fn hsv(h: f64, s: f64, v: f64) -> Color {
+    let (s, v) = (s.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
+    let chroma = v * s;
+    hue_to_rgb(h, chroma, v - chroma)
+}
+
+/// Returns a true-color from a hue (in degrees, wrapping around), saturation,
+/// and lightness (each from 0 to 1, clamped).
+///
+/// Example:
+///
+/// ```
+/// # use cod::prelude::*;
+/// use cod::color::{self, Color};
+///
+/// assert_eq!(color::hsl(240.0, 1.0, 0.5), Color::Rgb(0, 0, 255));
+/// assert_eq!(color::hsl(0.0, 0.0, 1.0), Color::Rgb(255, 255, 255));
+/// ```
+#[must_use]
+pub fn hsl(h: f64, s: f64, l: f64) -> Color {
+    let (s, l) = (s.clamp(0.0, 1.0), l.clamp(0.0, 1.0));
+    let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    hue_to_rgb(h, chroma, l - chroma / 2.0)
+}
+
+/// Returns the color with the given hue and chroma, lightened by `base`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn hue_to_rgb(hue: f64, chroma: f64, base: f64) -> Color {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let second = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector {
+        s if s < 1.0 => (chroma, second, 0.0),
+        s if s < 2.0 => (second, chroma, 0.0),
+        s if s < 3.0 => (0.0, chroma, second),
+        s if s < 4.0 => (0.0, second, chroma),
+        s if s < 5.0 => (second, 0.0, chroma),
+        _ => (chroma, 0.0, second),
+    };
+
+    let channel = |c: f64| ((c + base) * 255.0).round().clamp(0.0, 255.0) as u8;
+    Color::Rgb(channel(r), channel(g), channel(b))
+}
+
+/// The given text wasn't a valid [`Color`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseColorError;
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected a color name, a palette index, or a hex color code"
+        )
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(hex) = s.strip_prefix('#') {
+            if hex.len() != 6 || !hex.is_ascii() {
+                return Err(ParseColorError);
             }
+
+            let channel =
+                |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| ParseColorError);
+            Ok(Self::Rgb(channel(0)?, channel(2)?, channel(4)?))
+        } else if let Ok(c) = s.parse() {
+            Ok(Self::Ansi(c))
+        } else {
+            let name: String = s
+                .chars()
+                .filter(|c| !matches!(c, '-' | '_' | ' '))
+                .map(|c| c.to_ascii_lowercase())
+                .collect();
+            NAMED
+                .iter()
+                .find(|(_, n)| n.replace('_', "") == name)
+                .map(|&(c, _)| c)
+                .ok_or(ParseColorError)
         }
     }
 }
 
 macro_rules! do_color {
-    ( $( $color:ident, $de:ident, $doc:literal, [ $( $arg:ident : $typ:ty ),+ ], $fmt:literal ),+ $(,)? ) => {
+    ( $( $color:ident, $de:ident, $doc:literal, [ $( $arg:ident : $typ:ty ),+ ] ),+ $(,)? ) => {
         $(
             /// Set the
             #[doc = $doc]
             pub fn $color($($arg: $typ,)+) {
+                profile!();
                 #[cfg(not(feature = "color_stack"))]
                 { raw::$color($($arg,)+); }
 
@@ -156,30 +635,334 @@ macro_rules! do_color {
                 { stack::$de::push::$color($($arg),+); }
             }
         )+
-
-        mod raw {
-            $(
-                pub fn $color($($arg: $typ,)+) {
-                    crate::escape(format!($fmt, $($arg,)+));
-                }
-            )+
-        }
     };
 }
 
 do_color![
-    fg, fg, "foreground color.", [color: u8], "38;5;{}m",
-    bg, bg, "background color.", [color: u8], "48;5;{}m",
-    tc_fg, fg, "foreground color, using true-color.", [r: u8, g: u8, b: u8], "38;2;{};{};{}m",
-    tc_bg, bg, "background color, using true-color.", [r: u8, g: u8, b: u8], "48;2;{};{};{}m",
+    fg, fg, "foreground color.", [color: impl Into<Color>],
+    bg, bg, "background color.", [color: impl Into<Color>],
+    tc_fg, fg, "foreground color, using true-color.", [r: u8, g: u8, b: u8],
+    tc_bg, bg, "background color, using true-color.", [r: u8, g: u8, b: u8],
 ];
 
+/// Set the foreground color to the one named `name` in the active theme
+/// (see [`crate::palette`]), or the default color if it isn't set.
+///
+/// The color is looked up every time this is called, so applying a new
+/// theme recolors everything drawn afterwards.
+///
+/// Example:
+///
+/// ```
+/// # use cod::prelude::*;
+/// use cod::color::Color;
+/// use cod::palette::Theme;
+///
+/// let mut theme = Theme::new();
+/// theme.set("error", Color::BrightRed).set("accent", 12);
+/// theme.apply();
+///
+/// color::themed("error");
+/// cod::text("something went wrong", 0, 0);
+/// color::de::fg();
+/// ```
+pub fn themed(name: &str) {
+    profile!();
+    let color = crate::palette::get(name);
+    #[cfg(not(feature = "color_stack"))]
+    color.map_or_else(raw::reset_fg, raw::color_fg);
+
+    #[cfg(feature = "color_stack")]
+    stack::fg::push::color(color);
+}
+
+/// Set the background color to the one named `name` in the active theme
+/// (see [`crate::palette`]), or the default color if it isn't set. See
+/// [`themed`].
+pub fn themed_bg(name: &str) {
+    profile!();
+    let color = crate::palette::get(name);
+    #[cfg(not(feature = "color_stack"))]
+    color.map_or_else(raw::reset_bg, raw::color_bg);
+
+    #[cfg(feature = "color_stack")]
+    stack::bg::push::color(color);
+}
+
+/// Re-emit the current colors, e.g. after drawing something with different
+/// colors. With `color_stack`, these are the colors on top of the stacks;
+/// otherwise, they're the defaults.
+pub(crate) fn restore() {
+    #[cfg(feature = "color_stack")]
+    {
+        stack::fg::restore();
+        stack::bg::restore();
+    }
+
+    #[cfg(not(feature = "color_stack"))]
+    {
+        raw::reset_fg();
+        raw::reset_bg();
+    }
+}
+
+/// Returns the current foreground and background colors, i.e. what
+/// [`restore`] re-emits, or `None` for the defaults.
+pub(crate) fn current() -> (Option<Color>, Option<Color>) {
+    #[cfg(feature = "color_stack")]
+    let current = (stack::fg::top(), stack::bg::top());
+
+    #[cfg(not(feature = "color_stack"))]
+    let current = (None, None);
+
+    current
+}
+
+/// Emits color escapes, applying any active filters (see [`crate::a11y`]).
+pub(crate) mod raw {
+    use super::convert::{ansi_to_rgb, rgb_to_16, rgb_to_256};
+    use super::{depth, Color, ColorDepth};
+
+    const FG: u8 = 38;
+    const BG: u8 = 48;
+
+    /// Returns the SGR parameters setting a color, e.g. `38;5;196`, or
+    /// `None` if colors are turned off (see [`super::set_depth`]).
+    pub fn code(c: Color, background: bool) -> Option<String> {
+        if effective_depth() == ColorDepth::NoColor {
+            return None;
+        }
+
+        let layer = if background { BG } else { FG };
+        Some(match c.normalize() {
+            Color::Rgb(r, g, b) => rgb(layer, r, g, b),
+            other => ansi(layer, other.index().unwrap_or(0)),
+        })
+    }
+
+    /// Returns the SGR parameters setting a color at a given depth, without
+    /// any filters. Used to downgrade output that was already formatted, so
+    /// filters were applied the first time around.
+    pub fn code_at(c: Color, background: bool, depth: ColorDepth) -> Option<String> {
+        let layer = if background { BG } else { FG };
+        Some(match (c.normalize(), depth) {
+            (_, ColorDepth::NoColor) => return None,
+            (Color::Rgb(r, g, b), ColorDepth::TrueColor) => format!("{layer};2;{r};{g};{b}"),
+            (Color::Rgb(r, g, b), ColorDepth::Colors256) => {
+                format!("{layer};5;{}", rgb_to_256(r, g, b))
+            }
+            (Color::Rgb(r, g, b), ColorDepth::Colors16) => basic(layer, rgb_to_16(r, g, b)),
+            (other, ColorDepth::Colors16) => {
+                let c = other.index().unwrap_or(0);
+                let (r, g, b) = ansi_to_rgb(c);
+                basic(layer, if c < 16 { c } else { rgb_to_16(r, g, b) })
+            }
+            (other, _) => format!("{layer};5;{}", other.index().unwrap_or(0)),
+        })
+    }
+
+    fn ansi(layer: u8, c: u8) -> String {
+        if crate::a11y::is_high_contrast() {
+            let (r, g, b) = ansi_to_rgb(c);
+            contrast(layer, r, g, b)
+        } else if let Some(d) = crate::a11y::simulated() {
+            let (r, g, b) = ansi_to_rgb(c);
+            let (r, g, b) = d.simulate(r, g, b);
+            palette(layer, rgb_to_256(r, g, b))
+        } else {
+            palette(layer, c)
+        }
+    }
+
+    /// Returns the code for a palette color, as one of the basic 16 if
+    /// that's all there is.
+    fn palette(layer: u8, c: u8) -> String {
+        if effective_depth() == ColorDepth::Colors16 {
+            let (r, g, b) = ansi_to_rgb(c);
+            basic(layer, if c < 16 { c } else { rgb_to_16(r, g, b) })
+        } else {
+            format!("{layer};5;{c}")
+        }
+    }
+
+    /// Returns the depth to emit colors at: the basic colors at most in
+    /// compat mode (see [`crate::compat`]).
+    fn effective_depth() -> ColorDepth {
+        if crate::compat::is_enabled() {
+            depth().min(ColorDepth::Colors16)
+        } else {
+            depth()
+        }
+    }
+
+    /// Returns the original SGR code for one of the basic 16 colors, or for
+    /// its normal counterpart in compat mode.
+    fn basic(layer: u8, c: u8) -> String {
+        let base = if layer == BG { 40 } else { 30 };
+        if c < 8 || crate::compat::is_enabled() {
+            (base + c % 8).to_string()
+        } else {
+            (base + 60 + c - 8).to_string()
+        }
+    }
+
+    fn rgb(layer: u8, r: u8, g: u8, b: u8) -> String {
+        if crate::a11y::is_high_contrast() {
+            return contrast(layer, r, g, b);
+        }
+
+        let (r, g, b) = match crate::a11y::simulated() {
+            Some(d) => d.simulate(r, g, b),
+            None => (r, g, b),
+        };
+        match effective_depth() {
+            ColorDepth::Colors16 => basic(layer, rgb_to_16(r, g, b)),
+            ColorDepth::Colors256 => palette(layer, rgb_to_256(r, g, b)),
+            _ => format!("{layer};2;{r};{g};{b}"),
+        }
+    }
+
+    fn contrast(layer: u8, r: u8, g: u8, b: u8) -> String {
+        match crate::a11y::contrast_color(layer == BG, r, g, b) {
+            Some(c) => palette(layer, c),
+            // the terminal's default foreground suits its own background
+            None => "39".to_string(),
+        }
+    }
+
+    #[cfg(not(feature = "color_stack"))]
+    pub fn fg(c: impl Into<Color>) {
+        color_fg(c.into());
+    }
+
+    #[cfg(not(feature = "color_stack"))]
+    pub fn bg(c: impl Into<Color>) {
+        color_bg(c.into());
+    }
+
+    #[cfg(not(feature = "color_stack"))]
+    pub fn tc_fg(r: u8, g: u8, b: u8) {
+        color_fg(Color::Rgb(r, g, b));
+    }
+
+    #[cfg(not(feature = "color_stack"))]
+    pub fn tc_bg(r: u8, g: u8, b: u8) {
+        color_bg(Color::Rgb(r, g, b));
+    }
+
+    pub fn color_fg(c: Color) {
+        if let Some(code) = code(c, false) {
+            crate::escape(format_args!("{code}m"));
+        }
+    }
+
+    pub fn color_bg(c: Color) {
+        if let Some(code) = code(c, true) {
+            crate::escape(format_args!("{code}m"));
+        }
+    }
+
+    pub fn reset_fg() {
+        crate::escape("39m");
+    }
+
+    pub fn reset_bg() {
+        crate::a11y::reset_background();
+        crate::escape("49m");
+    }
+}
+
+/// Conversions between 256-color and true-color values.
+pub(crate) mod convert {
+    /// The levels used by each channel of the 6x6x6 color cube.
+    const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    /// xterm's default values for the 16 basic colors.
+    const BASIC: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+
+    /// Returns the (approximate) RGB value of a 256-color.
+    pub fn ansi_to_rgb(c: u8) -> (u8, u8, u8) {
+        match c {
+            0..=15 => BASIC[c as usize],
+            16..=231 => {
+                let c = c - 16;
+                (
+                    CUBE[(c / 36) as usize],
+                    CUBE[(c / 6 % 6) as usize],
+                    CUBE[(c % 6) as usize],
+                )
+            }
+            _ => {
+                let v = 8 + (c - 232) * 10;
+                (v, v, v)
+            }
+        }
+    }
+
+    fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+        let d = |a: u8, b: u8| u32::from(a.abs_diff(b)).pow(2);
+        d(r1, r2) + d(g1, g2) + d(b1, b2)
+    }
+
+    fn nearest_level(v: u8) -> u8 {
+        match v {
+            0..=47 => 0,
+            48..=114 => 1,
+            _ => (v - 35) / 40,
+        }
+    }
+
+    /// Returns the closest of the 16 basic colors, assuming xterm's defaults
+    /// for them.
+    pub fn rgb_to_16(r: u8, g: u8, b: u8) -> u8 {
+        (0..16u8)
+            .min_by_key(|&c| distance(BASIC[c as usize], (r, g, b)))
+            .unwrap_or(0)
+    }
+
+    /// Returns the closest color in the cube or grayscale ramp of the
+    /// 256-color palette, ignoring the 16 terminal-defined basic colors.
+    pub fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+        let (ri, gi, bi) = (nearest_level(r), nearest_level(g), nearest_level(b));
+        let cube = 16 + 36 * ri + 6 * gi + bi;
+
+        let avg = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+        #[allow(clippy::cast_possible_truncation)]
+        let gray = 232 + (avg.saturating_sub(3) / 10).min(23) as u8;
+
+        let target = (r, g, b);
+        if distance(ansi_to_rgb(gray), target) < distance(ansi_to_rgb(cube), target) {
+            gray
+        } else {
+            cube
+        }
+    }
+}
+
 /// Decolor your text.
 pub mod de {
     /// Reset the foreground color.
     ///
     /// With feature `color_stack`, instead pops the most recent color off.
     pub fn fg() {
+        profile!();
         #[cfg(feature = "color_stack")]
         {
             super::stack::fg::pop();
@@ -187,7 +970,7 @@ pub mod de {
 
         #[cfg(not(feature = "color_stack"))]
         {
-            crate::escape("39m");
+            super::raw::reset_fg();
         }
     }
 
@@ -195,6 +978,7 @@ pub mod de {
     ///
     /// With feature `color_stack`, instead pops the most recent color off.
     pub fn bg() {
+        profile!();
         #[cfg(feature = "color_stack")]
         {
             super::stack::bg::pop();
@@ -202,7 +986,7 @@ pub mod de {
 
         #[cfg(not(feature = "color_stack"))]
         {
-            crate::escape("49m");
+            super::raw::reset_bg();
         }
     }
 
@@ -210,11 +994,83 @@ pub mod de {
     ///
     /// With feature `color_stack`, pops the most recent colors off.
     pub fn all() {
+        profile!();
         fg();
         bg();
     }
 }
 
+/// Resets the foreground color when dropped; see [`fg_guard`].
+#[derive(Debug)]
+#[must_use = "the color is reset immediately unless the guard is kept"]
+pub struct FgGuard(());
+
+impl Drop for FgGuard {
+    fn drop(&mut self) {
+        de::fg();
+    }
+}
+
+/// Resets the background color when dropped; see [`bg_guard`].
+#[derive(Debug)]
+#[must_use = "the color is reset immediately unless the guard is kept"]
+pub struct BgGuard(());
+
+impl Drop for BgGuard {
+    fn drop(&mut self) {
+        de::bg();
+    }
+}
+
+/// Set the foreground color until the returned guard is dropped.
+///
+/// With feature `color_stack`, dropping the guard pops the color off,
+/// so guards nest, and an early return can't leave a color pushed.
+///
+/// Example:
+///
+/// ```
+/// # use cod::prelude::*;
+/// use cod::color::Color;
+///
+/// fn warn(message: &str) {
+///     let _yellow = color::fg_guard(Color::Yellow);
+///     cod::text("warning: ", 0, 0);
+///     {
+///         let _bright = color::fg_guard(Color::BrightYellow);
+///         cod::text(message, 9, 0);
+///     }
+///     cod::text("!", 9 + message.len() as u32, 0);
+/// }
+///
+/// warn("low on disk space");
+/// ```
+pub fn fg_guard(color: impl Into<Color>) -> FgGuard {
+    fg(color);
+    FgGuard(())
+}
+
+/// Set the background color until the returned guard is dropped. See
+/// [`fg_guard`].
+pub fn bg_guard(color: impl Into<Color>) -> BgGuard {
+    bg(color);
+    BgGuard(())
+}
+
+/// Set the foreground color, using true-color, until the returned guard is
+/// dropped. See [`fg_guard`].
+pub fn tc_fg_guard(r: u8, g: u8, b: u8) -> FgGuard {
+    tc_fg(r, g, b);
+    FgGuard(())
+}
+
+/// Set the background color, using true-color, until the returned guard is
+/// dropped. See [`fg_guard`].
+pub fn tc_bg_guard(r: u8, g: u8, b: u8) -> BgGuard {
+    tc_bg(r, g, b);
+    BgGuard(())
+}
+
 /// Color your text through closures.
 ///
 /// Example:
@@ -237,12 +1093,7 @@ pub mod with {
                 /// then run the function, then reset it.
                 pub fn $color($($arg: $typ,)+ f: impl FnOnce()) {
                     super::$color($($arg,)+);
-                    #[cfg(feature = "color_stack")]
-                    super::stack::$de::push::$color($($arg,)+);
                     (f)();
-                    #[cfg(feature = "color_stack")]
-                    super::stack::$de::pop();
-                    #[cfg(not(feature = "color_stack"))]
                     super::de::$de();
                 }
             )+
@@ -250,8 +1101,8 @@ pub mod with {
     }
 
     with_color![
-        fg, fg, "foreground color,", [color: u8],
-        bg, bg, "background color,", [color: u8],
+        fg, fg, "foreground color,", [color: impl Into<super::Color>],
+        bg, bg, "background color,", [color: impl Into<super::Color>],
         tc_fg, fg, "foreground color (using true-color),", [r: u8, g: u8, b: u8],
         tc_bg, bg, "background color (using true-color),", [r: u8, g: u8, b: u8],
     ];
@@ -0,0 +1,120 @@
+//! A fallback for ancient terminals, which can only show ASCII and a few
+//! colors.
+//!
+//! In compat mode, box-drawing and block characters are replaced with ASCII
+//! approximations (see [`ascii`]) as they're drawn, and every color is
+//! downgraded to one of the basic 8 (bright colors become their normal
+//! counterparts). Everything else works as usual, so an app drawn with
+//! [`crate::rect::line`] still gets its borders, just made of `+`, `-`, and
+//! `|`.
+//!
+//! Compat mode is turned on automatically for `TERM=vt100`, `vt52`, and
+//! `dumb`, or if `COD_COMPAT` is set (to anything but `0`). Use
+//! [`set_enabled`] to decide for yourself.
+//!
+//! Example:
+//!
+//! ```
+//! # use cod::prelude::*;
+//! compat::set_enabled(true);
+//!
+//! let output = target::capture(|| cod::text("┌─┐", 0, 0));
+//! assert_eq!(output, "\x1b[1;1H+\x1b[1;2H-\x1b[1;3H+");
+//! # compat::set_enabled(false);
+//! ```
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Whether compat mode is on: [`UNKNOWN`] until it's set or detected.
+static MODE: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+const UNKNOWN: u8 = 0;
+const OFF: u8 = 1;
+const ON: u8 = 2;
+
+/// Turn compat mode on or off, overriding what was detected.
+pub fn set_enabled(enabled: bool) {
+    MODE.store(if enabled { ON } else { OFF }, Ordering::Relaxed);
+}
+
+/// Returns true if compat mode is on. Unless it was set with
+/// [`set_enabled`], it's detected from the environment the first time this
+/// is called (see [`detect`]).
+#[must_use]
+pub fn is_enabled() -> bool {
+    match MODE.load(Ordering::Relaxed) {
+        UNKNOWN => {
+            let enabled = detect();
+            // don't overwrite a mode set in the meantime
+            let _ = MODE.compare_exchange(
+                UNKNOWN,
+                if enabled { ON } else { OFF },
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            );
+            MODE.load(Ordering::Relaxed) == ON
+        }
+        mode => mode == ON,
+    }
+}
+
+/// Guess whether the terminal needs compat mode from the environment.
+///
+/// Returns true if `COD_COMPAT` is set to anything but `0` (or empty), or
+/// if `TERM` is `vt100`, `vt52`, or `dumb`. A `COD_COMPAT` of `0` always
+/// returns false.
+#[must_use]
+pub fn detect() -> bool {
+    if let Some(var) = std::env::var_os("COD_COMPAT") {
+        if !var.is_empty() {
+            return var != "0";
+        }
+    }
+
+    matches!(
+        std::env::var("TERM").unwrap_or_default().as_str(),
+        "vt100" | "vt52" | "dumb"
+    )
+}
+
+/// Returns the ASCII approximation of a box-drawing or block character:
+/// `-` and `|` for horizontal and vertical lines, `+` for corners and
+/// junctions, `#` for solid blocks, and `.` for light shading. Any other
+/// character is returned as it is.
+///
+/// Example:
+///
+/// ```
+/// # use cod::prelude::*;
+/// assert_eq!(compat::ascii('═'), '-');
+/// assert_eq!(compat::ascii('╭'), '+');
+/// assert_eq!(compat::ascii('█'), '#');
+/// assert_eq!(compat::ascii('x'), 'x');
+/// ```
+#[must_use]
+pub fn ascii(c: char) -> char {
+    match c {
+        '─' | '━' | '┄' | '┅' | '┈' | '┉' | '╌' | '╍' | '═' | '╴' | '╶' | '╸' | '╺' | '╼' | '╾' => {
+            '-'
+        }
+        '│' | '┃' | '┆' | '┇' | '┊' | '┋' | '╎' | '╏' | '║' | '╵' | '╷' | '╹' | '╻' | '╽' | '╿' => {
+            '|'
+        }
+        '╱' => '/',
+        '╲' => '\\',
+        '╳' => 'X',
+        '\u{2500}'..='\u{257f}' => '+',
+        '░' => '.',
+        '\u{2580}'..='\u{259f}' => '#',
+        c => c,
+    }
+}
+
+/// Returns a character to draw, replaced with [`ascii`] in compat mode.
+pub(crate) fn filter(c: char) -> char {
+    if is_enabled() {
+        ascii(c)
+    } else {
+        c
+    }
+}
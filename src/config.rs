@@ -0,0 +1,224 @@
+//! Loading user configuration for cod-based apps.
+//!
+//! Only enabled on feature `config`.
+//!
+//! A config file is TOML, with three optional tables: `theme` (in the same
+//! format as a [theme file](crate::palette)), `keys` (mapping action names to
+//! key combinations), and `options` (anything else your app wants):
+//!
+//! ```toml
+//! [theme]
+//! error = "#ff5f5f"
+//! accent = 12
+//!
+//! [keys]
+//! quit = "q"
+//! save = "ctrl+s"
+//!
+//! [options]
+//! mouse = true
+//! tick_rate = 30
+//! ```
+//!
+//! Config files are looked for in the platform's standard location; see
+//! [`path`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::palette::{RawTheme, Theme};
+
+/// An error encountered while loading a config.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file couldn't be read.
+    Io(io::Error),
+    /// The file's contents weren't a valid config.
+    Parse(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read config: {e}"),
+            Self::Parse(e) => write!(f, "failed to parse config: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    theme: Option<RawTheme>,
+    #[serde(default)]
+    keys: HashMap<String, String>,
+    #[serde(default)]
+    options: toml::Table,
+}
+
+/// A user's configuration: theme, key bindings, and other options.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Config {
+    theme: Option<Theme>,
+    keys: HashMap<String, String>,
+    options: toml::Table,
+}
+
+impl Config {
+    /// Load the config for an app from the standard location (see [`path`]).
+    ///
+    /// If there is no config file, returns an empty config.
+    ///
+    /// # Errors
+    ///
+    /// If the file exists but can't be read or isn't a valid config, returns
+    /// an error.
+    pub fn load(app: &str) -> Result<Self, LoadError> {
+        match path(app) {
+            Some(path) if path.exists() => Self::load_from(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Load a config from a specific file.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read or isn't a valid config, returns an error.
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse a config from TOML.
+    ///
+    /// # Errors
+    ///
+    /// If the text isn't a valid config, returns an error.
+    pub fn from_toml(text: &str) -> Result<Self, LoadError> {
+        let raw: RawConfig = toml::from_str(text).map_err(|e| LoadError::Parse(e.to_string()))?;
+
+        let theme = raw
+            .theme
+            .map(Theme::try_from)
+            .transpose()
+            .map_err(|e| LoadError::Parse(e.to_string()))?;
+
+        Ok(Self {
+            theme,
+            keys: raw.keys,
+            options: raw.options,
+        })
+    }
+
+    /// Returns the theme, if the config has one.
+    #[must_use]
+    pub fn theme(&self) -> Option<&Theme> {
+        self.theme.as_ref()
+    }
+
+    /// Returns the key combination bound to an action, if the config binds
+    /// one.
+    #[must_use]
+    pub fn key(&self, action: &str) -> Option<&str> {
+        self.keys.get(action).map(String::as_str)
+    }
+
+    /// Returns all the actions and their key combinations, in no particular
+    /// order.
+    pub fn keys(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.keys.iter().map(|(a, k)| (a.as_str(), k.as_str()))
+    }
+
+    /// Returns an option converted to the requested type, or `None` if it's
+    /// missing or of the wrong type.
+    #[must_use]
+    pub fn option<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        self.options.get(name)?.clone().try_into().ok()
+    }
+
+    /// Returns an option converted to the requested type, or `default` if
+    /// it's missing or of the wrong type.
+    #[must_use]
+    pub fn option_or<T: DeserializeOwned>(&self, name: &str, default: T) -> T {
+        self.option(name).unwrap_or(default)
+    }
+
+    /// Move the bindings in a keymap to the key combinations given by the
+    /// config. `action` maps action names from the config to your actions;
+    /// names it returns `None` for are ignored, as are actions the keymap
+    /// doesn't bind.
+    ///
+    /// Only enabled on features `config` and `crossterm`.
+    ///
+    /// # Errors
+    ///
+    /// If the config contains an invalid key combination, returns an error
+    /// and leaves the keymap untouched.
+    #[cfg(feature = "crossterm")]
+    pub fn rebind<A, F>(
+        &self,
+        keymap: &mut crate::keymap::Keymap<A>,
+        mut action: F,
+    ) -> Result<(), LoadError>
+    where
+        A: PartialEq,
+        F: FnMut(&str) -> Option<A>,
+    {
+        let mut keys = Vec::with_capacity(self.keys.len());
+        for (name, key) in &self.keys {
+            let key: crate::read::Key = key
+                .trim()
+                .parse()
+                .map_err(|_| LoadError::Parse(format!("invalid key `{key}` for `{name}`")))?;
+            keys.push((name, key.code, key.modifiers));
+        }
+
+        for (name, code, modifiers) in keys {
+            if let Some(action) = action(name) {
+                keymap.rebind(&action, code, modifiers);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns where the config file for an app is expected to be, if the
+/// platform's config directory can be found.
+///
+/// On Windows, this is `%APPDATA%\<app>\config.toml`. Everywhere else, it's
+/// `$XDG_CONFIG_HOME/<app>/config.toml`, falling back to
+/// `~/.config/<app>/config.toml`.
+#[must_use]
+pub fn path(app: &str) -> Option<PathBuf> {
+    let dir = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+    }?;
+
+    Some(dir.join(app).join("config.toml"))
+}
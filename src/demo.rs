@@ -0,0 +1,318 @@
+//! A showcase of what cod can draw, doubling as a terminal compatibility
+//! test.
+//!
+//! [`crate::demo()`] runs through every [`Page`] in turn: shapes, colors,
+//! styles and characters, widgets, and finally an echo of keys and mouse
+//! events. If something looks wrong on a page, the terminal doesn't support
+//! it (or needs a different [profile](crate::profile)).
+//!
+//! To offer the same check in your own program, e.g. on a "test your
+//! terminal" screen, draw single pages with [`draw`].
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use cod::demo::{self, Page};
+//! use cod::rect::Rect;
+//!
+//! for page in Page::ALL {
+//!     cod::clear::all();
+//!     cod::text(page.title(), 0, 0);
+//!     demo::draw(page, Rect::new(0, 2, 80, 20));
+//!     cod::flush();
+//! }
+//! ```
+//!
+//! Only enabled on feature `crossterm`.
+
+use crate::bigtext::{self, Font};
+use crate::color::{self, Color};
+use crate::profile::Profile;
+use crate::progress::Bar;
+use crate::rect::{self, BoxStyle, Rect, TextBox};
+use crate::style::Style;
+use crate::{braille, halfblock};
+
+/// A page of the demo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Page {
+    /// Borders, lines, circles, and other shapes.
+    Shapes,
+    /// The 16 basic colors, the 256-color palette, and true-color.
+    Colors,
+    /// Bold, italic, and other styles, and a sample of Unicode.
+    Styles,
+    /// Text boxes, progress bars, big text, and pixel drawing.
+    Widgets,
+    /// Instructions for the echo of keys and mouse events. The echo itself
+    /// only happens in [`crate::demo()`].
+    Input,
+}
+
+impl Page {
+    /// Every page, in the order they're shown.
+    pub const ALL: [Self; 5] = [
+        Self::Shapes,
+        Self::Colors,
+        Self::Styles,
+        Self::Widgets,
+        Self::Input,
+    ];
+
+    /// Returns the page's title, e.g. `"Shapes"`.
+    #[must_use]
+    pub const fn title(self) -> &'static str {
+        match self {
+            Self::Shapes => "Shapes",
+            Self::Colors => "Colors",
+            Self::Styles => "Styles and characters",
+            Self::Widgets => "Widgets",
+            Self::Input => "Input",
+        }
+    }
+}
+
+/// Draw a page of the demo onto the screen, with its top-left corner at
+/// that of `area`. Pages are laid out for an area at least 60 columns wide
+/// and 16 rows high; some parts stretch to fill a wider one.
+pub fn draw(page: Page, area: Rect) {
+    profile!();
+    let (x, y) = (area.x, area.y);
+    match page {
+        Page::Shapes => shapes(x, y),
+        Page::Colors => colors(x, y, area.width),
+        Page::Styles => styles(x, y),
+        Page::Widgets => widgets(x, y),
+        Page::Input => {
+            crate::text(
+                "Press keys, click, drag, or scroll; they're shown below.",
+                x,
+                y,
+            );
+            crate::text("Press Esc to go on.", x, y + 1);
+        }
+    }
+}
+
+fn shapes(x: u32, y: u32) {
+    rect::border(Rect::new(x, y, 12, 6));
+    crate::circle('o', x + 20, y + 3, 3);
+    crate::triangle_fill('*', x + 27, y + 5, x + 32, y, x + 37, y + 5);
+    crate::ellipse('.', x + 48, y + 3, 8, 2);
+
+    for (label, offset) in [
+        ("border", 3),
+        ("circle", 17),
+        ("triangle", 28),
+        ("ellipse", 45),
+    ] {
+        crate::text(label, x + offset, y + 7);
+    }
+
+    crate::line('#', x, y + 9, x + 12, y + 13);
+    let _ = rect::path(
+        &[
+            (x + 17, y + 9),
+            (x + 27, y + 9),
+            (x + 27, y + 13),
+            (x + 37, y + 13),
+        ],
+        BoxStyle::Rounded,
+    );
+    crate::polygon_fill('%', &[(x + 42, y + 9), (x + 56, y + 10), (x + 50, y + 13)]);
+
+    for (label, offset) in [("line", 4), ("path", 20), ("polygon", 46)] {
+        crate::text(label, x + offset, y + 15);
+    }
+}
+
+fn colors(x: u32, y: u32, width: u32) {
+    crate::text("16 colors", x, y);
+    for i in 0..16u8 {
+        let style = Style::new().bg(Color::Ansi(i));
+        crate::text_styled("   ", x + u32::from(i) * 3, y + 1, style);
+    }
+
+    crate::text("256 colors", x, y + 3);
+    for i in 16..=255u8 {
+        let (col, row) = if i < 232 {
+            (u32::from(i - 16) % 36, u32::from(i - 16) / 36)
+        } else {
+            (u32::from(i - 232), 6)
+        };
+        crate::text_styled(" ", x + col, y + 4 + row, Style::new().bg(Color::Ansi(i)));
+    }
+
+    crate::text("true color", x, y + 12);
+    let width = width.clamp(1, 72);
+    for col in 0..width {
+        let hue = f64::from(col) * 360.0 / f64::from(width);
+        let style = Style::new().bg(color::hsv(hue, 0.8, 0.9));
+        crate::text_styled(" ", x + col, y + 13, style);
+    }
+
+    crate::text(
+        format!(
+            "color depth: {:?}, suggested profile: {:?}",
+            color::depth(),
+            Profile::detect()
+        ),
+        x,
+        y + 15,
+    );
+}
+
+fn styles(x: u32, y: u32) {
+    let styles = [
+        ("bold", Style::new().bold()),
+        ("faint", Style::new().faint()),
+        ("italic", Style::new().italic()),
+        ("underline", Style::new().underline()),
+        ("strike", Style::new().strike()),
+        ("all of them", Style::new().bold().italic().underline()),
+    ];
+    for ((name, style), row) in styles.into_iter().zip(y..) {
+        crate::text_styled(name, x, row, style);
+    }
+
+    crate::text_shadow("shadow", x + 16, y + 1, Color::Ansi(8), (1, 1));
+    crate::text_outline("outline", x + 17, y + 4, Color::Ansi(4));
+
+    crate::text("lines   ─│┌┐└┘ ╔═╗ ╭╮ ┏━┓", x, y + 8);
+    crate::text("blocks  ░▒▓█ ▀▄▌▐", x, y + 9);
+    crate::text("braille ⠁⠃⠇⡇⣇⣧⣷⣿", x, y + 10);
+    crate::text("accents é ñ ü ø", x, y + 11);
+    crate::text("wide    日本語 🙂", x, y + 12);
+    crate::text("If any of these look wrong, try COD_COMPAT=1.", x, y + 14);
+}
+
+fn widgets(x: u32, y: u32) {
+    rect::text_box(
+        "Text boxes wrap what's inside them, with a border and padding.",
+        x,
+        y,
+        x + 27,
+        y + 5,
+        TextBox::new(),
+    );
+
+    let mut bar = Bar::new(100);
+    bar.set(60);
+    bar.draw(x, y + 7, 28);
+
+    bigtext::text('#', "cod", x, y + 9, Font::Small, 1);
+
+    let mut plot = braille::Canvas::new(28, 4);
+    for dx in 0..plot.dot_width() {
+        let dy = 7.5 - 7.5 * (f64::from(dx) / 6.0).sin();
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        plot.set(dx, dy.round() as u32);
+    }
+    plot.draw(x + 31, y);
+
+    let mut pixels = halfblock::Canvas::new(28, 4);
+    for py in 0..pixels.pixel_height() {
+        for px in 0..pixels.width() {
+            let hue = f64::from(px) * 360.0 / f64::from(pixels.width());
+            let value = 1.0 - f64::from(py) / f64::from(pixels.pixel_height());
+            pixels.set(px, py, color::hsv(hue, 1.0, value));
+        }
+    }
+    pixels.draw(x + 31, y + 6);
+
+    crate::text("braille", x + 31, y + 4);
+    crate::text("half blocks", x + 31, y + 10);
+}
+
+/// Run through every page, waiting for a key between them, and echo input
+/// on the last. See [`crate::demo()`].
+pub(crate) fn run() -> std::io::Result<()> {
+    let mut terminal = crate::Terminal::new()?;
+    crate::read::enable_mouse_capture();
+    let result = pages(&mut terminal);
+    crate::read::disable_mouse_capture();
+    terminal.flush()?;
+    result
+}
+
+fn pages(terminal: &mut crate::Terminal) -> std::io::Result<()> {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind, MouseEventKind};
+
+    use crate::read::{Key, KeyExt};
+
+    let mut log: Vec<String> = Vec::new();
+    let mut i = 0;
+    while let Some(&page) = Page::ALL.get(i) {
+        let (width, height) = terminal.size();
+        let area = Rect::new(1, 2, width.saturating_sub(2), height.saturating_sub(3));
+
+        terminal.clear();
+        let keys = if page == Page::Input {
+            "esc: next, ctrl+c: quit"
+        } else {
+            "any key: next, backspace: back, q: quit"
+        };
+        let header = format!(
+            "cod demo - {} ({}/{}) - {keys}",
+            page.title(),
+            i + 1,
+            Page::ALL.len()
+        );
+        terminal.text_styled(header, 0, 0, Style::new().bold());
+        draw(page, area);
+
+        if page == Page::Input {
+            let rows = area.height.saturating_sub(3) as usize;
+            let shown = &log[log.len().saturating_sub(rows)..];
+            for (line, y) in shown.iter().zip(area.y + 3..) {
+                terminal.text(line, area.x, y);
+            }
+        }
+
+        terminal.normal();
+        terminal.flush()?;
+
+        match event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => {
+                if key.is_ctrl('c') {
+                    break;
+                }
+
+                match (page, key.code) {
+                    (Page::Input, KeyCode::Esc) => i += 1,
+                    (Page::Input, _) => log.push(format!("key {}", Key::from(key))),
+                    (_, KeyCode::Char('q') | KeyCode::Esc) => break,
+                    (_, KeyCode::Backspace | KeyCode::Left) => i = i.saturating_sub(1),
+                    _ => i += 1,
+                }
+            }
+            Event::Mouse(mouse) if page == Page::Input => {
+                let (x, y) = crate::read::mouse_position(&mouse);
+                let held = if mouse.modifiers.is_empty() {
+                    String::new()
+                } else {
+                    format!(" with {:?}", mouse.modifiers)
+                };
+                let line = format!("mouse {:?} at {x}, {y}{held}", mouse.kind);
+
+                // keep movement from flooding out everything else
+                let moved = matches!(mouse.kind, MouseEventKind::Moved);
+                if moved
+                    && log
+                        .last()
+                        .is_some_and(|last| last.starts_with("mouse Moved"))
+                {
+                    log.pop();
+                }
+                log.push(line);
+            }
+            Event::Resize(..) => {
+                terminal.refresh_size();
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
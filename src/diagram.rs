@@ -0,0 +1,336 @@
+//! Boxes-and-lines diagrams, e.g. of dependencies or pipelines.
+//!
+//! A [`Diagram`] is a set of labeled nodes and the edges between them. It
+//! lays them out in layers from top to bottom, so every edge points down,
+//! then draws each node as a box and each edge as a line from the bottom of
+//! one box to the top of another (see [`rect::path`]).
+//!
+//! Edges spanning more than one layer are routed between the boxes of the
+//! layers they pass, and edges that would point back up (i.e. in a cycle)
+//! are drawn as though they were reversed.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use cod::diagram::Diagram;
+//! use cod::rect::{BoxStyle, Rect};
+//!
+//! let mut diagram = Diagram::new();
+//! let parse = diagram.node("parse");
+//! let check = diagram.node("check");
+//! let build = diagram.node("build");
+//! diagram
+//!     .style(BoxStyle::Light)
+//!     .edge(parse, check)
+//!     .edge(parse, build);
+//!
+//! //      ┌───────┐
+//! //      │ parse │
+//! //      └───┬───┘
+//! //          │
+//! //     ┌────┴─────┐
+//! //     │          │
+//! // ┌───┴───┐  ┌───┴───┐
+//! // │ check │  │ build │
+//! // └───────┘  └───────┘
+//! assert_eq!(diagram.size(), (20, 9));
+//! assert_eq!(diagram.rects()[build], Rect::new(11, 6, 9, 3));
+//! diagram.draw(0, 0);
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::canvas::Canvas;
+use crate::rect::{self, BoxStyle, Rect};
+
+/// The space between the boxes in a layer.
+const H_GAP: u32 = 2;
+/// The space between layers, where edges turn.
+const V_GAP: u32 = 3;
+/// The height of a box.
+const BOX_HEIGHT: u32 = 3;
+
+/// A graph of labeled nodes, drawn as boxes connected by lines.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Diagram {
+    labels: Vec<String>,
+    edges: Vec<(usize, usize)>,
+    style: BoxStyle,
+}
+
+/// Something taking up space in a layer: a node, or an edge passing through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Slot {
+    node: Option<usize>,
+    layer: usize,
+    width: u32,
+}
+
+/// Where everything in a diagram goes, relative to its top-left corner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Layout {
+    rects: Vec<Rect>,
+    size: (u32, u32),
+    cells: BTreeMap<(u32, u32), char>,
+}
+
+impl Diagram {
+    /// Create an empty diagram.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the style of line to draw with.
+    pub fn style(&mut self, style: BoxStyle) -> &mut Self {
+        self.style = style;
+        self
+    }
+
+    /// Add a node, returning its index for use with [`Diagram::edge`].
+    pub fn node<S: Into<String>>(&mut self, label: S) -> usize {
+        self.labels.push(label.into());
+        self.labels.len() - 1
+    }
+
+    /// Add an edge from one node to another. Edges from a node to itself
+    /// aren't drawn.
+    ///
+    /// # Panics
+    ///
+    /// If either node doesn't exist, panics.
+    pub fn edge(&mut self, from: usize, to: usize) -> &mut Self {
+        assert!(
+            from < self.labels.len() && to < self.labels.len(),
+            "no such node in diagram"
+        );
+        self.edges.push((from, to));
+        self
+    }
+
+    /// Returns the box around each node, relative to the top-left corner of
+    /// the diagram, in the order they were added.
+    #[must_use]
+    pub fn rects(&self) -> Vec<Rect> {
+        self.layout().rects
+    }
+
+    /// Returns the width and height of the diagram.
+    #[must_use]
+    pub fn size(&self) -> (u32, u32) {
+        self.layout().size
+    }
+
+    /// Draw the diagram onto the screen, with its top-left corner at the
+    /// given position.
+    pub fn draw(&self, x: u32, y: u32) {
+        profile!();
+        let layout = self.layout();
+        for ((cy, cx), c) in layout.cells {
+            crate::pixel(c, x + cx, y + cy);
+        }
+        for (label, r) in self.labels.iter().zip(&layout.rects) {
+            crate::text(label, x + r.x + 2, y + r.y + 1);
+        }
+    }
+
+    /// Draw the diagram onto a canvas, with its top-left corner at the given
+    /// position.
+    pub fn draw_on(&self, canvas: &mut Canvas, x: u32, y: u32) {
+        let layout = self.layout();
+        for ((cy, cx), c) in layout.cells {
+            canvas.pixel(c, x + cx, y + cy);
+        }
+        for (label, r) in self.labels.iter().zip(&layout.rects) {
+            canvas.text(label, x + r.x + 2, y + r.y + 1);
+        }
+    }
+
+    /// Returns the edges, without loops, turned to point from earlier to
+    /// later in a topological order of the nodes (breaking cycles at the
+    /// earliest-added node).
+    fn forward_edges(&self) -> Vec<(usize, usize)> {
+        let n = self.labels.len();
+        let edges: Vec<(usize, usize)> = self
+            .edges
+            .iter()
+            .copied()
+            .filter(|&(from, to)| from != to)
+            .collect();
+
+        let mut incoming = vec![0; n];
+        for &(_, to) in &edges {
+            incoming[to] += 1;
+        }
+
+        let mut rank = vec![usize::MAX; n];
+        for next in 0..n {
+            let node = (0..n)
+                .find(|&i| rank[i] == usize::MAX && incoming[i] == 0)
+                .or_else(|| (0..n).find(|&i| rank[i] == usize::MAX))
+                .unwrap_or(0);
+
+            rank[node] = next;
+            for &(from, to) in &edges {
+                if from == node && rank[to] == usize::MAX {
+                    incoming[to] -= 1;
+                }
+            }
+        }
+
+        edges
+            .into_iter()
+            .map(|(from, to)| {
+                if rank[from] < rank[to] {
+                    (from, to)
+                } else {
+                    (to, from)
+                }
+            })
+            .collect()
+    }
+
+    /// Returns a slot for each node, in the same order, followed by slots
+    /// for edges passing through layers, and the segments of each edge
+    /// between slots in adjacent layers.
+    fn slots(&self) -> (Vec<Slot>, Vec<(usize, usize)>) {
+        let n = self.labels.len();
+        let edges = self.forward_edges();
+
+        // each node goes one layer below the lowest node with an edge to it
+        let mut layers = vec![0; n];
+        for _ in 0..n {
+            let mut changed = false;
+            for &(from, to) in &edges {
+                if layers[to] < layers[from] + 1 {
+                    layers[to] = layers[from] + 1;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let mut slots: Vec<Slot> = (0..n)
+            .map(|i| Slot {
+                node: Some(i),
+                layer: layers[i],
+                width: u32::try_from(crate::width::str_width(&self.labels[i])).unwrap_or(u32::MAX)
+                    + 4,
+            })
+            .collect();
+
+        // edges passing through layers go through a slot in each of them,
+        // so every segment connects adjacent layers
+        let mut segments = Vec::new();
+        for &(from, to) in &edges {
+            let mut previous = from;
+            for layer in layers[from] + 1..layers[to] {
+                slots.push(Slot {
+                    node: None,
+                    layer,
+                    width: 1,
+                });
+                segments.push((previous, slots.len() - 1));
+                previous = slots.len() - 1;
+            }
+            segments.push((previous, to));
+        }
+
+        (slots, segments)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn layout(&self) -> Layout {
+        let n = self.labels.len();
+        let (slots, segments) = self.slots();
+
+        let depth = slots.iter().map(|s| s.layer + 1).max().unwrap_or(0);
+        let mut rows: Vec<Vec<usize>> = vec![Vec::new(); depth];
+        for (i, slot) in slots.iter().enumerate() {
+            rows[slot.layer].push(i);
+        }
+
+        // order each layer by where the slots above it connect from, to cut
+        // down on crossings
+        let mut order = vec![0; slots.len()];
+        for row in &mut rows {
+            let keys: Vec<f64> = row
+                .iter()
+                .enumerate()
+                .map(|(i, &slot)| {
+                    let above: Vec<usize> = segments
+                        .iter()
+                        .filter(|&&(_, to)| to == slot)
+                        .map(|&(from, _)| order[from])
+                        .collect();
+                    if above.is_empty() {
+                        i as f64
+                    } else {
+                        above.iter().sum::<usize>() as f64 / above.len() as f64
+                    }
+                })
+                .collect();
+
+            let mut keyed: Vec<(f64, usize)> = keys.into_iter().zip(row.iter().copied()).collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            *row = keyed.into_iter().map(|(_, slot)| slot).collect();
+            for (i, &slot) in row.iter().enumerate() {
+                order[slot] = i;
+            }
+        }
+
+        let row_width = |row: &Vec<usize>| {
+            row.iter().map(|&s| slots[s].width).sum::<u32>()
+                + H_GAP * u32::try_from(row.len().saturating_sub(1)).unwrap_or(0)
+        };
+        let width = rows.iter().map(row_width).max().unwrap_or(0);
+        let depth = u32::try_from(depth).unwrap_or(u32::MAX);
+        let height = (depth * (BOX_HEIGHT + V_GAP)).saturating_sub(V_GAP);
+
+        let mut positions = vec![Rect::default(); slots.len()];
+        for (layer, row) in (0..).zip(&rows) {
+            let mut x = (width - row_width(row)) / 2;
+            for &slot in row {
+                let w = slots[slot].width;
+                positions[slot] = Rect::new(x, layer * (BOX_HEIGHT + V_GAP), w, BOX_HEIGHT);
+                x += w + H_GAP;
+            }
+        }
+
+        let mut joins = BTreeMap::new();
+        for (slot, r) in slots.iter().zip(&positions) {
+            let (right, bottom) = (r.x + r.width - 1, r.y + r.height - 1);
+            let points = if slot.node.is_some() {
+                vec![
+                    (r.x, r.y),
+                    (right, r.y),
+                    (right, bottom),
+                    (r.x, bottom),
+                    (r.x, r.y),
+                ]
+            } else {
+                vec![(r.x, r.y), (r.x, bottom)]
+            };
+            let _ = rect::join_path(&mut joins, &points);
+        }
+        for &(from, to) in &segments {
+            let (a, b) = (positions[from], positions[to]);
+            let (ax, bx) = (a.x + a.width / 2, b.x + b.width / 2);
+            let (bottom, top) = (a.y + a.height - 1, b.y);
+            let turn = bottom + V_GAP.div_ceil(2);
+            let _ = rect::join_path(
+                &mut joins,
+                &[(ax, bottom), (ax, turn), (bx, turn), (bx, top)],
+            );
+        }
+
+        Layout {
+            rects: positions.into_iter().take(n).collect(),
+            size: (width, height),
+            cells: rect::join_chars(joins, self.style),
+        }
+    }
+}
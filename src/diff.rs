@@ -0,0 +1,384 @@
+//! Drawing line-by-line differences between texts, like `diff -u`.
+//!
+//! [`render`] compares two texts itself; [`render_unified`] draws the
+//! output of a tool like `git diff`. Either way, each line is shown with its
+//! old and new line numbers, added and removed lines are colored, and long
+//! lines are wrapped.
+//!
+//! Colors are taken from the active [theme](palette), under the names
+//! `diff.added`, `diff.removed`, and `diff.header`, falling back to green,
+//! red, and cyan.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! # use cod::rect::Rect;
+//! let old = "one\ntwo\nthree";
+//! let new = "one\n2\nthree\nfour";
+//!
+//! cod::diff::render(old, new, Rect::new(0, 0, 40, 10));
+//! cod::flush();
+//! ```
+
+use std::fmt;
+
+use crate::color::Color;
+use crate::palette;
+use crate::rect::Rect;
+use crate::spans::{Overflow, Spans};
+use crate::style::Style;
+
+/// What happened to a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// The line is in both texts.
+    Context,
+    /// The line is only in the new text.
+    Added,
+    /// The line is only in the old text.
+    Removed,
+    /// Not a line of either text, e.g. a file name or hunk header in a
+    /// unified diff.
+    Header,
+}
+
+/// A single line of a diff.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Line {
+    /// What happened to the line.
+    pub kind: Kind,
+    /// The line's number in the old text, starting at 1.
+    pub old: Option<usize>,
+    /// The line's number in the new text, starting at 1.
+    pub new: Option<usize>,
+    /// The text of the line, without the leading `+`/`-`/space.
+    pub text: String,
+}
+
+impl Line {
+    fn new(kind: Kind, old: Option<usize>, new: Option<usize>, text: &str) -> Self {
+        Self {
+            kind,
+            old,
+            new,
+            text: text.to_string(),
+        }
+    }
+}
+
+/// Compare two texts line by line.
+///
+/// Uses a longest-common-subsequence search, which takes time and memory
+/// proportional to the product of the two texts' lengths (after common lines
+/// at the start and end are skipped).
+#[must_use]
+pub fn diff(old: &str, new: &str) -> Vec<Line> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    // lcs[i][j] is the length of the longest common subsequence of a[i..]
+    // and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines: Vec<Line> = (0..prefix)
+        .map(|i| Line::new(Kind::Context, Some(i + 1), Some(i + 1), old[i]))
+        .collect();
+
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        let (old_no, new_no) = (prefix + i + 1, prefix + j + 1);
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            lines.push(Line::new(Kind::Context, Some(old_no), Some(new_no), a[i]));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(Line::new(Kind::Removed, Some(old_no), None, a[i]));
+            i += 1;
+        } else {
+            lines.push(Line::new(Kind::Added, None, Some(new_no), b[j]));
+            j += 1;
+        }
+    }
+
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+    lines.extend((0..suffix).map(|k| {
+        Line::new(
+            Kind::Context,
+            Some(old_end + k + 1),
+            Some(new_end + k + 1),
+            old[old_end + k],
+        )
+    }));
+
+    lines
+}
+
+/// An error in a unified diff. Lines are counted from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParseError {
+    /// A hunk header wasn't of the form `@@ -start,count +start,count @@`.
+    InvalidHeader {
+        /// The line of the hunk header.
+        line: usize,
+    },
+    /// A hunk had more or fewer lines than its header said.
+    WrongLength {
+        /// The line of the hunk header.
+        line: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeader { line } => write!(f, "invalid hunk header on line {line}"),
+            Self::WrongLength { line } => write!(
+                f,
+                "the hunk on line {line} doesn't match the line counts in its header"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A hunk being parsed: its header's line, and how many old and new lines
+/// it has left.
+#[derive(Clone, Copy)]
+struct Hunk {
+    header: usize,
+    old: usize,
+    new: usize,
+}
+
+/// Parse a unified diff, as output by `diff -u` or `git diff`.
+///
+/// Lines outside of hunks (like file names) are kept as headers, as are the
+/// hunk headers themselves. Each hunk ends after as many lines as its header
+/// says, so e.g. a `---` file name right after a hunk isn't mistaken for a
+/// removed line.
+///
+/// ```rust
+/// use cod::diff::{self, Kind, ParseError};
+///
+/// let unified = "\
+/// --- a/hello.txt
+/// +++ b/hello.txt
+/// @@ -1,2 +1,2 @@
+///  hello
+/// -world
+/// +there
+/// ";
+/// let lines = diff::parse(unified).unwrap();
+/// assert_eq!(lines[4].kind, Kind::Removed);
+/// assert_eq!((lines[5].new, lines[5].text.as_str()), (Some(2), "there"));
+///
+/// let truncated = "@@ -1,3 +1,3 @@\n hello\n";
+/// assert_eq!(diff::parse(truncated), Err(ParseError::WrongLength { line: 1 }));
+/// ```
+///
+/// # Errors
+///
+/// If a hunk header is malformed, or a hunk has more or fewer lines than
+/// its header says, returns an error.
+pub fn parse(unified: &str) -> Result<Vec<Line>, ParseError> {
+    let mut lines = Vec::new();
+    let (mut old, mut new) = (0, 0);
+    let mut hunk: Option<Hunk> = None;
+    let mut after_hunk = false;
+
+    for (no, line) in (1..).zip(unified.lines()) {
+        let mut chars = line.chars();
+        let first = chars.next();
+        let text = chars.as_str();
+
+        let Some(mut current) = hunk else {
+            // `\ No newline at end of file`, after a hunk's last line
+            if first == Some('\\') && after_hunk {
+                continue;
+            }
+            after_hunk = false;
+
+            if let Some(header) = line.strip_prefix("@@ ") {
+                let (o, n) = hunk_ranges(header).ok_or(ParseError::InvalidHeader { line: no })?;
+                (old, new) = (o.0, n.0);
+                hunk = Some(Hunk {
+                    header: no,
+                    old: o.1,
+                    new: n.1,
+                })
+                .filter(|h| h.old > 0 || h.new > 0);
+            }
+
+            lines.push(Line::new(Kind::Header, None, None, line));
+            continue;
+        };
+
+        let kind = match first {
+            Some(' ') | None => Kind::Context,
+            Some('+') => Kind::Added,
+            Some('-') => Kind::Removed,
+            Some('\\') => continue,
+            _ => {
+                return Err(ParseError::WrongLength {
+                    line: current.header,
+                })
+            }
+        };
+
+        let (o, n) = match kind {
+            Kind::Added => (None, Some(new)),
+            Kind::Removed => (Some(old), None),
+            _ => (Some(old), Some(new)),
+        };
+        if (o.is_some() && current.old == 0) || (n.is_some() && current.new == 0) {
+            return Err(ParseError::WrongLength {
+                line: current.header,
+            });
+        }
+
+        current.old -= usize::from(o.is_some());
+        current.new -= usize::from(n.is_some());
+        hunk = Some(current).filter(|h| h.old > 0 || h.new > 0);
+        after_hunk = hunk.is_none();
+
+        old += usize::from(o.is_some());
+        new += usize::from(n.is_some());
+        lines.push(Line::new(kind, o, n, text));
+    }
+
+    match hunk {
+        Some(current) => Err(ParseError::WrongLength {
+            line: current.header,
+        }),
+        None => Ok(lines),
+    }
+}
+
+/// Parses the old and new line ranges, each a start and a count, from a
+/// hunk header, e.g. `-1,5 +1,6 @@`. Counts default to 1.
+fn hunk_ranges(header: &str) -> Option<((usize, usize), (usize, usize))> {
+    let mut parts = header.split_whitespace();
+    let old = parts.next()?.strip_prefix('-')?;
+    let new = parts.next()?.strip_prefix('+')?;
+    if parts.next() != Some("@@") {
+        return None;
+    }
+
+    let range = |range: &str| {
+        let (start, count) = range.split_once(',').unwrap_or((range, "1"));
+        Some((start.parse().ok()?, count.parse().ok()?))
+    };
+    Some((range(old)?, range(new)?))
+}
+
+/// Compare two texts, then draw the differences into a rectangle on the
+/// screen. See [`draw`].
+pub fn render(old: &str, new: &str, rect: Rect) {
+    profile!();
+    draw(&diff(old, new), rect);
+}
+
+/// Parse a unified diff, then draw it into a rectangle on the screen. See
+/// [`parse`] and [`draw`].
+///
+/// # Errors
+///
+/// If the diff can't be parsed, returns an error without drawing anything.
+pub fn render_unified(unified: &str, rect: Rect) -> Result<(), ParseError> {
+    profile!();
+    draw(&parse(unified)?, rect);
+    Ok(())
+}
+
+/// Draw lines of a diff into a rectangle on the screen, with line numbers on
+/// the left. Long lines are wrapped, and lines past the bottom of the
+/// rectangle are dropped.
+///
+/// Like [`Spans::draw`], styling is reset afterwards.
+pub fn draw(lines: &[Line], rect: Rect) {
+    profile!();
+    let digits = lines
+        .iter()
+        .filter_map(|l| l.old.max(l.new))
+        .max()
+        .map_or(1, |n| n.to_string().len());
+    let gutter = digits * 2 + 3;
+    let text_width = (rect.width as usize).saturating_sub(gutter).max(1);
+
+    let faint = Style {
+        faint: true,
+        ..Style::default()
+    };
+    let number = |n: Option<usize>| n.map_or(" ".repeat(digits), |n| format!("{n:>digits$}"));
+
+    let mut rows = rect.y..rect.y + rect.height;
+    for line in lines {
+        let (color, sign) = match line.kind {
+            Kind::Context => (None, ' '),
+            Kind::Added => (Some(color("diff.added", 2)), '+'),
+            Kind::Removed => (Some(color("diff.removed", 1)), '-'),
+            Kind::Header => (Some(color("diff.header", 6)), ' '),
+        };
+        let style = Style {
+            fg: color,
+            ..Style::default()
+        };
+
+        let mut first = true;
+        let text = Spans::from(line.text.as_str());
+        let width = if line.kind == Kind::Header {
+            rect.width as usize
+        } else {
+            text_width
+        };
+        for wrapped in text.lines(width, Overflow::Wrap) {
+            let Some(y) = rows.next() else {
+                return;
+            };
+
+            let mut row = Spans::new();
+            if line.kind != Kind::Header {
+                let gutter = if first {
+                    format!("{} {} ", number(line.old), number(line.new))
+                } else {
+                    " ".repeat(gutter - 1)
+                };
+                row.push(gutter, faint);
+                row.push(if first { sign } else { ' ' }, style);
+            }
+            for span in wrapped.spans() {
+                row.push(span.text.as_str(), style);
+            }
+
+            row.draw(Rect::new(rect.x, y, rect.width, 1), Overflow::Truncate);
+            first = false;
+        }
+    }
+}
+
+/// Returns a color from the theme, or a palette color if it isn't set.
+fn color(name: &str, fallback: u8) -> Color {
+    palette::get(name).unwrap_or(Color::Ansi(fallback))
+}
@@ -0,0 +1,530 @@
+//! An editable, multi-line text region, for building editors and chat
+//! inputs.
+//!
+//! A [`TextArea`] holds lines of text and a cursor, and soft-wraps long lines
+//! to the width it's drawn at. It keeps track of which lines were edited, so
+//! [`TextArea::render`] only redraws the rows that changed.
+//!
+//! The cursor isn't drawn; move the terminal's cursor to
+//! [`TextArea::cursor_position`] instead.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! # use cod::canvas::Canvas;
+//! # use cod::edit::TextArea;
+//! # use cod::rect::Rect;
+//! let mut input = TextArea::new();
+//! input.insert_str("hello\nworld");
+//! input.backspace();
+//! input.insert('m');
+//! assert_eq!(input.text(), "hello\nworlm");
+//!
+//! let mut canvas = Canvas::new(20, 5);
+//! let area = Rect::new(0, 0, 20, 5);
+//! input.render(&mut canvas, area);
+//! canvas.present(0, 0);
+//!
+//! if let Some((x, y)) = input.cursor_position(area) {
+//!     cod::goto::pos(x, y);
+//! }
+//! cod::flush();
+//! ```
+
+use std::collections::BTreeSet;
+
+use crate::canvas::Canvas;
+use crate::rect::Rect;
+
+/// What a [`TextArea`] last drew, to work out what needs redrawing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Shown {
+    area: Rect,
+    top: usize,
+    /// The line and starting column of each visible row.
+    rows: Vec<(usize, usize)>,
+}
+
+/// Returns text with its line breaks stripped, for pasting into a single
+/// line: trailing line breaks are removed, and the rest are replaced with
+/// spaces. `\r\n` and `\r` count as line breaks too.
+///
+/// ```
+/// # use cod::edit::single_line;
+/// assert_eq!(single_line("one\r\ntwo\n"), "one two");
+/// ```
+#[must_use]
+pub fn single_line(text: &str) -> String {
+    normalize_newlines(text)
+        .trim_end_matches('\n')
+        .replace('\n', " ")
+}
+
+/// Returns text with `\r\n` and `\r` line breaks replaced by `\n`.
+fn normalize_newlines(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Returns the character each row of a line starts at, wrapping it to a
+/// width in columns. Wide characters aren't split between rows, and there's
+/// always room for the cursor after the last character.
+fn wrap(line: &str, width: usize) -> Vec<usize> {
+    let width = width.max(1);
+    let mut starts = vec![0];
+    let mut used = 0;
+    for (i, c) in line.chars().enumerate() {
+        let columns = crate::width::char_width(c);
+        if used > 0 && used + columns > width {
+            starts.push(i);
+            used = 0;
+        }
+        used += columns;
+    }
+
+    if used >= width {
+        starts.push(line.chars().count());
+    }
+    starts
+}
+
+/// Returns the text of the row of a line starting at a character, wrapped
+/// to a width as in [`wrap`].
+fn row_text(line: &str, start: usize, width: usize) -> String {
+    let width = width.max(1);
+    let mut used = 0;
+    line.chars()
+        .skip(start)
+        .take_while(|&c| {
+            let columns = crate::width::char_width(c);
+            let fits = used == 0 || used + columns <= width;
+            used += columns;
+            fits
+        })
+        .collect()
+}
+
+/// An editable block of text with a cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextArea {
+    lines: Vec<String>,
+    /// The cursor's line, and column in characters.
+    cursor: (usize, usize),
+    /// The column to return to when moving up and down past shorter lines.
+    goal: Option<usize>,
+    /// The first row shown, counting wrapped rows.
+    top: usize,
+
+    /// Whether pasted line breaks are stripped.
+    single_line_paste: bool,
+
+    /// Lines edited since the last render.
+    dirty: BTreeSet<usize>,
+    /// Every line from here on has been edited (e.g. shifted by a newline).
+    dirty_from: Option<usize>,
+    shown: Option<Shown>,
+}
+
+impl Default for TextArea {
+    fn default() -> Self {
+        Self {
+            lines: vec![String::new()],
+            cursor: (0, 0),
+            goal: None,
+            top: 0,
+            single_line_paste: false,
+            dirty: BTreeSet::new(),
+            dirty_from: None,
+            shown: None,
+        }
+    }
+}
+
+impl TextArea {
+    /// Create an empty text area.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the text, with lines joined by `\n`.
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Returns the lines of text.
+    #[must_use]
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Replace the text, moving the cursor to the end.
+    pub fn set_text(&mut self, text: &str) {
+        self.lines = text.split('\n').map(str::to_string).collect();
+        let last = self.lines.len() - 1;
+        self.cursor = (last, self.len(last));
+        self.goal = None;
+        self.mark_from(0);
+    }
+
+    /// Returns the cursor's line, and column in characters.
+    #[must_use]
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    /// Move the cursor, clamped to the text.
+    pub fn set_cursor(&mut self, line: usize, col: usize) {
+        let line = line.min(self.lines.len() - 1);
+        self.cursor = (line, col.min(self.len(line)));
+        self.goal = None;
+    }
+
+    fn len(&self, line: usize) -> usize {
+        self.lines[line].chars().count()
+    }
+
+    /// Returns the byte index of a column in a line.
+    fn byte(&self, line: usize, col: usize) -> usize {
+        let text = &self.lines[line];
+        text.char_indices().nth(col).map_or(text.len(), |(i, _)| i)
+    }
+
+    fn mark(&mut self, line: usize) {
+        self.dirty.insert(line);
+    }
+
+    fn mark_from(&mut self, line: usize) {
+        self.dirty_from = Some(self.dirty_from.map_or(line, |l| l.min(line)));
+    }
+
+    fn is_dirty(&self, line: usize) -> bool {
+        self.dirty.contains(&line) || self.dirty_from.is_some_and(|l| line >= l)
+    }
+
+    /// Insert a character at the cursor, moving the cursor after it. A `\n`
+    /// splits the line.
+    pub fn insert(&mut self, c: char) {
+        if c == '\n' {
+            self.newline();
+            return;
+        }
+
+        let (line, col) = self.cursor;
+        let i = self.byte(line, col);
+        self.lines[line].insert(i, c);
+        self.cursor.1 += 1;
+        self.goal = None;
+        self.mark(line);
+    }
+
+    /// Insert text at the cursor, moving the cursor after it.
+    pub fn insert_str(&mut self, s: &str) {
+        for (i, part) in s.split('\n').enumerate() {
+            if i > 0 {
+                self.newline();
+            }
+
+            let (line, col) = self.cursor;
+            let at = self.byte(line, col);
+            self.lines[line].insert_str(at, part);
+            self.cursor.1 += part.chars().count();
+            self.mark(line);
+        }
+        self.goal = None;
+    }
+
+    /// Insert pasted text at the cursor, moving the cursor after it.
+    ///
+    /// Unlike [`TextArea::insert_str`], `\r\n` and `\r` are treated as line
+    /// breaks, since that's what terminals paste. If pasted line breaks are
+    /// stripped (see [`TextArea::strip_pasted_newlines`]), the text is
+    /// inserted as a single line instead.
+    pub fn paste(&mut self, text: &str) {
+        let text = if self.single_line_paste {
+            single_line(text)
+        } else {
+            normalize_newlines(text)
+        };
+        self.insert_str(&text);
+    }
+
+    /// Set whether pasted text is stripped of line breaks (see
+    /// [`single_line`]), e.g. for a text area used as a one-line input.
+    /// Off by default.
+    pub fn strip_pasted_newlines(&mut self, strip: bool) -> &mut Self {
+        self.single_line_paste = strip;
+        self
+    }
+
+    /// Split the line at the cursor, moving the cursor to the start of the
+    /// new line.
+    pub fn newline(&mut self) {
+        let (line, col) = self.cursor;
+        let at = self.byte(line, col);
+        let rest = self.lines[line].split_off(at);
+        self.lines.insert(line + 1, rest);
+        self.cursor = (line + 1, 0);
+        self.goal = None;
+        self.mark_from(line);
+    }
+
+    /// Delete the character before the cursor, joining lines at the start
+    /// of one. Returns false if there was nothing to delete.
+    pub fn backspace(&mut self) -> bool {
+        let (line, col) = self.cursor;
+        if col == 0 && line == 0 {
+            return false;
+        }
+
+        self.move_left();
+        self.delete()
+    }
+
+    /// Delete the character after the cursor, joining lines at the end of
+    /// one. Returns false if there was nothing to delete.
+    pub fn delete(&mut self) -> bool {
+        let (line, col) = self.cursor;
+        self.goal = None;
+        if col < self.len(line) {
+            let i = self.byte(line, col);
+            self.lines[line].remove(i);
+            self.mark(line);
+            true
+        } else if line + 1 < self.lines.len() {
+            let next = self.lines.remove(line + 1);
+            self.lines[line].push_str(&next);
+            self.mark_from(line);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Move the cursor back a character, onto the previous line at the start
+    /// of one.
+    pub fn move_left(&mut self) {
+        let (line, col) = self.cursor;
+        if col > 0 {
+            self.cursor.1 -= 1;
+        } else if line > 0 {
+            self.cursor = (line - 1, self.len(line - 1));
+        }
+        self.goal = None;
+    }
+
+    /// Move the cursor forward a character, onto the next line at the end
+    /// of one.
+    pub fn move_right(&mut self) {
+        let (line, col) = self.cursor;
+        if col < self.len(line) {
+            self.cursor.1 += 1;
+        } else if line + 1 < self.lines.len() {
+            self.cursor = (line + 1, 0);
+        }
+        self.goal = None;
+    }
+
+    /// Move the cursor up a line, keeping its column where possible.
+    pub fn move_up(&mut self) {
+        if self.cursor.0 > 0 {
+            self.move_vertically(self.cursor.0 - 1);
+        }
+    }
+
+    /// Move the cursor down a line, keeping its column where possible.
+    pub fn move_down(&mut self) {
+        if self.cursor.0 + 1 < self.lines.len() {
+            self.move_vertically(self.cursor.0 + 1);
+        }
+    }
+
+    fn move_vertically(&mut self, line: usize) {
+        let goal = *self.goal.get_or_insert(self.cursor.1);
+        self.cursor = (line, goal.min(self.len(line)));
+    }
+
+    /// Move the cursor to the start of its line.
+    pub fn home(&mut self) {
+        self.cursor.1 = 0;
+        self.goal = None;
+    }
+
+    /// Move the cursor to the end of its line.
+    pub fn end(&mut self) {
+        self.cursor.1 = self.len(self.cursor.0);
+        self.goal = None;
+    }
+
+    /// Handle a key press, returning true if it was used.
+    ///
+    /// Supports typing, Enter, Backspace, Delete, the arrow keys, Home, and
+    /// End.
+    ///
+    /// Only enabled on feature `crossterm`.
+    #[cfg(feature = "crossterm")]
+    pub fn handle(&mut self, key: &crate::read::KeyEvent) -> bool {
+        use crate::read::{KeyCode, KeyModifiers};
+
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return false;
+        }
+
+        match key.code {
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Enter => self.newline(),
+            KeyCode::Backspace => return self.backspace(),
+            KeyCode::Delete => return self.delete(),
+            KeyCode::Left => self.move_left(),
+            KeyCode::Right => self.move_right(),
+            KeyCode::Up => self.move_up(),
+            KeyCode::Down => self.move_down(),
+            KeyCode::Home => self.home(),
+            KeyCode::End => self.end(),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Handle a terminal event, returning true if it was used: keys as in
+    /// [`TextArea::handle`], and pastes (with bracketed paste enabled; see
+    /// [`crate::read::enable_bracketed_paste`]) as in [`TextArea::paste`].
+    /// A paste is inserted all at once, so none of its characters are
+    /// mistaken for keys.
+    ///
+    /// Only enabled on feature `crossterm`.
+    #[cfg(feature = "crossterm")]
+    pub fn handle_event(&mut self, event: &crate::read::Event) -> bool {
+        use crate::read::{Event, KeyEventKind};
+
+        match event {
+            Event::Key(key) if key.kind != KeyEventKind::Release => self.handle(key),
+            Event::Paste(text) => {
+                self.paste(text);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the line and starting column (in characters) of every row,
+    /// wrapping lines to a width. See [`wrap`].
+    fn layout(&self, width: usize) -> Vec<(usize, usize)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .flat_map(|(i, line)| wrap(line, width).into_iter().map(move |start| (i, start)))
+            .collect()
+    }
+
+    /// Returns the row of the cursor, counting wrapped rows, and its column
+    /// on screen within that row.
+    fn cursor_row(&self, width: usize) -> (usize, usize) {
+        let (line, col) = self.cursor;
+        let above: usize = self.lines[..line]
+            .iter()
+            .map(|l| wrap(l, width).len())
+            .sum();
+
+        let starts = wrap(&self.lines[line], width);
+        let row = starts.partition_point(|&start| start <= col) - 1;
+        let before = self.lines[line]
+            .chars()
+            .take(col)
+            .skip(starts[row])
+            .map(crate::width::char_width)
+            .sum();
+        (above + row, before)
+    }
+
+    /// Draw the text into an area of a canvas, scrolling to keep the cursor
+    /// in view. Returns the rows that were redrawn.
+    ///
+    /// Only rows whose text changed since the last render are redrawn,
+    /// unless the area changed or the text scrolled, in which case
+    /// everything is.
+    pub fn render(&mut self, canvas: &mut Canvas, area: Rect) -> Vec<Rect> {
+        profile!();
+        let (width, height) = (area.width as usize, area.height as usize);
+        if area.is_empty() {
+            return Vec::new();
+        }
+
+        let (row, _) = self.cursor_row(width);
+        if row < self.top {
+            self.top = row;
+        } else if row >= self.top + height {
+            self.top = row + 1 - height;
+        }
+
+        let layout = self.layout(width);
+        let rows: Vec<(usize, usize)> =
+            layout.iter().skip(self.top).take(height).copied().collect();
+        let previous = self
+            .shown
+            .take()
+            .filter(|s| s.area == area && s.top == self.top);
+
+        let mut redrawn = Vec::new();
+        for y in 0..height {
+            let now = rows.get(y).copied();
+            let before = previous.as_ref().and_then(|s| s.rows.get(y).copied());
+            let changed = now != before || now.is_some_and(|(line, _)| self.is_dirty(line));
+            if previous.is_some() && !changed {
+                continue;
+            }
+
+            let y = area.y + u32::try_from(y).unwrap_or(u32::MAX);
+            let rect = Rect::new(area.x, y, area.width, 1);
+            canvas.erase(rect);
+            if let Some((line, start)) = now {
+                canvas.text(row_text(&self.lines[line], start, width), area.x, y);
+            }
+            redrawn.push(rect);
+        }
+
+        self.dirty.clear();
+        self.dirty_from = None;
+        self.shown = Some(Shown {
+            area,
+            top: self.top,
+            rows,
+        });
+
+        redrawn
+    }
+
+    /// Returns where the cursor is on screen, if it was visible when the
+    /// text was last rendered into the given area.
+    ///
+    /// ```rust
+    /// # use cod::canvas::Canvas;
+    /// # use cod::edit::TextArea;
+    /// # use cod::rect::Rect;
+    /// let mut input = TextArea::new();
+    /// input.insert_str("日本語");
+    ///
+    /// let area = Rect::new(0, 0, 5, 2);
+    /// input.render(&mut Canvas::new(5, 2), area);
+    /// // two wide characters take four columns, and the third doesn't fit
+    /// assert_eq!(input.cursor_position(area), Some((2, 1)));
+    /// ```
+    #[must_use]
+    pub fn cursor_position(&self, area: Rect) -> Option<(u32, u32)> {
+        let (row, col) = self.cursor_row(area.width as usize);
+        let row = row.checked_sub(self.top)?;
+        if row >= area.height as usize {
+            return None;
+        }
+
+        Some((
+            area.x + u32::try_from(col).ok()?,
+            area.y + u32::try_from(row).ok()?,
+        ))
+    }
+}
@@ -0,0 +1,196 @@
+//! Keeping track of which widget has keyboard focus.
+//!
+//! A [`Focus`] holds a tab order of nodes in a [widget tree](crate::widget),
+//! at most one of which is focused at a time. Widgets are told when they
+//! gain or lose focus through
+//! [`Widget::set_focused`](crate::widget::Widget::set_focused), and are
+//! marked dirty if they want to be redrawn.
+//!
+//! With feature `crossterm`, `Focus::handle` does the usual thing with
+//! terminal events: Tab and Shift-Tab move focus, clicking a widget focuses
+//! it, and other keys go to the focused widget.
+
+use crate::widget::{NodeId, Tree};
+
+/// A tab order of widgets, one of which may be focused.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Focus {
+    order: Vec<NodeId>,
+    current: Option<usize>,
+}
+
+impl Focus {
+    /// Create a focus manager with an empty tab order.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a node to the end of the tab order. Nodes already in the tab
+    /// order are ignored.
+    pub fn add(&mut self, id: NodeId) -> &mut Self {
+        if !self.order.contains(&id) {
+            self.order.push(id);
+        }
+
+        self
+    }
+
+    /// Remove a node from the tab order, unfocusing it if needed.
+    pub fn remove<E>(&mut self, tree: &mut Tree<E>, id: NodeId) {
+        let Some(i) = self.order.iter().position(|&n| n == id) else {
+            return;
+        };
+
+        if self.current == Some(i) {
+            self.set(tree, None);
+        }
+
+        self.order.remove(i);
+        if let Some(current) = &mut self.current {
+            if *current > i {
+                *current -= 1;
+            }
+        }
+    }
+
+    /// Returns the tab order.
+    #[must_use]
+    pub fn order(&self) -> &[NodeId] {
+        &self.order
+    }
+
+    /// Returns the focused node, if any.
+    #[must_use]
+    pub fn focused(&self) -> Option<NodeId> {
+        Some(self.order[self.current?])
+    }
+
+    /// Move focus to a node, returning false (and leaving focus alone) if
+    /// it isn't in the tab order.
+    pub fn focus<E>(&mut self, tree: &mut Tree<E>, id: NodeId) -> bool {
+        match self.order.iter().position(|&n| n == id) {
+            Some(i) => {
+                self.set(tree, Some(i));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Unfocus the focused node, if any.
+    pub fn blur<E>(&mut self, tree: &mut Tree<E>) {
+        self.set(tree, None);
+    }
+
+    /// Move focus to the next node in the tab order, wrapping around at the
+    /// end. If nothing is focused, focuses the first node.
+    pub fn next<E>(&mut self, tree: &mut Tree<E>) {
+        if self.order.is_empty() {
+            return;
+        }
+
+        let i = self.current.map_or(0, |i| (i + 1) % self.order.len());
+        self.set(tree, Some(i));
+    }
+
+    /// Move focus to the previous node in the tab order, wrapping around at
+    /// the start. If nothing is focused, focuses the last node.
+    pub fn prev<E>(&mut self, tree: &mut Tree<E>) {
+        if self.order.is_empty() {
+            return;
+        }
+
+        let len = self.order.len();
+        let i = self.current.map_or(len - 1, |i| (i + len - 1) % len);
+        self.set(tree, Some(i));
+    }
+
+    /// Focus the innermost node in the tab order that contains a point, as
+    /// of the last [`Tree::render`]. Returns false (and leaves focus alone)
+    /// if there isn't one.
+    pub fn click<E>(&mut self, tree: &mut Tree<E>, x: u32, y: u32) -> bool {
+        let hit = self
+            .order
+            .iter()
+            .enumerate()
+            .filter(|&(_, &id)| tree.area(id).contains(x, y))
+            .max_by_key(|&(_, &id)| depth(tree, id))
+            .map(|(i, _)| i);
+
+        match hit {
+            Some(i) => {
+                self.set(tree, Some(i));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Pass an event to the focused widget, returning true if it changed.
+    pub fn update<E>(&self, tree: &mut Tree<E>, event: &E) -> bool {
+        self.focused().is_some_and(|id| tree.update_node(id, event))
+    }
+
+    fn set<E>(&mut self, tree: &mut Tree<E>, i: Option<usize>) {
+        if i == self.current {
+            return;
+        }
+
+        for (index, focused) in [(self.current, false), (i, true)] {
+            if let Some(index) = index {
+                tree.set_focused(self.order[index], focused);
+            }
+        }
+
+        self.current = i;
+    }
+}
+
+/// Returns how many ancestors a node has.
+fn depth<E>(tree: &Tree<E>, mut id: NodeId) -> usize {
+    let mut depth = 0;
+    while let Some(parent) = tree.parent(id) {
+        depth += 1;
+        id = parent;
+    }
+
+    depth
+}
+
+#[cfg(feature = "crossterm")]
+impl Focus {
+    /// Handle a terminal event: Tab and Shift-Tab move focus, a left click
+    /// focuses the widget under the cursor, and key presses are passed to the
+    /// focused widget. Returns true if the event was used.
+    ///
+    /// Only enabled on feature `crossterm`.
+    pub fn handle(
+        &mut self,
+        tree: &mut Tree<crossterm::event::Event>,
+        event: &crossterm::event::Event,
+    ) -> bool {
+        use crossterm::event::{Event, KeyCode, KeyEventKind, MouseButton, MouseEventKind};
+
+        match event {
+            Event::Key(key) if key.kind != KeyEventKind::Release && key.code == KeyCode::Tab => {
+                self.next(tree);
+                true
+            }
+            Event::Key(key)
+                if key.kind != KeyEventKind::Release && key.code == KeyCode::BackTab =>
+            {
+                self.prev(tree);
+                true
+            }
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                self.click(tree, u32::from(mouse.column), u32::from(mouse.row))
+            }
+            Event::Key(_) | Event::Paste(_) => {
+                self.update(tree, event);
+                self.focused().is_some()
+            }
+            _ => false,
+        }
+    }
+}
@@ -1,55 +1,100 @@
 //! Utilities for moving the cursor.
 
+use std::sync::{Mutex, PoisonError};
+
 use crate::escape;
 
+/// Where [`park`] moves the cursor, or `None` for the bottom-right corner.
+static PARKING: Mutex<Option<(u32, u32)>> = Mutex::new(None);
+
 /// Move the cursor up.
 pub fn up(y: u32) {
+    profile!();
     if y == 0 {
         return;
     }
-    escape(format!("{y}A"));
+    escape(format_args!("{y}A"));
 }
 
 /// Move the cursor down.
 pub fn down(y: u32) {
+    profile!();
     if y == 0 {
         return;
     }
-    escape(format!("{y}B"));
+    escape(format_args!("{y}B"));
 }
 
 /// Move the cursor left.
 pub fn left(x: u32) {
+    profile!();
     if x == 0 {
         return;
     }
-    escape(format!("{x}D"));
+    escape(format_args!("{x}D"));
 }
 
 /// Move the cursor right.
 pub fn right(x: u32) {
+    profile!();
     if x == 0 {
         return;
     }
-    escape(format!("{x}C"));
+    escape(format_args!("{x}C"));
 }
 
 /// Set cursor to a specific position.
 pub fn pos(x: u32, y: u32) {
-    escape(format!("{};{}H", y + 1, x + 1));
+    profile!();
+    escape(format_args!("{};{}H", y + 1, x + 1));
 }
 
 /// Move the cursor to the top left of screen.
 pub fn home() {
+    profile!();
     pos(0, 0);
 }
 
 /// Move the cursor to the bottom left of the screen.
 pub fn bot() {
+    profile!();
     pos(0, 9998);
 }
 
 /// Move the cursor to the start of the line.
 pub fn start() {
+    profile!();
     escape("G");
 }
+
+/// Set where [`park`] moves the cursor, or `None` for the bottom-right
+/// corner of the screen (see [`crate::layout::size`]), which is the
+/// default.
+pub fn set_parking(cell: Option<(u32, u32)>) {
+    *PARKING.lock().unwrap_or_else(PoisonError::into_inner) = cell;
+}
+
+/// Returns where [`park`] moves the cursor, resolving the default to the
+/// bottom-right corner of the screen.
+#[must_use]
+pub fn parking() -> (u32, u32) {
+    let cell = *PARKING.lock().unwrap_or_else(PoisonError::into_inner);
+    cell.unwrap_or_else(|| {
+        let (width, height) = crate::layout::size();
+        (width.saturating_sub(1), height.saturating_sub(1))
+    })
+}
+
+/// Move the cursor out of the way, to its parking cell (see
+/// [`set_parking`]), e.g. after drawing a frame.
+///
+/// Drawing leaves the cursor wherever the last thing was drawn. Unless
+/// it's hidden, it blinks there, in the middle of everything; parking it
+/// keeps it in one predictable place instead. See also [`Canvas::set_parking`].
+///
+/// [`Canvas::set_parking`]: crate::canvas::Canvas::set_parking
+pub fn park() {
+    profile!();
+    let (x, y) = parking();
+    pos(x, y);
+}
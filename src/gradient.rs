@@ -0,0 +1,148 @@
+//! Smooth true-color gradients, e.g. for headers and progress bars.
+//!
+//! Each cell of a gradient gets its own background color, mixed between the
+//! start and end colors (see [`Color::mix`]) by how far along the gradient
+//! it is.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use cod::color::Color;
+//! use cod::gradient::{self, Direction};
+//! use cod::rect::Rect;
+//!
+//! // a header fading from blue to purple
+//! let header = Rect::new(0, 0, 40, 3);
+//! gradient::fill(header, Color::Rgb(0, 64, 255), Color::Rgb(160, 0, 255), Direction::Horizontal);
+//!
+//! // a progress bar, 60% done
+//! let done = 60 * 30 / 100;
+//! gradient::line(0, 4, done - 1, 4, Color::Red, Color::Green);
+//! ```
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::rect::Rect;
+use crate::style::Style;
+
+/// Which way a gradient goes, from the start color to the end color.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// From left to right.
+    #[default]
+    Horizontal,
+    /// From top to bottom.
+    Vertical,
+    /// From the top-left corner to the bottom-right.
+    Diagonal,
+}
+
+/// Returns how far along a gradient over `rect` a cell is, from 0 to 1.
+#[allow(clippy::cast_precision_loss)]
+fn progress(rect: Rect, direction: Direction, x: u32, y: u32) -> f64 {
+    let (dx, dy) = (x - rect.x, y - rect.y);
+    let (w, h) = (rect.width.saturating_sub(1), rect.height.saturating_sub(1));
+    let (along, length) = match direction {
+        Direction::Horizontal => (dx, w),
+        Direction::Vertical => (dy, h),
+        Direction::Diagonal => (dx + dy, w + h),
+    };
+
+    if length == 0 {
+        0.0
+    } else {
+        f64::from(along) / f64::from(length)
+    }
+}
+
+/// Returns the color of a cell in a gradient over `rect`.
+///
+/// Example:
+///
+/// ```
+/// # use cod::prelude::*;
+/// use cod::color::Color;
+/// use cod::gradient::{self, Direction};
+/// use cod::rect::Rect;
+///
+/// let rect = Rect::new(0, 0, 3, 1);
+/// let (black, white) = (Color::Rgb(0, 0, 0), Color::Rgb(255, 255, 255));
+/// let middle = gradient::color_at(rect, black, white, Direction::Horizontal, 1, 0);
+/// assert_eq!(middle, Color::Rgb(128, 128, 128));
+/// ```
+///
+/// # Panics
+///
+/// If the cell is above or to the left of `rect`, panics.
+#[must_use]
+pub fn color_at<C: Into<Color>>(
+    rect: Rect,
+    from: C,
+    to: C,
+    direction: Direction,
+    x: u32,
+    y: u32,
+) -> Color {
+    from.into().mix(to.into(), progress(rect, direction, x, y))
+}
+
+/// Fill a rectangle of the screen with a gradient.
+pub fn fill<C: Into<Color>>(rect: Rect, from: C, to: C, direction: Direction) {
+    profile!();
+    let (from, to) = (from.into(), to.into());
+    let mut style = Style::new();
+    for y in rect.y..rect.y + rect.height {
+        if direction == Direction::Vertical {
+            style = Style::new().bg(color_at(rect, from, to, direction, rect.x, y));
+            style.apply();
+            crate::run(&" ".repeat(rect.width as usize), rect.x, y);
+            continue;
+        }
+
+        for x in rect.x..rect.x + rect.width {
+            style = Style::new().bg(color_at(rect, from, to, direction, x, y));
+            style.apply();
+            crate::run(" ", x, y);
+        }
+    }
+    crate::reset_style(style);
+}
+
+/// Fill a rectangle of a canvas with a gradient.
+pub fn fill_on<C: Into<Color>>(
+    canvas: &mut Canvas,
+    rect: Rect,
+    from: C,
+    to: C,
+    direction: Direction,
+) {
+    let (from, to) = (from.into(), to.into());
+    let previous = canvas.style();
+    for y in rect.y..rect.y + rect.height {
+        for x in rect.x..rect.x + rect.width {
+            canvas.set_style(Style::new().bg(color_at(rect, from, to, direction, x, y)));
+            canvas.pixel(' ', x, y);
+        }
+    }
+    canvas.set_style(previous);
+}
+
+/// Draw a line onto the screen (see [`crate::line`]) with a gradient from
+/// one end to the other, as a row of colored cells.
+pub fn line<C: Into<Color>>(x1: u32, y1: u32, x2: u32, y2: u32, from: C, to: C) {
+    profile!();
+    let (from, to) = (from.into(), to.into());
+    let points: Vec<(u32, u32)> = crate::line::Iter::new(x1, y1, x2, y2).collect();
+    let length = points.len().saturating_sub(1).max(1);
+
+    let mut style = Style::new();
+    for (i, &(x, y)) in points.iter().enumerate() {
+        #[allow(clippy::cast_precision_loss)]
+        let t = i as f64 / length as f64;
+        style = Style::new().bg(from.mix(to, t));
+        style.apply();
+        crate::run(" ", x, y);
+    }
+    crate::reset_style(style);
+}
@@ -0,0 +1,167 @@
+//! Drawing with colored pixels, two to a cell.
+//!
+//! Each cell holds two pixels, one above the other, drawn as a half block
+//! (`▀` or `▄`) whose foreground and background are the two colors. This
+//! doubles the vertical resolution, so pixels come out roughly square, which
+//! suits image-like rendering: pictures, heatmaps, or simple pixel art.
+//!
+//! Pixels that aren't set are transparent; a cell with neither pixel set is
+//! drawn as a plain space.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use cod::color::Color;
+//! use cod::halfblock::Canvas;
+//!
+//! // a red square, with a blue diagonal through it
+//! let mut art = Canvas::new(8, 4);
+//! for y in 0..8 {
+//!     for x in 0..8 {
+//!         art.set(x, y, Color::Rgb(200, 0, 0));
+//!     }
+//! }
+//! art.line(0, 0, 7, 7, Color::Rgb(0, 0, 200));
+//!
+//! art.draw(0, 0);
+//! cod::flush();
+//! ```
+
+use crate::color::Color;
+use crate::line;
+use crate::style::Style;
+
+/// A grid of colored pixels, two high per cell. See the
+/// [module docs](self).
+///
+/// Pixels outside of the canvas are ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<Option<Color>>,
+}
+
+impl Canvas {
+    /// Create a canvas `width` cells wide and `height` cells high, with no
+    /// pixels set.
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![None; width as usize * height as usize * 2],
+        }
+    }
+
+    /// The width of the canvas, in cells (and pixels).
+    #[must_use]
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the canvas, in cells.
+    #[must_use]
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The height of the canvas, in pixels.
+    #[must_use]
+    pub const fn pixel_height(&self) -> u32 {
+        self.height * 2
+    }
+
+    /// Returns the index of a pixel, if it's on the canvas.
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        (x < self.width && y < self.pixel_height())
+            .then(|| y as usize * self.width as usize + x as usize)
+    }
+
+    /// Set the color of a pixel.
+    pub fn set<C: Into<Color>>(&mut self, x: u32, y: u32, color: C) {
+        if let Some(i) = self.index(x, y) {
+            self.pixels[i] = Some(color.into());
+        }
+    }
+
+    /// Make a pixel transparent again.
+    pub fn unset(&mut self, x: u32, y: u32) {
+        if let Some(i) = self.index(x, y) {
+            self.pixels[i] = None;
+        }
+    }
+
+    /// Returns the color of a pixel, or `None` if it isn't set or is
+    /// outside of the canvas.
+    #[must_use]
+    pub fn get(&self, x: u32, y: u32) -> Option<Color> {
+        self.index(x, y).and_then(|i| self.pixels[i])
+    }
+
+    /// Make every pixel transparent.
+    pub fn clear(&mut self) {
+        self.pixels.fill(None);
+    }
+
+    /// Set the color of every pixel on a line between two pixels.
+    pub fn line<C: Into<Color>>(&mut self, x1: u32, y1: u32, x2: u32, y2: u32, color: C) {
+        let color = color.into();
+        for (x, y) in line::Iter::new(x1, y1, x2, y2) {
+            self.set(x, y, color);
+        }
+    }
+
+    /// Turn the pixels into a (regular) canvas of half blocks.
+    ///
+    /// ```rust
+    /// use cod::color::Color;
+    /// use cod::halfblock;
+    ///
+    /// let mut pixels = halfblock::Canvas::new(2, 1);
+    /// pixels.set(0, 0, Color::Rgb(255, 0, 0));
+    /// pixels.set(0, 1, Color::Rgb(0, 0, 255));
+    /// pixels.set(1, 1, Color::Rgb(0, 255, 0));
+    ///
+    /// let canvas = pixels.to_canvas();
+    /// let cells = canvas.row(0).unwrap();
+    /// assert_eq!(cells[0].c, '▀');
+    /// assert_eq!(cells[0].style.bg, Some(Color::Rgb(0, 0, 255)));
+    /// assert_eq!(cells[1].c, '▄');
+    /// ```
+    #[must_use]
+    pub fn to_canvas(&self) -> crate::canvas::Canvas {
+        let mut canvas = crate::canvas::Canvas::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (c, fg, bg) = match (self.get(x, y * 2), self.get(x, y * 2 + 1)) {
+                    (Some(top), bottom) => ('\u{2580}', Some(top), bottom),
+                    (None, Some(bottom)) => ('\u{2584}', Some(bottom), None),
+                    (None, None) => (' ', None, None),
+                };
+                canvas.set_style(Style {
+                    fg,
+                    bg,
+                    ..Style::default()
+                });
+                canvas.pixel(c, x, y);
+            }
+        }
+
+        canvas.set_style(Style::default());
+        canvas
+    }
+
+    /// Draw the canvas onto the screen, with its top-left corner at the
+    /// given position.
+    pub fn draw(&self, x: u32, y: u32) {
+        profile!();
+        self.to_canvas().draw(x, y);
+    }
+
+    /// Draw the canvas onto a (regular) canvas. See [`Canvas::draw`].
+    pub fn draw_on(&self, canvas: &mut crate::canvas::Canvas, x: u32, y: u32) {
+        canvas.stamp(&self.to_canvas(), x, y);
+    }
+}
@@ -0,0 +1,183 @@
+//! A "cheat sheet" overlay, listing the bindings in a [`Keymap`].
+//!
+//! Only enabled on feature `crossterm`.
+
+use crate::canvas::Canvas;
+use crate::keymap::Keymap;
+use crate::rect::{BoxDrawingChar, Rect};
+
+/// The space between two columns of bindings.
+const GUTTER: usize = 3;
+
+/// A panel listing the bindings of a [`Keymap`], grouped and laid out in
+/// columns.
+///
+/// The panel is laid out once, when it's created; to reflect changes to the
+/// keymap or terminal size, create a new one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overlay {
+    panel: Canvas,
+}
+
+impl Overlay {
+    /// Lay out the bindings of a keymap into a panel no larger than the given
+    /// size, if possible.
+    ///
+    /// Groups are listed in the order they first appear in the keymap, and
+    /// spill over into new columns when they don't fit.
+    #[must_use]
+    pub fn new<A>(keymap: &Keymap<A>, max_width: u32, max_height: u32) -> Self {
+        let key_width = keymap
+            .bindings()
+            .map(|b| crate::width::str_width(&b.key_name()))
+            .max()
+            .unwrap_or(0);
+
+        // leave room for the border and a line of padding at the top and bottom
+        let rows = (max_height.saturating_sub(4) as usize).max(1);
+
+        // keep each group within one column where possible, only splitting
+        // groups too tall to fit in any column
+        let mut columns = Vec::new();
+        let mut column: Vec<String> = Vec::new();
+        for group in keymap.groups() {
+            let mut block = vec![group.to_string()];
+            block.extend(keymap.bindings().filter(|b| b.group == group).map(|b| {
+                // pad by display width, since key names can have
+                // wide symbols in them
+                let key = b.key_name();
+                let pad = key_width - crate::width::str_width(&key);
+                format!("  {key}{:pad$}  {}", "", b.description)
+            }));
+
+            for chunk in block.chunks(rows) {
+                if !column.is_empty() && column.len() + 1 + chunk.len() <= rows {
+                    column.push(String::new());
+                } else if !column.is_empty() {
+                    columns.push(std::mem::take(&mut column));
+                }
+
+                column.extend_from_slice(chunk);
+            }
+        }
+        columns.push(column);
+
+        let widths: Vec<usize> = columns
+            .iter()
+            .map(|c| {
+                c.iter()
+                    .map(|l| crate::width::str_width(l))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let title = " Keybindings ";
+        let footer = " press any key ";
+        let inner_width = (widths.iter().sum::<usize>() + GUTTER * widths.len().saturating_sub(1))
+            .max(title.len())
+            .max(footer.len());
+        let inner_height = columns.iter().map(Vec::len).max().unwrap_or(0);
+
+        let width = u32::try_from(inner_width + 4)
+            .unwrap_or(u32::MAX)
+            .min(max_width.max(4));
+        let height = u32::try_from(inner_height + 4).unwrap_or(u32::MAX);
+        let mut panel = Canvas::new(width, height);
+
+        let mut x = 2;
+        for (column, width) in columns.iter().zip(&widths) {
+            for (y, line) in (2..).zip(column.iter()) {
+                panel.text(line, x, y);
+            }
+            x += u32::try_from(width + GUTTER).unwrap_or(u32::MAX);
+        }
+
+        border(&mut panel);
+        panel.text(title, 2, 0);
+        panel.text(
+            footer,
+            width.saturating_sub(u32::try_from(footer.len()).unwrap_or(0) + 2),
+            height - 1,
+        );
+
+        Self { panel }
+    }
+
+    /// The width of the panel, in columns.
+    #[must_use]
+    pub const fn width(&self) -> u32 {
+        self.panel.width()
+    }
+
+    /// The height of the panel, in rows.
+    #[must_use]
+    pub const fn height(&self) -> u32 {
+        self.panel.height()
+    }
+
+    /// Returns the area the panel would cover when centered in an area of
+    /// the given size.
+    #[must_use]
+    pub const fn centered(&self, width: u32, height: u32) -> Rect {
+        Rect::new(
+            width.saturating_sub(self.width()) / 2,
+            height.saturating_sub(self.height()) / 2,
+            self.width(),
+            self.height(),
+        )
+    }
+
+    /// Draw the panel to the screen, with its top-left corner at the given
+    /// position.
+    pub fn draw(&self, x: u32, y: u32) {
+        self.panel.draw(x, y);
+    }
+
+    /// Draw the panel onto a canvas, centered.
+    ///
+    /// To dismiss the panel later, take a [`Canvas::snapshot`] of
+    /// [`Overlay::centered`] beforehand, then [`Canvas::restore`] it.
+    pub fn draw_on(&self, canvas: &mut Canvas) {
+        let area = self.centered(canvas.width(), canvas.height());
+        canvas.paste(&self.panel.snapshot(self.panel.bounds()), area.x, area.y);
+    }
+}
+
+/// Draw a border around the edge of a canvas.
+fn border(canvas: &mut Canvas) {
+    let (right, bottom) = (canvas.width() - 1, canvas.height() - 1);
+    for x in 1..right {
+        canvas.pixel(BoxDrawingChar::Horizontal.into(), x, 0);
+        canvas.pixel(BoxDrawingChar::Horizontal.into(), x, bottom);
+    }
+
+    for y in 1..bottom {
+        canvas.pixel(BoxDrawingChar::Vertical.into(), 0, y);
+        canvas.pixel(BoxDrawingChar::Vertical.into(), right, y);
+    }
+
+    canvas.pixel(BoxDrawingChar::TopLeftCorner.into(), 0, 0);
+    canvas.pixel(BoxDrawingChar::TopRightCorner.into(), right, 0);
+    canvas.pixel(BoxDrawingChar::BottomLeftCorner.into(), 0, bottom);
+    canvas.pixel(BoxDrawingChar::BottomRightCorner.into(), right, bottom);
+}
+
+/// Show the bindings of a keymap in a panel centered on the screen, wait for
+/// any key, then erase the panel.
+///
+/// Erasing the panel leaves blank space behind; you'll want to redraw
+/// whatever was underneath. Expects raw mode to be enabled.
+pub fn show<A>(keymap: &Keymap<A>) {
+    let (width, height) = crate::term::size_or();
+    let overlay = Overlay::new(keymap, width, height);
+    let area = overlay.centered(width, height);
+
+    overlay.draw(area.x, area.y);
+    crate::flush();
+
+    while crate::read::key().is_none() {}
+
+    Canvas::new(area.width, area.height).draw(area.x, area.y);
+    crate::flush();
+}
@@ -0,0 +1,230 @@
+//! A hex dump viewer, for inspecting binary data.
+//!
+//! A [`HexView`] shows bytes in three columns: the offset of each row, the
+//! bytes in hex, and the bytes as ASCII (with `.` for anything that isn't
+//! printable). A range of bytes can be selected, which is highlighted, and
+//! the view can be scrolled and searched.
+//!
+//! The selection is highlighted with the [theme](palette) color
+//! `hex.selection`, falling back to blue. Both it and the faint offsets and
+//! separators (`hex.gutter`) can be restyled through the theme, or for a
+//! single view with [`HexView::set_selection_style`] and
+//! [`HexView::set_gutter_style`] (see [`palette::resolve`]).
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! # use cod::hex::HexView;
+//! # use cod::rect::Rect;
+//! let mut view = HexView::new(b"\x7fELF\x02\x01\x01\0 hello, world!".to_vec());
+//! assert_eq!(view.search(b"hello"), Some(9));
+//! assert_eq!(view.selection(), Some(9..14));
+//!
+//! view.draw(Rect::new(0, 0, 80, 10));
+//! cod::flush();
+//! ```
+
+use std::ops::Range;
+
+use crate::color::Color;
+use crate::palette;
+use crate::rect::Rect;
+use crate::spans::{Overflow, Spans};
+use crate::style::{Style, StyleOverride};
+
+/// A scrollable hex dump of some bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HexView {
+    data: Vec<u8>,
+    row_width: usize,
+    top: usize,
+    selection: Option<Range<usize>>,
+    gutter_style: StyleOverride,
+    selection_style: StyleOverride,
+}
+
+impl HexView {
+    /// Create a view of some bytes, with 16 bytes per row, scrolled to the
+    /// top.
+    #[must_use]
+    pub fn new(data: Vec<u8>) -> Self {
+        Self {
+            data,
+            row_width: 16,
+            top: 0,
+            selection: None,
+            gutter_style: StyleOverride::new(),
+            selection_style: StyleOverride::new(),
+        }
+    }
+
+    /// Returns the bytes being viewed.
+    #[must_use]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Replace the bytes being viewed, keeping the scroll position if
+    /// possible. The selection is cleared.
+    pub fn set_data(&mut self, data: Vec<u8>) {
+        self.data = data;
+        self.selection = None;
+        self.top = self.top.min(self.rows().saturating_sub(1));
+    }
+
+    /// Set how many bytes are shown per row (at least 1).
+    pub fn set_row_width(&mut self, bytes: usize) {
+        let offset = self.top * self.row_width;
+        self.row_width = bytes.max(1);
+        self.top = offset / self.row_width;
+    }
+
+    /// Returns how many bytes are shown per row.
+    #[must_use]
+    pub fn row_width(&self) -> usize {
+        self.row_width
+    }
+
+    /// Returns the number of rows needed to show every byte.
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.data.len().div_ceil(self.row_width)
+    }
+
+    /// Returns the first row shown.
+    #[must_use]
+    pub fn top(&self) -> usize {
+        self.top
+    }
+
+    /// Scroll by a number of rows (negative to scroll up), stopping at the
+    /// start and end of the data.
+    pub fn scroll_by(&mut self, rows: isize) {
+        self.top = self
+            .top
+            .saturating_add_signed(rows)
+            .min(self.rows().saturating_sub(1));
+    }
+
+    /// Scroll so that the row containing a byte is at the top.
+    pub fn scroll_to(&mut self, offset: usize) {
+        self.top = (offset / self.row_width).min(self.rows().saturating_sub(1));
+    }
+
+    /// Select a range of bytes, or clear the selection with `None`. The
+    /// range is clipped to the data.
+    pub fn select(&mut self, range: Option<Range<usize>>) {
+        self.selection = range
+            .map(|r| r.start.min(self.data.len())..r.end.min(self.data.len()))
+            .filter(|r| !r.is_empty());
+    }
+
+    /// Returns the selected range of bytes, if any.
+    #[must_use]
+    pub fn selection(&self) -> Option<Range<usize>> {
+        self.selection.clone()
+    }
+
+    /// Restyle the offsets and separators of this view, over the theme's
+    /// `hex.gutter` style.
+    pub fn set_gutter_style(&mut self, style: StyleOverride) {
+        self.gutter_style = style;
+    }
+
+    /// Restyle the selection of this view, over the theme's `hex.selection`
+    /// style.
+    pub fn set_selection_style(&mut self, style: StyleOverride) {
+        self.selection_style = style;
+    }
+
+    /// Find the next occurrence of some bytes after the start of the
+    /// selection (or from the start, if nothing is selected), wrapping
+    /// around at the end. If found, it's selected and scrolled to, and its
+    /// offset returned.
+    pub fn search(&mut self, needle: &[u8]) -> Option<usize> {
+        if needle.is_empty() || needle.len() > self.data.len() {
+            return None;
+        }
+
+        let from = self.selection.as_ref().map_or(0, |r| r.start + 1);
+        let starts = self.data.len() - needle.len() + 1;
+        let found = (from..starts)
+            .chain(0..from.min(starts))
+            .find(|&i| self.data[i..].starts_with(needle))?;
+
+        self.select(Some(found..found + needle.len()));
+        self.scroll_to(found);
+        Some(found)
+    }
+
+    /// Draw the view into a rectangle on the screen, starting from the
+    /// [top](HexView::top) row. Rows that don't fit are cut off.
+    ///
+    /// Like [`Spans::draw`], styling is reset afterwards.
+    pub fn draw(&self, rect: Rect) {
+        profile!();
+        let faint = Style {
+            faint: true,
+            ..Style::default()
+        };
+        let faint = palette::resolve("hex.gutter", faint, &self.gutter_style);
+        let selected = Style {
+            bg: Some(palette::get("hex.selection").unwrap_or(Color::Ansi(4))),
+            ..Style::default()
+        };
+        let selected = palette::resolve("hex.selection", selected, &self.selection_style);
+        let is_selected = |i: usize| self.selection.as_ref().is_some_and(|r| r.contains(&i));
+        let style = |i: usize| {
+            if is_selected(i) {
+                selected
+            } else {
+                Style::default()
+            }
+        };
+
+        let rows = self.data.chunks(self.row_width).enumerate().skip(self.top);
+        for (y, (row, bytes)) in (rect.y..rect.y + rect.height).zip(rows) {
+            let start = row * self.row_width;
+            let mut line = Spans::new();
+            line.push(format!("{start:08x} "), faint);
+
+            for col in 0..self.row_width {
+                if col % 8 == 0 {
+                    line.push(" ", Style::default());
+                }
+
+                match bytes.get(col) {
+                    Some(&b) => {
+                        let i = start + col;
+                        line.push(format!("{b:02x}"), style(i));
+
+                        // highlight the gap only between selected bytes
+                        let joined = (col + 1) % 8 != 0 && col + 1 < bytes.len();
+                        if joined && is_selected(i) && is_selected(i + 1) {
+                            line.push(" ", selected);
+                        } else {
+                            line.push(" ", Style::default());
+                        }
+                    }
+                    None => {
+                        line.push("   ", Style::default());
+                    }
+                }
+            }
+
+            line.push(" |", faint);
+            for (col, &b) in bytes.iter().enumerate() {
+                let c = if b.is_ascii_graphic() || b == b' ' {
+                    char::from(b)
+                } else {
+                    '.'
+                };
+                line.push(c, style(start + col));
+            }
+            line.push("|", faint);
+
+            line.draw(Rect::new(rect.x, y, rect.width, 1), Overflow::Truncate);
+        }
+    }
+}
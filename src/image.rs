@@ -0,0 +1,233 @@
+//! Showing pictures, like thumbnails and logos, in the terminal.
+//!
+//! Only enabled on feature `image`.
+//!
+//! Pictures are drawn with half blocks (see [`halfblock`]), so each cell
+//! shows two pixels, one above the other, in true color. They're drawn onto a
+//! [`Canvas`], which can then be drawn to the screen as usual. Pixels that
+//! are mostly transparent are left out, showing what's behind them.
+//!
+//! On terminals with fewer colors, pick another [`Pipeline`]: half blocks
+//! dithered to the 256-color palette, or braille dots in a single color.
+//! [`Pipeline::detect`] picks one to suit the [color depth](crate::color::depth).
+//!
+//! PNG and JPEG files can be loaded; anything else the [`image`] crate can
+//! decode can be rendered with [`render`].
+//!
+//! Example:
+//!
+//! ```no_run
+//! # use cod::prelude::*;
+//! use cod::image::Pipeline;
+//!
+//! let logo = cod::image::load("logo.png", 40, 20).unwrap();
+//! logo.draw(0, 0);
+//!
+//! let fallback = cod::image::load_with("logo.png", 40, 20, Pipeline::detect()).unwrap();
+//! fallback.draw(40, 0);
+//! cod::flush();
+//! ```
+
+use std::path::Path;
+
+use ::image::imageops::FilterType;
+use ::image::{DynamicImage, ImageResult, Rgba};
+
+use crate::canvas::Canvas;
+use crate::color::{self, Color, ColorDepth};
+use crate::{braille, halfblock};
+
+/// How pictures are turned into characters.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pipeline {
+    /// Half blocks in true color, two pixels to a cell.
+    #[default]
+    TrueColor,
+    /// Half blocks in the 256-color palette, two pixels to a cell. Colors
+    /// are dithered (with Floyd-Steinberg error diffusion), so gradients
+    /// and skin tones don't turn into flat bands.
+    Dithered256,
+    /// Braille dots with no color, eight pixels to a cell. Pixels at least
+    /// as bright as `threshold` (from 0 to 255) become dots, so pictures
+    /// come out as light-on-dark line art.
+    Braille {
+        /// The brightness a pixel needs to be drawn.
+        threshold: u8,
+    },
+}
+
+impl Pipeline {
+    /// Returns the pipeline that suits a color depth: true-color half
+    /// blocks for [`ColorDepth::TrueColor`], dithered ones for
+    /// [`ColorDepth::Colors256`], and braille (with a threshold of 128)
+    /// otherwise, since 16 colors are too few to dither to.
+    #[must_use]
+    pub const fn for_depth(depth: ColorDepth) -> Self {
+        match depth {
+            ColorDepth::TrueColor => Self::TrueColor,
+            ColorDepth::Colors256 => Self::Dithered256,
+            ColorDepth::Colors16 | ColorDepth::NoColor => Self::Braille { threshold: 128 },
+        }
+    }
+
+    /// Returns the pipeline that suits the current color depth (see
+    /// [`crate::color::set_depth`], or [`crate::profile`]).
+    #[must_use]
+    pub fn detect() -> Self {
+        Self::for_depth(color::depth())
+    }
+}
+
+/// Returns the size, in columns and rows, to show a picture of the given
+/// size in pixels at, as large as possible within `max_width` and
+/// `max_height` while keeping its shape. Each cell is taken to be two
+/// pixels high, and the result is at least one cell each way.
+///
+/// ```rust
+/// assert_eq!(cod::image::fit((200, 100), 40, 40), (40, 10));
+/// assert_eq!(cod::image::fit((100, 400), 40, 20), (10, 20));
+/// ```
+#[must_use]
+pub fn fit(size: (u32, u32), max_width: u32, max_height: u32) -> (u32, u32) {
+    let (width, height) = (f64::from(size.0.max(1)), f64::from(size.1.max(1)));
+    let scale = (f64::from(max_width) / width).min(f64::from(max_height) * 2.0 / height);
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let cells = |length: f64| (length.round() as u32).max(1);
+    (
+        cells(width * scale).min(max_width.max(1)),
+        cells(height * scale / 2.0).min(max_height.max(1)),
+    )
+}
+
+/// Draw a picture onto a new canvas exactly `width` columns wide and
+/// `height` rows high, stretching it if need be (see [`fit`]), in true
+/// color.
+#[must_use]
+pub fn render(image: &DynamicImage, width: u32, height: u32) -> Canvas {
+    render_with(image, width, height, Pipeline::TrueColor)
+}
+
+/// Draw a picture onto a new canvas exactly `width` columns wide and
+/// `height` rows high with a pipeline. See [`render`].
+///
+/// ```rust
+/// use cod::image::{self, Pipeline};
+/// use ::image::{DynamicImage, Rgb, RgbImage};
+///
+/// // black on the left, white on the right
+/// let picture = RgbImage::from_fn(8, 8, |x, _| Rgb([if x < 4 { 0 } else { 255 }; 3]));
+/// let picture = DynamicImage::ImageRgb8(picture);
+///
+/// let canvas = image::render_with(&picture, 2, 1, Pipeline::Braille { threshold: 128 });
+/// let row: String = canvas.row(0).unwrap().iter().map(|cell| cell.c).collect();
+/// assert_eq!(row, " \u{28ff}");
+/// ```
+#[must_use]
+pub fn render_with(image: &DynamicImage, width: u32, height: u32, pipeline: Pipeline) -> Canvas {
+    match pipeline {
+        Pipeline::TrueColor => half_blocks(image, width, height, false),
+        Pipeline::Dithered256 => half_blocks(image, width, height, true),
+        Pipeline::Braille { threshold } => dots(image, width, height, threshold),
+    }
+}
+
+/// Draw a picture with half blocks, either as it is, or dithered to the
+/// 256-color palette.
+fn half_blocks(image: &DynamicImage, width: u32, height: u32, dither: bool) -> Canvas {
+    let pixels = image
+        .resize_exact(width, height * 2, FilterType::Triangle)
+        .to_rgba8();
+
+    // the error carried to each pixel, as it was dithered
+    let mut error = vec![[0.0f32; 3]; width as usize * height as usize * 2];
+    let mut canvas = halfblock::Canvas::new(width, height);
+    for (x, y, &pixel) in pixels.enumerate_pixels() {
+        let Some(color) = color(pixel) else { continue };
+        if !dither {
+            canvas.set(x, y, color);
+            continue;
+        }
+
+        let i = y as usize * width as usize + x as usize;
+        let [r, g, b, _] = pixel.0;
+        let wanted = [r, g, b].map(f32::from);
+        let wanted: [f32; 3] = std::array::from_fn(|c| wanted[c] + error[i][c]);
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let [r, g, b] = wanted.map(|v| v.clamp(0.0, 255.0).round() as u8);
+        let closest = Color::Ansi(color::approximate_256(r, g, b));
+        canvas.set(x, y, closest);
+
+        let (r, g, b) = closest.rgb();
+        let got = [r, g, b].map(f32::from);
+        let spread = [(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)];
+        for (dx, dy, weight) in spread {
+            let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add(dy)) else {
+                continue;
+            };
+            if nx < width && ny < height * 2 {
+                let next = ny as usize * width as usize + nx as usize;
+                for c in 0..3 {
+                    error[next][c] += (wanted[c] - got[c]) * weight / 16.0;
+                }
+            }
+        }
+    }
+
+    canvas.to_canvas()
+}
+
+/// Draw a picture with braille dots, wherever a pixel is at least as bright
+/// as `threshold`.
+fn dots(image: &DynamicImage, width: u32, height: u32, threshold: u8) -> Canvas {
+    let pixels = image
+        .resize_exact(width * 2, height * 4, FilterType::Triangle)
+        .to_rgba8();
+
+    let mut dots = braille::Canvas::new(width, height);
+    for (x, y, &pixel) in pixels.enumerate_pixels() {
+        let [r, g, b, a] = pixel.0;
+        let luma = (299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b)) / 1000;
+        if a >= 128 && luma >= u32::from(threshold) {
+            dots.set(x, y);
+        }
+    }
+
+    let mut canvas = Canvas::new(width, height);
+    dots.draw_on(&mut canvas, 0, 0);
+    canvas
+}
+
+/// Load a PNG or JPEG file, and draw it onto a new canvas as large as
+/// possible within `max_width` and `max_height`, keeping its shape.
+///
+/// # Errors
+///
+/// If the file can't be read or decoded, returns an error.
+pub fn load<P: AsRef<Path>>(path: P, max_width: u32, max_height: u32) -> ImageResult<Canvas> {
+    load_with(path, max_width, max_height, Pipeline::TrueColor)
+}
+
+/// Load a PNG or JPEG file, and draw it onto a new canvas with a pipeline.
+/// See [`load`].
+///
+/// # Errors
+///
+/// If the file can't be read or decoded, returns an error.
+pub fn load_with<P: AsRef<Path>>(
+    path: P,
+    max_width: u32,
+    max_height: u32,
+    pipeline: Pipeline,
+) -> ImageResult<Canvas> {
+    let image = ::image::open(path)?;
+    let (width, height) = fit((image.width(), image.height()), max_width, max_height);
+    Ok(render_with(&image, width, height, pipeline))
+}
+
+/// Returns the color of a pixel, or `None` if it's mostly transparent.
+fn color(pixel: Rgba<u8>) -> Option<Color> {
+    let [r, g, b, a] = pixel.0;
+    (a >= 128).then_some(Color::Rgb(r, g, b))
+}
@@ -0,0 +1,230 @@
+//! Smoothing motion in games that draw more often than they update.
+//!
+//! With a fixed timestep, a game's state is updated at a steady rate (say,
+//! 20 times a second) no matter how often it's drawn. Drawing everything
+//! where it was at the last update makes motion look choppy whenever
+//! there's more than one frame per update. An [`Interpolator`] remembers
+//! where each sprite was at the previous update and the current one, and
+//! draws it part of the way between them, by how far along the next update
+//! is (the "alpha", from 0 to 1).
+//!
+//! [`FixedStep`] keeps the time for such a loop, telling you how many
+//! updates to run, and the alpha to draw with.
+//!
+//! Example:
+//!
+//! ```no_run
+//! # use cod::prelude::*;
+//! use std::time::Duration;
+//!
+//! use cod::interpolate::{FixedStep, Interpolator};
+//!
+//! let mut clock = FixedStep::new(Duration::from_millis(50));
+//! let mut sprites = Interpolator::new();
+//! let mut ball = (0.0, 10.0);
+//!
+//! loop {
+//!     for _ in 0..clock.updates() {
+//!         sprites.step();
+//!         ball.0 += 1.0;
+//!         sprites.set("ball", ball.0, ball.1);
+//!     }
+//!
+//!     clear::all();
+//!     sprites.draw(clock.alpha(), |_, x, y| cod::pixel('o', x, y));
+//!     cod::flush();
+//! #   break;
+//! }
+//! ```
+
+use std::time::{Duration, Instant};
+
+/// The most updates [`FixedStep::updates`] asks for at once; any more time
+/// than that is dropped, so a slow update can't snowball.
+const MAX_UPDATES: u32 = 5;
+
+/// A registered sprite's key, and its positions at the previous and
+/// current updates.
+#[derive(Debug, Clone, PartialEq)]
+struct Entry<K> {
+    key: K,
+    previous: (f64, f64),
+    current: (f64, f64),
+}
+
+/// The positions of sprites at the previous and current updates, to draw
+/// them in between.
+///
+/// Each sprite is identified by a key of any type, and drawn by a closure
+/// given the key and where to draw it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interpolator<K> {
+    entries: Vec<Entry<K>>,
+}
+
+impl<K> Default for Interpolator<K> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<K: PartialEq> Interpolator<K> {
+    /// Create an interpolator with no sprites.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entry(&mut self, key: K, x: f64, y: f64) -> &mut Entry<K> {
+        if let Some(i) = self.entries.iter().position(|e| e.key == key) {
+            return &mut self.entries[i];
+        }
+
+        self.entries.push(Entry {
+            key,
+            previous: (x, y),
+            current: (x, y),
+        });
+        self.entries.last_mut().unwrap()
+    }
+
+    /// Start a new update: every sprite's current position becomes its
+    /// previous one. Call this before moving anything in an update.
+    pub fn step(&mut self) {
+        for entry in &mut self.entries {
+            entry.previous = entry.current;
+        }
+    }
+
+    /// Move a sprite, so that it's drawn moving there from where it was at
+    /// the previous update. A new sprite appears there without moving.
+    pub fn set<N: Into<f64>>(&mut self, key: K, x: N, y: N) {
+        let (x, y) = (x.into(), y.into());
+        self.entry(key, x, y).current = (x, y);
+    }
+
+    /// Move a sprite without drawing it moving, e.g. when it teleports or
+    /// respawns.
+    pub fn place<N: Into<f64>>(&mut self, key: K, x: N, y: N) {
+        let (x, y) = (x.into(), y.into());
+        let entry = self.entry(key, x, y);
+        entry.previous = (x, y);
+        entry.current = (x, y);
+    }
+
+    /// Remove a sprite, returning true if it was there.
+    pub fn remove(&mut self, key: &K) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.key != *key);
+        self.entries.len() != before
+    }
+
+    /// Returns where a sprite is `alpha` of the way from its previous
+    /// position to its current one, or `None` if it isn't registered.
+    /// `alpha` is clamped between 0 and 1.
+    ///
+    /// ```
+    /// # use cod::interpolate::Interpolator;
+    /// let mut sprites = Interpolator::new();
+    /// sprites.set('@', 0, 0);
+    /// sprites.step();
+    /// sprites.set('@', 10, 4);
+    ///
+    /// assert_eq!(sprites.position(&'@', 0.5), Some((5.0, 2.0)));
+    /// ```
+    #[must_use]
+    pub fn position(&self, key: &K, alpha: f64) -> Option<(f64, f64)> {
+        let entry = self.entries.iter().find(|e| e.key == *key)?;
+        Some(lerp(entry.previous, entry.current, alpha))
+    }
+
+    /// Draw every sprite where it is `alpha` of the way between updates,
+    /// rounded to the nearest cell, by calling `draw` with its key and
+    /// position. Sprites above or to the left of the screen aren't drawn.
+    ///
+    /// With [reduced motion](crate::a11y::reduced_motion) on, sprites are
+    /// drawn at their current positions, whatever `alpha` is.
+    pub fn draw(&self, alpha: f64, mut draw: impl FnMut(&K, u32, u32)) {
+        profile!();
+        let alpha = if crate::a11y::reduced_motion() {
+            1.0
+        } else {
+            alpha
+        };
+        for entry in &self.entries {
+            let (x, y) = lerp(entry.previous, entry.current, alpha);
+            let (x, y) = (x.round(), y.round());
+            if x < 0.0 || y < 0.0 || x > f64::from(u32::MAX) || y > f64::from(u32::MAX) {
+                continue;
+            }
+
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            draw(&entry.key, x as u32, y as u32);
+        }
+    }
+}
+
+/// Returns the point `t` of the way from `a` to `b`, with `t` clamped
+/// between 0 and 1.
+fn lerp(a: (f64, f64), b: (f64, f64), t: f64) -> (f64, f64) {
+    let t = t.clamp(0.0, 1.0);
+    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+}
+
+/// Keeps time for a fixed-timestep loop: how many updates are due, and how
+/// far along the next one is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedStep {
+    step: Duration,
+    last: Instant,
+    /// Time not yet used up by updates.
+    lag: Duration,
+}
+
+impl FixedStep {
+    /// Create a clock for updates every `step`, starting now.
+    ///
+    /// # Panics
+    ///
+    /// If the step is zero, panics.
+    #[must_use]
+    pub fn new(step: Duration) -> Self {
+        assert!(!step.is_zero(), "a fixed timestep can't be 0 seconds");
+        Self {
+            step,
+            last: Instant::now(),
+            lag: Duration::ZERO,
+        }
+    }
+
+    /// Returns how many updates to run now, for the time passed since the
+    /// last call. Call this once per frame.
+    ///
+    /// At most 5 updates are asked for at once; if the game falls further
+    /// behind than that, the rest of the time is dropped.
+    pub fn updates(&mut self) -> u32 {
+        let now = Instant::now();
+        self.lag += now - self.last;
+        self.last = now;
+
+        let mut updates = 0;
+        while self.lag >= self.step {
+            self.lag -= self.step;
+            updates += 1;
+            if updates == MAX_UPDATES {
+                self.lag = self.lag.min(self.step);
+                break;
+            }
+        }
+        updates
+    }
+
+    /// Returns how far along the next update is, from 0 to 1, to draw
+    /// with (see [`Interpolator::draw`]).
+    #[must_use]
+    pub fn alpha(&self) -> f64 {
+        (self.lag.as_secs_f64() / self.step.as_secs_f64()).min(1.0)
+    }
+}
@@ -0,0 +1,158 @@
+//! Mapping keys to your app's actions.
+//!
+//! Only enabled on feature `crossterm`.
+//!
+//! Example:
+//!
+//! ```no_run
+//! # use cod::keymap::Keymap;
+//! # use cod::read::{KeyCode, KeyModifiers};
+//! #[derive(Clone, Copy)]
+//! enum Action {
+//!     Quit,
+//!     Save,
+//! }
+//!
+//! let mut keymap = Keymap::new();
+//! keymap
+//!     .bind("General", KeyCode::Char('q'), KeyModifiers::NONE, Action::Quit, "Quit")
+//!     .bind("File", KeyCode::Char('s'), KeyModifiers::CONTROL, Action::Save, "Save");
+//!
+//! if let Some(ev) = cod::read::key() {
+//!     match keymap.get(&ev) {
+//!         Some(Action::Quit) => {}
+//!         Some(Action::Save) => {}
+//!         None => {}
+//!     }
+//! }
+//! ```
+
+use crate::read::{Key, KeyCode, KeyEvent, KeyModifiers};
+
+/// A single key binding in a [`Keymap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binding<A> {
+    /// The group the binding belongs to, e.g. in the help overlay.
+    pub group: String,
+    /// The key that triggers the binding.
+    pub code: KeyCode,
+    /// The modifiers that must be held alongside the key.
+    pub modifiers: KeyModifiers,
+    /// The action the binding triggers.
+    pub action: A,
+    /// A short, human-readable description of the action.
+    pub description: String,
+}
+
+impl<A> Binding<A> {
+    /// Returns the key combination as text, e.g. `ctrl+s`. This can be
+    /// parsed back into a [`Key`].
+    #[must_use]
+    pub fn key_name(&self) -> String {
+        Key::new(self.code, self.modifiers).to_string()
+    }
+}
+
+/// A set of key bindings, mapping keys to actions of type `A`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap<A> {
+    bindings: Vec<Binding<A>>,
+}
+
+impl<A> Default for Keymap<A> {
+    fn default() -> Self {
+        Self {
+            bindings: Vec::new(),
+        }
+    }
+}
+
+impl<A> Keymap<A> {
+    /// Create an empty keymap.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a binding to the keymap.
+    ///
+    /// If the key combination was already bound, the old binding is replaced.
+    pub fn bind<G: Into<String>, D: Into<String>>(
+        &mut self,
+        group: G,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+        action: A,
+        description: D,
+    ) -> &mut Self {
+        self.unbind(code, modifiers);
+        self.bindings.push(Binding {
+            group: group.into(),
+            code,
+            modifiers,
+            action,
+            description: description.into(),
+        });
+
+        self
+    }
+
+    /// Remove a binding from the keymap, returning its action if it existed.
+    pub fn unbind(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Option<A> {
+        let i = self
+            .bindings
+            .iter()
+            .position(|b| b.code == code && b.modifiers == modifiers)?;
+
+        Some(self.bindings.remove(i).action)
+    }
+
+    /// Move the binding for an action to a different key combination,
+    /// returning false if the action isn't bound.
+    ///
+    /// If the new key combination was already bound, the old binding is
+    /// removed.
+    pub fn rebind(&mut self, action: &A, code: KeyCode, modifiers: KeyModifiers) -> bool
+    where
+        A: PartialEq,
+    {
+        let Some(i) = self.bindings.iter().position(|b| b.action == *action) else {
+            return false;
+        };
+
+        let mut binding = self.bindings.remove(i);
+        self.unbind(code, modifiers);
+        binding.code = code;
+        binding.modifiers = modifiers;
+        self.bindings.insert(i.min(self.bindings.len()), binding);
+
+        true
+    }
+
+    /// Returns the action bound to a key event, if any.
+    #[must_use]
+    pub fn get(&self, ev: &KeyEvent) -> Option<&A> {
+        self.bindings
+            .iter()
+            .find(|b| b.code == ev.code && b.modifiers == ev.modifiers)
+            .map(|b| &b.action)
+    }
+
+    /// Returns all the bindings, in the order they were added.
+    pub fn bindings(&self) -> impl Iterator<Item = &Binding<A>> {
+        self.bindings.iter()
+    }
+
+    /// Returns the names of all groups, in the order they first appeared.
+    #[must_use]
+    pub fn groups(&self) -> Vec<&str> {
+        let mut groups = Vec::new();
+        for b in &self.bindings {
+            if !groups.contains(&b.group.as_str()) {
+                groups.push(b.group.as_str());
+            }
+        }
+
+        groups
+    }
+}
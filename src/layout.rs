@@ -0,0 +1,137 @@
+//! Positioning things on screen relative to its size.
+//!
+//! Instead of working out where a dialog or a status line goes from the
+//! terminal size at every call, ask for it: [`center`] gives where to put
+//! something so it's centered, and [`anchor`] where to put it so it sits in
+//! a corner. Both have `_in` variants to position things within another
+//! rectangle instead of the whole screen. Positions and rectangles can
+//! also be given as percentages of the screen (see [`percent`] and
+//! [`Rect::percent`]).
+//!
+//! The terminal size is looked up once and remembered (see [`size`]). Events
+//! read through `cod::read` update it when the terminal is resized;
+//! otherwise, call [`refresh`].
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use cod::layout::{self, Corner};
+//!
+//! layout::set_size(80, 24);
+//!
+//! let (x, y) = layout::center((20, 5));
+//! assert_eq!((x, y), (30, 9));
+//! rect::text_box("Are you sure?", x, y, x + 19, y + 4, rect::TextBox::new());
+//!
+//! let (x, y) = layout::anchor(Corner::BottomRight, (8, 1), 1);
+//! assert_eq!((x, y), (71, 22));
+//! cod::text("F1: help", x, y);
+//! ```
+
+use std::sync::{Mutex, PoisonError};
+
+use crate::rect::Rect;
+
+/// The terminal size, once it's been looked up.
+static SIZE: Mutex<Option<(u32, u32)>> = Mutex::new(None);
+
+/// A corner of the screen, or of a rectangle.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Corner {
+    /// The top-left corner.
+    #[default]
+    TopLeft,
+    /// The top-right corner.
+    TopRight,
+    /// The bottom-left corner.
+    BottomLeft,
+    /// The bottom-right corner.
+    BottomRight,
+}
+
+/// Returns the terminal size in columns and rows, looking it up the first
+/// time, and remembering it after that. Without feature `crossterm`, or if
+/// it can't be found, it's 80x24 unless set with [`set_size`].
+#[allow(clippy::must_use_candidate)]
+pub fn size() -> (u32, u32) {
+    *SIZE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get_or_insert_with(crate::term::size_or_default)
+}
+
+/// Look up the terminal size again, e.g. after it's been resized.
+pub fn refresh() {
+    *SIZE.lock().unwrap_or_else(PoisonError::into_inner) = Some(crate::term::size_or_default());
+}
+
+/// Set the size positions are worked out from, e.g. from a resize event,
+/// or when drawing somewhere other than the terminal.
+pub fn set_size(cols: u32, rows: u32) {
+    *SIZE.lock().unwrap_or_else(PoisonError::into_inner) = Some((cols, rows));
+}
+
+/// Returns a rectangle covering the whole terminal (see [`size`]).
+#[must_use]
+pub fn screen() -> Rect {
+    let (width, height) = size();
+    Rect::new(0, 0, width, height)
+}
+
+/// Returns the point at percentages of the way across and down the screen,
+/// rounded down. See [`crate::rect::Rect::percent`].
+///
+/// ```rust
+/// # use cod::layout;
+/// layout::set_size(80, 24);
+/// assert_eq!(layout::percent(50, 25), (40, 6));
+/// ```
+#[must_use]
+pub fn percent(x: u32, y: u32) -> (u32, u32) {
+    let area = screen().percent_of(x, y, 0, 0);
+    (area.x, area.y)
+}
+
+/// Returns where to put the top-left corner of something of the given
+/// size, in columns and rows, to center it on the screen. If it doesn't
+/// fit, it's put against the top or left edge.
+#[must_use]
+pub fn center(size: (u32, u32)) -> (u32, u32) {
+    center_in(screen(), size)
+}
+
+/// Returns where to put the top-left corner of something of the given
+/// size to center it in a rectangle. See [`center`].
+#[must_use]
+pub fn center_in(area: Rect, size: (u32, u32)) -> (u32, u32) {
+    (
+        area.x + area.width.saturating_sub(size.0) / 2,
+        area.y + area.height.saturating_sub(size.1) / 2,
+    )
+}
+
+/// Returns where to put the top-left corner of something of the given
+/// size so it sits in a corner of the screen, `margin` cells away from
+/// both edges. If it doesn't fit, it's put against the top or left edge.
+#[must_use]
+pub fn anchor(corner: Corner, size: (u32, u32), margin: u32) -> (u32, u32) {
+    anchor_in(screen(), corner, size, margin)
+}
+
+/// Returns where to put the top-left corner of something of the given
+/// size so it sits in a corner of a rectangle. See [`anchor`].
+#[must_use]
+pub fn anchor_in(area: Rect, corner: Corner, size: (u32, u32), margin: u32) -> (u32, u32) {
+    let left = area.x + margin.min(area.width);
+    let top = area.y + margin.min(area.height);
+    let right = area.x + area.width.saturating_sub(size.0 + margin);
+    let bottom = area.y + area.height.saturating_sub(size.1 + margin);
+
+    match corner {
+        Corner::TopLeft => (left, top),
+        Corner::TopRight => (right, top),
+        Corner::BottomLeft => (left, bottom),
+        Corner::BottomRight => (right, bottom),
+    }
+}
@@ -2,26 +2,90 @@
 #![warn(clippy::pedantic)]
 #![warn(missing_docs)]
 
-use std::io::{stdout, Write};
-
 #[cfg(feature = "crossterm")]
 pub use crossterm;
 
+/// Records a call to a drawing function for [`stats`], under the name of the
+/// module it's called from. Does nothing without feature `stats`.
+macro_rules! profile {
+    () => {
+        #[cfg(feature = "stats")]
+        let _scope = $crate::stats::Scope::enter(module_path!());
+    };
+}
+
+pub mod a11y;
+pub mod animation;
+pub mod bigtext;
+pub mod braille;
+pub mod canvas;
 pub mod clear;
 pub mod color;
+pub mod compat;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod diagram;
+pub mod diff;
+pub mod edit;
+pub mod focus;
 pub mod goto;
+pub mod gradient;
 pub mod guard;
+pub mod halfblock;
+pub mod hex;
+#[cfg(feature = "image")]
+pub mod image;
+pub mod interpolate;
+pub mod layout;
+#[cfg(feature = "markdown")]
+pub mod markdown;
+#[cfg(feature = "mirror")]
+pub mod mirror;
+pub mod paint;
+pub mod palette;
+pub mod picker;
 pub mod prelude;
+pub mod profile;
+pub mod progress;
+#[cfg(all(feature = "pty", unix))]
+pub mod pty;
+pub mod queue;
 pub mod rect;
+pub mod region;
+pub mod scroll;
+pub mod spans;
+pub mod sprite;
+#[cfg(feature = "stats")]
+pub mod stats;
 pub mod style;
+pub mod target;
+pub mod template;
 pub mod term;
+pub mod terminal;
+pub mod timeline;
+pub mod timer;
+pub mod vt;
+pub mod widget;
+pub mod width;
 
 mod line;
 mod println;
 
+#[cfg(feature = "crossterm")]
+pub mod demo;
+#[cfg(feature = "crossterm")]
+pub mod help;
+#[cfg(feature = "crossterm")]
+pub mod keymap;
 #[cfg(feature = "crossterm")]
 pub mod read;
 
+pub use terminal::Terminal;
+
+/// The same module as [`palette`], under the name it had first.
+#[doc(no_inline)]
+pub use palette as theme;
+
 /// The user attempted to draw a non-orthogonal line through an orthogonal
 /// function, such as [`orth_line`] or [`rect::line`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -29,17 +93,96 @@ pub struct NonOrthogonal;
 
 /// Print an escape sequence.
 fn escape<T: std::fmt::Display>(code: T) {
-    print!("{}[{}", 27 as char, code);
+    if a11y::is_linear_output() {
+        return;
+    }
+
+    #[cfg(feature = "stats")]
+    let code = {
+        let code = code.to_string();
+        stats::escape_bytes(code.len() + 2);
+        code
+    };
+
+    target::write(format_args!("{}[{}", 27 as char, code));
+}
+
+/// Print an operating system command, e.g. to start a hyperlink.
+fn osc<T: std::fmt::Display>(code: T) {
+    if a11y::is_linear_output() {
+        return;
+    }
+
+    #[cfg(feature = "stats")]
+    let code = {
+        let code = code.to_string();
+        stats::escape_bytes(code.len() + 4);
+        code
+    };
+
+    target::write(format_args!("{}]{}{}\\", 27 as char, code, 27 as char));
 }
 
 /// Disable all style and color attributes.
 pub fn normal() {
+    profile!();
+    a11y::reset_background();
     escape("0m");
 }
 
 /// Draw a single character onto the screen.
 pub fn pixel(c: char, x: u32, y: u32) {
-    escape(format!("{};{}H{}", y + 1, x + 1, c));
+    profile!();
+    let c = compat::filter(c);
+    if a11y::is_linear_output() {
+        a11y::record(c, x, y);
+        return;
+    }
+
+    #[cfg(feature = "stats")]
+    stats::cells(1);
+
+    escape(format_args!("{};{}H{}", y + 1, x + 1, c));
+}
+
+/// Draw a grapheme cluster (see [`width::graphemes`]) onto the screen.
+fn grapheme(g: &str, x: u32, y: u32) {
+    let mut chars = g.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => pixel(c, x, y),
+        _ => run(g, x, y),
+    }
+}
+
+/// Draw a run of characters (without linebreaks) onto the screen, starting
+/// at the given position.
+pub(crate) fn run(s: &str, x: u32, y: u32) {
+    let ascii: String;
+    let s = if compat::is_enabled() {
+        ascii = s.chars().map(compat::ascii).collect();
+        &ascii
+    } else {
+        s
+    };
+
+    if a11y::is_linear_output() {
+        let mut x = x;
+        for c in s.chars() {
+            a11y::record(c, x, y);
+            x = x.saturating_add(width::columns(c));
+        }
+        return;
+    }
+
+    #[cfg(feature = "stats")]
+    stats::cells(s.chars().count() as u64);
+
+    escape(format_args!(
+        "{};{}H{}",
+        u64::from(y) + 1,
+        u64::from(x) + 1,
+        s
+    ));
 }
 
 /// Draw an orthogonal line to the screen.
@@ -48,6 +191,7 @@ pub fn pixel(c: char, x: u32, y: u32) {
 ///
 /// If the given line is non-orthogonal, returns an error.
 pub fn orth_line(c: char, x1: u32, y1: u32, x2: u32, y2: u32) -> Result<(), NonOrthogonal> {
+    profile!();
     if x1 != x2 && y1 != y2 {
         return Err(NonOrthogonal);
     }
@@ -73,11 +217,83 @@ pub fn orth_line(c: char, x1: u32, y1: u32, x2: u32, y2: u32) -> Result<(), NonO
     Ok(())
 }
 
+/// Draw an orthogonal line to the screen, snapping a non-orthogonal one to
+/// whichever axis it's closest to.
+///
+/// The line always starts at (`x1`, `y1`); if it's wider than it is tall, it
+/// ends at (`x2`, `y1`), otherwise at (`x1`, `y2`).
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// // drawn from (0, 0) to (10, 0)
+/// cod::orth_line_snap('-', 0, 0, 10, 1);
+/// ```
+#[allow(clippy::missing_panics_doc)]
+pub fn orth_line_snap(c: char, x1: u32, y1: u32, x2: u32, y2: u32) {
+    profile!();
+    if x1.abs_diff(x2) >= y1.abs_diff(y2) {
+        orth_line(c, x1, y1, x2, y1).unwrap();
+    } else {
+        orth_line(c, x1, y1, x1, y2).unwrap();
+    }
+}
+
+/// Connect two points with an L-shaped pair of orthogonal lines, going
+/// horizontally from (`x1`, `y1`) then vertically to (`x2`, `y2`), with
+/// `corner` at the turn.
+///
+/// If the points are already in line, this is just [`orth_line`], without a
+/// corner.
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// cod::orth_line_elbow('-', '+', 0, 0, 10, 5);
+/// ```
+#[allow(clippy::missing_panics_doc)]
+pub fn orth_line_elbow(c: char, corner: char, x1: u32, y1: u32, x2: u32, y2: u32) {
+    profile!();
+    if x1 == x2 || y1 == y2 {
+        orth_line(c, x1, y1, x2, y2).unwrap();
+        return;
+    }
+
+    orth_line(c, x1, y1, x2, y1).unwrap();
+    orth_line(c, x2, y1, x2, y2).unwrap();
+    pixel(corner, x2, y1);
+}
+
 /// Draw a line onto the screen.
+///
+/// ```rust
+/// # use std::io::{self, Write};
+/// # use std::sync::{Arc, Mutex};
+/// use cod::target::{self, Target};
+///
+/// # #[derive(Clone, Default)]
+/// # struct Log(Arc<Mutex<Vec<u8>>>);
+/// # impl Write for Log {
+/// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+/// #         self.0.lock().unwrap().write(buf)
+/// #     }
+/// #     fn flush(&mut self) -> io::Result<()> {
+/// #         Ok(())
+/// #     }
+/// # }
+/// // steep, and drawn from the bottom up
+/// let log = Log::default();
+/// target::with(Target::debug(log.clone()), || cod::line('#', 2, 4, 1, 0));
+///
+/// let log = String::from_utf8(log.0.lock().unwrap().clone()).unwrap();
+/// assert_eq!(
+///     log,
+///     r##"[goto 2,4]"#"[goto 2,3]"#"[goto 1,2]"#"[goto 1,1]"#"[goto 1,0]"#""##,
+/// );
+/// ```
 #[allow(clippy::missing_panics_doc)]
 pub fn line(c: char, x1: u32, y1: u32, x2: u32, y2: u32) {
+    profile!();
     if x1 == x2 || y1 == y2 {
-        orth_line(c, x1, x2, y1, y2).unwrap();
+        orth_line(c, x1, y1, x2, y2).unwrap();
         return;
     }
 
@@ -86,16 +302,74 @@ pub fn line(c: char, x1: u32, y1: u32, x2: u32, y2: u32) {
     }
 }
 
-/// Draw a "texture" onto the screen.
-pub fn blit<S: AsRef<str>>(src: S, mut x: u32, mut y: u32) {
-    let src = src.as_ref();
-    let rows = src.split('\n').map(|s| s.chars());
+/// Draw a line onto the screen in a style, then reset colors and attributes.
+/// See [`line`](fn@line) and [`pixel_styled`].
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// use cod::color::Color;
+/// use cod::style::Style;
+///
+/// cod::line_styled('*', 0, 0, 12, 4, Style::new().fg(Color::Yellow));
+/// ```
+pub fn line_styled(c: char, x1: u32, y1: u32, x2: u32, y2: u32, style: style::Style) {
+    profile!();
+    styled(style, || line(c, x1, y1, x2, y2));
+}
+
+/// Draw a line onto the screen, `thickness` characters wide.
+///
+/// The line is widened across its direction: mostly-horizontal lines are
+/// made taller, and mostly-vertical lines wider. Parts left of or above the
+/// screen are cut off.
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// // a bar three rows tall
+/// cod::line_thick('#', 2, 5, 30, 5, 3);
+/// ```
+pub fn line_thick(c: char, x1: u32, y1: u32, x2: u32, y2: u32, thickness: u32) {
+    profile!();
+    if thickness == 0 {
+        return;
+    }
+
+    let horizontal = x1.abs_diff(x2) >= y1.abs_diff(y2);
+    let (before, after) = ((thickness - 1) / 2, thickness / 2);
+    let mut cells = std::collections::BTreeSet::new();
+    for (x, y) in line::Iter::new(x1, y1, x2, y2) {
+        if horizontal {
+            for y in y.saturating_sub(before)..=y.saturating_add(after) {
+                cells.insert((y, x));
+            }
+        } else {
+            for x in x.saturating_sub(before)..=x.saturating_add(after) {
+                cells.insert((y, x));
+            }
+        }
+    }
+
+    let mut cells = cells.into_iter().peekable();
+    while let Some((y, left)) = cells.next() {
+        let mut right = left;
+        while cells.next_if(|&cell| cell == (y, right + 1)).is_some() {
+            right += 1;
+        }
 
+        let row: String = std::iter::repeat_n(c, (right - left) as usize + 1).collect();
+        run(&row, left, y);
+    }
+}
+
+/// Draw a "texture" onto the screen. To draw the same texture over and
+/// over, see [`sprite::Sprite`].
+pub fn blit<S: AsRef<str>>(src: S, mut x: u32, mut y: u32) {
+    profile!();
     let ox = x;
-    for row in rows {
-        for c in row {
-            pixel(c, x, y);
-            x += 1;
+    for row in src.as_ref().split('\n') {
+        for g in width::graphemes(row) {
+            grapheme(g, x, y);
+            x += width::grapheme_columns(g);
         }
         x = ox;
         y += 1;
@@ -116,57 +390,583 @@ pub fn blit<S: AsRef<str>>(src: S, mut x: u32, mut y: u32) {
 /// cod::blit_transparent("t _  n", '_', 0, 0);
 /// ```
 pub fn blit_transparent<S: AsRef<str>>(src: S, blank: char, mut x: u32, mut y: u32) {
-    let src = src.as_ref();
-    let rows = src.split('\n').map(|s| s.chars());
+    profile!();
+    let mut buf = [0; 4];
+    let blank = &*blank.encode_utf8(&mut buf);
 
     let ox = x;
-    for row in rows {
-        for c in row {
-            match c {
-                ' ' => goto::right(1),
-                ch if ch == blank => pixel(' ', x, y),
-                _ => pixel(c, x, y),
+    for row in src.as_ref().split('\n') {
+        for g in width::graphemes(row) {
+            match g {
+                " " => goto::right(1),
+                g if g == blank => pixel(' ', x, y),
+                _ => grapheme(g, x, y),
             }
-            x += 1;
+            x += width::grapheme_columns(g);
         }
         x = ox;
         y += 1;
     }
 }
 
+/// Draw a "texture" onto the screen, coloring each cell by the character in
+/// the same place in `map`, looked up in `palette`. Spaces in the map are
+/// drawn in the current colors. Afterwards, styling is reset and the colors
+/// are restored (see [`color`]).
+///
+/// See [`sprite::Sprite::styled`]; to draw the same texture over and over,
+/// use a sprite instead.
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// use cod::color::Color;
+/// use cod::style::Style;
+///
+/// cod::blit_colored(
+///     "<=#=>",
+///     "rwbwr",
+///     &[
+///         ('r', Style::new().fg(Color::Red)),
+///         ('w', Style::new().fg(Color::White)),
+///         ('b', Style::new().fg(Color::Blue).bold()),
+///     ],
+///     0,
+///     0,
+/// )
+/// .unwrap();
+/// ```
+///
+/// # Errors
+///
+/// If the texture has control characters in it, or the map has characters
+/// that aren't in the palette, returns an error without drawing anything.
+pub fn blit_colored<S: AsRef<str>, M: AsRef<str>>(
+    src: S,
+    map: M,
+    palette: &[(char, style::Style)],
+    x: u32,
+    y: u32,
+) -> Result<(), sprite::InvalidSprite> {
+    profile!();
+    sprite::Sprite::new(src)?
+        .styled(map.as_ref(), palette)?
+        .draw(x, y);
+    Ok(())
+}
+
 /// Draw a triangle onto the screen.
 pub fn triangle(c: char, x1: u32, y1: u32, x2: u32, y2: u32, x3: u32, y3: u32) {
-    line(c, x1, y1, x2, y2);
-    line(c, x2, y2, x3, y3);
-    line(c, x1, y1, x3, y3);
+    profile!();
+    polygon(c, &[(x1, y1), (x2, y2), (x3, y3)]);
+}
+
+/// Draw a filled triangle onto the screen. Covers the same cells as
+/// [`triangle`] does, plus everything inside.
+///
+/// Degenerate triangles (where the corners are in a line, or coincide) are
+/// drawn as a line or a single pixel.
+pub fn triangle_fill(c: char, x1: u32, y1: u32, x2: u32, y2: u32, x3: u32, y3: u32) {
+    profile!();
+    polygon_fill(c, &[(x1, y1), (x2, y2), (x3, y3)]);
+}
+
+/// Draw the outline of a closed shape onto the screen, with lines between
+/// each point and the next, and from the last back to the first.
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// // a diamond
+/// cod::polygon('*', &[(10, 0), (20, 5), (10, 10), (0, 5)]);
+/// ```
+pub fn polygon(c: char, points: &[(u32, u32)]) {
+    profile!();
+    for (x, y) in polygon_edges(points) {
+        pixel(c, x, y);
+    }
+}
+
+/// Draw a filled closed shape onto the screen. Covers the same cells as
+/// [`polygon`] does, plus everything inside.
+///
+/// Self-intersecting shapes are filled with the even-odd rule: a region is
+/// inside if a line from it crosses the outline an odd number of times, so
+/// e.g. the middle of a five-pointed star is left empty.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn polygon_fill(c: char, points: &[(u32, u32)]) {
+    profile!();
+    let Some(top) = points.iter().map(|&(_, y)| y).min() else {
+        return;
+    };
+    let bottom = points.iter().map(|&(_, y)| y).max().unwrap_or(top);
+    let mut rows = vec![Vec::new(); (bottom - top) as usize + 1];
+
+    // the outline, so the edges are covered the same as by `polygon`
+    for (x, y) in polygon_edges(points) {
+        rows[(y - top) as usize].push((x, x));
+    }
+
+    // the inside, between pairs of edge crossings along each row
+    let edges = points.iter().zip(points.iter().cycle().skip(1));
+    let mut crossings = Vec::new();
+    for (y, spans) in (top..).zip(&mut rows) {
+        crossings.clear();
+        for (&(x1, y1), &(x2, y2)) in edges.clone() {
+            // half-open, so a vertex shared by two edges counts once
+            if y1 == y2 || y < y1.min(y2) || y >= y1.max(y2) {
+                continue;
+            }
+
+            let t = (f64::from(y) - f64::from(y1)) / (f64::from(y2) - f64::from(y1));
+            crossings.push(f64::from(x1) + t * (f64::from(x2) - f64::from(x1)));
+        }
+
+        crossings.sort_by(f64::total_cmp);
+        for pair in crossings.chunks_exact(2) {
+            let (left, right) = (pair[0].ceil(), pair[1].floor());
+            if left <= right {
+                spans.push((left as u32, right as u32));
+            }
+        }
+    }
+
+    for (y, mut spans) in (top..).zip(rows) {
+        spans.sort_unstable();
+        let mut spans = spans.into_iter();
+        let Some(mut current) = spans.next() else {
+            continue;
+        };
+
+        for (left, right) in spans.chain([(u32::MAX, u32::MAX)]) {
+            if left <= current.1.saturating_add(1) {
+                current.1 = current.1.max(right);
+                continue;
+            }
+
+            let row: String =
+                std::iter::repeat_n(c, (current.1 - current.0) as usize + 1).collect();
+            run(&row, current.0, y);
+            current = (left, right);
+        }
+    }
+}
+
+/// Returns every cell on the outline of a closed shape.
+fn polygon_edges(points: &[(u32, u32)]) -> impl Iterator<Item = (u32, u32)> + '_ {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .flat_map(|(&(x1, y1), &(x2, y2))| line::Iter::new(x1, y1, x2, y2))
+}
+
+/// Draw a circle onto the screen, centered on (`cx`, `cy`).
+///
+/// Since character cells are about twice as tall as they are wide, this
+/// looks like a tall ellipse. For a circle that looks round, use
+/// [`ellipse`] with `rx` twice `ry`.
+pub fn circle(c: char, cx: u32, cy: u32, r: u32) {
+    profile!();
+    ellipse(c, cx, cy, r, r);
+}
+
+/// Draw a filled circle onto the screen, centered on (`cx`, `cy`). See
+/// [`circle`].
+pub fn circle_fill(c: char, cx: u32, cy: u32, r: u32) {
+    profile!();
+    ellipse_fill(c, cx, cy, r, r);
+}
+
+/// Draw a circle onto the screen in a style, then reset colors and
+/// attributes. See [`circle`] and [`pixel_styled`].
+pub fn circle_styled(c: char, cx: u32, cy: u32, r: u32, style: style::Style) {
+    profile!();
+    styled(style, || circle(c, cx, cy, r));
+}
+
+/// Draw a filled circle onto the screen in a style, then reset colors and
+/// attributes. See [`circle_fill`] and [`pixel_styled`].
+pub fn circle_fill_styled(c: char, cx: u32, cy: u32, r: u32, style: style::Style) {
+    profile!();
+    styled(style, || circle_fill(c, cx, cy, r));
+}
+
+/// Draw an ellipse onto the screen, centered on (`cx`, `cy`), with radii
+/// `rx` horizontally and `ry` vertically. Parts left of or above the screen
+/// are cut off.
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// // looks round on most terminals
+/// cod::ellipse('o', 20, 10, 16, 8);
+/// ```
+pub fn ellipse(c: char, cx: u32, cy: u32, rx: u32, ry: u32) {
+    profile!();
+    let mut points = std::collections::BTreeSet::new();
+    for (x, y) in ellipse_quadrant(rx, ry) {
+        for (sx, sy) in [(1, 1), (-1, 1), (1, -1), (-1, -1)] {
+            let px = i64::from(cx) + sx * x;
+            let py = i64::from(cy) + sy * y;
+            if let (Ok(px), Ok(py)) = (u32::try_from(px), u32::try_from(py)) {
+                points.insert((py, px));
+            }
+        }
+    }
+
+    for (y, x) in points {
+        pixel(c, x, y);
+    }
+}
+
+/// Draw a filled ellipse onto the screen. See [`ellipse`].
+pub fn ellipse_fill(c: char, cx: u32, cy: u32, rx: u32, ry: u32) {
+    profile!();
+    let mut widths = vec![0; ry as usize + 1];
+    for (x, y) in ellipse_quadrant(rx, ry) {
+        let width = &mut widths[usize::try_from(y).unwrap_or(0)];
+        *width = (*width).max(x);
+    }
+
+    for (dy, &dx) in (0..).zip(&widths) {
+        let left = i64::from(cx) - dx;
+        let right = i64::from(cx) + dx;
+        let Ok(right) = u32::try_from(right) else {
+            continue;
+        };
+        let left = u32::try_from(left.max(0)).unwrap_or(0);
+        let row: String = std::iter::repeat_n(c, (right - left) as usize + 1).collect();
+
+        let rows = if dy == 0 {
+            vec![i64::from(cy)]
+        } else {
+            vec![i64::from(cy) - dy, i64::from(cy) + dy]
+        };
+        for y in rows {
+            if let Ok(y) = u32::try_from(y) {
+                run(&row, left, y);
+            }
+        }
+    }
 }
 
-// TODO: do this ever
-// /// Draw a filled triangle onto the screen.
-// pub fn triangle_fill(c: char, x1: u32, y1: u32, x2: u32, y2: u32, x3: u32, y3: u32) {
-//     todo!()
-// }
+/// Returns the points of one quarter of an ellipse centered on the origin,
+/// with non-negative coordinates, using the midpoint ellipse algorithm.
+#[allow(clippy::cast_possible_truncation)]
+fn ellipse_quadrant(rx: u32, ry: u32) -> Vec<(i64, i64)> {
+    let (rx, ry) = (i64::from(rx), i64::from(ry));
+    if rx == 0 || ry == 0 {
+        return (0..=rx)
+            .map(|x| (x, 0))
+            .chain((0..=ry).map(|y| (0, y)))
+            .collect();
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let (rx2, ry2) = ((rx * rx) as f64, (ry * ry) as f64);
+    let mut points = Vec::new();
+    let (mut x, mut y) = (0i64, ry);
+    #[allow(clippy::cast_precision_loss)]
+    let (mut dx, mut dy) = (0.0, 2.0 * rx2 * y as f64);
+
+    // where the slope is shallower than -1, step along x
+    #[allow(clippy::cast_precision_loss)]
+    let mut d = ry2 - rx2 * ry as f64 + 0.25 * rx2;
+    while dx < dy {
+        points.push((x, y));
+        x += 1;
+        dx += 2.0 * ry2;
+        if d < 0.0 {
+            d += dx + ry2;
+        } else {
+            y -= 1;
+            dy -= 2.0 * rx2;
+            d += dx - dy + ry2;
+        }
+    }
+
+    // then step along y
+    #[allow(clippy::cast_precision_loss)]
+    let mut d = ry2 * (x as f64 + 0.5).powi(2) + rx2 * ((y - 1) as f64).powi(2) - rx2 * ry2;
+    while y >= 0 {
+        points.push((x, y));
+        y -= 1;
+        dy -= 2.0 * rx2;
+        if d > 0.0 {
+            d += rx2 - dy;
+        } else {
+            x += 1;
+            dx += 2.0 * ry2;
+            d += dx - dy + rx2;
+        }
+    }
+
+    points
+}
 
 /// Draw text onto the screen (non-wrapping, but respects linebreaks).
+///
+/// Wide characters (see [`width`]) take up two columns, so whatever comes
+/// after them still lines up. With feature `graphemes`, each grapheme
+/// cluster is drawn as one unit.
 pub fn text<S: AsRef<str>>(s: S, x: u32, mut y: u32) {
-    let chars = s.as_ref().chars();
-    let mut nx = x;
-    for ch in chars {
-        if ch == '\n' {
-            nx = x;
-            y += 1;
+    profile!();
+    for line in s.as_ref().split('\n') {
+        let mut nx = x;
+        for g in width::graphemes(line) {
+            grapheme(g, nx, y);
+            nx += width::grapheme_columns(g);
         }
+        y += 1;
+    }
+}
+
+/// Draw text onto the screen, aligned within a rectangle: e.g. centered in
+/// a box, or right-aligned in a column. Each line is aligned on its own, and
+/// cut off if it doesn't fit; lines below the rectangle are left out.
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// use cod::rect::{Align, Rect};
+///
+/// let content = rect::border(Rect::new(0, 0, 30, 5));
+/// cod::text_aligned("Settings", content, Align::Center);
+///
+/// // a column of numbers
+/// for (y, n) in (3..).zip([7, 42, 1337]) {
+///     cod::text_aligned(n.to_string(), Rect::new(20, y, 6, 1), Align::Right);
+/// }
+/// # let edge = Rect::new(u32::MAX - 1, u32::MAX - 1, 10, 10);
+/// # target::capture(|| cod::text_aligned("off", edge, Align::Center));
+/// ```
+pub fn text_aligned<S: AsRef<str>>(s: S, rect: rect::Rect, align: rect::Align) {
+    profile!();
+    let mut lines = s.as_ref().split('\n').peekable();
+    for y in rect.y..rect.y.saturating_add(rect.height) {
+        let Some(line) = lines.next() else {
+            break;
+        };
 
-        pixel(ch, nx, y);
-        nx += 1;
+        // the last line of each paragraph isn't justified
+        let last = lines.peek().is_none_or(|next| next.trim().is_empty());
+        let (text, offset) = rect::align_line(line, rect.width, align, last);
+        run(&text, rect.x.saturating_add(offset), y);
     }
 }
 
-/// Flush to stdout.
+/// Draw a single character onto the screen in a style, then reset colors
+/// and attributes. The style is set with a single escape sequence.
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// use cod::color::Color;
+/// use cod::style::Style;
+///
+/// let warning = Style::new().fg(Color::Ansi(3)).bold();
+/// cod::pixel_styled('!', 0, 0, warning);
+/// ```
+pub fn pixel_styled(c: char, x: u32, y: u32, style: style::Style) {
+    profile!();
+    style.apply();
+    pixel(c, x, y);
+    reset_style(style);
+}
+
+/// Draw text onto the screen in a style, then reset colors and attributes.
+/// See [`text`] and [`pixel_styled`].
+pub fn text_styled<S: AsRef<str>>(s: S, x: u32, y: u32, style: style::Style) {
+    profile!();
+    style.apply();
+    text(s, x, y);
+    reset_style(style);
+}
+
+/// Draw text onto the screen with a shadow: a copy of it in another color,
+/// offset by `(dx, dy)` cells, underneath. The text itself is drawn in the
+/// current colors. Parts of the shadow off the top or left of the screen
+/// aren't drawn.
+///
+/// Like in [`blit_transparent`], spaces in the shadow are skipped, so it
+/// doesn't blot out what's behind the text.
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// use cod::color::Color;
+///
+/// cod::text_shadow("GAME OVER", 10, 5, Color::Ansi(8), (1, 1));
+/// ```
+pub fn text_shadow<S: AsRef<str>>(
+    s: S,
+    x: u32,
+    y: u32,
+    shadow: impl Into<color::Color>,
+    offset: (i32, i32),
+) {
+    profile!();
+    let s = s.as_ref();
+    copies(s, x, y, shadow.into(), &[offset]);
+    text(s, x, y);
+}
+
+/// Draw text onto the screen with an outline: copies of it in another
+/// color in each of the eight cells around it, underneath. The text itself
+/// is drawn in the current colors. See [`text_shadow`].
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// use cod::color::Color;
+///
+/// cod::text_outline("SCORE: 9001", 1, 1, Color::Black);
+/// ```
+pub fn text_outline<S: AsRef<str>>(s: S, x: u32, y: u32, outline: impl Into<color::Color>) {
+    profile!();
+    let s = s.as_ref();
+    #[rustfmt::skip]
+    let around = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+    copies(s, x, y, outline.into(), &around);
+    text(s, x, y);
+}
+
+/// Draw copies of text in a color at offsets from a position, skipping
+/// spaces, and anything off the top or left of the screen.
+fn copies(s: &str, x: u32, y: u32, color: color::Color, offsets: &[(i32, i32)]) {
+    styled(style::Style::new().fg(color), || {
+        for &(dx, dy) in offsets {
+            let (Some(x), Some(y)) = (x.checked_add_signed(dx), y.checked_add_signed(dy)) else {
+                continue;
+            };
+
+            for (y, line) in (y..).zip(s.split('\n')) {
+                let mut x = x;
+                for g in width::graphemes(line) {
+                    if g != " " {
+                        grapheme(g, x, y);
+                    }
+                    x += width::grapheme_columns(g);
+                }
+            }
+        }
+    });
+}
+
+/// Apply a style, draw, then reset colors and attributes. Used by the
+/// `_styled` variants of drawing functions.
+fn styled<T>(style: style::Style, draw: impl FnOnce() -> T) -> T {
+    style.apply();
+    let result = draw();
+    reset_style(style);
+    result
+}
+
+/// Reset colors and attributes after drawing in a style, if it changed
+/// anything.
+fn reset_style(style: style::Style) {
+    if style != style::Style::default() {
+        normal();
+        color::restore();
+    }
+}
+
+/// Redraw the screen periodically, like `watch(1)`, until the user presses
+/// `q`, `Esc`, or Ctrl-C.
+///
+/// Runs in the secondary screen, in raw mode (see [`Terminal`]). Every
+/// `interval`, and whenever the terminal is resized, clears the screen and
+/// calls `draw`. The terminal is restored before returning, even if `draw`
+/// panics.
+///
+/// ```no_run
+/// use std::time::{Duration, SystemTime};
+///
+/// cod::watch(Duration::from_secs(1), || {
+///     let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap();
+///     cod::text(format!("{} seconds since the epoch", now.as_secs()), 0, 0);
+/// })
+/// .unwrap();
+/// ```
+///
+/// Only enabled on feature `crossterm`.
+///
+/// # Errors
+///
+/// If the terminal can't be set up, or reading input fails, returns the
+/// error.
+#[cfg(feature = "crossterm")]
+pub fn watch(interval: std::time::Duration, mut draw: impl FnMut()) -> std::io::Result<()> {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+    use std::time::Instant;
+
+    let mut terminal = Terminal::new()?;
+    loop {
+        terminal.clear();
+        draw();
+        terminal.normal();
+        terminal.flush()?;
+
+        let next = Instant::now() + interval;
+        loop {
+            let timeout = next.saturating_duration_since(Instant::now());
+            if !event::poll(timeout)? {
+                break;
+            }
+
+            match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => {
+                    let ctrl_c = key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL);
+                    if ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                        return Ok(());
+                    }
+                }
+                Event::Resize(..) => {
+                    terminal.refresh_size();
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Run a showcase of what cod can draw: shapes, colors, styles, widgets,
+/// and an echo of keys and mouse events, one page at a time. Useful as a
+/// terminal compatibility test, for you or your users. See [`demo`](mod@demo)
+/// to draw single pages yourself.
+///
+/// Takes over the terminal like a [`Terminal`] until the last page is done,
+/// or `q` is pressed.
+///
+/// ```no_run
+/// if std::env::args().any(|arg| arg == "--test-terminal") {
+///     cod::demo().unwrap();
+/// }
+/// ```
+///
+/// Only enabled on feature `crossterm`.
+///
+/// # Errors
+///
+/// If the terminal can't be set up, or reading input fails, returns the
+/// error.
+#[cfg(feature = "crossterm")]
+pub fn demo() -> std::io::Result<()> {
+    demo::run()
+}
+
+/// Flush to stdout (or wherever [output is going](target)).
+///
+/// In [linear output mode](a11y::set_linear_output), first prints everything
+/// drawn since the last flush.
+///
+/// With feature `stats`, also marks the end of a frame.
 ///
 /// # Panics
 ///
 /// If flushing fails, panics with `Failed to flush to stdout`.
 pub fn flush() {
-    stdout().flush().expect("Failed to flush stdout");
+    #[cfg(feature = "stats")]
+    stats::end_frame();
+
+    if a11y::is_linear_output() {
+        a11y::present_linear();
+    }
+
+    target::flush();
 }
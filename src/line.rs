@@ -24,31 +24,24 @@ impl Iter {
         let mut li = Self {
             x1: i64::from(x1),
             y1: i64::from(y1),
-            dx: i64::from(x2) - i64::from(x1),
-            dy: i64::from(y2) - i64::from(y1),
+            dx: (i64::from(x2) - i64::from(x1)).abs(),
+            dy: (i64::from(y2) - i64::from(y1)).abs(),
             ..Default::default()
         };
 
         let sx = if x2 > x1 { 1 } else { -1 };
         let sy = if y2 > y1 { 1 } else { -1 };
 
-        li.err = (li.dy << 1) - li.dx;
-
-        if li.dx > li.dy {
+        if li.dx >= li.dy {
             li.xx = sx;
-            li.xy = 0i64;
-            li.yx = 0i64;
             li.yy = sy;
         } else {
             std::mem::swap(&mut li.dx, &mut li.dy);
-            li.xx = 0i64;
             li.xy = sy;
             li.yx = sx;
-            li.yy = 0i64;
         }
 
-        li.x1 = i64::from(x1);
-        li.y1 = i64::from(y1);
+        li.err = (li.dy << 1) - li.dx;
 
         li
     }
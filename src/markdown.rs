@@ -0,0 +1,151 @@
+//! Rendering a small subset of Markdown as [rich text](crate::spans).
+//!
+//! Only enabled on feature `markdown`.
+//!
+//! Supported are:
+//! - headings (`# Title`), drawn bold, and underlined for the top level
+//! - emphasis (`*italic*`, `_italic_`, `**bold**`)
+//! - code spans (`` `code` ``), drawn in color
+//! - lists (`- item`, `* item`, `+ item`, `1. item`)
+//! - rules (`---`, `***`, `___`)
+//!
+//! A backslash escapes the character after it. Everything else is shown as
+//! written.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! # use cod::rect::Rect;
+//! let help = "# Help\n\nPress `q` to **quit**.\n\n- arrows move\n- *space* selects";
+//! cod::markdown::draw(help, Rect::new(0, 0, 40, 10));
+//! cod::flush();
+//! ```
+
+use crate::color::Color;
+use crate::rect::Rect;
+use crate::spans::{Overflow, Spans};
+use crate::style::Style;
+
+/// The color of code spans.
+const CODE: Color = Color::Ansi(6);
+
+/// Convert Markdown to rich text. Rules are drawn `width` columns wide.
+#[must_use]
+pub fn to_spans(text: &str, width: u32) -> Spans {
+    let mut out = Spans::new();
+    for (i, line) in text.lines().enumerate() {
+        if i != 0 {
+            out.push("\n", Style::default());
+        }
+
+        let trimmed = line.trim();
+        if is_rule(trimmed) {
+            out.push("\u{2500}".repeat(width as usize), Style::default());
+        } else if let Some((level, title)) = heading(trimmed) {
+            let style = Style {
+                bold: true,
+                underline: level == 1,
+                ..Style::default()
+            };
+            inline(&mut out, title, style);
+        } else if let Some((indent, marker, item)) = list_item(line) {
+            out.push(format!("{indent}{marker} "), Style::default());
+            inline(&mut out, item, Style::default());
+        } else {
+            inline(&mut out, line, Style::default());
+        }
+    }
+
+    out
+}
+
+/// Draw Markdown into a rectangle on the screen, wrapping long lines.
+///
+/// Like [`Spans::draw`], styling is reset afterwards.
+pub fn draw(text: &str, rect: Rect) {
+    profile!();
+    to_spans(text, rect.width).draw(rect, Overflow::Wrap);
+}
+
+/// Returns true if a (trimmed) line is a horizontal rule.
+fn is_rule(line: &str) -> bool {
+    let mut chars = line.chars().filter(|&c| c != ' ');
+    let Some(first) = chars.next() else {
+        return false;
+    };
+
+    matches!(first, '-' | '*' | '_') && chars.clone().all(|c| c == first) && chars.count() >= 2
+}
+
+/// Returns the level and text of a (trimmed) heading line.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let title = line[level..].strip_prefix(' ')?;
+    (1..=6)
+        .contains(&level)
+        .then(|| (level, title.trim().trim_end_matches('#').trim_end()))
+}
+
+/// Returns the indentation, bullet, and text of a list item.
+fn list_item(line: &str) -> Option<(&str, String, &str)> {
+    let text = line.trim_start();
+    let indent = &line[..line.len() - text.len()];
+
+    if let Some(item) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| text.strip_prefix(bullet))
+    {
+        return Some((indent, "\u{2022}".to_string(), item));
+    }
+
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    let item = text[digits..].strip_prefix(". ")?;
+    (digits > 0).then(|| (indent, text[..=digits].to_string(), item))
+}
+
+/// Parses emphasis and code spans in a single line.
+fn inline(out: &mut Spans, line: &str, base: Style) {
+    let mut style = base;
+    let mut text = String::new();
+    let mut chars = line.chars().peekable();
+    let mut prev = ' ';
+
+    while let Some(c) = chars.next() {
+        let next = chars.peek().copied();
+        match c {
+            '\\' if next.is_some_and(|n| n.is_ascii_punctuation()) => {
+                text.extend(chars.next());
+            }
+            '`' => {
+                out.push(std::mem::take(&mut text), style);
+                let code: String = chars.by_ref().take_while(|&c| c != '`').collect();
+                let code_style = Style {
+                    fg: Some(CODE),
+                    ..style
+                };
+                out.push(code, code_style);
+            }
+            '*' if next == Some('*') => {
+                chars.next();
+                out.push(std::mem::take(&mut text), style);
+                style.bold = !style.bold || base.bold;
+            }
+            '_' if prev.is_alphanumeric() && next.is_some_and(char::is_alphanumeric) => {
+                text.push(c);
+            }
+            '*' | '_' if prev.is_whitespace() && next.is_none_or(char::is_whitespace) => {
+                text.push(c);
+            }
+            '*' | '_' => {
+                out.push(std::mem::take(&mut text), style);
+                style.italic = !style.italic || base.italic;
+            }
+            _ => text.push(c),
+        }
+
+        prev = c;
+    }
+
+    out.push(text, style);
+}
@@ -0,0 +1,395 @@
+//! Mirroring a canvas to other terminals over the network, e.g. to watch an
+//! app's UI from another machine while debugging it.
+//!
+//! A [`Server`] listens on a TCP (or Unix) socket. Presenting a canvas
+//! through it draws to the screen as usual, and also sends the same output
+//! to every connected client as a [`Frame`]: a full frame for clients that
+//! just connected, and only the changes after that. [`replay`] connects to
+//! a server and prints the frames it receives, so running it in another
+//! terminal shows whatever the app shows.
+//!
+//! Each frame is sent as a byte for its [`FrameKind`], then the length of
+//! its data as a big-endian `u32`, then the data: exactly what would be
+//! printed to the terminal.
+//!
+//! Only enabled on feature `mirror`.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use std::net::TcpStream;
+//!
+//! use cod::canvas::Canvas;
+//! use cod::mirror::{self, FrameKind, Server};
+//!
+//! let server = Server::bind("127.0.0.1:0").unwrap();
+//! let mut client = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+//! # while server.clients() == 0 { std::thread::yield_now(); }
+//!
+//! let mut canvas = Canvas::new(20, 5);
+//! canvas.text("hello", 0, 0);
+//! server.present(&mut canvas, 0, 0);
+//!
+//! let frame = mirror::read_frame(&mut client).unwrap().unwrap();
+//! assert_eq!(frame.kind, FrameKind::Full);
+//!
+//! canvas.text("world", 0, 1);
+//! server.present(&mut canvas, 0, 0);
+//!
+//! let frame = mirror::read_frame(&mut client).unwrap().unwrap();
+//! assert_eq!(frame.kind, FrameKind::Diff);
+//! assert_eq!(frame.data, b"\x1b[2;1Hworld");
+//! ```
+
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::canvas::Canvas;
+
+/// How often the server checks for new clients.
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long a client can take to accept a frame before it's disconnected.
+const WRITE_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// How many frames can wait to be sent to a client before it's considered
+/// too far behind, and disconnected.
+const QUEUED_FRAMES: usize = 8;
+
+/// The biggest frame [`read_frame`] accepts, so a corrupt length can't make
+/// it allocate gigabytes.
+pub const MAX_FRAME: u32 = 64 * 1024 * 1024;
+
+/// Whether a [`Frame`] redraws everything, or only what changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameKind {
+    /// Everything; the screen should be cleared before printing it.
+    Full,
+    /// Only what changed since the last frame.
+    Diff,
+}
+
+/// One presented frame, as sent to a client.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Frame {
+    /// Whether the frame redraws everything.
+    pub kind: FrameKind,
+    /// Exactly what would be printed to the terminal.
+    pub data: Vec<u8>,
+}
+
+/// Write a frame to a stream, in the format [`read_frame`] reads.
+///
+/// # Errors
+///
+/// If writing fails, or the frame is bigger than 4 GiB, returns an error.
+pub fn write_frame<W: Write>(w: &mut W, frame: &Frame) -> io::Result<()> {
+    let kind = match frame.kind {
+        FrameKind::Full => 0,
+        FrameKind::Diff => 1,
+    };
+    let len = u32::try_from(frame.data.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "frame too big"))?;
+
+    w.write_all(&[kind])?;
+    w.write_all(&len.to_be_bytes())?;
+    w.write_all(&frame.data)?;
+    w.flush()
+}
+
+/// Read a frame from a stream, as written by [`write_frame`]. Returns
+/// `None` if the stream ended cleanly before the frame.
+///
+/// # Errors
+///
+/// If reading fails, what's read isn't a frame, or the frame is bigger
+/// than [`MAX_FRAME`], returns an error.
+pub fn read_frame<R: Read>(r: &mut R) -> io::Result<Option<Frame>> {
+    let mut kind = [0];
+    if r.read(&mut kind)? == 0 {
+        return Ok(None);
+    }
+
+    let kind = match kind[0] {
+        0 => FrameKind::Full,
+        1 => FrameKind::Diff,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unknown frame kind",
+            ))
+        }
+    };
+
+    let mut len = [0; 4];
+    r.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len);
+    if len > MAX_FRAME {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "frame too big"));
+    }
+
+    let mut data = vec![0; len as usize];
+    r.read_exact(&mut data)?;
+
+    Ok(Some(Frame { kind, data }))
+}
+
+/// A connected client.
+struct Client {
+    /// Frames for the thread writing to the client.
+    frames: SyncSender<Frame>,
+    /// Whether the client still needs a full frame.
+    fresh: bool,
+}
+
+impl Client {
+    /// Start writing frames to a stream in a background thread, until the
+    /// client is dropped or the stream fails.
+    fn new(mut stream: Box<dyn Write + Send>) -> Self {
+        let (frames, queue) = mpsc::sync_channel::<Frame>(QUEUED_FRAMES);
+        std::thread::spawn(move || {
+            for frame in queue {
+                if write_frame(&mut stream, &frame).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            frames,
+            fresh: true,
+        }
+    }
+}
+
+/// Listens for clients, and sends them every frame presented through it.
+///
+/// Clients that fall too far behind are disconnected, rather than holding
+/// up the app. Stops listening, and disconnects every client, when dropped;
+/// a Unix socket's file is removed then too.
+pub struct Server {
+    clients: Arc<Mutex<Vec<Client>>>,
+    addr: Option<SocketAddr>,
+    /// The Unix socket's file, to remove when dropped.
+    path: Option<std::path::PathBuf>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for Server {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Server")
+            .field("addr", &self.addr)
+            .field("clients", &self.clients())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Server {
+    /// Listen for clients on a TCP address, in a background thread.
+    ///
+    /// # Errors
+    ///
+    /// If the address can't be bound, returns an error.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr().ok();
+
+        let mut server = Self::spawn(move || {
+            let (stream, _) = listener.accept()?;
+            stream.set_nonblocking(false)?;
+            stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+            stream.set_nodelay(true)?;
+            Ok(Box::new(stream))
+        });
+        server.addr = addr;
+        Ok(server)
+    }
+
+    /// Listen for clients on a Unix socket, in a background thread. The
+    /// socket's file is removed when the server is dropped.
+    ///
+    /// Only available on Unix.
+    ///
+    /// # Errors
+    ///
+    /// If the socket can't be bound, returns an error.
+    #[cfg(unix)]
+    pub fn bind_unix<P: AsRef<std::path::Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let listener = std::os::unix::net::UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+
+        let mut server = Self::spawn(move || {
+            let (stream, _) = listener.accept()?;
+            stream.set_nonblocking(false)?;
+            stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+            Ok(Box::new(stream))
+        });
+        server.path = Some(path.to_path_buf());
+        Ok(server)
+    }
+
+    /// Accept clients with `accept` until the server is dropped. `accept`
+    /// should return [`io::ErrorKind::WouldBlock`] when there's no client
+    /// waiting.
+    fn spawn(
+        mut accept: impl FnMut() -> io::Result<Box<dyn Write + Send>> + Send + 'static,
+    ) -> Self {
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let (clients, stop) = (Arc::clone(&clients), Arc::clone(&stop));
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    match accept() {
+                        Ok(stream) => clients
+                            .lock()
+                            .unwrap_or_else(std::sync::PoisonError::into_inner)
+                            .push(Client::new(stream)),
+                        // WouldBlock when there's no client waiting; anything
+                        // else (e.g. out of file descriptors) is likely to
+                        // happen again right away
+                        Err(_) => std::thread::sleep(ACCEPT_INTERVAL),
+                    }
+                }
+            })
+        };
+
+        Self {
+            clients,
+            addr: None,
+            path: None,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns the address the server is listening on, if it's listening on
+    /// TCP.
+    #[must_use]
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.addr
+    }
+
+    /// Returns how many clients are connected.
+    #[must_use]
+    pub fn clients(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Client>> {
+        self.clients
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Present a canvas to the screen (see [`Canvas::present`]), and send
+    /// the same frame to every client. Clients that just connected get the
+    /// whole canvas instead.
+    ///
+    /// Frames are written to each client in the background, so this never
+    /// waits on the network. Clients that can't be written to, or fall too
+    /// far behind, are disconnected.
+    pub fn present(&self, canvas: &mut Canvas, x: u32, y: u32) {
+        profile!();
+        let diff = crate::target::capture(|| canvas.present(x, y));
+        crate::target::write(format_args!("{diff}"));
+
+        let mut clients = self.lock();
+        if clients.is_empty() {
+            return;
+        }
+
+        let full = clients
+            .iter()
+            .any(|c| c.fresh)
+            .then(|| crate::target::capture(|| canvas.draw(x, y)));
+
+        clients.retain_mut(|client| {
+            let frame = match (&full, client.fresh) {
+                (Some(full), true) => Frame {
+                    kind: FrameKind::Full,
+                    data: full.clone().into_bytes(),
+                },
+                _ => Frame {
+                    kind: FrameKind::Diff,
+                    data: diff.clone().into_bytes(),
+                },
+            };
+
+            client.fresh = false;
+            client.frames.try_send(frame).is_ok()
+        });
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+
+        if let Some(path) = &self.path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Print every frame from a stream to stdout, clearing the screen before
+/// each full frame, until the stream ends.
+///
+/// # Errors
+///
+/// If reading from the stream or writing to stdout fails, returns an error.
+pub fn replay_from<R: Read>(r: &mut R) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    while let Some(frame) = read_frame(r)? {
+        if frame.kind == FrameKind::Full {
+            stdout.write_all(b"\x1b[0m\x1b[2J")?;
+        }
+        stdout.write_all(&frame.data)?;
+        stdout.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Connect to a [`Server`] over TCP, and print every frame it sends to
+/// stdout until it disconnects. See [`replay_from`].
+///
+/// Example:
+///
+/// ```no_run
+/// // in another terminal, watching an app that mirrors on port 7777
+/// cod::mirror::replay("127.0.0.1:7777").unwrap();
+/// ```
+///
+/// # Errors
+///
+/// If connecting, reading, or writing to stdout fails, returns an error.
+pub fn replay<A: ToSocketAddrs>(addr: A) -> io::Result<()> {
+    replay_from(&mut TcpStream::connect(addr)?)
+}
+
+/// Connect to a [`Server`] over a Unix socket, and print every frame it
+/// sends to stdout until it disconnects. See [`replay_from`].
+///
+/// Only available on Unix.
+///
+/// # Errors
+///
+/// If connecting, reading, or writing to stdout fails, returns an error.
+#[cfg(unix)]
+pub fn replay_unix<P: AsRef<std::path::Path>>(path: P) -> io::Result<()> {
+    replay_from(&mut std::os::unix::net::UnixStream::connect(path)?)
+}
@@ -0,0 +1,372 @@
+//! Building blocks for drawing programs, e.g. an ASCII-art editor.
+//!
+//! A [`Painter`] holds the current [`Brush`] and [`Tool`], and turns
+//! presses, drags, and releases (from the mouse, or anywhere else) into
+//! strokes on a [`Canvas`]. Each stroke can be undone and redone.
+//!
+//! Lines and rectangles are previewed while dragging, and only committed
+//! when released. Freehand strokes draw a line between each position
+//! dragged over, so fast movements don't leave gaps.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use cod::canvas::Canvas;
+//! use cod::color::Color;
+//! use cod::paint::{Brush, Painter, Tool};
+//!
+//! let mut canvas = Canvas::new(20, 10);
+//! let mut painter = Painter::new();
+//! painter
+//!     .brush(Brush::new('#').fg(Color::Green))
+//!     .tool(Tool::Rect);
+//!
+//! painter.press(&mut canvas, 2, 2);
+//! painter.drag(&mut canvas, 8, 5);
+//! painter.release(&mut canvas, 10, 6);
+//!
+//! painter.tool(Tool::Fill).brush(Brush::new('.'));
+//! painter.press(&mut canvas, 5, 4);
+//! painter.release(&mut canvas, 5, 4);
+//!
+//! // take the fill back
+//! assert!(painter.undo(&mut canvas));
+//! canvas.present(0, 0);
+//! ```
+
+use crate::canvas::{Canvas, Cell, Texture};
+use crate::color::Color;
+use crate::style::Style;
+
+/// What a [`Painter`] draws with: a character and its colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Brush {
+    /// The character to draw.
+    pub c: char,
+    /// The foreground color, or `None` for the default.
+    pub fg: Option<Color>,
+    /// The background color, or `None` for the default.
+    pub bg: Option<Color>,
+}
+
+impl Brush {
+    /// Create a brush drawing a character in the default colors.
+    #[must_use]
+    pub const fn new(c: char) -> Self {
+        Self {
+            c,
+            fg: None,
+            bg: None,
+        }
+    }
+
+    /// Set the foreground color.
+    #[must_use]
+    pub fn fg<C: Into<Color>>(mut self, color: C) -> Self {
+        self.fg = Some(color.into());
+        self
+    }
+
+    /// Set the background color.
+    #[must_use]
+    pub fn bg<C: Into<Color>>(mut self, color: C) -> Self {
+        self.bg = Some(color.into());
+        self
+    }
+
+    /// Returns the cell the brush draws.
+    #[must_use]
+    pub fn cell(self) -> Cell {
+        let style = Style {
+            fg: self.fg,
+            bg: self.bg,
+            ..Style::new()
+        };
+        Cell::new(self.c, style)
+    }
+}
+
+impl Default for Brush {
+    /// A `#` in the default colors.
+    fn default() -> Self {
+        Self::new('#')
+    }
+}
+
+/// How a [`Painter`] draws.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tool {
+    /// Wherever the brush is dragged.
+    #[default]
+    Freehand,
+    /// A straight line from where the brush was pressed to where it was
+    /// released.
+    Line,
+    /// The outline of a rectangle, with corners where the brush was pressed
+    /// and released.
+    Rect,
+    /// Every cell connected to the one pressed on that looks the same as
+    /// it, i.e. a flood fill.
+    Fill,
+}
+
+/// A stroke in progress.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stroke {
+    start: (u32, u32),
+    last: (u32, u32),
+    /// The canvas before the stroke, to draw previews over.
+    before: Texture,
+}
+
+/// Draws strokes onto a canvas with a brush and a tool, keeping a history
+/// to undo and redo them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Painter {
+    brush: Brush,
+    tool: Tool,
+    stroke: Option<Stroke>,
+    undo: Vec<Texture>,
+    redo: Vec<Texture>,
+    /// The most strokes that can be undone.
+    history: usize,
+}
+
+impl Default for Painter {
+    fn default() -> Self {
+        Self {
+            brush: Brush::default(),
+            tool: Tool::default(),
+            stroke: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            history: 100,
+        }
+    }
+}
+
+impl Painter {
+    /// Create a painter with the default brush, drawing freehand.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the brush to draw with.
+    pub fn brush(&mut self, brush: Brush) -> &mut Self {
+        self.brush = brush;
+        self
+    }
+
+    /// Set the tool to draw with. Switching tools mid-stroke commits the
+    /// stroke as it is.
+    pub fn tool(&mut self, tool: Tool) -> &mut Self {
+        self.stroke = None;
+        self.tool = tool;
+        self
+    }
+
+    /// Set the most strokes that can be undone (100 by default).
+    pub fn history(&mut self, strokes: usize) -> &mut Self {
+        self.history = strokes;
+        self.trim();
+        self
+    }
+
+    /// Returns the brush being drawn with.
+    #[must_use]
+    pub fn current_brush(&self) -> Brush {
+        self.brush
+    }
+
+    /// Returns the tool being drawn with.
+    #[must_use]
+    pub fn current_tool(&self) -> Tool {
+        self.tool
+    }
+
+    /// Returns true if a stroke has been pressed, but not released.
+    #[must_use]
+    pub fn is_drawing(&self) -> bool {
+        self.stroke.is_some()
+    }
+
+    fn trim(&mut self) {
+        let excess = self.undo.len().saturating_sub(self.history);
+        self.undo.drain(..excess);
+    }
+
+    /// Draw a cell in the brush, keeping the canvas' style.
+    fn dab(&self, canvas: &mut Canvas, x: u32, y: u32) {
+        let cell = self.brush.cell();
+        let previous = canvas.style();
+        canvas.set_style(cell.style);
+        canvas.pixel(cell.c, x, y);
+        canvas.set_style(previous);
+    }
+
+    /// Start a stroke at a position on the canvas.
+    pub fn press(&mut self, canvas: &mut Canvas, x: u32, y: u32) {
+        let before = canvas.snapshot(canvas.bounds());
+        self.undo.push(before.clone());
+        self.redo.clear();
+        self.trim();
+
+        match self.tool {
+            Tool::Fill => self.flood(canvas, x, y),
+            Tool::Freehand | Tool::Line | Tool::Rect => {
+                self.dab(canvas, x, y);
+                self.stroke = Some(Stroke {
+                    start: (x, y),
+                    last: (x, y),
+                    before,
+                });
+            }
+        }
+    }
+
+    /// Continue a stroke to a position on the canvas. Does nothing if no
+    /// stroke was started.
+    pub fn drag(&mut self, canvas: &mut Canvas, x: u32, y: u32) {
+        let Some(stroke) = self.stroke.take() else {
+            return;
+        };
+
+        let (sx, sy) = stroke.start;
+        match self.tool {
+            Tool::Freehand => {
+                let (lx, ly) = stroke.last;
+                self.line(canvas, lx, ly, x, y);
+            }
+            Tool::Line => {
+                canvas.restore(&stroke.before);
+                self.line(canvas, sx, sy, x, y);
+            }
+            Tool::Rect => {
+                canvas.restore(&stroke.before);
+                let (left, top) = (sx.min(x), sy.min(y));
+                let (right, bottom) = (sx.max(x), sy.max(y));
+                self.line(canvas, left, top, right, top);
+                self.line(canvas, left, bottom, right, bottom);
+                self.line(canvas, left, top, left, bottom);
+                self.line(canvas, right, top, right, bottom);
+            }
+            Tool::Fill => {}
+        }
+
+        self.stroke = Some(Stroke {
+            last: (x, y),
+            ..stroke
+        });
+    }
+
+    /// Finish a stroke at a position on the canvas.
+    pub fn release(&mut self, canvas: &mut Canvas, x: u32, y: u32) {
+        self.drag(canvas, x, y);
+        self.stroke = None;
+    }
+
+    fn line(&self, canvas: &mut Canvas, x1: u32, y1: u32, x2: u32, y2: u32) {
+        for (x, y) in crate::line::Iter::new(x1, y1, x2, y2) {
+            self.dab(canvas, x, y);
+        }
+    }
+
+    /// Fill every cell connected to (x, y) that looks the same as it.
+    fn flood(&self, canvas: &mut Canvas, x: u32, y: u32) {
+        let (Some(target), brush) = (canvas.get(x, y), self.brush.cell()) else {
+            return;
+        };
+        if target == brush {
+            return;
+        }
+
+        let mut todo = vec![(x, y)];
+        while let Some((x, y)) = todo.pop() {
+            if canvas.get(x, y) != Some(target) {
+                continue;
+            }
+
+            self.dab(canvas, x, y);
+            todo.push((x + 1, y));
+            todo.push((x, y + 1));
+            if let Some(x) = x.checked_sub(1) {
+                todo.push((x, y));
+            }
+            if let Some(y) = y.checked_sub(1) {
+                todo.push((x, y));
+            }
+        }
+    }
+
+    /// Undo the last stroke, returning true if there was one to undo.
+    pub fn undo(&mut self, canvas: &mut Canvas) -> bool {
+        self.stroke = None;
+        let Some(before) = self.undo.pop() else {
+            return false;
+        };
+
+        self.redo.push(canvas.snapshot(canvas.bounds()));
+        canvas.restore(&before);
+        true
+    }
+
+    /// Redo the last stroke undone, returning true if there was one to
+    /// redo.
+    pub fn redo(&mut self, canvas: &mut Canvas) -> bool {
+        self.stroke = None;
+        let Some(after) = self.redo.pop() else {
+            return false;
+        };
+
+        self.undo.push(canvas.snapshot(canvas.bounds()));
+        canvas.restore(&after);
+        true
+    }
+
+    /// Handle a mouse event, drawing with the left button. The canvas is
+    /// expected to be drawn with its top-left corner at (x, y) on screen.
+    /// Returns true if the canvas changed.
+    ///
+    /// Only enabled on feature `crossterm`.
+    #[cfg(feature = "crossterm")]
+    pub fn handle(
+        &mut self,
+        canvas: &mut Canvas,
+        event: &crate::read::Event,
+        x: u32,
+        y: u32,
+    ) -> bool {
+        use crossterm::event::{Event, MouseButton, MouseEventKind};
+
+        let Event::Mouse(mouse) = event else {
+            return false;
+        };
+
+        let (column, row) = (u32::from(mouse.column), u32::from(mouse.row));
+        let area = crate::rect::Rect::new(x, y, canvas.width(), canvas.height());
+        if area.is_empty() {
+            return false;
+        }
+
+        let inside = area.contains(column, row);
+        // keep dragging along the edge when the mouse leaves the canvas
+        let (cx, cy) = (
+            column.clamp(x, x + area.width - 1) - x,
+            row.clamp(y, y + area.height - 1) - y,
+        );
+
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) if inside => self.press(canvas, cx, cy),
+            MouseEventKind::Drag(MouseButton::Left) if self.is_drawing() => {
+                self.drag(canvas, cx, cy);
+            }
+            MouseEventKind::Up(MouseButton::Left) if self.is_drawing() => {
+                self.release(canvas, cx, cy);
+            }
+            _ => return false,
+        }
+        true
+    }
+}
@@ -0,0 +1,430 @@
+//! Semantic colors, registered once and drawn by name.
+//!
+//! A [`Theme`] maps names (like `"error"` or `"accent"`) to [`Color`]s. One
+//! theme at a time is active; [`Theme::apply`] makes a theme active, and
+//! [`get`] looks up colors from it. Draw with [`themed`] and [`themed_bg`]
+//! to pick up the active theme's colors, so applying a new theme recolors
+//! everything without changing how it's drawn.
+//!
+//! Themes can also hold [style overrides](StyleOverride) for the parts of
+//! widgets, which cascade like a tiny stylesheet: see [`resolve`].
+//!
+//! With feature `theme_files`, themes can be loaded from TOML or JSON files
+//! (see `Theme::load`), and reloaded whenever the file changes (see
+//! `watch`). Theme files map names directly to colors, either palette
+//! indices, names, or hex codes:
+//!
+//! ```toml
+//! error = "#ff5f5f"
+//! warning = "bright_yellow"
+//! accent = 12
+//! ```
+//!
+//! This module is also available as [`crate::theme`], the name it had
+//! before it gathered the themed drawing functions, so code written against
+//! that name keeps working. [`themed`] and [`themed_bg`] live in
+//! [`crate::color`], next to the other ways to set colors, and are
+//! re-exported here.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use cod::color::Color;
+//! use cod::palette::{self, Theme};
+//!
+//! let mut light = Theme::new();
+//! light.set("error", Color::Red).set("accent", Color::Blue);
+//! let mut dark = light.clone();
+//! dark.set("error", Color::BrightRed).set("accent", Color::BrightCyan);
+//!
+//! for theme in [light, dark] {
+//!     theme.apply();
+//!
+//!     palette::themed("error");
+//!     cod::text("something went wrong", 0, 0);
+//!     color::de::fg();
+//!     cod::flush();
+//! }
+//!
+//! assert_eq!(palette::get("accent"), Some(Color::BrightCyan));
+//! ```
+
+pub use crate::color::{themed, themed_bg};
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::color::Color;
+use crate::style::{Style, StyleOverride};
+
+static CURRENT: RwLock<Option<Theme>> = RwLock::new(None);
+
+/// A set of named colors, and named style overrides.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Theme {
+    colors: HashMap<String, Color>,
+    styles: HashMap<String, StyleOverride>,
+}
+
+impl Theme {
+    /// Create an empty theme.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the color for a name, replacing any previous color.
+    pub fn set<S: Into<String>, C: Into<Color>>(&mut self, name: S, color: C) -> &mut Self {
+        self.colors.insert(name.into(), color.into());
+        self
+    }
+
+    /// Returns the color for a name, if it's set.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<Color> {
+        self.colors.get(name).copied()
+    }
+
+    /// Returns all the names and colors in the theme, in no particular order.
+    pub fn colors(&self) -> impl Iterator<Item = (&str, Color)> {
+        self.colors.iter().map(|(name, &c)| (name.as_str(), c))
+    }
+
+    /// Set the style override for a name, replacing any previous one. See
+    /// [`resolve`].
+    pub fn set_style<S: Into<String>>(&mut self, name: S, style: StyleOverride) -> &mut Self {
+        self.styles.insert(name.into(), style);
+        self
+    }
+
+    /// Returns the style override for a name, if it's set. Unlike
+    /// [`resolve`], this doesn't look at the names it's nested in.
+    #[must_use]
+    pub fn style(&self, name: &str) -> Option<StyleOverride> {
+        self.styles.get(name).copied()
+    }
+
+    /// Make this the active theme, replacing the previous one.
+    ///
+    /// # Panics
+    ///
+    /// If another thread panicked while applying a theme, panics.
+    pub fn apply(self) {
+        *CURRENT.write().unwrap() = Some(self);
+    }
+
+    /// Returns a copy of the active theme, if any.
+    ///
+    /// # Panics
+    ///
+    /// If another thread panicked while applying a theme, panics.
+    #[must_use]
+    pub fn current() -> Option<Self> {
+        CURRENT.read().unwrap().clone()
+    }
+}
+
+/// Returns the color for a name from the active theme, if there is one and
+/// the name is set.
+///
+/// # Panics
+///
+/// If another thread panicked while applying a theme, panics.
+#[must_use]
+pub fn get(name: &str) -> Option<Color> {
+    CURRENT.read().unwrap().as_ref()?.get(name)
+}
+
+/// Returns the style to draw a named part of a widget in, cascading from
+/// least to most specific:
+///
+/// 1. the widget's `default` style,
+/// 2. the active theme's style overrides for the name and every name it's
+///    nested in, outermost first (for `"hex.selection"`, `"hex"` then
+///    `"hex.selection"`),
+/// 3. the widget's own `overrides`.
+///
+/// ```rust
+/// use cod::style::{Style, StyleOverride};
+/// use cod::palette::{self, Theme};
+///
+/// let mut theme = Theme::new();
+/// theme
+///     .set_style("list", StyleOverride::new().fg(7))
+///     .set_style("list.selected", StyleOverride::new().bold(true));
+/// theme.apply();
+///
+/// let selected = palette::resolve("list.selected", Style::new().bg(4), &StyleOverride::new());
+/// assert_eq!(selected, Style::new().fg(7).bg(4).bold());
+///
+/// let tweaked = StyleOverride::new().fg(2).bold(false);
+/// let selected = palette::resolve("list.selected", Style::new().bg(4), &tweaked);
+/// assert_eq!(selected, Style::new().fg(2).bg(4));
+/// ```
+///
+/// # Panics
+///
+/// If another thread panicked while applying a theme, panics.
+#[must_use]
+pub fn resolve(name: &str, default: Style, overrides: &StyleOverride) -> Style {
+    let mut style = default;
+    if let Some(theme) = CURRENT.read().unwrap().as_ref() {
+        let ends = name.match_indices('.').map(|(i, _)| i).chain([name.len()]);
+        for end in ends {
+            if let Some(theme_style) = theme.style(&name[..end]) {
+                style = theme_style.over(style);
+            }
+        }
+    }
+
+    overrides.over(style)
+}
+
+#[cfg(feature = "theme_files")]
+pub use files::{watch, LoadError, Watcher};
+
+#[cfg(feature = "config")]
+pub(crate) use files::RawTheme;
+
+#[cfg(feature = "theme_files")]
+mod files {
+    use std::fmt;
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread::JoinHandle;
+    use std::time::{Duration, SystemTime};
+
+    use serde::Deserialize;
+
+    use super::Theme;
+    use crate::color::Color;
+
+    /// How often [`watch`] checks the file for changes.
+    const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+    /// A color as written in a theme file.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawColor {
+        Ansi(u8),
+        Text(String),
+    }
+
+    impl TryFrom<RawColor> for Color {
+        type Error = String;
+
+        fn try_from(raw: RawColor) -> Result<Self, Self::Error> {
+            match raw {
+                RawColor::Ansi(c) => Ok(Color::Ansi(c)),
+                RawColor::Text(s) => s.parse().map_err(|e| format!("invalid color `{s}`: {e}")),
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(transparent)]
+    pub(crate) struct RawTheme(std::collections::HashMap<String, RawColor>);
+
+    impl TryFrom<RawTheme> for Theme {
+        type Error = LoadError;
+
+        fn try_from(raw: RawTheme) -> Result<Self, Self::Error> {
+            let mut theme = Theme::new();
+            for (name, color) in raw.0 {
+                theme.set(name, Color::try_from(color).map_err(LoadError::Parse)?);
+            }
+
+            Ok(theme)
+        }
+    }
+
+    /// An error encountered while loading a theme.
+    #[derive(Debug)]
+    pub enum LoadError {
+        /// The file couldn't be read.
+        Io(io::Error),
+        /// The file's extension was neither `.toml` nor `.json`.
+        UnknownFormat,
+        /// The file's contents weren't a valid theme.
+        Parse(String),
+    }
+
+    impl fmt::Display for LoadError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Io(e) => write!(f, "failed to read theme: {e}"),
+                Self::UnknownFormat => write!(f, "theme files must end in `.toml` or `.json`"),
+                Self::Parse(e) => write!(f, "failed to parse theme: {e}"),
+            }
+        }
+    }
+
+    impl std::error::Error for LoadError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match self {
+                Self::Io(e) => Some(e),
+                _ => None,
+            }
+        }
+    }
+
+    impl From<io::Error> for LoadError {
+        fn from(e: io::Error) -> Self {
+            Self::Io(e)
+        }
+    }
+
+    impl Theme {
+        /// Load a theme from a TOML or JSON file, depending on its extension.
+        ///
+        /// Only enabled on feature `theme_files`.
+        ///
+        /// # Errors
+        ///
+        /// If the file can't be read or isn't a valid theme, returns an error.
+        pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
+            let path = path.as_ref();
+            let text = std::fs::read_to_string(path)?;
+
+            match path.extension().and_then(|e| e.to_str()) {
+                Some("toml") => Self::from_toml(&text),
+                Some("json") => Self::from_json(&text),
+                _ => Err(LoadError::UnknownFormat),
+            }
+        }
+
+        /// Parse a theme from TOML.
+        ///
+        /// Only enabled on feature `theme_files`.
+        ///
+        /// # Errors
+        ///
+        /// If the text isn't a valid theme, returns an error.
+        pub fn from_toml(text: &str) -> Result<Self, LoadError> {
+            toml::from_str::<RawTheme>(text)
+                .map_err(|e| LoadError::Parse(e.to_string()))?
+                .try_into()
+        }
+
+        /// Parse a theme from JSON.
+        ///
+        /// Only enabled on feature `theme_files`.
+        ///
+        /// # Errors
+        ///
+        /// If the text isn't a valid theme, returns an error.
+        pub fn from_json(text: &str) -> Result<Self, LoadError> {
+            serde_json::from_str::<RawTheme>(text)
+                .map_err(|e| LoadError::Parse(e.to_string()))?
+                .try_into()
+        }
+    }
+
+    /// Watches a theme file, reloading it when it changes. Stops watching
+    /// when dropped.
+    ///
+    /// Only enabled on feature `theme_files`.
+    #[must_use = "stops watching immediately unless stored"]
+    #[derive(Debug)]
+    pub struct Watcher {
+        stop: Arc<AtomicBool>,
+        thread: Option<JoinHandle<()>>,
+    }
+
+    impl Drop for Watcher {
+        fn drop(&mut self) {
+            self.stop.store(true, Ordering::Relaxed);
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Load and apply a theme file, then keep watching it for changes from a
+    /// background thread. Whenever the file changes, it's reloaded and
+    /// applied, then `on_reload` is called so you can redraw.
+    ///
+    /// Theme files only hold colors, so applying one replaces the active
+    /// theme's colors, but keeps its [style overrides](Theme::set_style).
+    ///
+    /// If a changed file fails to load, the previous theme stays active and
+    /// `on_reload` isn't called; the next change is picked up as usual.
+    ///
+    /// Only enabled on feature `theme_files`.
+    ///
+    /// ```rust
+    /// use cod::palette::{self, Theme};
+    /// use cod::style::StyleOverride;
+    ///
+    /// let path = std::env::temp_dir().join(format!("cod-theme-{}.toml", std::process::id()));
+    /// std::fs::write(&path, "error = 1").unwrap();
+    ///
+    /// let mut theme = Theme::new();
+    /// theme.set_style("rule", StyleOverride::new().bold(true));
+    /// theme.apply();
+    ///
+    /// let watcher = palette::watch(&path, |_| {}).unwrap();
+    /// let active = Theme::current().unwrap();
+    /// assert!(active.get("error").is_some());
+    /// assert!(active.style("rule").is_some());
+    /// # drop(watcher);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the initial load fails, returns an error and doesn't start watching.
+    pub fn watch<P, F>(path: P, mut on_reload: F) -> Result<Watcher, LoadError>
+    where
+        P: Into<PathBuf>,
+        F: FnMut(&Theme) + Send + 'static,
+    {
+        let path = path.into();
+        let mut last = modified(&path);
+        apply_colors(Theme::load(&path)?);
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    std::thread::sleep(POLL_INTERVAL);
+
+                    let now = modified(&path);
+                    if now == last {
+                        continue;
+                    }
+                    last = now;
+
+                    if let Ok(theme) = Theme::load(&path) {
+                        on_reload(&apply_colors(theme));
+                    }
+                }
+            })
+        };
+
+        Ok(Watcher {
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Make a theme loaded from a file active, keeping the active theme's
+    /// style overrides, and return it as applied.
+    fn apply_colors(mut theme: Theme) -> Theme {
+        let mut current = super::CURRENT.write().unwrap();
+        if let Some(active) = current.as_ref() {
+            theme.styles.clone_from(&active.styles);
+        }
+
+        *current = Some(theme.clone());
+        theme
+    }
+}
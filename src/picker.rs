@@ -0,0 +1,751 @@
+//! Interactive pickers, for choosing a value with the keyboard.
+//!
+//! Each picker can be embedded in your own interface, drawing onto a
+//! [`Canvas`] and handling key events, or used on its own as a blocking
+//! prompt in the middle of the screen (on feature `crossterm`).
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use cod::canvas::Canvas;
+//! use cod::color::Color;
+//! use cod::picker::ColorPicker;
+//!
+//! let mut picker = ColorPicker::new(Color::Ansi(196));
+//! let (width, height) = ColorPicker::SIZE;
+//!
+//! let mut canvas = Canvas::new(width, height);
+//! let area = canvas.bounds();
+//! picker.render(&mut canvas, area);
+//! canvas.present(0, 0);
+//! assert_eq!(picker.color(), Color::Ansi(196));
+//! ```
+
+use crate::canvas::Canvas;
+use crate::color::convert::{ansi_to_rgb, rgb_to_256};
+use crate::color::Color;
+use crate::rect::Rect;
+use crate::style::Style;
+
+/// What a picker did with an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    /// The event wasn't used.
+    Ignored,
+    /// The selection changed, so the picker needs to be redrawn.
+    Changed,
+    /// The user chose the current selection.
+    Chosen,
+    /// The user backed out without choosing anything.
+    Cancelled,
+}
+
+/// How a [`ColorPicker`] chooses colors.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorMode {
+    /// From a grid of the 256-color palette.
+    #[default]
+    Palette,
+    /// With a slider for each of red, green, and blue.
+    Rgb,
+}
+
+/// A picker for a [`Color`], either from the 256-color palette or by RGB.
+///
+/// Tab switches between the palette and RGB sliders. In the palette, the
+/// arrow keys move around the grid; with the sliders, up and down pick a
+/// channel, and left and right change it (by 16 with shift held). Enter
+/// chooses the color, and Esc cancels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorPicker {
+    mode: ColorMode,
+    index: u8,
+    rgb: [u8; 3],
+    /// The slider selected in RGB mode.
+    channel: usize,
+}
+
+impl ColorPicker {
+    /// The width and height a color picker needs to draw in full.
+    pub const SIZE: (u32, u32) = (34, 18);
+
+    /// Create a picker starting at the given color, in palette mode for a
+    /// palette color or RGB mode for a true-color.
+    #[must_use]
+    pub fn new(initial: Color) -> Self {
+        let (r, g, b) = initial.rgb();
+        let (mode, index) = match initial.index() {
+            Some(c) => (ColorMode::Palette, c),
+            None => (ColorMode::Rgb, rgb_to_256(r, g, b)),
+        };
+
+        Self {
+            mode,
+            index,
+            rgb: [r, g, b],
+            channel: 0,
+        }
+    }
+
+    /// Returns the color currently selected.
+    #[must_use]
+    pub fn color(&self) -> Color {
+        match self.mode {
+            ColorMode::Palette => Color::Ansi(self.index),
+            ColorMode::Rgb => Color::Rgb(self.rgb[0], self.rgb[1], self.rgb[2]),
+        }
+    }
+
+    /// Returns how colors are being chosen.
+    #[must_use]
+    pub fn mode(&self) -> ColorMode {
+        self.mode
+    }
+
+    /// Switch how colors are chosen, starting from the closest match to the
+    /// current color.
+    pub fn set_mode(&mut self, mode: ColorMode) {
+        match (self.mode, mode) {
+            (ColorMode::Palette, ColorMode::Rgb) => {
+                let (r, g, b) = ansi_to_rgb(self.index);
+                self.rgb = [r, g, b];
+            }
+            (ColorMode::Rgb, ColorMode::Palette) => {
+                self.index = rgb_to_256(self.rgb[0], self.rgb[1], self.rgb[2]);
+            }
+            _ => {}
+        }
+        self.mode = mode;
+    }
+
+    /// Move the selection in the palette grid.
+    #[cfg(feature = "crossterm")]
+    fn move_index(&mut self, dx: i32, dy: i32) -> bool {
+        let (x, y) = (i32::from(self.index % 16), i32::from(self.index / 16));
+        let (x, y) = ((x + dx).clamp(0, 15), (y + dy).clamp(0, 15));
+        let index = u8::try_from(y * 16 + x).unwrap_or(self.index);
+        let changed = index != self.index;
+        self.index = index;
+        changed
+    }
+
+    /// Change the selected RGB channel.
+    #[cfg(feature = "crossterm")]
+    fn nudge(&mut self, by: i32) -> bool {
+        let value = &mut self.rgb[self.channel];
+        let new = u8::try_from((i32::from(*value) + by).clamp(0, 255)).unwrap_or(*value);
+        let changed = new != *value;
+        *value = new;
+        changed
+    }
+
+    /// Handle a key press.
+    ///
+    /// Only enabled on feature `crossterm`.
+    #[cfg(feature = "crossterm")]
+    pub fn handle(&mut self, key: &crate::read::KeyEvent) -> Outcome {
+        use crate::read::{KeyCode, KeyEventKind, KeyModifiers};
+
+        if key.kind == KeyEventKind::Release {
+            return Outcome::Ignored;
+        }
+
+        let step = if key.modifiers.contains(KeyModifiers::SHIFT) {
+            16
+        } else {
+            1
+        };
+        let changed = match (self.mode, key.code) {
+            (_, KeyCode::Enter) => return Outcome::Chosen,
+            (_, KeyCode::Esc) => return Outcome::Cancelled,
+            (_, KeyCode::Tab | KeyCode::BackTab) => {
+                self.set_mode(match self.mode {
+                    ColorMode::Palette => ColorMode::Rgb,
+                    ColorMode::Rgb => ColorMode::Palette,
+                });
+                true
+            }
+
+            (ColorMode::Palette, KeyCode::Left) => self.move_index(-1, 0),
+            (ColorMode::Palette, KeyCode::Right) => self.move_index(1, 0),
+            (ColorMode::Palette, KeyCode::Up) => self.move_index(0, -1),
+            (ColorMode::Palette, KeyCode::Down) => self.move_index(0, 1),
+
+            (ColorMode::Rgb, KeyCode::Up) if self.channel > 0 => {
+                self.channel -= 1;
+                true
+            }
+            (ColorMode::Rgb, KeyCode::Down) if self.channel < 2 => {
+                self.channel += 1;
+                true
+            }
+            (ColorMode::Rgb, KeyCode::Left) => self.nudge(-step),
+            (ColorMode::Rgb, KeyCode::Right) => self.nudge(step),
+            _ => return Outcome::Ignored,
+        };
+
+        if changed {
+            Outcome::Changed
+        } else {
+            Outcome::Ignored
+        }
+    }
+
+    /// Draw the picker into an area of a canvas; see [`ColorPicker::SIZE`]
+    /// for how much room it needs.
+    pub fn render(&self, canvas: &mut Canvas, area: Rect) {
+        let previous = canvas.style();
+        canvas.erase(area);
+
+        let tabs = match self.mode {
+            ColorMode::Palette => "[Palette]  RGB ",
+            ColorMode::Rgb => " Palette  [RGB]",
+        };
+        canvas.set_style(Style::new());
+        canvas.text(tabs, area.x + 1, area.y);
+
+        match self.mode {
+            ColorMode::Palette => self.render_palette(canvas, area),
+            ColorMode::Rgb => self.render_sliders(canvas, area),
+        }
+
+        let color = self.color();
+        let bottom = area.y + area.height.saturating_sub(1);
+        canvas.set_style(Style::new().fg(color));
+        canvas.text("\u{2588}\u{2588}\u{2588}\u{2588}", area.x + 1, bottom);
+        canvas.set_style(Style::new());
+        let (r, g, b) = color.rgb();
+        let label = match color.index() {
+            Some(c) => format!("{c:>3}  #{r:02x}{g:02x}{b:02x}"),
+            None => format!("#{r:02x}{g:02x}{b:02x}"),
+        };
+        canvas.text(label, area.x + 6, bottom);
+
+        canvas.set_style(previous);
+    }
+
+    fn render_palette(&self, canvas: &mut Canvas, area: Rect) {
+        for index in 0..=255u8 {
+            let x = area.x + 1 + u32::from(index % 16) * 2;
+            let y = area.y + 1 + u32::from(index / 16);
+
+            let bg = Color::Ansi(index);
+            if index == self.index {
+                canvas.set_style(Style::new().fg(contrast(bg)).bg(bg));
+                canvas.text("[]", x, y);
+            } else {
+                canvas.set_style(Style::new().bg(bg));
+                canvas.text("  ", x, y);
+            }
+        }
+    }
+
+    fn render_sliders(&self, canvas: &mut Canvas, area: Rect) {
+        let width = area.width.saturating_sub(12).max(1);
+        let names = ["R", "G", "B"];
+        for (channel, (name, &value)) in names.iter().zip(&self.rgb).enumerate() {
+            let y = area.y + 2 + u32::try_from(channel).unwrap_or(0) * 2;
+            let marker = if channel == self.channel { '>' } else { ' ' };
+
+            canvas.set_style(Style::new());
+            canvas.text(format!("{marker}{name} {value:>3}"), area.x + 1, y);
+
+            let filled = (u32::from(value) * width).div_ceil(255);
+            let mut shade = [0; 3];
+            shade[channel] = 255;
+            canvas.set_style(Style::new().fg(Color::Rgb(shade[0], shade[1], shade[2])));
+            for x in 0..width {
+                let c = if x < filled { '\u{2588}' } else { '\u{2500}' };
+                canvas.pixel(c, area.x + 8 + x, y);
+            }
+        }
+    }
+}
+
+/// Returns black or white, whichever stands out more against a color.
+fn contrast(color: Color) -> Color {
+    let (r, g, b) = color.rgb();
+    let luma = u32::from(r) * 299 + u32::from(g) * 587 + u32::from(b) * 114;
+    if luma > 128_000 {
+        Color::Ansi(16)
+    } else {
+        Color::Ansi(231)
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl crate::widget::Widget<crate::read::KeyEvent> for ColorPicker {
+    fn update(&mut self, event: &crate::read::KeyEvent) -> bool {
+        self.handle(event) == Outcome::Changed
+    }
+
+    fn render(&self, canvas: &mut Canvas, area: Rect) {
+        ColorPicker::render(self, canvas, area);
+    }
+}
+
+/// Show a color picker in the middle of the screen and wait for the user to
+/// choose a color, returning it, or `None` if they cancelled.
+///
+/// Erases the picker afterwards, leaving blank space behind. Expects raw
+/// mode to be enabled.
+///
+/// Only enabled on feature `crossterm`.
+#[cfg(feature = "crossterm")]
+#[allow(clippy::must_use_candidate)]
+pub fn pick_color(initial: Color) -> Option<Color> {
+    let mut picker = ColorPicker::new(initial);
+    let chosen = prompt(ColorPicker::SIZE, |canvas, area, key| {
+        if let Some(key) = key {
+            match picker.handle(key) {
+                Outcome::Chosen => return Some(Some(picker.color())),
+                Outcome::Cancelled => return Some(None),
+                _ => {}
+            }
+        }
+        picker.render(canvas, area);
+        None
+    });
+    chosen.flatten()
+}
+
+/// A set of glyphs to choose from in a [`GlyphPicker`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GlyphCategory {
+    /// Every glyph.
+    #[default]
+    All,
+    /// Box-drawing lines and corners.
+    Box,
+    /// Blocks and shades.
+    Block,
+    /// Arrows and triangles.
+    Arrow,
+}
+
+impl GlyphCategory {
+    const ALL: [Self; 4] = [Self::All, Self::Box, Self::Block, Self::Arrow];
+
+    /// Returns the category's name, as shown in the picker.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::All => "All",
+            Self::Box => "Box",
+            Self::Block => "Block",
+            Self::Arrow => "Arrow",
+        }
+    }
+
+    fn glyphs(self) -> &'static [(char, &'static str)] {
+        match self {
+            Self::All => &[],
+            Self::Box => BOX_GLYPHS,
+            Self::Block => BLOCK_GLYPHS,
+            Self::Arrow => ARROW_GLYPHS,
+        }
+    }
+
+    #[cfg(feature = "crossterm")]
+    fn next(self, by: isize) -> Self {
+        let i = Self::ALL.iter().position(|&c| c == self).unwrap_or(0);
+        let i = (i.cast_signed() + by).rem_euclid(Self::ALL.len().cast_signed());
+        Self::ALL[i.cast_unsigned()]
+    }
+}
+
+const BOX_GLYPHS: &[(char, &str)] = &[
+    ('\u{2500}', "light horizontal"),
+    ('\u{2502}', "light vertical"),
+    ('\u{250c}', "light down and right"),
+    ('\u{2510}', "light down and left"),
+    ('\u{2514}', "light up and right"),
+    ('\u{2518}', "light up and left"),
+    ('\u{251c}', "light vertical and right"),
+    ('\u{2524}', "light vertical and left"),
+    ('\u{252c}', "light down and horizontal"),
+    ('\u{2534}', "light up and horizontal"),
+    ('\u{253c}', "light vertical and horizontal"),
+    ('\u{2501}', "heavy horizontal"),
+    ('\u{2503}', "heavy vertical"),
+    ('\u{250f}', "heavy down and right"),
+    ('\u{2513}', "heavy down and left"),
+    ('\u{2517}', "heavy up and right"),
+    ('\u{251b}', "heavy up and left"),
+    ('\u{2523}', "heavy vertical and right"),
+    ('\u{252b}', "heavy vertical and left"),
+    ('\u{2533}', "heavy down and horizontal"),
+    ('\u{253b}', "heavy up and horizontal"),
+    ('\u{254b}', "heavy vertical and horizontal"),
+    ('\u{2550}', "double horizontal"),
+    ('\u{2551}', "double vertical"),
+    ('\u{2554}', "double down and right"),
+    ('\u{2557}', "double down and left"),
+    ('\u{255a}', "double up and right"),
+    ('\u{255d}', "double up and left"),
+    ('\u{2560}', "double vertical and right"),
+    ('\u{2563}', "double vertical and left"),
+    ('\u{2566}', "double down and horizontal"),
+    ('\u{2569}', "double up and horizontal"),
+    ('\u{256c}', "double vertical and horizontal"),
+    ('\u{256d}', "rounded down and right"),
+    ('\u{256e}', "rounded down and left"),
+    ('\u{2570}', "rounded up and right"),
+    ('\u{256f}', "rounded up and left"),
+    ('\u{2504}', "light dashed horizontal"),
+    ('\u{2506}', "light dashed vertical"),
+    ('\u{2505}', "heavy dashed horizontal"),
+    ('\u{2507}', "heavy dashed vertical"),
+    ('\u{2571}', "diagonal rising"),
+    ('\u{2572}', "diagonal falling"),
+    ('\u{2573}', "diagonal cross"),
+];
+
+const BLOCK_GLYPHS: &[(char, &str)] = &[
+    ('\u{2588}', "full block"),
+    ('\u{2580}', "upper half block"),
+    ('\u{2584}', "lower half block"),
+    ('\u{258c}', "left half block"),
+    ('\u{2590}', "right half block"),
+    ('\u{2591}', "light shade"),
+    ('\u{2592}', "medium shade"),
+    ('\u{2593}', "dark shade"),
+    ('\u{2581}', "lower one eighth block"),
+    ('\u{2582}', "lower one quarter block"),
+    ('\u{2583}', "lower three eighths block"),
+    ('\u{2585}', "lower five eighths block"),
+    ('\u{2586}', "lower three quarters block"),
+    ('\u{2587}', "lower seven eighths block"),
+    ('\u{2594}', "upper one eighth block"),
+    ('\u{258f}', "left one eighth block"),
+    ('\u{258e}', "left one quarter block"),
+    ('\u{258d}', "left three eighths block"),
+    ('\u{258b}', "left five eighths block"),
+    ('\u{258a}', "left three quarters block"),
+    ('\u{2589}', "left seven eighths block"),
+    ('\u{2595}', "right one eighth block"),
+    ('\u{2596}', "quadrant lower left"),
+    ('\u{2597}', "quadrant lower right"),
+    ('\u{2598}', "quadrant upper left"),
+    ('\u{259d}', "quadrant upper right"),
+    ('\u{259a}', "quadrant upper left and lower right"),
+    ('\u{259e}', "quadrant upper right and lower left"),
+    ('\u{2599}', "quadrant all but upper right"),
+    ('\u{259b}', "quadrant all but lower right"),
+    ('\u{259c}', "quadrant all but lower left"),
+    ('\u{259f}', "quadrant all but upper left"),
+    ('\u{25a0}', "black square"),
+    ('\u{25a1}', "white square"),
+    ('\u{25aa}', "black small square"),
+    ('\u{25ab}', "white small square"),
+];
+
+const ARROW_GLYPHS: &[(char, &str)] = &[
+    ('\u{2190}', "left arrow"),
+    ('\u{2192}', "right arrow"),
+    ('\u{2191}', "up arrow"),
+    ('\u{2193}', "down arrow"),
+    ('\u{2194}', "left right arrow"),
+    ('\u{2195}', "up down arrow"),
+    ('\u{2196}', "up left arrow"),
+    ('\u{2197}', "up right arrow"),
+    ('\u{2198}', "down right arrow"),
+    ('\u{2199}', "down left arrow"),
+    ('\u{21d0}', "double left arrow"),
+    ('\u{21d2}', "double right arrow"),
+    ('\u{21d1}', "double up arrow"),
+    ('\u{21d3}', "double down arrow"),
+    ('\u{21d4}', "double left right arrow"),
+    ('\u{21d5}', "double up down arrow"),
+    ('\u{21a9}', "left arrow with hook"),
+    ('\u{21aa}', "right arrow with hook"),
+    ('\u{21ba}', "anticlockwise circle arrow"),
+    ('\u{21bb}', "clockwise circle arrow"),
+    ('\u{25b2}', "black up triangle"),
+    ('\u{25bc}', "black down triangle"),
+    ('\u{25c0}', "black left triangle"),
+    ('\u{25b6}', "black right triangle"),
+    ('\u{25b3}', "white up triangle"),
+    ('\u{25bd}', "white down triangle"),
+    ('\u{25c1}', "white left triangle"),
+    ('\u{25b7}', "white right triangle"),
+];
+
+/// A picker for a box-drawing, block, or arrow glyph.
+///
+/// Typing searches the glyphs by name (e.g. "heavy down"), and Backspace
+/// deletes from the search. Tab and Shift-Tab switch between categories,
+/// and the arrow keys move around the grid. Enter chooses the glyph, and
+/// Esc cancels.
+///
+/// Example:
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// use cod::picker::{GlyphCategory, GlyphPicker};
+///
+/// let mut picker = GlyphPicker::new();
+/// picker.set_category(GlyphCategory::Box);
+/// picker.set_query("double down right");
+/// assert_eq!(picker.glyph(), Some('\u{2554}'));
+/// assert_eq!(picker.matches().len(), 1);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GlyphPicker {
+    category: GlyphCategory,
+    query: String,
+    /// The index of the selection in the matching glyphs.
+    selected: usize,
+}
+
+impl GlyphPicker {
+    /// The width and height a glyph picker needs to draw in full.
+    pub const SIZE: (u32, u32) = (34, 12);
+
+    /// Glyphs per row of the grid.
+    const COLUMNS: usize = 16;
+
+    /// Create a picker showing every glyph, with nothing searched for.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the category of glyphs shown.
+    #[must_use]
+    pub fn category(&self) -> GlyphCategory {
+        self.category
+    }
+
+    /// Show only the glyphs in a category, keeping the selection if it's
+    /// still shown.
+    pub fn set_category(&mut self, category: GlyphCategory) {
+        self.refilter(|picker| picker.category = category);
+    }
+
+    /// Returns the text being searched for.
+    #[must_use]
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Show only the glyphs whose names contain every word of `query`,
+    /// keeping the selection if it's still shown.
+    pub fn set_query<S: Into<String>>(&mut self, query: S) {
+        let query = query.into();
+        self.refilter(|picker| picker.query = query);
+    }
+
+    /// Returns every glyph shown, with its name.
+    #[must_use]
+    pub fn matches(&self) -> Vec<(char, &'static str)> {
+        let glyphs: Vec<(char, &'static str)> = if self.category == GlyphCategory::All {
+            [BOX_GLYPHS, BLOCK_GLYPHS, ARROW_GLYPHS].concat()
+        } else {
+            self.category.glyphs().to_vec()
+        };
+
+        let query = self.query.to_lowercase();
+        glyphs
+            .into_iter()
+            .filter(|(_, name)| query.split_whitespace().all(|word| name.contains(word)))
+            .collect()
+    }
+
+    /// Returns the glyph currently selected, or `None` if nothing matches
+    /// the search.
+    #[must_use]
+    pub fn glyph(&self) -> Option<char> {
+        self.matches().get(self.selected).map(|&(c, _)| c)
+    }
+
+    /// Change the filter, then select the same glyph as before if it's
+    /// still shown, or the first one otherwise.
+    fn refilter(&mut self, change: impl FnOnce(&mut Self)) {
+        let previous = self.glyph();
+        change(self);
+        self.selected = self
+            .matches()
+            .iter()
+            .position(|&(c, _)| Some(c) == previous)
+            .unwrap_or(0);
+    }
+
+    /// Move the selection in the grid.
+    #[cfg(feature = "crossterm")]
+    fn move_selection(&mut self, by: isize) -> bool {
+        let count = self.matches().len();
+        let Some(selected) = self.selected.checked_add_signed(by) else {
+            return false;
+        };
+        if selected >= count {
+            return false;
+        }
+
+        self.selected = selected;
+        true
+    }
+
+    /// Handle a key press.
+    ///
+    /// Only enabled on feature `crossterm`.
+    #[cfg(feature = "crossterm")]
+    pub fn handle(&mut self, key: &crate::read::KeyEvent) -> Outcome {
+        use crate::read::{KeyCode, KeyEventKind, KeyModifiers};
+
+        if key.kind == KeyEventKind::Release {
+            return Outcome::Ignored;
+        }
+
+        let columns = Self::COLUMNS.cast_signed();
+        let changed = match key.code {
+            KeyCode::Enter if self.glyph().is_some() => return Outcome::Chosen,
+            KeyCode::Esc => return Outcome::Cancelled,
+            KeyCode::Tab => {
+                self.set_category(self.category.next(1));
+                true
+            }
+            KeyCode::BackTab => {
+                self.set_category(self.category.next(-1));
+                true
+            }
+            KeyCode::Backspace => {
+                let mut query = self.query.clone();
+                let changed = query.pop().is_some();
+                self.set_query(query);
+                changed
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let query = format!("{}{c}", self.query);
+                self.set_query(query);
+                true
+            }
+            KeyCode::Left => self.move_selection(-1),
+            KeyCode::Right => self.move_selection(1),
+            KeyCode::Up => self.move_selection(-columns),
+            KeyCode::Down => self.move_selection(columns),
+            _ => return Outcome::Ignored,
+        };
+
+        if changed {
+            Outcome::Changed
+        } else {
+            Outcome::Ignored
+        }
+    }
+
+    /// Draw the picker into an area of a canvas; see [`GlyphPicker::SIZE`]
+    /// for how much room it needs.
+    pub fn render(&self, canvas: &mut Canvas, area: Rect) {
+        let previous = canvas.style();
+        canvas.erase(area);
+        canvas.set_style(Style::new());
+
+        let mut x = area.x + 1;
+        for category in GlyphCategory::ALL {
+            let name = category.name();
+            let tab = if category == self.category {
+                format!("[{name}]")
+            } else {
+                format!(" {name} ")
+            };
+            canvas.text(&tab, x, area.y);
+            x += u32::try_from(tab.len()).unwrap_or(0) + 1;
+        }
+        canvas.text(format!("/ {}_", self.query), area.x + 1, area.y + 1);
+
+        let matches = self.matches();
+        let rows = area.height.saturating_sub(4).max(1) as usize;
+        let first = (self.selected / Self::COLUMNS).saturating_sub(rows - 1);
+        for (i, &(c, _)) in matches
+            .iter()
+            .enumerate()
+            .skip(first * Self::COLUMNS)
+            .take(rows * Self::COLUMNS)
+        {
+            let column = u32::try_from(i % Self::COLUMNS).unwrap_or(0);
+            let row = u32::try_from(i / Self::COLUMNS - first).unwrap_or(0);
+            let (x, y) = (area.x + 1 + column * 2, area.y + 2 + row);
+            if i == self.selected {
+                canvas.set_style(Style::new().fg(Color::Black).bg(Color::White));
+            }
+            canvas.pixel(c, x, y);
+            canvas.set_style(Style::new());
+        }
+
+        let bottom = area.y + area.height.saturating_sub(1);
+        let preview = match matches.get(self.selected) {
+            Some(&(c, name)) => format!("{c}{c}{c}  U+{:04X} {name}", u32::from(c)),
+            None => "no matches".to_string(),
+        };
+        canvas.text(preview, area.x + 1, bottom);
+
+        canvas.set_style(previous);
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl crate::widget::Widget<crate::read::KeyEvent> for GlyphPicker {
+    fn update(&mut self, event: &crate::read::KeyEvent) -> bool {
+        self.handle(event) == Outcome::Changed
+    }
+
+    fn render(&self, canvas: &mut Canvas, area: Rect) {
+        GlyphPicker::render(self, canvas, area);
+    }
+}
+
+/// Show a glyph picker in the middle of the screen and wait for the user to
+/// choose a glyph, returning it, or `None` if they cancelled.
+///
+/// Erases the picker afterwards, leaving blank space behind. Expects raw
+/// mode to be enabled.
+///
+/// Only enabled on feature `crossterm`.
+#[cfg(feature = "crossterm")]
+#[allow(clippy::must_use_candidate)]
+pub fn pick_glyph() -> Option<char> {
+    let mut picker = GlyphPicker::new();
+    let chosen = prompt(GlyphPicker::SIZE, |canvas, area, key| {
+        if let Some(key) = key {
+            match picker.handle(key) {
+                Outcome::Chosen => return Some(picker.glyph()),
+                Outcome::Cancelled => return Some(None),
+                _ => {}
+            }
+        }
+        picker.render(canvas, area);
+        None
+    });
+    chosen.flatten()
+}
+
+/// Run a picker in the middle of the screen until `step` returns a result.
+/// `step` is first called without a key, to draw the picker.
+#[cfg(feature = "crossterm")]
+fn prompt<T>(
+    (width, height): (u32, u32),
+    mut step: impl FnMut(&mut Canvas, Rect, Option<&crate::read::KeyEvent>) -> Option<T>,
+) -> Option<T> {
+    let (cols, rows) = crate::term::size_or();
+    let (width, height) = (width.min(cols), height.min(rows));
+    let (x, y) = ((cols - width) / 2, (rows - height) / 2);
+
+    let mut canvas = Canvas::new(width, height);
+    let area = canvas.bounds();
+    let mut result = step(&mut canvas, area, None);
+    while result.is_none() {
+        canvas.present(x, y);
+        crate::flush();
+
+        let Some(key) = crate::read::key() else {
+            continue;
+        };
+        result = step(&mut canvas, area, Some(&key));
+    }
+
+    Canvas::new(width, height).draw(x, y);
+    crate::flush();
+    result
+}
@@ -1,6 +1,29 @@
 #![allow(missing_docs)]
 
-pub use crate::{clear, color, goto, guard, rect, style, term};
+pub use crate::{
+    a11y, animation, bigtext, braille, canvas, clear, color, compat, diagram, diff, edit, focus,
+    goto, gradient, guard, halfblock, hex, interpolate, layout, paint, palette, picker, profile,
+    progress, queue, rect, region, scroll, spans, sprite, style, target, template, term, terminal,
+    theme, timeline, timer, vt, widget, width,
+};
+
+#[cfg(feature = "config")]
+pub use crate::config;
+
+#[cfg(feature = "image")]
+pub use crate::image;
+
+#[cfg(feature = "markdown")]
+pub use crate::markdown;
+
+#[cfg(feature = "mirror")]
+pub use crate::mirror;
+
+#[cfg(all(feature = "pty", unix))]
+pub use crate::pty;
+
+#[cfg(feature = "stats")]
+pub use crate::stats;
 
 #[cfg(feature = "crossterm")]
-pub use crate::read;
+pub use crate::{demo, help, keymap, read};
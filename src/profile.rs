@@ -0,0 +1,219 @@
+//! Choosing how much of the terminal to use, all at once.
+//!
+//! How many colors to send, whether to draw box-drawing and block
+//! characters or fall back to ASCII, and whether to show pictures with a
+//! graphics protocol are usually decided separately, and often
+//! inconsistently. A [`Profile`] ties them together:
+//!
+//! | Profile               | Colors                        | Characters | Graphics               |
+//! |-----------------------|-------------------------------|------------|------------------------|
+//! | [`Profile::Full`]     | [true-color](ColorDepth)      | Unicode    | [detected](Graphics)   |
+//! | [`Profile::Standard`] | [256 colors](ColorDepth)      | Unicode    | none                   |
+//! | [`Profile::Minimal`]  | [16 colors](ColorDepth)       | ASCII      | none                   |
+//!
+//! Pick one at startup with [`set`], usually the one [`Profile::detect`]
+//! suggests, or use [`with`] to draw something with another one, e.g. a
+//! plain-text log of the screen. Colors are never sent if
+//! [`NO_COLOR`](https://no-color.org) is set, whatever the profile.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use cod::profile::{self, Graphics, Profile};
+//!
+//! profile::set(Profile::detect());
+//!
+//! if profile::graphics() == Graphics::None {
+//!     rect::border(rect::Rect::new(0, 0, 10, 4));
+//! }
+//!
+//! // the same box, as a plain ASCII fallback
+//! let plain = profile::with(Profile::Minimal, || {
+//!     target::capture(|| {
+//!         rect::border(rect::Rect::new(0, 0, 10, 4));
+//!     })
+//! });
+//! assert!(plain.is_ascii());
+//! # profile::set(Profile::Full);
+//! ```
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::color::{self, ColorDepth};
+use crate::compat;
+
+/// The profile chosen with [`set`], or 0 if none has been.
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+/// A graphics protocol for showing pictures at full resolution.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Graphics {
+    /// No graphics protocol; pictures have to be drawn with characters,
+    /// e.g. with [`crate::halfblock`].
+    #[default]
+    None,
+    /// Sixel graphics.
+    Sixel,
+    /// The kitty graphics protocol.
+    Kitty,
+}
+
+impl Graphics {
+    /// Guess which graphics protocol the terminal supports from the
+    /// environment.
+    ///
+    /// kitty, `WezTerm`, and Ghostty support the kitty protocol; a `TERM`
+    /// mentioning `sixel`, as well as foot, mlterm, and iTerm2, mean sixel.
+    /// Inside `tmux` or `screen`, pictures don't make it through, so this
+    /// is always [`Graphics::None`].
+    #[must_use]
+    pub fn detect() -> Self {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        let term = var("TERM");
+        let term_program = var("TERM_PROGRAM");
+
+        if std::env::var_os("TMUX").is_some()
+            || term.starts_with("screen")
+            || term.starts_with("tmux")
+        {
+            Self::None
+        } else if std::env::var_os("KITTY_WINDOW_ID").is_some()
+            || term == "xterm-kitty"
+            || term == "xterm-ghostty"
+            || matches!(term_program.as_str(), "WezTerm" | "ghostty")
+        {
+            Self::Kitty
+        } else if term.contains("sixel")
+            || term.starts_with("foot")
+            || term.starts_with("mlterm")
+            || term_program == "iTerm.app"
+        {
+            Self::Sixel
+        } else {
+            Self::None
+        }
+    }
+}
+
+/// How much of the terminal's capabilities to use. See the
+/// [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Profile {
+    /// Only ASCII and the 16 basic colors, for ancient or limited terminals.
+    Minimal,
+    /// Unicode and the 256-color palette, which almost every terminal
+    /// supports.
+    Standard,
+    /// Everything: true-color, Unicode, and a graphics protocol if one is
+    /// available.
+    Full,
+}
+
+impl Profile {
+    /// Guess the best profile for the terminal from the environment.
+    ///
+    /// It's [`Profile::Minimal`] if [compat mode](compat::detect) would be
+    /// turned on or the terminal only has [16 colors or
+    /// fewer](ColorDepth::detect), [`Profile::Full`] if it has true-color,
+    /// and [`Profile::Standard`] otherwise.
+    #[must_use]
+    pub fn detect() -> Self {
+        if compat::detect() {
+            return Self::Minimal;
+        }
+
+        match ColorDepth::detect() {
+            ColorDepth::NoColor | ColorDepth::Colors16 => Self::Minimal,
+            ColorDepth::Colors256 => Self::Standard,
+            ColorDepth::TrueColor => Self::Full,
+        }
+    }
+
+    /// Returns how many colors the profile sends, before taking `NO_COLOR`
+    /// into account.
+    #[must_use]
+    pub const fn color_depth(self) -> ColorDepth {
+        match self {
+            Self::Minimal => ColorDepth::Colors16,
+            Self::Standard => ColorDepth::Colors256,
+            Self::Full => ColorDepth::TrueColor,
+        }
+    }
+
+    /// Returns true if the profile draws Unicode box-drawing and block
+    /// characters, rather than [ASCII approximations](compat::ascii).
+    #[must_use]
+    pub const fn unicode(self) -> bool {
+        !matches!(self, Self::Minimal)
+    }
+
+    /// Returns the graphics protocol the profile uses: the
+    /// [detected](Graphics::detect) one for [`Profile::Full`], otherwise
+    /// none.
+    #[must_use]
+    pub fn graphics(self) -> Graphics {
+        match self {
+            Self::Full => Graphics::detect(),
+            Self::Standard | Self::Minimal => Graphics::None,
+        }
+    }
+
+    fn from_u8(x: u8) -> Option<Self> {
+        match x {
+            1 => Some(Self::Minimal),
+            2 => Some(Self::Standard),
+            3 => Some(Self::Full),
+            _ => None,
+        }
+    }
+
+    const fn to_u8(self) -> u8 {
+        self as u8 + 1
+    }
+}
+
+/// Use a profile from now on: sets the [color depth](color::set_depth) and
+/// [compat mode](compat::set_enabled) to match it.
+pub fn set(profile: Profile) {
+    let depth = if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        ColorDepth::NoColor
+    } else {
+        profile.color_depth()
+    };
+
+    color::set_depth(depth);
+    compat::set_enabled(!profile.unicode());
+    CURRENT.store(profile.to_u8(), Ordering::Relaxed);
+}
+
+/// Returns the profile in use, or `None` if one hasn't been chosen with
+/// [`set`].
+#[must_use]
+pub fn current() -> Option<Profile> {
+    Profile::from_u8(CURRENT.load(Ordering::Relaxed))
+}
+
+/// Returns the graphics protocol of the profile in use (see
+/// [`Profile::graphics`]), or [`Graphics::None`] if no profile has been
+/// chosen.
+#[must_use]
+pub fn graphics() -> Graphics {
+    current().map_or(Graphics::None, Profile::graphics)
+}
+
+/// Run a function with a profile in use, then put back the settings from
+/// before, whether or not they came from a profile.
+pub fn with<R, F: FnOnce() -> R>(profile: Profile, f: F) -> R {
+    let depth = color::depth();
+    let compat = compat::is_enabled();
+    let previous = CURRENT.load(Ordering::Relaxed);
+
+    set(profile);
+    let result = f();
+
+    color::set_depth(depth);
+    compat::set_enabled(compat);
+    CURRENT.store(previous, Ordering::Relaxed);
+    result
+}
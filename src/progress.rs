@@ -0,0 +1,240 @@
+//! Progress bars, and formatting for the numbers that go with them.
+//!
+//! [`human_bytes`], [`format_rate`], and [`format_eta`] produce labels like
+//! `12.3 MB/s, 00:42 left`. An [`Estimator`] smooths out the rate of
+//! progress so those labels don't jump around, and a [`Bar`] ties it all
+//! together.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! # use std::time::Duration;
+//! use cod::progress::{format_eta, format_rate, human_bytes};
+//!
+//! assert_eq!(human_bytes(12_345_678), "12.3 MB");
+//! assert_eq!(format_rate(12_345_678.0), "12.3 MB/s");
+//! assert_eq!(format_eta(Duration::from_secs(42)), "00:42");
+//! assert_eq!(format_eta(Duration::from_secs(3723)), "1:02:03");
+//! ```
+
+use std::time::{Duration, Instant};
+
+/// Format a number of bytes with a unit, e.g. `12.3 MB`. Uses powers of
+/// 1000, like most download tools.
+#[must_use]
+pub fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["KB", "MB", "GB", "TB", "PB", "EB"];
+
+    if bytes < 1000 {
+        return format!("{bytes} B");
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let mut n = bytes as f64 / 1000.0;
+    let mut unit = 0;
+    while n >= 999.95 && unit < UNITS.len() - 1 {
+        n /= 1000.0;
+        unit += 1;
+    }
+
+    format!("{n:.1} {}", UNITS[unit])
+}
+
+/// Format a rate in bytes per second, e.g. `12.3 MB/s`.
+#[must_use]
+pub fn format_rate(bytes_per_sec: f64) -> String {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let bytes = bytes_per_sec.max(0.0).round() as u64;
+    format!("{}/s", human_bytes(bytes))
+}
+
+/// Format a remaining time as `mm:ss`, or `h:mm:ss` if it's an hour or more.
+#[must_use]
+pub fn format_eta(eta: Duration) -> String {
+    let secs = eta.as_secs();
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m:02}:{s:02}")
+    }
+}
+
+/// Estimates the rate of progress, smoothed over time.
+///
+/// Uses an exponential moving average: each update moves the estimate a
+/// fraction of the way towards the latest measured rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimator {
+    smoothing: f64,
+    rate: Option<f64>,
+    last: Option<(Instant, u64)>,
+}
+
+impl Default for Estimator {
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
+
+impl Estimator {
+    /// Create an estimator. `smoothing` is how much weight each update
+    /// gets, from 0 (never change) to 1 (only use the latest update).
+    #[must_use]
+    pub fn new(smoothing: f64) -> Self {
+        Self {
+            smoothing: smoothing.clamp(0.0, 1.0),
+            rate: None,
+            last: None,
+        }
+    }
+
+    /// Record the total amount of progress made so far.
+    pub fn update(&mut self, done: u64) {
+        self.update_at(done, Instant::now());
+    }
+
+    /// Record the total amount of progress made as of a given time.
+    pub fn update_at(&mut self, done: u64, now: Instant) {
+        if let Some((then, before)) = self.last {
+            let elapsed = now.saturating_duration_since(then).as_secs_f64();
+            if elapsed <= 0.0 {
+                return;
+            }
+
+            #[allow(clippy::cast_precision_loss)]
+            let rate = done.saturating_sub(before) as f64 / elapsed;
+            self.rate = Some(match self.rate {
+                Some(old) => old + (rate - old) * self.smoothing,
+                None => rate,
+            });
+        }
+
+        self.last = Some((now, done));
+    }
+
+    /// Returns the estimated rate, in units per second, once there have been
+    /// at least two updates.
+    #[must_use]
+    pub fn rate(&self) -> Option<f64> {
+        self.rate
+    }
+
+    /// Returns the estimated time left to make some more progress, if the
+    /// rate is known and positive.
+    #[must_use]
+    pub fn eta(&self, remaining: u64) -> Option<Duration> {
+        let rate = self.rate.filter(|&r| r > 0.0)?;
+        #[allow(clippy::cast_precision_loss)]
+        Duration::try_from_secs_f64(remaining as f64 / rate).ok()
+    }
+}
+
+/// A progress bar, measured in bytes, e.g.
+/// `[#######       ]  50% 12.3 MB/s, 00:42 left`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bar {
+    total: u64,
+    done: u64,
+    estimator: Estimator,
+}
+
+impl Bar {
+    /// Create an empty progress bar.
+    #[must_use]
+    pub fn new(total: u64) -> Self {
+        Self {
+            total,
+            done: 0,
+            estimator: Estimator::default(),
+        }
+    }
+
+    /// Use a different estimator, e.g. with different smoothing.
+    #[must_use]
+    pub fn with_estimator(mut self, estimator: Estimator) -> Self {
+        self.estimator = estimator;
+        self
+    }
+
+    /// Set the total amount of progress made.
+    pub fn set(&mut self, done: u64) {
+        self.done = done.min(self.total);
+        self.estimator.update(self.done);
+    }
+
+    /// Add to the amount of progress made.
+    pub fn inc(&mut self, n: u64) {
+        self.set(self.done.saturating_add(n));
+    }
+
+    /// Returns the amount of progress made.
+    #[must_use]
+    pub fn done(&self) -> u64 {
+        self.done
+    }
+
+    /// Returns the total amount of progress to be made.
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Returns the fraction of progress made, from 0 to 1.
+    #[must_use]
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            return 1.0;
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let fraction = self.done as f64 / self.total as f64;
+        fraction
+    }
+
+    /// Returns the text after the bar, e.g. `50% 12.3 MB/s, 00:42 left`.
+    #[must_use]
+    pub fn label(&self) -> String {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let percent = (self.fraction() * 100.0).floor() as u8;
+        let mut label = format!("{percent:>3}%");
+
+        if let Some(rate) = self.estimator.rate() {
+            label.push(' ');
+            label.push_str(&format_rate(rate));
+            if let Some(eta) = self.estimator.eta(self.total - self.done) {
+                label.push_str(", ");
+                label.push_str(&format_eta(eta));
+                label.push_str(" left");
+            }
+        }
+
+        label
+    }
+
+    /// Draw the bar and its label onto the screen, `width` columns wide in
+    /// total.
+    pub fn draw(&self, x: u32, y: u32, width: u32) {
+        profile!();
+        let label = self.label();
+        let label_width = u32::try_from(crate::width::str_width(&label)).unwrap_or(u32::MAX);
+        let inner = width.saturating_sub(label_width + 3) as usize;
+
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let filled = ((inner as f64 * self.fraction()) as usize).min(inner);
+
+        let mut bar = String::with_capacity(width as usize);
+        bar.push('[');
+        bar.extend(std::iter::repeat_n('#', filled));
+        bar.extend(std::iter::repeat_n(' ', inner - filled));
+        bar.push_str("] ");
+        bar.push_str(&label);
+
+        crate::run(&bar, x, y);
+    }
+}
@@ -0,0 +1,425 @@
+//! Running a program inside a pane of your app, e.g. to embed a shell or an
+//! external tool.
+//!
+//! A [`Pane`] spawns a command on a pseudo-terminal, so the program thinks
+//! it's talking to a terminal. Everything it prints is interpreted by a
+//! minimal terminal emulator (see [`crate::vt`]) into cells, which can be
+//! drawn onto a [`Canvas`] like any other widget. When the pane is focused,
+//! keys are forwarded to the program.
+//!
+//! The emulator only understands the basics: text, cursor movement,
+//! erasing, scrolling, and colors. Line-based programs (shells, compilers,
+//! REPLs) work well, but full-screen programs may not draw correctly.
+//!
+//! Only enabled on feature `pty`, and only available on Unix.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use std::process::Command;
+//!
+//! use cod::canvas::Canvas;
+//! use cod::pty::Pane;
+//!
+//! let mut command = Command::new("echo");
+//! command.arg("hello");
+//! let mut pane = Pane::spawn(command, 20, 3).unwrap();
+//! while pane.is_running() {
+//!     pane.pump();
+//! }
+//! pane.pump();
+//!
+//! let mut canvas = Canvas::new(20, 3);
+//! let area = canvas.bounds();
+//! pane.render(&mut canvas, area);
+//! # assert_eq!(canvas.get(0, 0).unwrap().c, 'h');
+//! canvas.present(0, 0);
+//! ```
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::rect::Rect;
+use crate::style::Style;
+use crate::vt::Screen;
+
+/// The most [`Pane::pump`] reads in one call, so a program that never stops
+/// printing can't hold up the app.
+const PUMP_BUDGET: usize = 64 * 1024;
+
+/// How long [`Pane::write`] waits for a program that isn't reading its
+/// input.
+const WRITE_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Returns the last OS error if `result` is -1.
+fn check(result: libc::c_int) -> io::Result<libc::c_int> {
+    if result == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(result)
+    }
+}
+
+/// Returns a terminal size as `ioctl` expects it.
+fn winsize(width: u32, height: u32) -> libc::winsize {
+    libc::winsize {
+        ws_row: u16::try_from(height).unwrap_or(u16::MAX),
+        ws_col: u16::try_from(width).unwrap_or(u16::MAX),
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    }
+}
+
+/// A program running on a pseudo-terminal, and what it's shown so far.
+///
+/// Kills the program when dropped.
+#[derive(Debug)]
+pub struct Pane {
+    screen: Screen,
+    master: File,
+    child: Child,
+    focused: bool,
+}
+
+impl Pane {
+    /// Run a command on a new pseudo-terminal of the given size. The
+    /// command's stdin, stdout, and stderr are all replaced with the
+    /// terminal, and it's made the program's controlling terminal, so the
+    /// command is taken by value rather than left changed.
+    ///
+    /// # Errors
+    ///
+    /// If the pseudo-terminal can't be opened, or the command can't be
+    /// spawned, returns an error.
+    pub fn spawn(mut command: Command, width: u32, height: u32) -> io::Result<Self> {
+        let (mut master, mut slave) = (0, 0);
+        let mut size = winsize(width, height);
+        // SAFETY: the pointers are valid for the duration of the call, and
+        // null is allowed for the name and terminal settings
+        check(unsafe {
+            libc::openpty(
+                &raw mut master,
+                &raw mut slave,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                &raw mut size,
+            )
+        })?;
+        // SAFETY: openpty just opened both, and nothing else owns them
+        let (master, slave) =
+            unsafe { (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) };
+
+        let fd = master.as_raw_fd();
+        // SAFETY: fd is open; these only change its flags
+        unsafe {
+            check(libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC))?;
+            let flags = check(libc::fcntl(fd, libc::F_GETFL))?;
+            check(libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK))?;
+        }
+
+        command
+            .stdin(Stdio::from(slave.try_clone()?))
+            .stdout(Stdio::from(slave.try_clone()?))
+            .stderr(Stdio::from(slave));
+        // SAFETY: setsid and ioctl are async-signal-safe
+        unsafe {
+            command.pre_exec(|| {
+                // make the terminal the child's controlling terminal
+                check(libc::setsid())?;
+                check(libc::ioctl(0, libc::TIOCSCTTY as _, 0))?;
+                Ok(())
+            });
+        }
+
+        let child = command.spawn();
+        // close our copies of the terminal, so only the child has it open
+        drop(command);
+
+        Ok(Self {
+            screen: Screen::new(width, height),
+            master: File::from(master),
+            child: child?,
+            focused: false,
+        })
+    }
+
+    /// Returns the width of the pane, in columns.
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        self.screen.canvas().width()
+    }
+
+    /// Returns the height of the pane, in rows.
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.screen.canvas().height()
+    }
+
+    /// Returns what the program has shown so far.
+    #[must_use]
+    pub fn canvas(&self) -> &Canvas {
+        self.screen.canvas()
+    }
+
+    /// Returns the position of the program's cursor within the pane.
+    #[must_use]
+    pub fn cursor(&self) -> (u32, u32) {
+        self.screen.cursor()
+    }
+
+    /// Read whatever the program has printed since the last call, without
+    /// blocking, returning true if there was anything (i.e. if the pane
+    /// needs to be redrawn).
+    ///
+    /// At most 64 KiB is read per call, so a program that prints endlessly
+    /// (like `yes`) can't freeze the app; the rest is read by the next
+    /// calls.
+    pub fn pump(&mut self) -> bool {
+        let mut buf = [0; 4096];
+        let mut read = 0;
+        while read < PUMP_BUDGET {
+            match self.master.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    self.screen.feed(&buf[..n]);
+                    read += n;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                // WouldBlock when there's nothing more to read, or EIO once
+                // the program has exited
+                Err(_) => break,
+            }
+        }
+
+        read > 0
+    }
+
+    /// Send input to the program, as if it were typed. If the terminal's
+    /// input buffer is full, waits up to 250 ms for the program to read
+    /// some of it.
+    ///
+    /// # Errors
+    ///
+    /// If writing to the terminal fails, or the program doesn't read its
+    /// input in time, returns an error; some of the input may have been
+    /// sent by then.
+    pub fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let deadline = Instant::now() + WRITE_TIMEOUT;
+        let mut bytes = bytes;
+        while !bytes.is_empty() {
+            match self.master.write(bytes) {
+                Ok(n) => bytes = &bytes[n..],
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    if left.is_zero() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "the program isn't reading its input",
+                        ));
+                    }
+
+                    let mut fd = libc::pollfd {
+                        fd: self.master.as_raw_fd(),
+                        events: libc::POLLOUT,
+                        revents: 0,
+                    };
+                    let timeout = libc::c_int::try_from(left.as_millis()).unwrap_or(0).max(1);
+                    // SAFETY: fd is valid for the duration of the call
+                    match check(unsafe { libc::poll(&raw mut fd, 1, timeout) }) {
+                        Err(e) if e.kind() != io::ErrorKind::Interrupted => return Err(e),
+                        _ => {}
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Change the size of the pane, telling the program about it. Whatever
+    /// it's shown that still fits is kept.
+    ///
+    /// # Errors
+    ///
+    /// If the terminal can't be resized, returns an error.
+    pub fn resize(&mut self, width: u32, height: u32) -> io::Result<()> {
+        let size = winsize(width, height);
+        // SAFETY: the fd is open, and size is valid for the call
+        check(unsafe { libc::ioctl(self.master.as_raw_fd(), libc::TIOCSWINSZ, &raw const size) })?;
+        self.screen.resize(width, height);
+        Ok(())
+    }
+
+    /// Returns the program's exit status if it has exited, without waiting.
+    ///
+    /// # Errors
+    ///
+    /// See [`Child::try_wait`].
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        self.child.try_wait()
+    }
+
+    /// Returns true if the program is still running.
+    pub fn is_running(&mut self) -> bool {
+        matches!(self.try_wait(), Ok(None))
+    }
+
+    /// Kill the program.
+    ///
+    /// # Errors
+    ///
+    /// See [`Child::kill`].
+    pub fn kill(&mut self) -> io::Result<()> {
+        self.child.kill()
+    }
+
+    /// Returns true if the pane is focused, i.e. forwards keys to the
+    /// program and shows its cursor.
+    #[must_use]
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Set whether the pane is focused.
+    pub fn set_focused(&mut self, focused: bool) -> &mut Self {
+        self.focused = focused;
+        self
+    }
+
+    /// Draw what the program has shown onto a canvas, clipped to `area`.
+    /// If the pane is focused, the cursor is shown with its colors swapped,
+    /// unless the program hid it.
+    pub fn render(&self, canvas: &mut Canvas, area: Rect) {
+        let shown = Rect::new(0, 0, area.width, area.height).intersect(self.canvas().bounds());
+        canvas.paste(&self.canvas().snapshot(shown), area.x, area.y);
+
+        let (x, y) = self.cursor();
+        if self.focused && self.screen.is_cursor_visible() && shown.contains(x, y) {
+            let cell = self.canvas().get(x, y).unwrap_or_default();
+            let previous = canvas.style();
+            canvas.set_style(Style {
+                fg: Some(cell.style.bg.unwrap_or(Color::Black)),
+                bg: Some(cell.style.fg.unwrap_or(Color::White)),
+                ..cell.style
+            });
+            canvas.pixel(cell.c, area.x + x, area.y + y);
+            canvas.set_style(previous);
+        }
+    }
+
+    /// Forward a key to the program, if the pane is focused, returning
+    /// true if it was sent. Keys with no terminal encoding, and releases,
+    /// are ignored.
+    ///
+    /// Only enabled on feature `crossterm`.
+    ///
+    /// # Errors
+    ///
+    /// If writing to the terminal fails, returns an error.
+    #[cfg(feature = "crossterm")]
+    pub fn handle(&mut self, key: &crate::read::KeyEvent) -> io::Result<bool> {
+        if !self.focused {
+            return Ok(false);
+        }
+
+        match key_bytes(key) {
+            Some(bytes) => self.write(&bytes).map(|()| true),
+            None => Ok(false),
+        }
+    }
+}
+
+impl Drop for Pane {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Returns what a terminal sends for a key, if anything.
+#[cfg(feature = "crossterm")]
+fn key_bytes(key: &crate::read::KeyEvent) -> Option<Vec<u8>> {
+    use crate::read::{KeyCode, KeyEventKind, KeyModifiers};
+
+    if key.kind == KeyEventKind::Release {
+        return None;
+    }
+
+    let bytes: Vec<u8> = match key.code {
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            match c.to_ascii_lowercase() {
+                c @ 'a'..='z' => vec![c as u8 - b'a' + 1],
+                ' ' | '@' | '2' => vec![0],
+                '[' | '3' => vec![0x1b],
+                '\\' | '4' => vec![0x1c],
+                ']' | '5' => vec![0x1d],
+                '^' | '6' => vec![0x1e],
+                '_' | '7' | '/' => vec![0x1f],
+                _ => return None,
+            }
+        }
+        KeyCode::Char(c) => c.to_string().into_bytes(),
+        KeyCode::Enter => vec![b'\r'],
+        KeyCode::Tab => vec![b'\t'],
+        KeyCode::BackTab => b"\x1b[Z".to_vec(),
+        KeyCode::Backspace => vec![0x7f],
+        KeyCode::Esc => vec![0x1b],
+        KeyCode::Up => b"\x1b[A".to_vec(),
+        KeyCode::Down => b"\x1b[B".to_vec(),
+        KeyCode::Right => b"\x1b[C".to_vec(),
+        KeyCode::Left => b"\x1b[D".to_vec(),
+        KeyCode::Home => b"\x1b[H".to_vec(),
+        KeyCode::End => b"\x1b[F".to_vec(),
+        KeyCode::Insert => b"\x1b[2~".to_vec(),
+        KeyCode::Delete => b"\x1b[3~".to_vec(),
+        KeyCode::PageUp => b"\x1b[5~".to_vec(),
+        KeyCode::PageDown => b"\x1b[6~".to_vec(),
+        KeyCode::F(n @ 1..=4) => vec![0x1b, b'O', b'P' + n - 1],
+        KeyCode::F(n) => {
+            let code = match n {
+                5 => 15,
+                6..=10 => n + 11,
+                11..=12 => n + 12,
+                _ => return None,
+            };
+            format!("\x1b[{code}~").into_bytes()
+        }
+        _ => return None,
+    };
+
+    // alt sends an ESC first
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        Some([&[0x1b], bytes.as_slice()].concat())
+    } else {
+        Some(bytes)
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl crate::widget::Widget<crate::read::KeyEvent> for Pane {
+    /// Forward a key to the program if the pane is focused. The pane itself
+    /// only changes once the program responds; see [`Pane::pump`].
+    fn update(&mut self, event: &crate::read::KeyEvent) -> bool {
+        let _ = self.handle(event);
+        false
+    }
+
+    fn set_focused(&mut self, focused: bool) -> bool {
+        let changed = self.focused != focused;
+        self.focused = focused;
+        changed
+    }
+
+    fn render(&self, canvas: &mut Canvas, area: Rect) {
+        Pane::render(self, canvas, area);
+    }
+}
@@ -0,0 +1,596 @@
+//! Queueing up draws and submitting them all at once.
+//!
+//! A [`Queue`] sits between drawing immediately (e.g. [`crate::pixel`]) and
+//! drawing to a [`Canvas`]: draws are recorded, then checked and written in
+//! a single go by [`Queue::submit`]. If any of them is invalid, nothing is
+//! drawn at all.
+//!
+//! With [`Queue::optimize`], the queue may also skip cells that are drawn
+//! over later, and reorder the rest to need as few escape sequences as
+//! possible.
+//!
+//! Draws that are the same every frame (like borders and labels) can be
+//! recorded once into a [`DisplayList`], then replayed as often as needed,
+//! anywhere on the screen.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! # use cod::queue::Queue;
+//! # fn main() -> Result<(), cod::NonOrthogonal> {
+//! let mut queue = Queue::new();
+//! queue
+//!     .rect_fill(' ', 0, 0, 9, 4)
+//!     .rect('#', 0, 0, 9, 4)
+//!     .text("hi!", 2, 2);
+//!
+//! queue.submit()?;
+//! cod::flush();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::canvas::Canvas;
+use crate::color::{raw, Color};
+use crate::rect::{self, Borders, BoxStyle, Rect, Title};
+use crate::style::Style;
+use crate::{line, target, NonOrthogonal};
+
+/// A single queued draw.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Command {
+    Pixel(char, u32, u32),
+    Text(String, u32, u32),
+    Line(char, u32, u32, u32, u32),
+    OrthLine(char, u32, u32, u32, u32),
+    Rect(char, u32, u32, u32, u32),
+    RectFill(char, u32, u32, u32, u32),
+    Fg(Option<Color>),
+    Bg(Option<Color>),
+}
+
+impl Command {
+    /// Returns an error if the command can't be drawn.
+    fn validate(&self) -> Result<(), NonOrthogonal> {
+        match *self {
+            Self::OrthLine(_, x1, y1, x2, y2) if x1 != x2 && y1 != y2 => Err(NonOrthogonal),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The colors a cell is drawn with; `None` is the terminal's default.
+type Colors = (Option<Color>, Option<Color>);
+
+/// A cell to be drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cell {
+    c: char,
+    x: u32,
+    y: u32,
+    colors: Colors,
+}
+
+/// Turns commands into the cells they draw, in order.
+fn rasterize(commands: &[Command]) -> Vec<Cell> {
+    let mut cells = Vec::new();
+    let mut colors: Colors = (None, None);
+    let mut put = |c, x, y, colors| cells.push(Cell { c, x, y, colors });
+
+    for command in commands {
+        match *command {
+            Command::Pixel(c, x, y) => put(c, x, y, colors),
+            Command::Text(ref s, x, y) => {
+                for (y, row) in (y..).zip(s.split('\n')) {
+                    let mut x = x;
+                    for c in row.chars() {
+                        put(c, x, y, colors);
+                        x += crate::width::columns(c);
+                    }
+                }
+            }
+            Command::Line(c, x1, y1, x2, y2) if x1 != x2 && y1 != y2 => {
+                for (x, y) in line::Iter::new(x1, y1, x2, y2) {
+                    put(c, x, y, colors);
+                }
+            }
+            Command::Line(c, x1, y1, x2, y2)
+            | Command::OrthLine(c, x1, y1, x2, y2)
+            | Command::RectFill(c, x1, y1, x2, y2) => {
+                for y in y1.min(y2)..=y1.max(y2) {
+                    for x in x1.min(x2)..=x1.max(x2) {
+                        put(c, x, y, colors);
+                    }
+                }
+            }
+            Command::Rect(c, x1, y1, x2, y2) => {
+                let (left, right) = (x1.min(x2), x1.max(x2));
+                let (top, bottom) = (y1.min(y2), y1.max(y2));
+                for y in top..=bottom {
+                    if y == top || y == bottom {
+                        for x in left..=right {
+                            put(c, x, y, colors);
+                        }
+                    } else {
+                        put(c, left, y, colors);
+                        if right != left {
+                            put(c, right, y, colors);
+                        }
+                    }
+                }
+            }
+            Command::Fg(c) => colors.0 = c,
+            Command::Bg(c) => colors.1 = c,
+        }
+    }
+
+    cells
+}
+
+/// Draws cells in the given order, merging neighbouring cells of the same
+/// colors into a single run.
+///
+/// Uncolored cells are drawn in the default colors, not whatever colors
+/// were set outside the queue, which are restored afterwards.
+fn emit(cells: impl IntoIterator<Item = Cell>) {
+    let outside = crate::color::current();
+    let mut current = outside;
+    let mut run = String::new();
+    let mut start = (0, 0);
+    let mut next = None;
+
+    for cell in cells {
+        if cell.colors != current || next != Some((cell.x, cell.y)) {
+            if !run.is_empty() {
+                crate::run(&run, start.0, start.1);
+                run.clear();
+            }
+            start = (cell.x, cell.y);
+        }
+
+        if cell.colors.0 != current.0 {
+            cell.colors.0.map_or_else(raw::reset_fg, raw::color_fg);
+        }
+        if cell.colors.1 != current.1 {
+            cell.colors.1.map_or_else(raw::reset_bg, raw::color_bg);
+        }
+        current = cell.colors;
+
+        run.push(cell.c);
+        next = Some((cell.x + crate::width::columns(cell.c), cell.y));
+    }
+
+    if !run.is_empty() {
+        crate::run(&run, start.0, start.1);
+    }
+
+    if current != outside {
+        crate::color::restore();
+    }
+}
+
+/// A list of draws, submitted to the screen all at once.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Queue {
+    commands: Vec<Command>,
+    optimize: bool,
+}
+
+impl Queue {
+    /// Create an empty queue.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether the queue may skip and reorder cells when submitting.
+    /// Disabled by default.
+    ///
+    /// The result on screen is the same either way, but optimized output is
+    /// usually much smaller, since cells that are drawn over are skipped and
+    /// the rest are drawn row by row.
+    pub fn optimize(&mut self, optimize: bool) -> &mut Self {
+        self.optimize = optimize;
+        self
+    }
+
+    /// Queue a single character.
+    pub fn pixel(&mut self, c: char, x: u32, y: u32) -> &mut Self {
+        self.commands.push(Command::Pixel(c, x, y));
+        self
+    }
+
+    /// Queue some text (non-wrapping, but respects linebreaks).
+    pub fn text<S: Into<String>>(&mut self, s: S, x: u32, y: u32) -> &mut Self {
+        self.commands.push(Command::Text(s.into(), x, y));
+        self
+    }
+
+    /// Queue a line.
+    pub fn line(&mut self, c: char, x1: u32, y1: u32, x2: u32, y2: u32) -> &mut Self {
+        self.commands.push(Command::Line(c, x1, y1, x2, y2));
+        self
+    }
+
+    /// Queue an orthogonal line. If it isn't orthogonal, [`Queue::submit`]
+    /// will fail.
+    pub fn orth_line(&mut self, c: char, x1: u32, y1: u32, x2: u32, y2: u32) -> &mut Self {
+        self.commands.push(Command::OrthLine(c, x1, y1, x2, y2));
+        self
+    }
+
+    /// Queue an empty rectangle, given two (inclusive) corners.
+    pub fn rect(&mut self, c: char, x1: u32, y1: u32, x2: u32, y2: u32) -> &mut Self {
+        self.commands.push(Command::Rect(c, x1, y1, x2, y2));
+        self
+    }
+
+    /// Queue a filled rectangle, given two (inclusive) corners.
+    pub fn rect_fill(&mut self, c: char, x1: u32, y1: u32, x2: u32, y2: u32) -> &mut Self {
+        self.commands.push(Command::RectFill(c, x1, y1, x2, y2));
+        self
+    }
+
+    /// Queue a box along the edges of a rectangle, with titles on its top
+    /// and bottom edges, like [`rect::titled`].
+    pub fn border(&mut self, rect: Rect, style: BoxStyle, titles: &[Title]) -> &mut Self {
+        let mut borders = Borders::new();
+        borders.style(style).add(rect);
+        for ((y, x), c) in borders.cells() {
+            self.commands.push(Command::Pixel(c, x, y));
+        }
+        for (text, x, y) in rect::title_runs(rect, titles) {
+            self.commands.push(Command::Text(text, x, y));
+        }
+
+        self
+    }
+
+    /// Set the foreground color for everything queued after this.
+    pub fn fg<C: Into<Color>>(&mut self, color: C) -> &mut Self {
+        self.commands.push(Command::Fg(Some(color.into())));
+        self
+    }
+
+    /// Set the background color for everything queued after this.
+    pub fn bg<C: Into<Color>>(&mut self, color: C) -> &mut Self {
+        self.commands.push(Command::Bg(Some(color.into())));
+        self
+    }
+
+    /// Use the default colors for everything queued after this.
+    pub fn normal(&mut self) -> &mut Self {
+        self.commands.push(Command::Fg(None));
+        self.commands.push(Command::Bg(None));
+        self
+    }
+
+    /// Returns the number of queued commands.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns true if nothing is queued.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Discard everything queued.
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Draw everything queued in a single write, then empty the queue.
+    ///
+    /// Colors set in the queue only apply within it; afterwards, the colors
+    /// are restored (see [`crate::color`]). Likewise, cells queued without
+    /// a color (before [`Queue::fg`] or after [`Queue::normal`]) are drawn
+    /// in the default colors, even if others were set outside the queue
+    /// with feature `color_stack`.
+    ///
+    /// # Errors
+    ///
+    /// If any queued command is invalid (e.g. a non-orthogonal
+    /// [`Queue::orth_line`]), returns an error without drawing anything. The
+    /// queue is left untouched.
+    pub fn submit(&mut self) -> Result<(), NonOrthogonal> {
+        profile!();
+        for command in &self.commands {
+            command.validate()?;
+        }
+
+        write(self.cells().iter().copied());
+        self.commands.clear();
+        Ok(())
+    }
+
+    /// Returns the cells drawn by the queue, in the order they should be
+    /// drawn.
+    fn cells(&self) -> Vec<Cell> {
+        let cells = rasterize(&self.commands);
+        if self.optimize {
+            let cells: BTreeMap<_, _> = cells.into_iter().map(|c| ((c.y, c.x), c)).collect();
+            cells.into_values().collect()
+        } else {
+            cells
+        }
+    }
+}
+
+/// Draws cells in a single write.
+fn write(cells: impl IntoIterator<Item = Cell>) {
+    let out = target::capture(|| emit(cells));
+    if !out.is_empty() {
+        target::write(format_args!("{out}"));
+    }
+}
+
+/// A recorded list of draws, which can be replayed any number of times.
+///
+/// Draws are checked and laid out once, when recording, so replaying is
+/// cheap. To replay into a different [target], use [`target::with`].
+///
+/// Example:
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// # use cod::queue::DisplayList;
+/// # fn main() -> Result<(), cod::NonOrthogonal> {
+/// let frame = DisplayList::record(|q| {
+///     q.rect('#', 0, 0, 11, 2).text("status", 3, 1);
+/// })?;
+///
+/// // once per frame, at the bottom-left of the screen
+/// frame.replay(0, 20);
+/// cod::flush();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DisplayList {
+    cells: Vec<Cell>,
+}
+
+impl DisplayList {
+    /// Record the draws queued by a function. Coordinates are relative to
+    /// the position the list is replayed at.
+    ///
+    /// # Errors
+    ///
+    /// If any queued command is invalid, returns an error.
+    pub fn record(f: impl FnOnce(&mut Queue)) -> Result<Self, NonOrthogonal> {
+        let mut queue = Queue::new();
+        f(&mut queue);
+        Self::try_from(queue)
+    }
+
+    /// Returns true if replaying the list draws nothing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Draw the list to the screen in a single write, offset by the given
+    /// position. Cells offset past the largest coordinates are left out.
+    ///
+    /// Like [`Queue::submit`], colors are restored afterwards.
+    pub fn replay(&self, x: u32, y: u32) {
+        profile!();
+        write(self.offset(x, y));
+    }
+
+    /// Draw the list onto a canvas, offset by the given position. Cells
+    /// outside the canvas are ignored. The canvas' style is left as it was.
+    ///
+    /// ```rust
+    /// # use cod::canvas::Canvas;
+    /// # use cod::queue::DisplayList;
+    /// # fn main() -> Result<(), cod::NonOrthogonal> {
+    /// let label = DisplayList::record(|q| {
+    ///     q.text("hi", 0, 0);
+    /// })?;
+    ///
+    /// let mut canvas = Canvas::new(10, 2);
+    /// label.replay_on(&mut canvas, 3, 1);
+    /// label.replay_on(&mut canvas, u32::MAX, u32::MAX);
+    /// assert_eq!(canvas.get(4, 1).unwrap().c, 'i');
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn replay_on(&self, canvas: &mut Canvas, x: u32, y: u32) {
+        let style = canvas.style();
+        for cell in self.offset(x, y) {
+            canvas.set_style(Style {
+                fg: cell.colors.0,
+                bg: cell.colors.1,
+                ..Style::default()
+            });
+            canvas.pixel(cell.c, cell.x, cell.y);
+        }
+        canvas.set_style(style);
+    }
+
+    /// Returns the cells offset by the given position, leaving out those
+    /// that would go past the largest coordinates.
+    fn offset(&self, x: u32, y: u32) -> impl Iterator<Item = Cell> + '_ {
+        self.cells.iter().filter_map(move |&cell| {
+            Some(Cell {
+                x: cell.x.checked_add(x)?,
+                y: cell.y.checked_add(y)?,
+                ..cell
+            })
+        })
+    }
+}
+
+impl TryFrom<Queue> for DisplayList {
+    type Error = NonOrthogonal;
+
+    /// Record everything in a queue, respecting [`Queue::optimize`].
+    fn try_from(queue: Queue) -> Result<Self, Self::Error> {
+        for command in &queue.commands {
+            command.validate()?;
+        }
+
+        Ok(Self {
+            cells: queue.cells(),
+        })
+    }
+}
+
+/// Draw a batch of shapes, declared one per statement, in a single
+/// [`Queue`] submit.
+///
+/// Each statement is a command with its positional arguments, optionally
+/// followed by options as `name = value`:
+///
+/// - `pixel(x, y, c)`
+/// - `text(x, y, s)`
+/// - `line(x1, y1, x2, y2, c)`
+/// - `fill(x, y, width, height, c)`
+/// - `rect(x, y, width, height)`, a box like [`rect::titled`], with options
+///   `style` (a [`BoxStyle`], whose variants can be named directly) and
+///   `title` (any number of times)
+///
+/// Every command takes the options `fg` and `bg`, which can be anything that
+/// converts into a [`Color`]. They only apply to that command; everything
+/// else is drawn in the terminal's default colors.
+///
+/// Example:
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// cod::draw! {
+///     rect(0, 0, 30, 8, style = Rounded, title = "Log", fg = 4);
+///     text(2, 2, "hello");
+///     text(2, 3, "world", fg = 2, bg = (40, 40, 40));
+///     line(2, 5, 27, 5, '~');
+///     fill(2, 6, 5, 1, '#', fg = 1);
+/// }
+/// cod::flush();
+/// ```
+#[macro_export]
+macro_rules! draw {
+    (@cmd $q:ident, pixel($x:expr, $y:expr, $c:expr $(, $k:ident = $v:expr)* $(,)?)) => {
+        $crate::draw!(@colors [] $q $(, $k = $v)*);
+        $q.pixel($c, $x, $y);
+        $crate::draw!(@reset $q $(, $k = $v)*);
+    };
+    (@cmd $q:ident, text($x:expr, $y:expr, $s:expr $(, $k:ident = $v:expr)* $(,)?)) => {
+        $crate::draw!(@colors [] $q $(, $k = $v)*);
+        $q.text($s, $x, $y);
+        $crate::draw!(@reset $q $(, $k = $v)*);
+    };
+    (@cmd $q:ident, line(
+        $x1:expr, $y1:expr, $x2:expr, $y2:expr, $c:expr $(, $k:ident = $v:expr)* $(,)?
+    )) => {
+        $crate::draw!(@colors [] $q $(, $k = $v)*);
+        $q.line($c, $x1, $y1, $x2, $y2);
+        $crate::draw!(@reset $q $(, $k = $v)*);
+    };
+    (@cmd $q:ident, fill(
+        $x:expr, $y:expr, $w:expr, $h:expr, $c:expr $(, $k:ident = $v:expr)* $(,)?
+    )) => {
+        $crate::draw!(@colors [] $q $(, $k = $v)*);
+        let rect = $crate::rect::Rect::new($x, $y, $w, $h);
+        if !rect.is_empty() {
+            $q.rect_fill(
+                $c,
+                rect.x,
+                rect.y,
+                rect.x + rect.width - 1,
+                rect.y + rect.height - 1,
+            );
+        }
+        $crate::draw!(@reset $q $(, $k = $v)*);
+    };
+    (@cmd $q:ident, rect($x:expr, $y:expr, $w:expr, $h:expr $(, $k:ident = $v:expr)* $(,)?)) => {
+        $crate::draw!(@colors [box] $q $(, $k = $v)*);
+        $q.border(
+            $crate::rect::Rect::new($x, $y, $w, $h),
+            $crate::draw!(@style $(, $k = $v)*),
+            &$crate::draw!(@titles [] $(, $k = $v)*),
+        );
+        $crate::draw!(@reset $q $(, $k = $v)*);
+    };
+    (@cmd $q:ident, $cmd:ident($($args:tt)*)) => {
+        ::core::compile_error!(::core::concat!(
+            "unknown command or wrong arguments: `",
+            ::core::stringify!($cmd),
+            "(",
+            ::core::stringify!($($args)*),
+            ")`",
+        ));
+    };
+
+    // sets the colors given as options, and checks the other options
+    (@colors [$($box:ident)?] $q:ident) => {};
+    (@colors [$($box:ident)?] $q:ident, fg = $v:expr $(, $k:ident = $w:expr)*) => {
+        $q.fg($crate::color::Color::from($v));
+        $crate::draw!(@colors [$($box)?] $q $(, $k = $w)*);
+    };
+    (@colors [$($box:ident)?] $q:ident, bg = $v:expr $(, $k:ident = $w:expr)*) => {
+        $q.bg($crate::color::Color::from($v));
+        $crate::draw!(@colors [$($box)?] $q $(, $k = $w)*);
+    };
+    (@colors [box] $q:ident, style = $v:expr $(, $k:ident = $w:expr)*) => {
+        $crate::draw!(@colors [box] $q $(, $k = $w)*);
+    };
+    (@colors [box] $q:ident, title = $v:expr $(, $k:ident = $w:expr)*) => {
+        $crate::draw!(@colors [box] $q $(, $k = $w)*);
+    };
+    (@colors [$($box:ident)?] $q:ident, $o:ident = $v:expr $(, $k:ident = $w:expr)*) => {
+        ::core::compile_error!(::core::concat!(
+            "unknown option `",
+            ::core::stringify!($o),
+            "`",
+        ));
+    };
+
+    // goes back to the default colors if any were set
+    (@reset $q:ident) => {};
+    (@reset $q:ident, fg = $v:expr $(, $k:ident = $w:expr)*) => {
+        $q.normal();
+    };
+    (@reset $q:ident, bg = $v:expr $(, $k:ident = $w:expr)*) => {
+        $q.normal();
+    };
+    (@reset $q:ident, $o:ident = $v:expr $(, $k:ident = $w:expr)*) => {
+        $crate::draw!(@reset $q $(, $k = $w)*);
+    };
+
+    (@style) => {
+        $crate::rect::BoxStyle::default()
+    };
+    (@style, style = $v:expr $(, $k:ident = $w:expr)*) => {{
+        #[allow(unused_imports)]
+        use $crate::rect::BoxStyle::*;
+        $v
+    }};
+    (@style, $o:ident = $v:expr $(, $k:ident = $w:expr)*) => {
+        $crate::draw!(@style $(, $k = $w)*)
+    };
+
+    (@titles [$($t:expr),*]) => {
+        [$($t),*]
+    };
+    (@titles [$($t:expr),*], title = $v:expr $(, $k:ident = $w:expr)*) => {
+        $crate::draw!(@titles [$($t,)* $crate::rect::Title::new($v)] $(, $k = $w)*)
+    };
+    (@titles [$($t:expr),*], $o:ident = $v:expr $(, $k:ident = $w:expr)*) => {
+        $crate::draw!(@titles [$($t),*] $(, $k = $w)*)
+    };
+
+    ($($cmd:ident($($args:tt)*));* $(;)?) => {{
+        let mut queue = $crate::queue::Queue::new();
+        $($crate::draw!(@cmd queue, $cmd($($args)*));)*
+
+        // none of the commands above can fail to validate
+        let _ = queue.submit();
+    }};
+}
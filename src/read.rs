@@ -1,12 +1,79 @@
 //! Utilities for reading from stdin.
 #![allow(clippy::must_use_candidate)]
 
-use crossterm::event::{Event, KeyEventKind};
-pub use crossterm::event::{KeyCode, KeyEvent, KeyEventState as KeyState, KeyModifiers};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState as KeyState, KeyModifiers, MouseButton,
+    MouseEvent, MouseEventKind,
+};
+use crossterm::event::{MediaKeyCode, ModifierKeyCode};
+
+/// When the user last did something (see [`record_activity`]), or `None`
+/// if nothing's been read yet.
+static LAST_INPUT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Returns when the user last did something, starting the clock if nothing
+/// has been read yet.
+fn last_input() -> Instant {
+    *LAST_INPUT
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .get_or_insert_with(Instant::now)
+}
+
+/// Note that the user just did something, resetting [`idle_for`].
+///
+/// Every function here that reads input does this for key presses, mouse
+/// events, and pastes; call it yourself if you read input some other way.
+pub fn record_activity() {
+    *LAST_INPUT
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(Instant::now());
+}
+
+/// Returns true if the user hasn't pressed a key, used the mouse, or pasted
+/// anything for at least `duration`, e.g. to start a screensaver. Before
+/// anything has been read, counts from the first call.
+///
+/// ```no_run
+/// # use cod::read;
+/// use std::time::Duration;
+///
+/// loop {
+///     for event in read::drain() {
+///         // handle the event
+///     }
+///
+///     if read::idle_for(Duration::from_secs(60)) {
+///         // lock the screen
+///     }
+/// #   break;
+/// }
+/// ```
+pub fn idle_for(duration: Duration) -> bool {
+    last_input().elapsed() >= duration
+}
+
+/// Read a single event from stdin, noting any activity (key presses, mouse
+/// events, and pastes).
+fn event() -> std::io::Result<Event> {
+    let event = crossterm::event::read()?;
+    match event {
+        Event::Key(_) | Event::Mouse(_) | Event::Paste(_) => record_activity(),
+        Event::Resize(cols, rows) => crate::layout::set_size(u32::from(cols), u32::from(rows)),
+        _ => {}
+    }
+    Ok(event)
+}
 
 /// Read a single key from stdin.
 pub fn key() -> Option<KeyEvent> {
-    match crossterm::event::read().ok()? {
+    match event().ok()? {
         Event::Key(ev) if ev.kind != KeyEventKind::Release => Some(ev),
         _ => None,
     }
@@ -14,28 +81,772 @@ pub fn key() -> Option<KeyEvent> {
 
 /// Read a single key from stdin, discarding key repeats.
 pub fn key_once() -> Option<KeyEvent> {
-    match crossterm::event::read().ok()? {
+    match event().ok()? {
         Event::Key(ev) if ev.kind == KeyEventKind::Press => Some(ev),
         _ => None,
     }
 }
 
+/// Read a single mouse event from stdin. Mouse capture has to be enabled
+/// first (see [`enable_mouse_capture`]).
+///
+/// ```no_run
+/// # use cod::read::{self, MouseButton, MouseEventKind};
+/// read::enable_mouse_capture();
+/// cod::flush();
+///
+/// while let Some(event) = read::mouse() {
+///     let (x, y) = read::mouse_position(&event);
+///     match event.kind {
+///         MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left) => {
+///             cod::pixel('#', x, y);
+///         }
+///         MouseEventKind::Down(MouseButton::Right) => break,
+///         _ => {}
+///     }
+///     cod::flush();
+/// }
+///
+/// read::disable_mouse_capture();
+/// ```
+pub fn mouse() -> Option<MouseEvent> {
+    match event().ok()? {
+        Event::Mouse(ev) => Some(ev),
+        _ => None,
+    }
+}
+
+/// Returns the cell a mouse event happened at, as `(x, y)`.
+pub fn mouse_position(event: &MouseEvent) -> (u32, u32) {
+    (u32::from(event.column), u32::from(event.row))
+}
+
+/// Read every event already waiting on stdin, without blocking.
+///
+/// Call this once per frame in a game loop to handle all the input since the
+/// last frame, instead of falling behind by reading one key at a time.
+///
+/// ```no_run
+/// # use cod::read::{self, Event};
+/// loop {
+///     for event in read::drain() {
+///         if let Event::Key(key) = event {
+///             // handle the key
+///         }
+///     }
+///
+///     // draw the frame
+/// #   break;
+/// }
+/// ```
+pub fn drain() -> Vec<Event> {
+    let mut events = Vec::new();
+    while crossterm::event::poll(std::time::Duration::ZERO).unwrap_or(false) {
+        match event() {
+            Ok(event) => events.push(event),
+            Err(_) => break,
+        }
+    }
+
+    events
+}
+
 /// Read a line from stdin.
 ///
-/// Is *not* a full line editor.
+/// Is *not* a full line editor. With bracketed paste enabled (see
+/// [`enable_bracketed_paste`]), pasted text is added all at once, with its
+/// line breaks stripped (see [`crate::edit::single_line`]), instead of
+/// ending the line at the first one.
 pub fn line() -> String {
     let mut line = String::new();
-    while let Some(KeyEvent { code, .. }) = key() {
-        match code {
-            KeyCode::Enter => {
-                break;
-            }
-            KeyCode::Char(c) => {
-                line.push(c);
+    while let Ok(event) = event() {
+        match event {
+            Event::Key(KeyEvent { code, kind, .. }) if kind != KeyEventKind::Release => {
+                match code {
+                    KeyCode::Enter => {
+                        break;
+                    }
+                    KeyCode::Char(c) => {
+                        line.push(c);
+                    }
+                    _ => {}
+                }
             }
+            Event::Paste(text) => line.push_str(&crate::edit::single_line(&text)),
             _ => {}
         }
     }
 
     line
 }
+
+/// Returns true if the terminal can report key releases and repeats
+/// separately from presses (the kitty keyboard protocol). Must be called in
+/// raw mode.
+pub fn supports_release_events() -> bool {
+    crossterm::terminal::supports_keyboard_enhancement().unwrap_or(false)
+}
+
+/// Ask the terminal to report key releases and repeats, if it supports the
+/// kitty keyboard protocol. Undo with [`disable_release_events`].
+pub fn enable_release_events() {
+    crate::escape(">2u");
+}
+
+/// Stop reporting key releases and repeats. See [`enable_release_events`].
+pub fn disable_release_events() {
+    crate::escape("<1u");
+}
+
+/// Ask the terminal to mark pasted text, so it arrives as a single
+/// [`Event::Paste`] rather than as keys. Undo with
+/// [`disable_bracketed_paste`].
+pub fn enable_bracketed_paste() {
+    crate::escape("?2004h");
+}
+
+/// Stop marking pasted text. See [`enable_bracketed_paste`].
+pub fn disable_bracketed_paste() {
+    crate::escape("?2004l");
+}
+
+/// Ask the terminal to report the mouse: presses, releases, drags, and
+/// movement with no buttons held, along with the modifiers held, as
+/// [`Event::Mouse`]. Undo with [`disable_mouse_capture`].
+///
+/// While the mouse is captured, most terminals don't let the user select
+/// text unless they hold shift.
+pub fn enable_mouse_capture() {
+    // buttons, drags, all movement, then SGR coordinates so positions past
+    // column 223 come through
+    for mode in ["?1000h", "?1002h", "?1003h", "?1006h"] {
+        crate::escape(mode);
+    }
+}
+
+/// Stop reporting the mouse. See [`enable_mouse_capture`].
+pub fn disable_mouse_capture() {
+    for mode in ["?1006l", "?1003l", "?1002l", "?1000l"] {
+        crate::escape(mode);
+    }
+}
+
+/// How long a key counts as held after a press or repeat, when the terminal
+/// doesn't report releases.
+const HOLD_TIMEOUT: Duration = Duration::from_millis(150);
+
+/// The state of a held key, for [`RepeatLimiter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Held {
+    /// When the key was last let through.
+    delivered: Instant,
+    /// When an event for the key was last seen.
+    seen: Instant,
+}
+
+/// Throttles held-key repeats to a steady rate, so e.g. movement in a game
+/// doesn't depend on the user's repeat settings. Also keeps track of which
+/// keys are held.
+///
+/// Pass each key event through [`RepeatLimiter::filter`], and ignore the
+/// ones it rejects. Presses are always let through, as are releases; repeats
+/// of a held key are let through at most the given number of times per
+/// second.
+///
+/// Most terminals send a repeat as just another press. If the terminal
+/// reports releases (see [`enable_release_events`]), presses and repeats are
+/// told apart exactly; otherwise, presses of the same key come through at
+/// most at the given rate too, and a key counts as held for a short while
+/// after each event. Once that's passed, the key is taken to have been let
+/// go, and its next event is a fresh press.
+///
+/// ```rust
+/// # use cod::read::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers, RepeatLimiter};
+/// let mut limiter = RepeatLimiter::new(10);
+/// let press = KeyEvent::new(KeyCode::Right, KeyModifiers::NONE);
+/// let repeat = KeyEvent::new_with_kind(KeyCode::Right, KeyModifiers::NONE, KeyEventKind::Repeat);
+///
+/// assert!(limiter.filter(&press));
+/// // too soon after the last one
+/// assert!(!limiter.filter(&repeat));
+/// assert!(limiter.is_held(KeyCode::Right));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepeatLimiter {
+    interval: Option<Duration>,
+    keys: HashMap<KeyCode, Held>,
+    /// Whether the terminal has reported a release, so presses are known to
+    /// be separate from repeats.
+    releases: bool,
+}
+
+impl RepeatLimiter {
+    /// Create a limiter letting through at most `per_second` repeats of a
+    /// held key each second. If it's zero, repeats are dropped entirely.
+    #[must_use]
+    pub fn new(per_second: u32) -> Self {
+        Self {
+            interval: (per_second > 0).then(|| Duration::from_secs(1) / per_second),
+            keys: HashMap::new(),
+            releases: false,
+        }
+    }
+
+    /// Returns true if a key event should be handled, or false if it's a
+    /// repeat that came too soon.
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use cod::read::{KeyCode, KeyEvent, KeyModifiers, RepeatLimiter};
+    /// let mut limiter = RepeatLimiter::new(0);
+    /// let press = KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE);
+    /// assert!(limiter.filter(&press));
+    /// assert!(!limiter.filter(&press));
+    ///
+    /// // no release was reported, but the key went quiet, so it was let go
+    /// std::thread::sleep(Duration::from_millis(200));
+    /// assert!(limiter.filter(&press));
+    /// ```
+    pub fn filter(&mut self, key: &KeyEvent) -> bool {
+        let now = Instant::now();
+        if key.kind == KeyEventKind::Release {
+            self.releases = true;
+            self.keys.remove(&key.code);
+            return true;
+        }
+
+        let fresh = Held {
+            delivered: now,
+            seen: now,
+        };
+        if key.kind == KeyEventKind::Press && self.releases {
+            self.keys.insert(key.code, fresh);
+            return true;
+        }
+
+        // without releases, a key that's gone quiet was let go
+        let releases = self.releases;
+        let held = self
+            .keys
+            .get_mut(&key.code)
+            .filter(|held| releases || now.duration_since(held.seen) < HOLD_TIMEOUT);
+        let Some(held) = held else {
+            self.keys.insert(key.code, fresh);
+            return true;
+        };
+
+        held.seen = now;
+        match self.interval {
+            Some(interval) if now.duration_since(held.delivered) >= interval => {
+                held.delivered = now;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns true if a key is being held down.
+    #[must_use]
+    pub fn is_held(&self, code: KeyCode) -> bool {
+        self.keys
+            .get(&code)
+            .is_some_and(|held| self.releases || held.seen.elapsed() < HOLD_TIMEOUT)
+    }
+}
+
+/// Something that happened, from [`Events`]: input, or a timer going off.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimedEvent<T> {
+    /// An input event, e.g. a key press or a resize.
+    Input(Event),
+    /// A timer went off, with the tag it was set with.
+    Timer(T),
+    /// The user has been idle for as long as was set with
+    /// [`Events::idle_after`]. Sent once, then again after the next time
+    /// they're idle that long.
+    Idle,
+}
+
+/// A timer set on [`Events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Timer<T> {
+    due: Instant,
+    /// How often the timer repeats, if it does.
+    every: Option<Duration>,
+    tag: T,
+}
+
+/// Reads input events and timers together, so that animations and
+/// timeouts arrive in the same loop as input, in the order they happen.
+///
+/// Each timer has a tag, which is handed back when it goes off, to tell
+/// timers apart. A repeating timer that falls behind (e.g. while the app is
+/// busy drawing) goes off once, then picks up from there, rather than going
+/// off once for every time it missed.
+///
+/// ```no_run
+/// # use cod::read::{Event, Events, KeyCode, TimedEvent};
+/// use std::time::Duration;
+///
+/// #[derive(Clone, PartialEq)]
+/// enum Tick {
+///     Frame,
+///     HideToast,
+/// }
+///
+/// let mut events = Events::new();
+/// events
+///     .every(Duration::from_millis(50), Tick::Frame)
+///     .after(Duration::from_secs(3), Tick::HideToast);
+///
+/// loop {
+///     match events.read().unwrap() {
+///         TimedEvent::Timer(Tick::Frame) => { /* advance the animation */ }
+///         TimedEvent::Timer(Tick::HideToast) => { /* hide the toast */ }
+///         TimedEvent::Input(Event::Key(key)) if key.code == KeyCode::Esc => break,
+///         TimedEvent::Input(_) | TimedEvent::Idle => {}
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Events<T> {
+    timers: Vec<Timer<T>>,
+    /// How long the user has to be idle for [`TimedEvent::Idle`].
+    idle: Option<Duration>,
+    /// When the user was last active before [`TimedEvent::Idle`] was last
+    /// sent, so it's only sent once per idle spell.
+    idle_since: Option<Instant>,
+}
+
+impl<T> Default for Events<T> {
+    fn default() -> Self {
+        Self {
+            timers: Vec::new(),
+            idle: None,
+            idle_since: None,
+        }
+    }
+}
+
+impl<T: Clone + PartialEq> Events<T> {
+    /// Create an event reader with no timers.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a timer to go off once, after a delay.
+    pub fn after(&mut self, delay: Duration, tag: T) -> &mut Self {
+        self.timers.push(Timer {
+            due: Instant::now() + delay,
+            every: None,
+            tag,
+        });
+        self
+    }
+
+    /// Set a timer to go off repeatedly, starting one interval from now.
+    ///
+    /// # Panics
+    ///
+    /// If the interval is zero, panics.
+    pub fn every(&mut self, interval: Duration, tag: T) -> &mut Self {
+        assert!(!interval.is_zero(), "a timer can't repeat every 0 seconds");
+        self.timers.push(Timer {
+            due: Instant::now() + interval,
+            every: Some(interval),
+            tag,
+        });
+        self
+    }
+
+    /// Send [`TimedEvent::Idle`] once the user hasn't pressed a key, used
+    /// the mouse, or pasted anything for `duration` (see [`idle_for`]), or
+    /// stop sending it with `None`.
+    pub fn idle_after(&mut self, duration: Option<Duration>) -> &mut Self {
+        self.idle = duration;
+        self
+    }
+
+    /// Stop every timer set with a tag, returning true if there were any.
+    pub fn cancel(&mut self, tag: &T) -> bool {
+        let before = self.timers.len();
+        self.timers.retain(|timer| timer.tag != *tag);
+        self.timers.len() != before
+    }
+
+    /// Returns true if a timer with a tag is set, and hasn't gone off (or
+    /// repeats).
+    #[must_use]
+    pub fn is_set(&self, tag: &T) -> bool {
+        self.timers.iter().any(|timer| timer.tag == *tag)
+    }
+
+    /// Wait for the next input event or timer.
+    ///
+    /// # Errors
+    ///
+    /// If reading input fails, returns the error.
+    pub fn read(&mut self) -> std::io::Result<TimedEvent<T>> {
+        loop {
+            if let Some(event) = self.wait(None)? {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Wait for the next input event or timer, for at most `timeout`.
+    /// Returns `None` if nothing happened in that time.
+    ///
+    /// # Errors
+    ///
+    /// If reading input fails, returns the error.
+    pub fn poll(&mut self, timeout: Duration) -> std::io::Result<Option<TimedEvent<T>>> {
+        self.wait(Instant::now().checked_add(timeout))
+    }
+
+    /// Wait for the next input event or timer, until `deadline` (if any).
+    fn wait(&mut self, deadline: Option<Instant>) -> std::io::Result<Option<TimedEvent<T>>> {
+        loop {
+            let now = Instant::now();
+            if let Some(tag) = self.fire(now) {
+                return Ok(Some(TimedEvent::Timer(tag)));
+            }
+            let last = last_input();
+            let idle_at = self
+                .idle
+                .filter(|_| self.idle_since != Some(last))
+                .map(|idle| last + idle);
+            if idle_at.is_some_and(|idle_at| idle_at <= now) {
+                self.idle_since = Some(last);
+                return Ok(Some(TimedEvent::Idle));
+            }
+
+            let next_timer = self
+                .timers
+                .iter()
+                .map(|timer| timer.due)
+                .chain(idle_at)
+                .min();
+            let until = match (deadline, next_timer) {
+                (Some(deadline), Some(due)) => Some(deadline.min(due)),
+                (deadline, due) => deadline.or(due),
+            };
+
+            let input = match until {
+                Some(until) => crossterm::event::poll(until.saturating_duration_since(now))?,
+                None => true,
+            };
+            if input {
+                return event().map(|e| Some(TimedEvent::Input(e)));
+            }
+
+            let now = Instant::now();
+            if deadline.is_some_and(|deadline| now >= deadline) {
+                return Ok(self.fire(now).map(TimedEvent::Timer));
+            }
+        }
+    }
+
+    /// Set off the timer that's been due the longest, if any, returning its
+    /// tag.
+    fn fire(&mut self, now: Instant) -> Option<T> {
+        let (i, _) = self
+            .timers
+            .iter()
+            .enumerate()
+            .filter(|(_, timer)| timer.due <= now)
+            .min_by_key(|(_, timer)| timer.due)?;
+
+        let timer = &mut self.timers[i];
+        let Some(every) = timer.every else {
+            return Some(self.timers.remove(i).tag);
+        };
+
+        timer.due += every;
+        if timer.due <= now {
+            // fell behind; skip the missed ticks
+            timer.due = now + every;
+        }
+        Some(timer.tag.clone())
+    }
+}
+
+/// A key combination: a key, and the modifiers held alongside it.
+///
+/// Compares equal to key events with the same key and modifiers, and to
+/// characters typed without modifiers (other than shift).
+///
+/// Displays as, and parses from, text like `ctrl+alt+f5`: modifiers joined
+/// with `+`, then the key. Any key combination displayed parses back to
+/// itself. Parsing ignores the case of modifier and key names, but not of
+/// characters.
+///
+/// ```rust
+/// # use cod::read::{Key, KeyCode, KeyEvent, KeyExt, KeyModifiers};
+/// let ev = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL);
+/// assert!(ev.is_ctrl('s'));
+/// assert_eq!(ev, Key::ctrl('s'));
+///
+/// let ev = KeyEvent::new(KeyCode::Char('Q'), KeyModifiers::SHIFT);
+/// assert!(ev.is_char('Q'));
+/// assert_eq!(Key::from(ev), 'Q');
+///
+/// let key: Key = "ctrl+alt+f5".parse().unwrap();
+/// assert_eq!(key.to_string(), "ctrl+alt+f5");
+/// assert_eq!("ctrl++".parse(), Ok(Key::ctrl('+')));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    /// The key.
+    pub code: KeyCode,
+    /// The modifiers held alongside the key.
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// Create a key combination.
+    #[must_use]
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /// A character, without modifiers.
+    #[must_use]
+    pub const fn char(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    /// A character with control held, e.g. `Key::ctrl('c')`.
+    #[must_use]
+    pub const fn ctrl(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    /// A character with alt held.
+    #[must_use]
+    pub const fn alt(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::ALT)
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(ev: KeyEvent) -> Self {
+        Self::new(ev.code, ev.modifiers)
+    }
+}
+
+impl From<KeyCode> for Key {
+    fn from(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+}
+
+impl PartialEq<char> for Key {
+    fn eq(&self, c: &char) -> bool {
+        self.is_char(*c)
+    }
+}
+
+impl PartialEq<Key> for KeyEvent {
+    fn eq(&self, key: &Key) -> bool {
+        self.code == key.code && self.modifiers == key.modifiers
+    }
+}
+
+impl PartialEq<KeyEvent> for Key {
+    fn eq(&self, ev: &KeyEvent) -> bool {
+        ev == self
+    }
+}
+
+/// Shorthands for checking which key was pressed, for both [`Key`] and
+/// [`KeyEvent`].
+pub trait KeyExt {
+    /// Returns the key combination.
+    fn key(&self) -> Key;
+
+    /// Returns true if the key is a character, typed without modifiers
+    /// (other than shift).
+    fn is_char(&self, c: char) -> bool {
+        let key = self.key();
+        key.code == KeyCode::Char(c) && (key.modifiers - KeyModifiers::SHIFT).is_empty()
+    }
+
+    /// Returns true if the key is a character typed with control (and
+    /// nothing else but shift). Letters match regardless of case.
+    fn is_ctrl(&self, c: char) -> bool {
+        self.is_with(KeyModifiers::CONTROL, c)
+    }
+
+    /// Returns true if the key is a character typed with alt (and nothing
+    /// else but shift). Letters match regardless of case.
+    fn is_alt(&self, c: char) -> bool {
+        self.is_with(KeyModifiers::ALT, c)
+    }
+
+    /// Returns true if the key is a character typed with exactly the given
+    /// modifiers (ignoring shift). Letters match regardless of case.
+    fn is_with(&self, modifiers: KeyModifiers, c: char) -> bool {
+        let key = self.key();
+        matches!(key.code, KeyCode::Char(k) if k.eq_ignore_ascii_case(&c))
+            && key.modifiers - KeyModifiers::SHIFT == modifiers - KeyModifiers::SHIFT
+    }
+}
+
+impl KeyExt for Key {
+    fn key(&self) -> Key {
+        *self
+    }
+}
+
+impl KeyExt for KeyEvent {
+    fn key(&self) -> Key {
+        Key::from(*self)
+    }
+}
+
+/// Modifiers, in the order they're displayed.
+const MODIFIERS: [(KeyModifiers, &str); 6] = [
+    (KeyModifiers::CONTROL, "ctrl"),
+    (KeyModifiers::ALT, "alt"),
+    (KeyModifiers::SHIFT, "shift"),
+    (KeyModifiers::SUPER, "super"),
+    (KeyModifiers::HYPER, "hyper"),
+    (KeyModifiers::META, "meta"),
+];
+
+/// Names of keys other than characters and function keys.
+const NAMED_KEYS: [(KeyCode, &str); 51] = [
+    (KeyCode::Char(' '), "space"),
+    (KeyCode::Backspace, "backspace"),
+    (KeyCode::Enter, "enter"),
+    (KeyCode::Left, "left"),
+    (KeyCode::Right, "right"),
+    (KeyCode::Up, "up"),
+    (KeyCode::Down, "down"),
+    (KeyCode::Home, "home"),
+    (KeyCode::End, "end"),
+    (KeyCode::PageUp, "pageup"),
+    (KeyCode::PageDown, "pagedown"),
+    (KeyCode::Tab, "tab"),
+    (KeyCode::BackTab, "backtab"),
+    (KeyCode::Delete, "delete"),
+    (KeyCode::Insert, "insert"),
+    (KeyCode::Null, "null"),
+    (KeyCode::Esc, "esc"),
+    (KeyCode::CapsLock, "capslock"),
+    (KeyCode::ScrollLock, "scrolllock"),
+    (KeyCode::NumLock, "numlock"),
+    (KeyCode::PrintScreen, "printscreen"),
+    (KeyCode::Pause, "pause"),
+    (KeyCode::Menu, "menu"),
+    (KeyCode::KeypadBegin, "keypadbegin"),
+    (KeyCode::Media(MediaKeyCode::Play), "play"),
+    (KeyCode::Media(MediaKeyCode::Pause), "mediapause"),
+    (KeyCode::Media(MediaKeyCode::PlayPause), "playpause"),
+    (KeyCode::Media(MediaKeyCode::Reverse), "reverse"),
+    (KeyCode::Media(MediaKeyCode::Stop), "stop"),
+    (KeyCode::Media(MediaKeyCode::FastForward), "fastforward"),
+    (KeyCode::Media(MediaKeyCode::Rewind), "rewind"),
+    (KeyCode::Media(MediaKeyCode::TrackNext), "tracknext"),
+    (KeyCode::Media(MediaKeyCode::TrackPrevious), "trackprevious"),
+    (KeyCode::Media(MediaKeyCode::Record), "record"),
+    (KeyCode::Media(MediaKeyCode::LowerVolume), "volumedown"),
+    (KeyCode::Media(MediaKeyCode::RaiseVolume), "volumeup"),
+    (KeyCode::Media(MediaKeyCode::MuteVolume), "mute"),
+    (KeyCode::Modifier(ModifierKeyCode::LeftShift), "leftshift"),
+    (KeyCode::Modifier(ModifierKeyCode::LeftControl), "leftctrl"),
+    (KeyCode::Modifier(ModifierKeyCode::LeftAlt), "leftalt"),
+    (KeyCode::Modifier(ModifierKeyCode::LeftSuper), "leftsuper"),
+    (KeyCode::Modifier(ModifierKeyCode::LeftHyper), "lefthyper"),
+    (KeyCode::Modifier(ModifierKeyCode::LeftMeta), "leftmeta"),
+    (KeyCode::Modifier(ModifierKeyCode::RightShift), "rightshift"),
+    (
+        KeyCode::Modifier(ModifierKeyCode::RightControl),
+        "rightctrl",
+    ),
+    (KeyCode::Modifier(ModifierKeyCode::RightAlt), "rightalt"),
+    (KeyCode::Modifier(ModifierKeyCode::RightSuper), "rightsuper"),
+    (KeyCode::Modifier(ModifierKeyCode::RightHyper), "righthyper"),
+    (KeyCode::Modifier(ModifierKeyCode::RightMeta), "rightmeta"),
+    (
+        KeyCode::Modifier(ModifierKeyCode::IsoLevel3Shift),
+        "isolevel3shift",
+    ),
+    (
+        KeyCode::Modifier(ModifierKeyCode::IsoLevel5Shift),
+        "isolevel5shift",
+    ),
+];
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in MODIFIERS {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{name}+")?;
+            }
+        }
+
+        if let Some((_, name)) = NAMED_KEYS.iter().find(|(code, _)| *code == self.code) {
+            return f.write_str(name);
+        }
+
+        match self.code {
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "f{n}"),
+            other => write!(f, "{other:?}"),
+        }
+    }
+}
+
+/// An error returned when parsing a [`Key`] fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseKeyError;
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected a key combination like `ctrl+alt+f5`")
+    }
+}
+
+impl std::error::Error for ParseKeyError {}
+
+impl FromStr for Key {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        while let Some((head, tail)) = rest.split_once('+') {
+            if head.is_empty() || tail.is_empty() {
+                break;
+            }
+
+            let (modifier, _) = MODIFIERS
+                .iter()
+                .find(|(_, name)| name.eq_ignore_ascii_case(head))
+                .ok_or(ParseKeyError)?;
+            modifiers |= *modifier;
+            rest = tail;
+        }
+
+        let mut chars = rest.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(Self::new(KeyCode::Char(c), modifiers));
+        }
+
+        if let Some((code, _)) = NAMED_KEYS
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(rest))
+        {
+            return Ok(Self::new(*code, modifiers));
+        }
+
+        let n = rest
+            .strip_prefix(['f', 'F'])
+            .and_then(|n| n.parse().ok())
+            .ok_or(ParseKeyError)?;
+        Ok(Self::new(KeyCode::F(n), modifiers))
+    }
+}
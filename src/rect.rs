@@ -1,8 +1,12 @@
 //! Utilities for drawing various rectangles and boxes.
+use std::collections::BTreeMap;
+
+use crate::spans::{Overflow, Spans};
+use crate::style::{Style, StyleOverride};
 use crate::{orth_line, pixel, NonOrthogonal};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum BoxDrawingChar {
+pub(crate) enum BoxDrawingChar {
     Horizontal,
     Vertical,
 
@@ -10,22 +14,321 @@ enum BoxDrawingChar {
     TopRightCorner,
     BottomLeftCorner,
     BottomRightCorner,
+
+    TeeDown,
+    TeeUp,
+    TeeRight,
+    TeeLeft,
+    Cross,
+}
+
+impl BoxDrawingChar {
+    pub(crate) const UP: u8 = 1;
+    pub(crate) const DOWN: u8 = 2;
+    pub(crate) const LEFT: u8 = 4;
+    pub(crate) const RIGHT: u8 = 8;
+
+    /// Returns the character joining lines in the given directions (a
+    /// combination of [`BoxDrawingChar::UP`], etc.).
+    pub(crate) fn junction(dirs: u8) -> Self {
+        const UP: u8 = BoxDrawingChar::UP;
+        const DOWN: u8 = BoxDrawingChar::DOWN;
+        const LEFT: u8 = BoxDrawingChar::LEFT;
+        const RIGHT: u8 = BoxDrawingChar::RIGHT;
+
+        match dirs {
+            0b1111 => Self::Cross,
+            d if d == DOWN | LEFT | RIGHT => Self::TeeDown,
+            d if d == UP | LEFT | RIGHT => Self::TeeUp,
+            d if d == UP | DOWN | RIGHT => Self::TeeRight,
+            d if d == UP | DOWN | LEFT => Self::TeeLeft,
+            d if d == DOWN | RIGHT => Self::TopLeftCorner,
+            d if d == DOWN | LEFT => Self::TopRightCorner,
+            d if d == UP | RIGHT => Self::BottomLeftCorner,
+            d if d == UP | LEFT => Self::BottomRightCorner,
+            d if d & (LEFT | RIGHT) == 0 => Self::Vertical,
+            _ => Self::Horizontal,
+        }
+    }
 }
 
 impl From<BoxDrawingChar> for char {
     fn from(ch: BoxDrawingChar) -> char {
-        match ch {
-            BoxDrawingChar::Horizontal => '\u{2550}',
-            BoxDrawingChar::Vertical => '\u{2551}',
+        BoxStyle::Double.char(ch)
+    }
+}
+
+/// The lines boxes are drawn with, e.g. by [`Borders`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BoxStyle {
+    /// Double lines: `╔═╗`.
+    #[default]
+    Double,
+    /// Thin lines: `┌─┐`.
+    Light,
+    /// Thick lines: `┏━┓`.
+    Heavy,
+    /// Thin lines with rounded corners: `╭─╮`.
+    Rounded,
+    /// Plain ASCII: `+-+`.
+    Ascii,
+}
+
+impl BoxStyle {
+    /// Returns the character for a part of a box in this style.
+    pub(crate) fn char(self, ch: BoxDrawingChar) -> char {
+        use BoxDrawingChar as B;
+
+        match (self, ch) {
+            (Self::Ascii, B::Horizontal) => '-',
+            (Self::Ascii, B::Vertical) => '|',
+            (Self::Ascii, _) => '+',
+
+            (Self::Rounded, B::TopLeftCorner) => '\u{256D}',
+            (Self::Rounded, B::TopRightCorner) => '\u{256E}',
+            (Self::Rounded, B::BottomLeftCorner) => '\u{2570}',
+            (Self::Rounded, B::BottomRightCorner) => '\u{256F}',
 
-            BoxDrawingChar::TopLeftCorner => '\u{2554}',
-            BoxDrawingChar::TopRightCorner => '\u{2557}',
-            BoxDrawingChar::BottomLeftCorner => '\u{255A}',
-            BoxDrawingChar::BottomRightCorner => '\u{255D}',
+            (Self::Light | Self::Rounded, _) => match ch {
+                B::Horizontal => '\u{2500}',
+                B::Vertical => '\u{2502}',
+                B::TopLeftCorner => '\u{250C}',
+                B::TopRightCorner => '\u{2510}',
+                B::BottomLeftCorner => '\u{2514}',
+                B::BottomRightCorner => '\u{2518}',
+                B::TeeDown => '\u{252C}',
+                B::TeeUp => '\u{2534}',
+                B::TeeRight => '\u{251C}',
+                B::TeeLeft => '\u{2524}',
+                B::Cross => '\u{253C}',
+            },
+
+            (Self::Heavy, _) => match ch {
+                B::Horizontal => '\u{2501}',
+                B::Vertical => '\u{2503}',
+                B::TopLeftCorner => '\u{250F}',
+                B::TopRightCorner => '\u{2513}',
+                B::BottomLeftCorner => '\u{2517}',
+                B::BottomRightCorner => '\u{251B}',
+                B::TeeDown => '\u{2533}',
+                B::TeeUp => '\u{253B}',
+                B::TeeRight => '\u{2523}',
+                B::TeeLeft => '\u{252B}',
+                B::Cross => '\u{254B}',
+            },
+
+            (Self::Double, _) => match ch {
+                B::Horizontal => '\u{2550}',
+                B::Vertical => '\u{2551}',
+                B::TopLeftCorner => '\u{2554}',
+                B::TopRightCorner => '\u{2557}',
+                B::BottomLeftCorner => '\u{255A}',
+                B::BottomRightCorner => '\u{255D}',
+                B::TeeDown => '\u{2566}',
+                B::TeeUp => '\u{2569}',
+                B::TeeRight => '\u{2560}',
+                B::TeeLeft => '\u{2563}',
+                B::Cross => '\u{256C}',
+            },
         }
     }
 }
 
+/// A rectangular region, given by its top-left corner and its size.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    /// The leftmost column.
+    pub x: u32,
+    /// The topmost row.
+    pub y: u32,
+    /// The number of columns.
+    pub width: u32,
+    /// The number of rows.
+    pub height: u32,
+}
+
+impl Rect {
+    /// Create a new rectangle from its top-left corner and its size.
+    #[must_use]
+    pub const fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Create a new rectangle from two (inclusive) corners, in any order.
+    #[must_use]
+    pub fn from_corners(x1: u32, y1: u32, x2: u32, y2: u32) -> Self {
+        Self {
+            x: x1.min(x2),
+            y: y1.min(y2),
+            width: x1.abs_diff(x2) + 1,
+            height: y1.abs_diff(y2) + 1,
+        }
+    }
+
+    /// Create a rectangle from percentages of the terminal's size (see
+    /// [`crate::layout::size`]), for layouts that follow the terminal as
+    /// it's resized. Call this each time you draw, rather than keeping the
+    /// result. See [`Rect::percent_of`].
+    #[must_use]
+    pub fn percent(x: u32, y: u32, width: u32, height: u32) -> Self {
+        crate::layout::screen().percent_of(x, y, width, height)
+    }
+
+    /// Create a rectangle from percentages of this one's size, relative to
+    /// its top-left corner.
+    ///
+    /// Edges are rounded down, so rectangles whose percentages meet also
+    /// meet on screen, with no gaps or overlap between them.
+    ///
+    /// ```rust
+    /// # use cod::rect::Rect;
+    /// let screen = Rect::new(0, 0, 81, 24);
+    /// let sidebar = screen.percent_of(0, 0, 25, 100);
+    /// let main = screen.percent_of(25, 0, 75, 100);
+    ///
+    /// assert_eq!(sidebar, Rect::new(0, 0, 20, 24));
+    /// assert_eq!(main, Rect::new(20, 0, 61, 24));
+    /// assert_eq!(main.percent_of(50, 50, 50, 50), Rect::new(50, 12, 31, 12));
+    /// ```
+    #[must_use]
+    pub fn percent_of(&self, x: u32, y: u32, width: u32, height: u32) -> Rect {
+        let along = |length: u32, percent: u32| {
+            u32::try_from(u64::from(length) * u64::from(percent) / 100).unwrap_or(u32::MAX)
+        };
+
+        let (left, right) = (
+            along(self.width, x),
+            along(self.width, x.saturating_add(width)),
+        );
+        let (top, bottom) = (
+            along(self.height, y),
+            along(self.height, y.saturating_add(height)),
+        );
+        Rect {
+            x: self.x.saturating_add(left),
+            y: self.y.saturating_add(top),
+            width: right - left,
+            height: bottom - top,
+        }
+    }
+
+    /// Returns true if the rectangle covers no cells.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Returns true if the given point lies within the rectangle.
+    #[must_use]
+    pub const fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && y >= self.y && x - self.x < self.width && y - self.y < self.height
+    }
+
+    /// Returns the rectangle left after taking margins off each side. If the
+    /// margins don't fit, the result is empty.
+    ///
+    /// ```rust
+    /// # use cod::rect::{Margins, Rect};
+    /// let rect = Rect::new(0, 0, 10, 5);
+    /// assert_eq!(rect.inner(Margins::symmetric(1, 2)), Rect::new(2, 1, 6, 3));
+    /// assert_eq!(rect.pad(3), Rect::new(3, 3, 4, 0));
+    /// ```
+    #[must_use]
+    pub fn inner(&self, margins: Margins) -> Rect {
+        let left = margins.left.min(self.width);
+        let top = margins.top.min(self.height);
+        Rect {
+            x: self.x + left,
+            y: self.y + top,
+            width: (self.width - left).saturating_sub(margins.right),
+            height: (self.height - top).saturating_sub(margins.bottom),
+        }
+    }
+
+    /// Returns the rectangle left after taking `n` cells off every side.
+    /// Shorthand for `rect.inner(Margins::all(n))`.
+    #[must_use]
+    pub fn pad(&self, n: u32) -> Rect {
+        self.inner(Margins::all(n))
+    }
+
+    /// Returns the overlap between two rectangles, which may be empty.
+    /// Rectangles reaching past the largest coordinate are cut off there.
+    ///
+    /// ```rust
+    /// # use cod::rect::Rect;
+    /// let screen = Rect::new(0, 0, 80, 24);
+    /// assert_eq!(screen.intersect(Rect::new(70, 20, 20, 20)), Rect::new(70, 20, 10, 4));
+    ///
+    /// let everything = Rect::new(5, 5, u32::MAX, u32::MAX);
+    /// assert_eq!(screen.intersect(everything), Rect::new(5, 5, 75, 19));
+    /// ```
+    #[must_use]
+    pub fn intersect(&self, other: Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self
+            .x
+            .saturating_add(self.width)
+            .min(other.x.saturating_add(other.width));
+        let bottom = self
+            .y
+            .saturating_add(self.height)
+            .min(other.y.saturating_add(other.height));
+
+        Rect {
+            x,
+            y,
+            width: right.saturating_sub(x),
+            height: bottom.saturating_sub(y),
+        }
+    }
+}
+
+/// Space to leave on each side of a rectangle, for [`Rect::inner`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Margins {
+    /// Rows to leave at the top.
+    pub top: u32,
+    /// Columns to leave on the right.
+    pub right: u32,
+    /// Rows to leave at the bottom.
+    pub bottom: u32,
+    /// Columns to leave on the left.
+    pub left: u32,
+}
+
+impl Margins {
+    /// Create margins for each side, in CSS order.
+    #[must_use]
+    pub const fn new(top: u32, right: u32, bottom: u32, left: u32) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// Create the same margin on every side.
+    #[must_use]
+    pub const fn all(n: u32) -> Self {
+        Self::new(n, n, n, n)
+    }
+
+    /// Create one margin for the top and bottom, and another for the left
+    /// and right.
+    #[must_use]
+    pub const fn symmetric(vertical: u32, horizontal: u32) -> Self {
+        Self::new(vertical, horizontal, vertical, horizontal)
+    }
+}
+
 /// Characters to use when calling [`with`].
 ///
 /// Note that `corner` will be used on all four corners.
@@ -45,6 +348,7 @@ pub struct Chars {
 ///
 /// If the given line is non-orthogonal, returns an error.
 pub fn line(c: char, x1: u32, y1: u32, x2: u32, y2: u32) -> Result<(), NonOrthogonal> {
+    profile!();
     orth_line(c, x1, y1, x1, y2)?;
     orth_line(c, x1, y1, x2, y1)?;
     orth_line(c, x2, y2, x1, y2)?;
@@ -59,6 +363,7 @@ pub fn line(c: char, x1: u32, y1: u32, x2: u32, y2: u32) -> Result<(), NonOrthog
 ///
 /// If the given line is non-orthogonal, returns an error.
 pub fn fill(c: char, x1: u32, y1: u32, x2: u32, y2: u32) -> Result<(), NonOrthogonal> {
+    profile!();
     let mut y = y1;
     while y != y2 {
         orth_line(c, x1, y, x2, y)?;
@@ -68,12 +373,59 @@ pub fn fill(c: char, x1: u32, y1: u32, x2: u32, y2: u32) -> Result<(), NonOrthog
     Ok(())
 }
 
+/// Draw an empty rectangle onto the screen in a style, then reset colors
+/// and attributes. See [`line`](fn@crate::line) and [`crate::pixel_styled`].
+///
+/// # Errors
+///
+/// If the given line is non-orthogonal, returns an error.
+pub fn line_styled(
+    c: char,
+    x1: u32,
+    y1: u32,
+    x2: u32,
+    y2: u32,
+    style: Style,
+) -> Result<(), NonOrthogonal> {
+    profile!();
+    crate::styled(style, || line(c, x1, y1, x2, y2))
+}
+
+/// Draw a filled rectangle onto the screen in a style, then reset colors
+/// and attributes. See [`fill`] and [`crate::pixel_styled`].
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// use cod::color::Color;
+/// use cod::style::Style;
+///
+/// // a blue panel with a white frame, no nesting required
+/// rect::fill_styled(' ', 2, 1, 30, 8, Style::new().bg(Color::Blue)).unwrap();
+/// rect::line_styled('#', 1, 0, 31, 8, Style::new().fg(Color::White)).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// If the given line is non-orthogonal, returns an error.
+pub fn fill_styled(
+    c: char,
+    x1: u32,
+    y1: u32,
+    x2: u32,
+    y2: u32,
+    style: Style,
+) -> Result<(), NonOrthogonal> {
+    profile!();
+    crate::styled(style, || fill(c, x1, y1, x2, y2))
+}
+
 /// Draw a rectangle using a given set of characters, via [`Chars`].
 ///
 /// # Errors
 ///
 /// If the given line is non-orthogonal, returns an error.
 pub fn with(chars: Chars, x1: u32, y1: u32, x2: u32, y2: u32) -> Result<(), NonOrthogonal> {
+    profile!();
     orth_line(chars.horizontal, x1, y1, x2, y1)?;
     orth_line(chars.horizontal, x1, y2, x2, y2)?;
     orth_line(chars.vertical, x1, y1, x1, y2)?;
@@ -92,6 +444,7 @@ pub fn with(chars: Chars, x1: u32, y1: u32, x2: u32, y2: u32) -> Result<(), NonO
 ///
 /// If the given line is non-orthogonal, returns an error.
 pub fn ascii(x1: u32, y1: u32, x2: u32, y2: u32) -> Result<(), NonOrthogonal> {
+    profile!();
     orth_line(BoxDrawingChar::Horizontal.into(), x1 + 1, y1, x2 - 1, y1)?;
     orth_line(BoxDrawingChar::Horizontal.into(), x1 + 1, y2, x2 - 1, y2)?;
     orth_line(BoxDrawingChar::Vertical.into(), x1, y1 + 1, x1, y2 - 1)?;
@@ -104,3 +457,694 @@ pub fn ascii(x1: u32, y1: u32, x2: u32, y2: u32) -> Result<(), NonOrthogonal> {
 
     Ok(())
 }
+
+/// Draw a box along the edges of a rectangle, using box-drawing characters,
+/// and return the space inside it.
+///
+/// ```rust
+/// # use cod::rect::{self, Rect};
+/// let content = rect::border(Rect::new(0, 0, 20, 5));
+/// assert_eq!(content, Rect::new(1, 1, 18, 3));
+/// cod::text("inside!", content.x, content.y);
+/// ```
+#[allow(clippy::must_use_candidate)]
+pub fn border(rect: Rect) -> Rect {
+    profile!();
+    Borders::new().add(rect).draw();
+    rect.pad(1)
+}
+
+/// Horizontal alignment, e.g. of a [`Title`] or [`crate::text_aligned`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Align {
+    /// Against the left edge.
+    #[default]
+    Left,
+    /// In the middle, rounding to the left.
+    Center,
+    /// Against the right edge.
+    Right,
+    /// Against both edges, by widening the gaps between words. The last
+    /// line of a paragraph (i.e. before a blank line, or the end), and any
+    /// line with only one word, is against the left edge instead; so are
+    /// titles.
+    Justify,
+}
+
+/// Returns a line of text laid out in `width` cells, cut off if it doesn't
+/// fit, along with how far from the left edge to draw it. The line is only
+/// justified if it isn't `last` in its paragraph.
+pub(crate) fn align_line(line: &str, width: u32, align: Align, last: bool) -> (String, u32) {
+    let text = crate::width::truncate(line, width as usize).to_string();
+    let space = width - u32::try_from(crate::width::str_width(&text)).unwrap_or(width);
+
+    match align {
+        Align::Left => (text, 0),
+        Align::Center => (text, space / 2),
+        Align::Right => (text, space),
+        Align::Justify if last => (text, 0),
+        Align::Justify => (justify(&text, width as usize), 0),
+    }
+}
+
+/// Spread the words of a line out to fill `width` cells, putting any extra
+/// spaces in the leftmost gaps.
+fn justify(line: &str, width: usize) -> String {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    if words.len() < 2 {
+        return line.to_string();
+    }
+
+    let letters: usize = words.iter().map(|word| crate::width::str_width(word)).sum();
+    let gaps = words.len() - 1;
+    let spaces = width.saturating_sub(letters).max(gaps);
+    let (each, extra) = (spaces / gaps, spaces % gaps);
+
+    let mut justified = String::with_capacity(width);
+    for (i, word) in words.iter().enumerate() {
+        if i > 0 {
+            justified.extend(std::iter::repeat_n(' ', each + usize::from(i <= extra)));
+        }
+        justified.push_str(word);
+    }
+    justified
+}
+
+/// A piece of text drawn on the edge of a box by [`titled`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Title {
+    text: String,
+    align: Align,
+    bottom: bool,
+}
+
+impl Title {
+    /// Create a title, aligned left on the top edge.
+    pub fn new<S: Into<String>>(text: S) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Align the title along its edge.
+    #[must_use]
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Put the title on the bottom edge instead of the top.
+    #[must_use]
+    pub fn bottom(mut self) -> Self {
+        self.bottom = true;
+        self
+    }
+}
+
+/// Draw a box along the edges of a rectangle, like [`border`], with titles
+/// on its top and bottom edges. Returns the space inside the box.
+///
+/// Each title is padded with a space on either side, and cut off if it
+/// doesn't fit between the corners. Titles on the same edge are drawn in
+/// order, so later ones cover earlier ones if they overlap.
+///
+/// ```rust
+/// # use cod::rect::{self, Align, Rect, Title};
+/// rect::titled(
+///     Rect::new(0, 0, 40, 10),
+///     &[
+///         Title::new("Downloads"),
+///         Title::new("3/5").align(Align::Right),
+///         Title::new("press q to quit").align(Align::Center).bottom(),
+///     ],
+/// );
+/// ```
+#[allow(clippy::must_use_candidate)]
+pub fn titled(rect: Rect, titles: &[Title]) -> Rect {
+    profile!();
+    let content = border(rect);
+    for (text, x, y) in title_runs(rect, titles) {
+        crate::run(&text, x, y);
+    }
+
+    content
+}
+
+/// Returns each title as it's drawn on the edges of a box, with its
+/// position.
+pub(crate) fn title_runs(rect: Rect, titles: &[Title]) -> Vec<(String, u32, u32)> {
+    if rect.width < 3 || rect.height < 2 {
+        return Vec::new();
+    }
+
+    let space = rect.width - 2;
+    titles
+        .iter()
+        .map(|title| {
+            let text = format!(" {} ", title.text);
+            let text = crate::width::truncate(&text, space as usize).to_string();
+            let width = u32::try_from(crate::width::str_width(&text)).unwrap_or(space);
+
+            let offset = match title.align {
+                Align::Left | Align::Justify => 0,
+                Align::Center => (space - width) / 2,
+                Align::Right => space - width,
+            };
+            let y = if title.bottom {
+                rect.y + rect.height - 1
+            } else {
+                rect.y
+            };
+
+            (text, rect.x + 1 + offset, y)
+        })
+        .collect()
+}
+
+/// Borders around several boxes at once, drawn with shared edges.
+///
+/// Where boxes touch, each would normally draw its own edge, doubling it up.
+/// Instead, edges of boxes that are side by side (with no gap between them)
+/// are merged into a single line, and lines that meet are joined with the
+/// right junction characters (`╦`, `╠`, `╬`, etc., depending on the
+/// [style](Borders::style)).
+///
+/// Example:
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// # use cod::rect::{Borders, Rect};
+/// let mut borders = Borders::new();
+/// borders
+///     .add(Rect::new(0, 0, 10, 5))
+///     .add(Rect::new(10, 0, 10, 5))
+///     .add(Rect::new(0, 5, 20, 3));
+///
+/// // the second box's left edge moved onto the first's right edge
+/// assert_eq!(borders.rects()[1], Rect::new(9, 0, 11, 5));
+///
+/// borders.draw();
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Borders {
+    rects: Vec<Rect>,
+    style: BoxStyle,
+}
+
+impl Borders {
+    /// Create an empty set of borders.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the style of line to draw with.
+    pub fn style(&mut self, style: BoxStyle) -> &mut Self {
+        self.style = style;
+        self
+    }
+
+    /// Add a box. If it sits directly right of or below a box already
+    /// added, it's stretched by one cell to share that box's edge.
+    pub fn add(&mut self, rect: Rect) -> &mut Self {
+        let mut rect = rect;
+        if rect.is_empty() {
+            return self;
+        }
+
+        for other in &self.rects {
+            let rows_overlap = rect.y < other.y + other.height && other.y < rect.y + rect.height;
+            let cols_overlap = rect.x < other.x + other.width && other.x < rect.x + rect.width;
+
+            if rows_overlap && rect.x > 0 && other.x + other.width == rect.x {
+                rect.x -= 1;
+                rect.width += 1;
+            } else if cols_overlap && rect.y > 0 && other.y + other.height == rect.y {
+                rect.y -= 1;
+                rect.height += 1;
+            }
+        }
+
+        self.rects.push(rect);
+        self
+    }
+
+    /// Returns the boxes, as adjusted to share edges, in the order they were
+    /// added. Use [`Rect::pad`] to get the space inside each border.
+    #[must_use]
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    /// Returns the border character for each cell on an edge, keyed by
+    /// `(y, x)`.
+    pub(crate) fn cells(&self) -> BTreeMap<(u32, u32), char> {
+        const UP: u8 = BoxDrawingChar::UP;
+        const DOWN: u8 = BoxDrawingChar::DOWN;
+        const LEFT: u8 = BoxDrawingChar::LEFT;
+        const RIGHT: u8 = BoxDrawingChar::RIGHT;
+
+        let mut joins: BTreeMap<(u32, u32), u8> = BTreeMap::new();
+        for r in &self.rects {
+            let (right, bottom) = (r.x + r.width - 1, r.y + r.height - 1);
+            for x in r.x..=right {
+                let mut dirs = 0;
+                if x > r.x {
+                    dirs |= LEFT;
+                }
+                if x < right {
+                    dirs |= RIGHT;
+                }
+                *joins.entry((r.y, x)).or_default() |= dirs;
+                *joins.entry((bottom, x)).or_default() |= dirs;
+            }
+            for y in r.y..=bottom {
+                let mut dirs = 0;
+                if y > r.y {
+                    dirs |= UP;
+                }
+                if y < bottom {
+                    dirs |= DOWN;
+                }
+                *joins.entry((y, r.x)).or_default() |= dirs;
+                *joins.entry((y, right)).or_default() |= dirs;
+            }
+        }
+
+        joins
+            .into_iter()
+            .map(|(pos, dirs)| (pos, self.style.char(BoxDrawingChar::junction(dirs))))
+            .collect()
+    }
+
+    /// Draw every border onto the screen.
+    pub fn draw(&self) {
+        profile!();
+        for ((y, x), c) in self.cells() {
+            pixel(c, x, y);
+        }
+    }
+
+    /// Draw every border onto a canvas.
+    pub fn draw_on(&self, canvas: &mut crate::canvas::Canvas) {
+        for ((y, x), c) in self.cells() {
+            canvas.pixel(c, x, y);
+        }
+    }
+}
+
+/// Adds the lines along a path to a map of the directions joined at each
+/// cell, keyed by `(y, x)`.
+pub(crate) fn join_path(
+    joins: &mut BTreeMap<(u32, u32), u8>,
+    points: &[(u32, u32)],
+) -> Result<(), NonOrthogonal> {
+    const UP: u8 = BoxDrawingChar::UP;
+    const DOWN: u8 = BoxDrawingChar::DOWN;
+    const LEFT: u8 = BoxDrawingChar::LEFT;
+    const RIGHT: u8 = BoxDrawingChar::RIGHT;
+
+    if points
+        .windows(2)
+        .any(|pair| pair[0].0 != pair[1].0 && pair[0].1 != pair[1].1)
+    {
+        return Err(NonOrthogonal);
+    }
+
+    if let Some(&(x, y)) = points.first() {
+        joins.entry((y, x)).or_default();
+    }
+
+    for pair in points.windows(2) {
+        let ((x1, y1), (x2, y2)) = (pair[0], pair[1]);
+        if x1 == x2 {
+            let (top, bottom) = (y1.min(y2), y1.max(y2));
+            for y in top..=bottom {
+                let mut dirs = 0;
+                if y > top {
+                    dirs |= UP;
+                }
+                if y < bottom {
+                    dirs |= DOWN;
+                }
+                *joins.entry((y, x1)).or_default() |= dirs;
+            }
+        } else {
+            let (left, right) = (x1.min(x2), x1.max(x2));
+            for x in left..=right {
+                let mut dirs = 0;
+                if x > left {
+                    dirs |= LEFT;
+                }
+                if x < right {
+                    dirs |= RIGHT;
+                }
+                *joins.entry((y1, x)).or_default() |= dirs;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the character for each cell of a map from [`join_path`].
+pub(crate) fn join_chars(
+    joins: BTreeMap<(u32, u32), u8>,
+    style: BoxStyle,
+) -> BTreeMap<(u32, u32), char> {
+    joins
+        .into_iter()
+        .map(|(pos, dirs)| (pos, style.char(BoxDrawingChar::junction(dirs))))
+        .collect()
+}
+
+/// Returns the character for each cell along a path, keyed by `(y, x)`.
+fn path_cells(
+    points: &[(u32, u32)],
+    style: BoxStyle,
+) -> Result<BTreeMap<(u32, u32), char>, NonOrthogonal> {
+    let mut joins = BTreeMap::new();
+    join_path(&mut joins, points)?;
+    Ok(join_chars(joins, style))
+}
+
+/// Draw a route through the given points with box-drawing lines, e.g. to
+/// connect the nodes of a flowchart. Turns get corners, and where the path
+/// crosses or touches itself, the lines are joined with tees and crosses.
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// use cod::rect::BoxStyle;
+///
+/// // ┌────┐
+/// // │    │
+/// // └────┼──
+/// //      │
+/// cod::rect::path(
+///     &[(5, 3), (5, 0), (0, 0), (0, 2), (7, 2)],
+///     BoxStyle::Light,
+/// )
+/// .unwrap();
+/// ```
+///
+/// # Errors
+///
+/// If any segment of the path isn't orthogonal, returns an error without
+/// drawing anything.
+pub fn path(points: &[(u32, u32)], style: BoxStyle) -> Result<(), NonOrthogonal> {
+    profile!();
+    for ((y, x), c) in path_cells(points, style)? {
+        pixel(c, x, y);
+    }
+
+    Ok(())
+}
+
+/// Draw a route through the given points onto a canvas. See [`path`].
+///
+/// # Errors
+///
+/// If any segment of the path isn't orthogonal, returns an error without
+/// drawing anything.
+pub fn path_on(
+    canvas: &mut crate::canvas::Canvas,
+    points: &[(u32, u32)],
+    style: BoxStyle,
+) -> Result<(), NonOrthogonal> {
+    for ((y, x), c) in path_cells(points, style)? {
+        canvas.pixel(c, x, y);
+    }
+
+    Ok(())
+}
+
+/// How [`text_box`] lays out its box.
+///
+/// By default, the box has a double-lined border, one column of padding on
+/// the left and right, and wraps long lines, aligned left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextBox {
+    border: Option<BoxStyle>,
+    padding: Margins,
+    align: Align,
+    overflow: Overflow,
+}
+
+impl Default for TextBox {
+    fn default() -> Self {
+        Self {
+            border: Some(BoxStyle::default()),
+            padding: Margins::symmetric(0, 1),
+            align: Align::default(),
+            overflow: Overflow::default(),
+        }
+    }
+}
+
+impl TextBox {
+    /// Create the default options.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the style of the border, or leave it out with `None`.
+    #[must_use]
+    pub fn border(mut self, border: Option<BoxStyle>) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Set the space left between the border and the text.
+    #[must_use]
+    pub fn padding(mut self, padding: Margins) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Set how lines are aligned.
+    #[must_use]
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Set whether long lines are wrapped or cut off.
+    #[must_use]
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Returns the border cells, keyed by `(y, x)`, the lines of text with
+    /// their positions, and the space inside the padding.
+    fn layout(self, content: &str, rect: Rect) -> TextBoxLayout {
+        let mut border = BTreeMap::new();
+        let mut inside = rect;
+        if let Some(style) = self.border {
+            border = Borders::new().style(style).add(rect).cells();
+            inside = rect.pad(1);
+        }
+        let area = inside.inner(self.padding);
+
+        let mut text = Spans::new();
+        text.push(content, Style::default());
+        let wrapped: Vec<String> = text
+            .lines(area.width as usize, self.overflow)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        let lines = wrapped
+            .iter()
+            .enumerate()
+            .take(area.height as usize)
+            .zip(area.y..)
+            .map(|((i, line), y)| {
+                let last = wrapped.get(i + 1).is_none_or(|next| next.trim().is_empty());
+                let (line, offset) = align_line(line, area.width, self.align, last);
+                (line, area.x + offset, y)
+            })
+            .collect();
+
+        (border, lines, area)
+    }
+}
+
+/// Border cells keyed by `(y, x)`, lines of text with their positions, and
+/// the space for text, as laid out by [`TextBox`].
+type TextBoxLayout = (BTreeMap<(u32, u32), char>, Vec<(String, u32, u32)>, Rect);
+
+/// Draw a box between two (inclusive) corners, with text wrapped inside it.
+/// Returns the space the text was laid out in, i.e. inside the border and
+/// padding.
+///
+/// Linebreaks in the text are kept, and lines that don't fit across the
+/// box are wrapped or cut off (see [`TextBox::overflow`]). Lines past the
+/// bottom of the box are left out. The inside of the box isn't cleared
+/// first.
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// use cod::rect::{Align, Margins, Rect, TextBox};
+///
+/// let area = rect::text_box(
+///     "The quick brown fox jumps over the lazy dog.",
+///     0,
+///     0,
+///     19,
+///     6,
+///     TextBox::new().padding(Margins::all(1)).align(Align::Center),
+/// );
+/// assert_eq!(area, Rect::new(2, 2, 16, 3));
+/// ```
+#[allow(clippy::must_use_candidate)]
+pub fn text_box<S: AsRef<str>>(
+    content: S,
+    x1: u32,
+    y1: u32,
+    x2: u32,
+    y2: u32,
+    options: TextBox,
+) -> Rect {
+    profile!();
+    let (border, lines, area) =
+        options.layout(content.as_ref(), Rect::from_corners(x1, y1, x2, y2));
+    for ((y, x), c) in border {
+        pixel(c, x, y);
+    }
+    for (line, x, y) in lines {
+        crate::run(&line, x, y);
+    }
+
+    area
+}
+
+/// Draw a box with text wrapped inside it onto a canvas. See [`text_box`].
+#[allow(clippy::must_use_candidate)]
+pub fn text_box_on<S: AsRef<str>>(
+    canvas: &mut crate::canvas::Canvas,
+    content: S,
+    x1: u32,
+    y1: u32,
+    x2: u32,
+    y2: u32,
+    options: TextBox,
+) -> Rect {
+    let (border, lines, area) =
+        options.layout(content.as_ref(), Rect::from_corners(x1, y1, x2, y2));
+    for ((y, x), c) in border {
+        canvas.pixel(c, x, y);
+    }
+    for (line, x, y) in lines {
+        canvas.text(line, x, y);
+    }
+
+    area
+}
+
+/// Returns a line of `length` `c`s, and a label centered on it, with a
+/// space on either side, along with its offset. The label is cut off if it
+/// doesn't fit with a line cell either side.
+fn rule(length: u32, c: char, label: Option<&str>) -> (String, Option<(String, u32)>) {
+    let line = std::iter::repeat_n(c, length as usize).collect();
+    let label = label.filter(|_| length > 2).map(|label| {
+        let space = length - 2;
+        let text = format!(" {label} ");
+        let text = crate::width::truncate(&text, space as usize).to_string();
+        let width = u32::try_from(crate::width::str_width(&text)).unwrap_or(space);
+        (text, 1 + (space - width) / 2)
+    });
+
+    (line, label)
+}
+
+/// Returns the style to draw part of a rule in: `base`, with the named
+/// color from the active theme (see [`crate::palette`]) if it's set, and the
+/// theme's style overrides on top.
+fn rule_style(base: Style, name: &str) -> Style {
+    let base = Style {
+        fg: crate::palette::get(name).or(base.fg),
+        ..base
+    };
+    crate::palette::resolve(name, base, &StyleOverride::new())
+}
+
+/// Draw a horizontal line across the whole width of the terminal, with an
+/// optional label in the middle. The width is checked every time, so the
+/// line follows the terminal as it's resized.
+///
+/// If the active theme (see [`crate::palette`]) has a `"rule"` color, the
+/// line is drawn in it, and likewise the label in `"rule.label"`; otherwise
+/// they're drawn in the current colors. Style overrides for those names in
+/// the theme apply too (see [`crate::palette::resolve`]).
+///
+/// ```rust
+/// # use cod::prelude::*;
+/// use cod::rect::BoxStyle;
+///
+/// cod::text("Settings", 0, 0);
+/// rect::hr(1, BoxStyle::Light, None);
+/// cod::text("Volume: 11", 0, 2);
+/// rect::hr(3, BoxStyle::Double, Some("Advanced"));
+/// ```
+pub fn hr(y: u32, style: BoxStyle, label: Option<&str>) {
+    profile!();
+    let (width, _) = crate::term::size_or_default();
+    let (line, label) = rule(width, style.char(BoxDrawingChar::Horizontal), label);
+
+    crate::styled(rule_style(Style::default(), "rule"), || {
+        crate::run(&line, 0, y);
+    });
+    if let Some((label, x)) = label {
+        crate::styled(rule_style(Style::default(), "rule.label"), || {
+            crate::run(&label, x, y);
+        });
+    }
+}
+
+/// Draw a vertical line down the whole height of the terminal. See [`hr`].
+pub fn vr(x: u32, style: BoxStyle) {
+    profile!();
+    let (_, height) = crate::term::size_or_default();
+    let c = style.char(BoxDrawingChar::Vertical);
+
+    crate::styled(rule_style(Style::default(), "rule"), || {
+        for y in 0..height {
+            pixel(c, x, y);
+        }
+    });
+}
+
+/// Draw a horizontal line across the whole width of a canvas, with an
+/// optional label in the middle. See [`hr`]; colors from the theme
+/// replace the canvas' current foreground color.
+pub fn hr_on(canvas: &mut crate::canvas::Canvas, y: u32, style: BoxStyle, label: Option<&str>) {
+    let (line, label) = rule(
+        canvas.width(),
+        style.char(BoxDrawingChar::Horizontal),
+        label,
+    );
+
+    let current = canvas.style();
+    canvas.set_style(rule_style(current, "rule"));
+    canvas.text(line, 0, y);
+    if let Some((label, x)) = label {
+        canvas.set_style(rule_style(current, "rule.label"));
+        canvas.text(label, x, y);
+    }
+    canvas.set_style(current);
+}
+
+/// Draw a vertical line down the whole height of a canvas. See [`hr_on`].
+pub fn vr_on(canvas: &mut crate::canvas::Canvas, x: u32, style: BoxStyle) {
+    let c = style.char(BoxDrawingChar::Vertical);
+
+    let current = canvas.style();
+    canvas.set_style(rule_style(current, "rule"));
+    for y in 0..canvas.height() {
+        canvas.pixel(c, x, y);
+    }
+    canvas.set_style(current);
+}
@@ -0,0 +1,266 @@
+//! Sharing the screen between threads, each drawing in its own area.
+//!
+//! When several tasks draw at once (e.g. the output of parallel jobs), they
+//! can scribble over each other's part of the screen, or interleave their
+//! escape sequences so one task's colors leak into another's text.
+//!
+//! [`Regions`] hands out leases on non-overlapping rectangles of the screen.
+//! Each [`Region`] draws relative to its own top-left corner, cuts off
+//! anything outside of its rectangle, and prints each drawing call in one
+//! go, so calls from different threads never mix. When a region is dropped,
+//! its rectangle can be leased again.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use cod::rect::Rect;
+//! use cod::region::Regions;
+//!
+//! let regions = Regions::new();
+//! let jobs: Vec<_> = (0..3)
+//!     .map(|i| regions.lease(Rect::new(0, i * 2, 40, 2)).unwrap())
+//!     .collect();
+//!
+//! // this area is taken
+//! assert!(regions.lease(Rect::new(10, 1, 5, 1)).is_err());
+//!
+//! let handles: Vec<_> = (0..)
+//!     .zip(jobs)
+//!     .map(|(i, region)| {
+//!         std::thread::spawn(move || {
+//!             region.text(format!("job {i}: running"), 0, 0);
+//!             region.text("done", 0, 1);
+//!         })
+//!     })
+//!     .collect();
+//!
+//! for handle in handles {
+//!     handle.join().unwrap();
+//! }
+//! ```
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::canvas::Canvas;
+use crate::rect::Rect;
+use crate::style::Style;
+
+/// The requested rectangle overlaps a region that's already leased.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overlap;
+
+impl std::fmt::Display for Overlap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the region overlaps one already leased")
+    }
+}
+
+impl std::error::Error for Overlap {}
+
+/// The state shared between [`Regions`] and every [`Region`] it leased.
+#[derive(Debug, Default)]
+struct Shared {
+    leased: Mutex<Vec<Rect>>,
+    /// Held while a region prints, so output from different regions doesn't
+    /// interleave.
+    output: Mutex<()>,
+}
+
+/// Hands out [`Region`]s of the screen, making sure no two overlap.
+///
+/// Cloning gives another handle to the same set of leases.
+#[derive(Debug, Default, Clone)]
+pub struct Regions {
+    shared: Arc<Shared>,
+}
+
+impl Regions {
+    /// Create a new set of leases, with the whole screen free.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lease a rectangle of the screen, until the returned region is
+    /// dropped.
+    ///
+    /// # Errors
+    ///
+    /// If the rectangle overlaps a region that's already leased, returns
+    /// [`Overlap`].
+    ///
+    /// # Panics
+    ///
+    /// If another thread panicked while leasing or releasing a region,
+    /// panics.
+    pub fn lease(&self, rect: Rect) -> Result<Region, Overlap> {
+        let mut leased = self.shared.leased.lock().unwrap();
+        if leased
+            .iter()
+            .any(|other| !rect.intersect(*other).is_empty())
+        {
+            return Err(Overlap);
+        }
+
+        leased.push(rect);
+        Ok(Region {
+            rect,
+            shared: Arc::clone(&self.shared),
+        })
+    }
+
+    /// Returns every rectangle currently leased.
+    ///
+    /// # Panics
+    ///
+    /// If another thread panicked while leasing or releasing a region,
+    /// panics.
+    #[must_use]
+    pub fn leased(&self) -> Vec<Rect> {
+        self.shared.leased.lock().unwrap().clone()
+    }
+}
+
+/// A lease on a rectangle of the screen, from [`Regions::lease`].
+///
+/// Coordinates are relative to the top-left corner of the rectangle, and
+/// anything drawn outside of it is cut off. Each call prints and flushes
+/// without being interrupted by other regions.
+#[derive(Debug)]
+pub struct Region {
+    rect: Rect,
+    shared: Arc<Shared>,
+}
+
+impl Region {
+    /// Returns the rectangle of the screen this region covers.
+    #[must_use]
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// Returns a blank canvas the size of this region, to draw onto and
+    /// then [present](Region::present).
+    #[must_use]
+    pub fn canvas(&self) -> Canvas {
+        Canvas::new(self.rect.width, self.rect.height)
+    }
+
+    /// Hold the output lock, then flush once done.
+    fn output(&self) -> Output<'_> {
+        Output {
+            _lock: self
+                .shared
+                .output
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        }
+    }
+
+    /// Returns the position on screen of a position in the region, if it's
+    /// inside of it.
+    fn at(&self, x: u32, y: u32) -> Option<(u32, u32)> {
+        (x < self.rect.width && y < self.rect.height).then(|| (self.rect.x + x, self.rect.y + y))
+    }
+
+    /// Draw a single character.
+    pub fn pixel(&self, c: char, x: u32, y: u32) {
+        self.pixel_styled(c, x, y, Style::new());
+    }
+
+    /// Draw a single character in a style. See [`crate::pixel_styled`].
+    pub fn pixel_styled(&self, c: char, x: u32, y: u32, style: Style) {
+        profile!();
+        let Some((x, y)) = self.at(x, y) else {
+            return;
+        };
+
+        let _output = self.output();
+        crate::pixel_styled(c, x, y, style);
+    }
+
+    /// Draw text (non-wrapping, but respects linebreaks).
+    pub fn text<S: AsRef<str>>(&self, s: S, x: u32, y: u32) {
+        self.text_styled(s, x, y, Style::new());
+    }
+
+    /// Draw text in a style. See [`crate::text_styled`].
+    pub fn text_styled<S: AsRef<str>>(&self, s: S, x: u32, y: u32, style: Style) {
+        profile!();
+        let _output = self.output();
+        style.apply();
+        for (y, line) in (y..).zip(s.as_ref().split('\n')) {
+            if y >= self.rect.height || x >= self.rect.width {
+                continue;
+            }
+
+            let room = (self.rect.width - x) as usize;
+            let line = crate::width::truncate(line, room);
+            crate::run(line, self.rect.x + x, self.rect.y + y);
+        }
+        crate::reset_style(style);
+    }
+
+    /// Fill the whole region with a character.
+    pub fn fill(&self, c: char) {
+        profile!();
+        let _output = self.output();
+        let row: String = std::iter::repeat_n(c, self.rect.width as usize).collect();
+        for y in self.rect.y..self.rect.y + self.rect.height {
+            crate::run(&row, self.rect.x, y);
+        }
+    }
+
+    /// Fill the whole region with spaces.
+    pub fn clear(&self) {
+        self.fill(' ');
+    }
+
+    /// Draw a canvas with its top-left corner at the region's, cutting off
+    /// whatever doesn't fit.
+    pub fn draw(&self, canvas: &Canvas) {
+        let _output = self.output();
+        let area = Rect::new(0, 0, self.rect.width, self.rect.height);
+        canvas.draw_region(area, self.rect.x, self.rect.y);
+    }
+
+    /// Draw the changes to a canvas since it was last presented, with its
+    /// top-left corner at the region's. See [`Canvas::present`].
+    ///
+    /// A canvas bigger than the region is drawn in full every time, cut off
+    /// to fit.
+    pub fn present(&self, canvas: &mut Canvas) {
+        let _output = self.output();
+        if canvas.width() <= self.rect.width && canvas.height() <= self.rect.height {
+            canvas.present(self.rect.x, self.rect.y);
+        } else {
+            let area = Rect::new(0, 0, self.rect.width, self.rect.height);
+            canvas.draw_region(area, self.rect.x, self.rect.y);
+        }
+    }
+}
+
+impl Drop for Region {
+    fn drop(&mut self) {
+        let mut leased = self
+            .shared
+            .leased
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(i) = leased.iter().position(|&r| r == self.rect) {
+            leased.remove(i);
+        }
+    }
+}
+
+/// Holds the output lock while a region draws, flushing before letting go.
+struct Output<'a> {
+    _lock: MutexGuard<'a, ()>,
+}
+
+impl Drop for Output<'_> {
+    fn drop(&mut self) {
+        crate::flush();
+    }
+}
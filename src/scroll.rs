@@ -0,0 +1,260 @@
+//! Scrolling through content bigger than the area it's shown in.
+//!
+//! A [`ScrollView`] keeps track of how far a pane is scrolled, keeps that
+//! within the content, and tells you which part of the content is visible.
+//! It doesn't draw anything itself: draw the [visible](ScrollView::visible)
+//! part of your content into its [viewport](ScrollView::viewport).
+//!
+//! Scrolling with the mouse wheel has a little inertia, carrying on for a
+//! few frames after the wheel stops; call [`ScrollView::tick`] once a frame
+//! to keep it moving.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use cod::rect::Rect;
+//! use cod::scroll::ScrollView;
+//!
+//! let lines: Vec<String> = (0..100).map(|i| format!("line {i}")).collect();
+//!
+//! let mut view = ScrollView::new(Rect::new(0, 0, 20, 10));
+//! view.set_content(20, 100);
+//! view.scroll_by(0, 95);
+//! // can't scroll past the end
+//! assert_eq!(view.offset(), (0, 90));
+//!
+//! let (area, visible) = (view.viewport(), view.visible());
+//! let shown = lines.iter().skip(visible.y as usize).take(visible.height as usize);
+//! for (y, line) in (area.y..).zip(shown) {
+//!     cod::text(line, area.x, y);
+//! }
+//! ```
+
+use crate::rect::Rect;
+
+/// How much of its speed inertial scrolling keeps each frame.
+const FRICTION: f64 = 0.6;
+
+/// The scroll position of a pane, and the bounds it's kept within.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrollView {
+    viewport: Rect,
+    content: (u32, u32),
+    offset: (u32, u32),
+
+    /// Rows scrolled per wheel notch.
+    step: u32,
+    inertia: bool,
+    /// Rows left to scroll from inertia, per frame.
+    velocity: f64,
+}
+
+impl ScrollView {
+    /// Create a view onto the given area of the screen, scrolled to the top,
+    /// with no content yet.
+    #[must_use]
+    pub fn new(viewport: Rect) -> Self {
+        Self {
+            viewport,
+            content: (0, 0),
+            offset: (0, 0),
+            step: 3,
+            inertia: !crate::a11y::reduced_motion(),
+            velocity: 0.0,
+        }
+    }
+
+    /// Set how many rows each notch of the mouse wheel scrolls (3 by
+    /// default).
+    pub fn step(&mut self, rows: u32) -> &mut Self {
+        self.step = rows;
+        self
+    }
+
+    /// Set whether mouse wheel scrolling carries on for a while after the
+    /// wheel stops (on by default, unless
+    /// [reduced motion](crate::a11y::reduced_motion) is on).
+    pub fn inertia(&mut self, inertia: bool) -> &mut Self {
+        self.inertia = inertia;
+        if !inertia {
+            self.velocity = 0.0;
+        }
+        self
+    }
+
+    /// Returns the area of the screen the content is shown in.
+    #[must_use]
+    pub fn viewport(&self) -> Rect {
+        self.viewport
+    }
+
+    /// Move or resize the area of the screen the content is shown in,
+    /// keeping the offset within bounds.
+    pub fn set_viewport(&mut self, viewport: Rect) {
+        self.viewport = viewport;
+        self.clamp();
+    }
+
+    /// Returns the width and height of the content.
+    #[must_use]
+    pub fn content_size(&self) -> (u32, u32) {
+        self.content
+    }
+
+    /// Set the width and height of the content, keeping the offset within
+    /// bounds.
+    pub fn set_content(&mut self, width: u32, height: u32) {
+        self.content = (width, height);
+        self.clamp();
+    }
+
+    /// Returns how far the content is scrolled, in columns and rows.
+    #[must_use]
+    pub fn offset(&self) -> (u32, u32) {
+        self.offset
+    }
+
+    /// Returns the furthest the content can be scrolled, such that the end
+    /// of it is at the edge of the viewport.
+    #[must_use]
+    pub fn max_offset(&self) -> (u32, u32) {
+        (
+            self.content.0.saturating_sub(self.viewport.width),
+            self.content.1.saturating_sub(self.viewport.height),
+        )
+    }
+
+    /// Returns the part of the content that's visible, in the content's
+    /// coordinates.
+    #[must_use]
+    pub fn visible(&self) -> Rect {
+        let (x, y) = self.offset;
+        Rect::new(
+            x,
+            y,
+            self.viewport.width.min(self.content.0 - x),
+            self.viewport.height.min(self.content.1 - y),
+        )
+    }
+
+    /// Returns true if the content is scrolled all the way down.
+    #[must_use]
+    pub fn at_bottom(&self) -> bool {
+        self.offset.1 == self.max_offset().1
+    }
+
+    fn clamp(&mut self) {
+        let (max_x, max_y) = self.max_offset();
+        self.offset = (self.offset.0.min(max_x), self.offset.1.min(max_y));
+    }
+
+    /// Scroll to an offset, clamped to the content. Returns true if the
+    /// offset changed.
+    pub fn scroll_to(&mut self, x: u32, y: u32) -> bool {
+        let before = self.offset;
+        self.offset = (x, y);
+        self.clamp();
+        self.offset != before
+    }
+
+    /// Scroll by some number of columns and rows (negative is left or up),
+    /// clamped to the content. Returns true if the offset changed.
+    pub fn scroll_by(&mut self, dx: i64, dy: i64) -> bool {
+        let shift = |n: u32, d: i64| u32::try_from((i64::from(n) + d).max(0)).unwrap_or(u32::MAX);
+        self.scroll_to(shift(self.offset.0, dx), shift(self.offset.1, dy))
+    }
+
+    /// Scroll up by a viewport's height, less one row to keep some context.
+    /// Returns true if the offset changed.
+    pub fn page_up(&mut self) -> bool {
+        let page = i64::from(self.viewport.height.saturating_sub(1).max(1));
+        self.scroll_by(0, -page)
+    }
+
+    /// Scroll down by a viewport's height, less one row to keep some
+    /// context. Returns true if the offset changed.
+    pub fn page_down(&mut self) -> bool {
+        let page = i64::from(self.viewport.height.saturating_sub(1).max(1));
+        self.scroll_by(0, page)
+    }
+
+    /// Scroll as though the mouse wheel was turned some number of notches
+    /// (negative is up), adding to the inertia. Returns true if the offset
+    /// changed.
+    pub fn wheel(&mut self, notches: i32) -> bool {
+        let rows = f64::from(notches) * f64::from(self.step);
+        if self.inertia {
+            if self.velocity * rows < 0.0 {
+                self.velocity = 0.0;
+            }
+            self.velocity += rows * FRICTION;
+        }
+
+        self.scroll_by(0, i64::from(notches) * i64::from(self.step))
+    }
+
+    /// Returns true if the view is still moving from inertia.
+    #[must_use]
+    pub fn is_scrolling(&self) -> bool {
+        self.velocity.abs() >= 1.0
+    }
+
+    /// Carry on inertial scrolling for a frame. Returns true if the offset
+    /// changed.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn tick(&mut self) -> bool {
+        if !self.is_scrolling() {
+            self.velocity = 0.0;
+            return false;
+        }
+
+        let rows = self.velocity.round() as i64;
+        self.velocity *= FRICTION;
+        let moved = self.scroll_by(0, rows);
+        if !moved {
+            self.velocity = 0.0;
+        }
+        moved
+    }
+
+    /// Handle an event, returning true if the offset changed.
+    ///
+    /// Scrolls with the mouse wheel over the viewport, the arrow keys, Page
+    /// Up and Page Down, and Home and End.
+    ///
+    /// Only enabled on feature `crossterm`.
+    #[cfg(feature = "crossterm")]
+    pub fn handle(&mut self, event: &crate::read::Event) -> bool {
+        use crossterm::event::{Event, KeyCode, KeyEventKind, MouseEventKind};
+
+        match event {
+            Event::Mouse(mouse) => {
+                let (x, y) = (u32::from(mouse.column), u32::from(mouse.row));
+                if !self.viewport.contains(x, y) {
+                    return false;
+                }
+
+                match mouse.kind {
+                    MouseEventKind::ScrollUp => self.wheel(-1),
+                    MouseEventKind::ScrollDown => self.wheel(1),
+                    MouseEventKind::ScrollLeft => self.scroll_by(-i64::from(self.step), 0),
+                    MouseEventKind::ScrollRight => self.scroll_by(i64::from(self.step), 0),
+                    _ => false,
+                }
+            }
+            Event::Key(key) if key.kind != KeyEventKind::Release => match key.code {
+                KeyCode::Up => self.scroll_by(0, -1),
+                KeyCode::Down => self.scroll_by(0, 1),
+                KeyCode::Left => self.scroll_by(-1, 0),
+                KeyCode::Right => self.scroll_by(1, 0),
+                KeyCode::PageUp => self.page_up(),
+                KeyCode::PageDown => self.page_down(),
+                KeyCode::Home => self.scroll_to(0, 0),
+                KeyCode::End => self.scroll_to(self.offset.0, u32::MAX),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! Rich text: strings made of differently-styled pieces.
+//!
+//! [`Spans`] is a sequence of [`Span`]s, each a piece of text with a
+//! [`Style`]. It can be concatenated, sliced by column, laid out into lines
+//! of a given width, and drawn into a rectangle.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! # use cod::color::Color;
+//! # use cod::rect::Rect;
+//! # use cod::spans::{Overflow, Spans};
+//! # use cod::style::Style;
+//! let error = Style {
+//!     fg: Some(Color::Ansi(9)),
+//!     bold: true,
+//!     ..Style::default()
+//! };
+//!
+//! let mut message = Spans::new();
+//! message.push("error: ", error).push("file not found", Style::default());
+//!
+//! assert_eq!(message.width(), 21);
+//! assert_eq!(message.slice(0, 5).to_string(), "error");
+//!
+//! message.draw(Rect::new(0, 0, 10, 3), Overflow::Wrap);
+//! cod::flush();
+//! ```
+
+use std::fmt;
+use std::ops::{Add, AddAssign};
+
+use crate::rect::Rect;
+use crate::style::{Style, Tracker};
+use crate::width::char_width;
+
+/// A piece of text with a single style.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// The text.
+    pub text: String,
+    /// The style of the text.
+    pub style: Style,
+}
+
+impl Span {
+    /// Create a new span.
+    pub fn new<S: Into<String>>(text: S, style: Style) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
+
+    /// Returns the number of columns the span takes up.
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.text.chars().map(char_width).sum()
+    }
+}
+
+/// What to do with lines too long to fit.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Overflow {
+    /// Break lines between words where possible, and anywhere otherwise.
+    #[default]
+    Wrap,
+    /// Cut lines off at the edge.
+    Truncate,
+}
+
+/// A sequence of styled pieces of text.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Spans {
+    spans: Vec<Span>,
+}
+
+impl Spans {
+    /// Create an empty sequence.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a piece of text to the end.
+    ///
+    /// If it has the same style as the last piece, the two are merged.
+    pub fn push<S: Into<String>>(&mut self, text: S, style: Style) -> &mut Self {
+        let text = text.into();
+        if text.is_empty() {
+            return self;
+        }
+
+        match self.spans.last_mut() {
+            Some(last) if last.style == style => last.text.push_str(&text),
+            _ => self.spans.push(Span { text, style }),
+        }
+
+        self
+    }
+
+    /// Returns the pieces of text, in order.
+    #[must_use]
+    pub fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    /// Returns true if there's no text.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Returns the number of columns the text takes up, ignoring linebreaks.
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.spans.iter().map(Span::width).sum()
+    }
+
+    /// Returns the text between two columns (`start` inclusive, `end`
+    /// exclusive), keeping styles.
+    #[must_use]
+    pub fn slice(&self, start: usize, end: usize) -> Self {
+        let mut out = Self::new();
+        let mut col = 0;
+        for span in &self.spans {
+            let text: String = span
+                .text
+                .chars()
+                .filter(|&c| {
+                    let keep = col >= start && col + char_width(c) <= end;
+                    col += char_width(c);
+                    keep
+                })
+                .collect();
+            out.push(text, span.style);
+        }
+
+        out
+    }
+
+    /// Split the text into lines at most `width` columns wide, breaking at
+    /// linebreaks and handling longer lines as requested.
+    #[must_use]
+    pub fn lines(&self, width: usize, overflow: Overflow) -> Vec<Self> {
+        let mut lines = Vec::new();
+        let mut line: Vec<(char, Style)> = Vec::new();
+        let mut line_width = 0;
+
+        let chars = self
+            .spans
+            .iter()
+            .flat_map(|span| span.text.chars().map(move |c| (c, span.style)));
+
+        for (c, style) in chars {
+            if c == '\n' {
+                lines.push(collect(&line));
+                line.clear();
+                line_width = 0;
+                continue;
+            }
+
+            if line_width + char_width(c) > width {
+                match overflow {
+                    Overflow::Truncate => continue,
+                    Overflow::Wrap if c == ' ' => {
+                        lines.push(collect(&line));
+                        line.clear();
+                        line_width = 0;
+                        continue;
+                    }
+                    Overflow::Wrap => {
+                        let rest = match line.iter().rposition(|&(c, _)| c == ' ') {
+                            Some(i) => line.split_off(i + 1),
+                            None => Vec::new(),
+                        };
+                        if line.last().is_some_and(|&(c, _)| c == ' ') {
+                            line.pop();
+                        }
+
+                        lines.push(collect(&line));
+                        line = rest;
+                        line_width = line.iter().map(|&(c, _)| char_width(c)).sum();
+                    }
+                }
+            }
+
+            if char_width(c) <= width {
+                line_width += char_width(c);
+                line.push((c, style));
+            }
+        }
+
+        lines.push(collect(&line));
+        lines
+    }
+
+    /// Draw the text into a rectangle on the screen, wrapping or truncating
+    /// lines that don't fit. Lines past the bottom of the rectangle are
+    /// dropped.
+    ///
+    /// Afterwards, styling is reset and the colors are restored (see
+    /// [`crate::color`]).
+    pub fn draw(&self, rect: Rect, overflow: Overflow) {
+        profile!();
+        let mut tracker = Tracker::new();
+        let lines = self.lines(rect.width as usize, overflow);
+        for (y, line) in (rect.y..rect.y + rect.height).zip(lines) {
+            let mut x = rect.x;
+            for span in &line.spans {
+                tracker.set(span.style);
+
+                crate::run(&span.text, x, y);
+                x += u32::try_from(span.width()).unwrap_or(u32::MAX);
+            }
+        }
+
+        tracker.reset();
+    }
+}
+
+/// Gathers styled characters back into spans.
+fn collect(chars: &[(char, Style)]) -> Spans {
+    let mut spans = Spans::new();
+    for &(c, style) in chars {
+        spans.push(c, style);
+    }
+
+    spans
+}
+
+impl fmt::Display for Spans {
+    /// Displays the text without any styling.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.spans
+            .iter()
+            .try_for_each(|span| f.write_str(&span.text))
+    }
+}
+
+impl From<Span> for Spans {
+    fn from(span: Span) -> Self {
+        let mut spans = Self::new();
+        spans.push(span.text, span.style);
+        spans
+    }
+}
+
+impl From<&str> for Spans {
+    fn from(text: &str) -> Self {
+        Span::new(text, Style::default()).into()
+    }
+}
+
+impl From<String> for Spans {
+    fn from(text: String) -> Self {
+        Span::new(text, Style::default()).into()
+    }
+}
+
+impl AddAssign for Spans {
+    fn add_assign(&mut self, rhs: Self) {
+        for span in rhs.spans {
+            self.push(span.text, span.style);
+        }
+    }
+}
+
+impl Add for Spans {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+impl Extend<Span> for Spans {
+    fn extend<T: IntoIterator<Item = Span>>(&mut self, iter: T) {
+        for span in iter {
+            self.push(span.text, span.style);
+        }
+    }
+}
+
+impl FromIterator<Span> for Spans {
+    fn from_iter<T: IntoIterator<Item = Span>>(iter: T) -> Self {
+        let mut spans = Self::new();
+        spans.extend(iter);
+        spans
+    }
+}
@@ -0,0 +1,557 @@
+//! Reusable textures, parsed once and drawn many times.
+//!
+//! [`crate::blit`] takes a texture as a string, and works out where each
+//! character goes every time it's called. A [`Sprite`] does that once, up
+//! front: it knows its size, checks that it can be drawn at all, and can be
+//! drawn (or asked about) as often as needed.
+//!
+//! Sprites can also be [colored](Sprite::styled), cell by cell, with a
+//! second texture of the same shape saying which style each cell is drawn
+//! in.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use cod::sprite::Sprite;
+//!
+//! let ship = Sprite::new(" /\\\n/__\\").unwrap();
+//! assert_eq!((ship.width(), ship.height()), (4, 2));
+//!
+//! for x in [0, 10, 20] {
+//!     // spaces don't cover what's behind the ship
+//!     ship.draw_transparent(' ', x, 5);
+//! }
+//! ```
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::canvas::Canvas;
+use crate::rect::Rect;
+use crate::style::{Style, Tracker};
+use crate::width;
+
+/// A grapheme cluster of a sprite, the column it starts at, and its style.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Glyph {
+    x: u32,
+    text: String,
+    style: Option<Style>,
+}
+
+/// A texture, laid out ahead of time. See the [module docs](self).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Sprite {
+    rows: Vec<Vec<Glyph>>,
+    width: u32,
+}
+
+impl Sprite {
+    /// Create a sprite from a texture, like one given to [`crate::blit`].
+    /// Each line is a row; a single linebreak at the end is ignored, as are
+    /// carriage returns at the ends of lines.
+    ///
+    /// # Errors
+    ///
+    /// If the texture has control characters (other than linebreaks) in it,
+    /// like tabs, which can't be drawn in a single place, returns an error.
+    pub fn new<S: AsRef<str>>(texture: S) -> Result<Self, InvalidSprite> {
+        let texture = texture.as_ref();
+        let texture = texture.strip_suffix('\n').unwrap_or(texture);
+
+        let mut rows = Vec::new();
+        let mut width = 0;
+        for (y, line) in (0..).zip(texture.split('\n')) {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+
+            let mut row = Vec::new();
+            let mut x = 0;
+            for g in width::graphemes(line) {
+                if let Some(c) = g.chars().find(|c| c.is_control()) {
+                    return Err(InvalidSprite { c, x, y });
+                }
+
+                row.push(Glyph {
+                    x,
+                    text: g.to_string(),
+                    style: None,
+                });
+                x += width::grapheme_columns(g);
+            }
+
+            width = width.max(x);
+            rows.push(row);
+        }
+
+        Ok(Self { rows, width })
+    }
+
+    /// Load a sprite from a file. See [`Sprite::new`].
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read or isn't a valid sprite, returns an error.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
+        Ok(Self::new(std::fs::read_to_string(path)?)?)
+    }
+
+    /// Returns the width of the sprite, i.e. of its widest row.
+    #[must_use]
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the number of rows in the sprite.
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        u32::try_from(self.rows.len()).unwrap_or(u32::MAX)
+    }
+
+    /// Returns the space the sprite covers when drawn at a position.
+    ///
+    /// ```rust
+    /// # use cod::rect::Rect;
+    /// # use cod::sprite::Sprite;
+    /// let player = Sprite::new("o\n|\n^").unwrap();
+    /// let enemy = Sprite::new("<=>").unwrap();
+    ///
+    /// let hit = player.bounds(4, 1).intersect(enemy.bounds(3, 2));
+    /// assert!(!hit.is_empty());
+    /// ```
+    #[must_use]
+    pub fn bounds(&self, x: u32, y: u32) -> Rect {
+        Rect::new(x, y, self.width, self.height())
+    }
+
+    /// Returns the grapheme cluster (see [`width::graphemes`]) that starts
+    /// at a column and row of the sprite, if any. Columns covered by the
+    /// right half of a wide character, and past the end of their row, have
+    /// none.
+    #[must_use]
+    pub fn get(&self, x: u32, y: u32) -> Option<&str> {
+        let row = self.rows.get(y as usize)?;
+        let i = row.binary_search_by_key(&x, |glyph| glyph.x).ok()?;
+        Some(&row[i].text)
+    }
+
+    /// Color the sprite using a map of the same shape, where each character
+    /// stands for the style of the cell in the same place, as given in
+    /// `palette`. Spaces in the map, and cells past the end of its rows,
+    /// are left unstyled, i.e. in the current colors when drawn.
+    ///
+    /// ```rust
+    /// # use cod::prelude::*;
+    /// use cod::color::Color;
+    /// use cod::sprite::Sprite;
+    /// use cod::style::Style;
+    ///
+    /// let tree = Sprite::new(" ^ \n/^\\\n | ")
+    ///     .unwrap()
+    ///     .styled(
+    ///         " g \nggg\n b ",
+    ///         &[
+    ///             ('g', Style::new().fg(Color::Green)),
+    ///             ('b', Style::new().fg(Color::Yellow).bg(Color::Black)),
+    ///         ],
+    ///     )
+    ///     .unwrap();
+    ///
+    /// tree.draw(0, 0);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the map has a character that isn't in the palette, returns an
+    /// error.
+    pub fn styled(mut self, map: &str, palette: &[(char, Style)]) -> Result<Self, InvalidSprite> {
+        for glyph in self.rows.iter_mut().flatten() {
+            glyph.style = None;
+        }
+
+        for ((y, line), row) in (0..).zip(map.lines()).zip(&mut self.rows) {
+            for (x, c) in (0..).zip(line.chars()) {
+                if c == ' ' {
+                    continue;
+                }
+
+                let &(_, style) = palette
+                    .iter()
+                    .find(|&&(key, _)| key == c)
+                    .ok_or(InvalidSprite { c, x, y })?;
+                if let Ok(i) = row.binary_search_by_key(&x, |glyph| glyph.x) {
+                    row[i].style = Some(style);
+                }
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Returns true if any cell of the sprite has a style of its own (see
+    /// [`Sprite::styled`]).
+    #[must_use]
+    pub fn is_styled(&self) -> bool {
+        self.rows
+            .iter()
+            .flatten()
+            .any(|glyph| glyph.style.is_some())
+    }
+
+    /// Returns a copy of the sprite mirrored left to right, with characters
+    /// that point one way or the other (like `/`, `(`, or `┌`) swapped for
+    /// their mirror images.
+    ///
+    /// ```rust
+    /// # use cod::sprite::Sprite;
+    /// let right = Sprite::new("┌─>\n│").unwrap();
+    /// assert_eq!(right.flip_h().to_string(), "<─┐\n  │");
+    /// ```
+    #[must_use]
+    pub fn flip_h(&self) -> Self {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let mut cells = vec![None; self.width as usize];
+                for glyph in row {
+                    let width = width::grapheme_columns(&glyph.text).max(1);
+                    let x = self.width.saturating_sub(glyph.x + width);
+                    cells[x as usize] = Some(glyph.remap(&swap(FLIP_H)));
+                }
+                cells
+            })
+            .collect();
+
+        Self::from_cells(rows, self.width)
+    }
+
+    /// Returns a copy of the sprite mirrored top to bottom, with characters
+    /// that point up or down (like `^`, `/`, or `┌`) swapped for their
+    /// mirror images.
+    #[must_use]
+    pub fn flip_v(&self) -> Self {
+        let rows = self
+            .rows
+            .iter()
+            .rev()
+            .map(|row| row.iter().map(|glyph| glyph.remap(&swap(FLIP_V))).collect())
+            .collect();
+
+        Self {
+            rows,
+            width: self.width,
+        }
+    }
+
+    /// Returns a copy of the sprite turned 90° clockwise, with characters
+    /// that point some way (like `-`, `>`, or `┌`) turned to match.
+    ///
+    /// Characters are taller than they are wide, so the result is stretched
+    /// out; a sprite 4 columns wide and 2 rows high becomes 2 columns wide
+    /// and 4 rows high. Wide characters can't be turned on their side, and
+    /// may cover what's next to them afterwards.
+    ///
+    /// ```rust
+    /// # use cod::sprite::Sprite;
+    /// let arrow = Sprite::new("-->").unwrap();
+    /// assert_eq!(arrow.rotate_cw().to_string(), "|\n|\nv");
+    /// ```
+    #[must_use]
+    pub fn rotate_cw(&self) -> Self {
+        let cells = self.cells();
+        let height = cells.len();
+
+        let rows = (0..self.width as usize)
+            .map(|x| {
+                (0..height)
+                    .map(|y| {
+                        let cell = cells[height - 1 - y][x].as_ref();
+                        cell.map(|glyph| glyph.remap(&rotate))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self::from_cells(rows, self.height())
+    }
+
+    /// Returns the sprite as a grid of cells, each with the grapheme
+    /// cluster starting there, if any.
+    fn cells(&self) -> Vec<Vec<Option<Glyph>>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                let mut cells = vec![None; self.width as usize];
+                for glyph in row {
+                    cells[glyph.x as usize] = Some(glyph.clone());
+                }
+                cells
+            })
+            .collect()
+    }
+
+    /// Create a sprite from a grid of cells (see [`Sprite::cells`]). Empty
+    /// cells before the last grapheme cluster in a row become spaces, and
+    /// clusters that would overlap a wide one before them are left out.
+    fn from_cells(cells: Vec<Vec<Option<Glyph>>>, width: u32) -> Self {
+        let rows = cells
+            .into_iter()
+            .map(|cells| {
+                let mut row = Vec::new();
+                let mut free = 0;
+                for (x, cell) in (0..).zip(cells) {
+                    if x < free {
+                        continue;
+                    }
+
+                    let mut glyph = cell.unwrap_or_else(|| Glyph {
+                        x,
+                        text: " ".to_string(),
+                        style: None,
+                    });
+                    glyph.x = x;
+                    free = x + width::grapheme_columns(&glyph.text);
+                    row.push(glyph);
+                }
+
+                while row
+                    .last()
+                    .is_some_and(|glyph: &Glyph| glyph.text == " " && glyph.style.is_none())
+                {
+                    row.pop();
+                }
+                row
+            })
+            .collect();
+
+        Self { rows, width }
+    }
+
+    /// Returns each grapheme cluster of the sprite with its position,
+    /// relative to the top-left corner, and its style, if any. With
+    /// `blank`, unstyled spaces are left out, and `blank`s become spaces.
+    fn glyphs(&self, blank: Option<char>) -> impl Iterator<Item = (u32, u32, &str, Option<Style>)> {
+        (0..).zip(&self.rows).flat_map(move |(y, row)| {
+            row.iter().filter_map(move |glyph| {
+                let text = match blank {
+                    Some(_) if glyph.text == " " && glyph.style.is_none() => return None,
+                    Some(blank) if is_char(&glyph.text, blank) => " ",
+                    _ => glyph.text.as_str(),
+                };
+                Some((glyph.x, y, text, glyph.style))
+            })
+        })
+    }
+
+    /// Draw the sprite's cells onto the screen, switching styles as needed.
+    fn draw_glyphs(&self, blank: Option<char>, x: u32, y: u32) {
+        let mut tracker = self.is_styled().then(Tracker::new);
+        for (gx, gy, g, style) in self.glyphs(blank) {
+            if let Some(tracker) = &mut tracker {
+                tracker.set(style.unwrap_or_default());
+            }
+            crate::grapheme(g, x + gx, y + gy);
+        }
+
+        if let Some(mut tracker) = tracker {
+            tracker.reset();
+        }
+    }
+
+    /// Draw the sprite's cells onto a canvas, switching styles as needed.
+    fn draw_glyphs_on(&self, canvas: &mut Canvas, blank: Option<char>, x: u32, y: u32) {
+        let current = canvas.style();
+        for (gx, gy, g, style) in self.glyphs(blank) {
+            canvas.set_style(style.unwrap_or(current));
+            canvas.text(g, x + gx, y + gy);
+        }
+        canvas.set_style(current);
+    }
+
+    /// Draw the sprite onto the screen, with its top-left corner at the
+    /// given position.
+    ///
+    /// If the sprite is [styled](Sprite::styled), each cell is drawn in its
+    /// style, and afterwards, styling is reset and the colors are restored
+    /// (see [`crate::color`]).
+    pub fn draw(&self, x: u32, y: u32) {
+        profile!();
+        self.draw_glyphs(None, x, y);
+    }
+
+    /// Draw the sprite onto the screen, skipping over (unstyled) spaces,
+    /// and drawing `blank`s as actual spaces. See
+    /// [`crate::blit_transparent`] and [`Sprite::draw`].
+    pub fn draw_transparent(&self, blank: char, x: u32, y: u32) {
+        profile!();
+        self.draw_glyphs(Some(blank), x, y);
+    }
+
+    /// Draw the sprite onto a canvas. See [`Sprite::draw`]. Unstyled cells
+    /// are drawn in the canvas' current style.
+    pub fn draw_on(&self, canvas: &mut Canvas, x: u32, y: u32) {
+        self.draw_glyphs_on(canvas, None, x, y);
+    }
+
+    /// Draw the sprite onto a canvas, skipping over (unstyled) spaces. See
+    /// [`Sprite::draw_transparent`] and [`Sprite::draw_on`].
+    pub fn draw_transparent_on(&self, canvas: &mut Canvas, blank: char, x: u32, y: u32) {
+        self.draw_glyphs_on(canvas, Some(blank), x, y);
+    }
+}
+
+impl Glyph {
+    /// Returns a copy of the glyph with its character replaced, if it's a
+    /// single character that `map` has a replacement for.
+    fn remap(&self, map: &impl Fn(char) -> Option<char>) -> Self {
+        let mut chars = self.text.chars();
+        let text = match (chars.next().and_then(map), chars.next()) {
+            (Some(c), None) => c.to_string(),
+            _ => self.text.clone(),
+        };
+
+        Self {
+            text,
+            ..self.clone()
+        }
+    }
+}
+
+/// Pairs of characters that are mirror images of each other left to right.
+#[rustfmt::skip]
+const FLIP_H: &[(char, char)] = &[
+    ('/', '\\'), ('(', ')'), ('[', ']'), ('{', '}'), ('<', '>'),
+    ('┌', '┐'), ('└', '┘'), ('├', '┤'), ('╭', '╮'), ('╰', '╯'),
+    ('┏', '┓'), ('┗', '┛'), ('┣', '┫'), ('╔', '╗'), ('╚', '╝'), ('╠', '╣'),
+    ('▌', '▐'), ('╱', '╲'),
+];
+
+/// Pairs of characters that are mirror images of each other top to bottom.
+#[rustfmt::skip]
+const FLIP_V: &[(char, char)] = &[
+    ('/', '\\'), ('^', 'v'), ('_', '‾'),
+    ('┌', '└'), ('┐', '┘'), ('┬', '┴'), ('╭', '╰'), ('╮', '╯'),
+    ('┏', '┗'), ('┓', '┛'), ('┳', '┻'), ('╔', '╚'), ('╗', '╝'), ('╦', '╩'),
+    ('▀', '▄'), ('╱', '╲'),
+];
+
+/// Characters that turn into each other, in order, when turned 90°
+/// clockwise.
+#[rustfmt::skip]
+const ROTATE: &[[char; 4]] = &[
+    ['-', '|', '-', '|'], ['─', '│', '─', '│'], ['━', '┃', '━', '┃'],
+    ['═', '║', '═', '║'], ['/', '\\', '/', '\\'], ['╱', '╲', '╱', '╲'],
+    ['^', '>', 'v', '<'], ['▀', '▐', '▄', '▌'],
+    ['┌', '┐', '┘', '└'], ['├', '┬', '┤', '┴'], ['╭', '╮', '╯', '╰'],
+    ['┏', '┓', '┛', '┗'], ['┣', '┳', '┫', '┻'],
+    ['╔', '╗', '╝', '╚'], ['╠', '╦', '╣', '╩'],
+];
+
+/// Returns a function swapping each character in `pairs` for the other.
+fn swap(pairs: &[(char, char)]) -> impl Fn(char) -> Option<char> + '_ {
+    move |c| {
+        pairs.iter().find_map(|&(a, b)| match c {
+            _ if c == a => Some(b),
+            _ if c == b => Some(a),
+            _ => None,
+        })
+    }
+}
+
+/// Returns what a character turns into when turned 90° clockwise, if it
+/// changes.
+fn rotate(c: char) -> Option<char> {
+    ROTATE.iter().find_map(|turns| {
+        turns
+            .iter()
+            .position(|&t| t == c)
+            .map(|i| turns[(i + 1) % 4])
+    })
+}
+
+/// Returns true if a grapheme cluster is just the given character.
+fn is_char(g: &str, c: char) -> bool {
+    let mut chars = g.chars();
+    chars.next() == Some(c) && chars.next().is_none()
+}
+
+impl fmt::Display for Sprite {
+    /// Writes the sprite back out as a texture.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.rows.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for glyph in row {
+                f.write_str(&glyph.text)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A texture had a character that can't be drawn in a sprite, or a color
+/// map had one that isn't in its palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidSprite {
+    /// The character.
+    pub c: char,
+    /// The column it was in.
+    pub x: u32,
+    /// The row it was in.
+    pub y: u32,
+}
+
+impl fmt::Display for InvalidSprite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "can't use {:?} in a sprite (row {}, column {})",
+            self.c, self.y, self.x
+        )
+    }
+}
+
+impl std::error::Error for InvalidSprite {}
+
+/// An error encountered while loading a sprite.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file couldn't be read.
+    Io(io::Error),
+    /// The file's contents weren't a valid sprite.
+    Invalid(InvalidSprite),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read sprite: {e}"),
+            Self::Invalid(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Invalid(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<InvalidSprite> for LoadError {
+    fn from(e: InvalidSprite) -> Self {
+        Self::Invalid(e)
+    }
+}
@@ -0,0 +1,166 @@
+//! A simple profiler for cod's drawing functions.
+//!
+//! Only enabled on feature `stats`.
+//!
+//! While enabled, every drawing function records, under the name of the
+//! module it's in:
+//! - that it was called
+//! - how many cells it drew
+//! - how many bytes of escape sequences it emitted
+//! - how long it took
+//!
+//! Only the outermost call is counted, so e.g. [`crate::rect::line`] counts
+//! as a single call to `cod::rect`, rather than as four calls to
+//! [`crate::orth_line`] as well. Frames are delimited by [`crate::flush`] (or
+//! [`end_frame`]), and [`report`] summarizes everything recorded so far.
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The name used for output emitted outside of any drawing function.
+const UNSCOPED: &str = "(other)";
+
+static STATS: Mutex<Stats> = Mutex::new(Stats {
+    frames: 0,
+    modules: BTreeMap::new(),
+});
+
+thread_local! {
+    static CURRENT: Cell<Option<&'static str>> = const { Cell::new(None) };
+}
+
+struct Stats {
+    frames: u64,
+    modules: BTreeMap<&'static str, ModuleStats>,
+}
+
+/// Statistics recorded for a single module.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ModuleStats {
+    /// The number of drawing functions called.
+    pub calls: u64,
+    /// The number of cells drawn.
+    pub cells: u64,
+    /// The number of bytes of escape sequences emitted.
+    pub escape_bytes: u64,
+    /// The time spent in drawing functions.
+    pub time: Duration,
+}
+
+/// A summary of everything recorded since the last [`reset`].
+///
+/// Displays as a table, with times averaged per frame.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// The number of frames completed.
+    pub frames: u64,
+    /// Statistics for each module, sorted by module name.
+    pub modules: Vec<(&'static str, ModuleStats)>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<16} {:>10} {:>10} {:>12} {:>14}",
+            "module", "calls", "cells", "escape bytes", "time/frame"
+        )?;
+
+        let frames = u32::try_from(self.frames.max(1)).unwrap_or(u32::MAX);
+        for (name, m) in &self.modules {
+            writeln!(
+                f,
+                "{:<16} {:>10} {:>10} {:>12} {:>14}",
+                name,
+                m.calls,
+                m.cells,
+                m.escape_bytes,
+                format!("{:.1?}", m.time / frames),
+            )?;
+        }
+
+        write!(f, "{} frame(s)", self.frames)
+    }
+}
+
+fn record(f: impl FnOnce(&mut ModuleStats)) {
+    let module = CURRENT.with(Cell::get).unwrap_or(UNSCOPED);
+    f(STATS.lock().unwrap().modules.entry(module).or_default());
+}
+
+/// Times a drawing function, attributing everything it does to its module.
+/// Nested scopes are ignored.
+pub(crate) struct Scope {
+    start: Option<Instant>,
+}
+
+impl Scope {
+    pub(crate) fn enter(module: &'static str) -> Self {
+        if CURRENT.with(Cell::get).is_some() {
+            return Self { start: None };
+        }
+
+        CURRENT.with(|c| c.set(Some(module)));
+        record(|m| m.calls += 1);
+        Self {
+            start: Some(Instant::now()),
+        }
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            let elapsed = start.elapsed();
+            record(|m| m.time += elapsed);
+            CURRENT.with(|c| c.set(None));
+        }
+    }
+}
+
+/// Records cells drawn by the current drawing function.
+pub(crate) fn cells(n: u64) {
+    record(|m| m.cells += n);
+}
+
+/// Records escape sequence bytes emitted by the current drawing function.
+pub(crate) fn escape_bytes(n: usize) {
+    record(|m| m.escape_bytes += n as u64);
+}
+
+/// Mark the end of a frame. Called automatically by [`crate::flush`].
+///
+/// # Panics
+///
+/// If another thread panicked while recording, panics.
+pub fn end_frame() {
+    STATS.lock().unwrap().frames += 1;
+}
+
+/// Returns a summary of everything recorded since the last [`reset`].
+///
+/// # Panics
+///
+/// If another thread panicked while recording, panics.
+#[must_use]
+pub fn report() -> Report {
+    let stats = STATS.lock().unwrap();
+    Report {
+        frames: stats.frames,
+        modules: stats.modules.iter().map(|(&k, &v)| (k, v)).collect(),
+    }
+}
+
+/// Discard everything recorded so far.
+///
+/// # Panics
+///
+/// If another thread panicked while recording, panics.
+pub fn reset() {
+    let mut stats = STATS.lock().unwrap();
+    stats.frames = 0;
+    stats.modules.clear();
+}
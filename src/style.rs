@@ -4,6 +4,7 @@
 //! [`de::weight`] resets both simultaneously. This behavior extends to
 //! [`with::bold`] and [`with::faint`].
 
+use crate::color::{self, Color};
 use crate::escape;
 
 macro_rules! do_style {
@@ -12,6 +13,7 @@ macro_rules! do_style {
             /// Enable
             #[doc = concat!(stringify!($style), ".")]
             pub fn $style() {
+                profile!();
                 escape(concat!(stringify!($code), "m"));
             }
         )+
@@ -30,12 +32,14 @@ pub mod de {
                 /// Disable
                 #[doc = concat!(stringify!($style), ".")]
                 pub fn $style() {
+                    profile!();
                     escape(concat!(stringify!($code), "m"));
                 }
             )+
 
             /// Disable all style attributes.
             pub fn all() {
+                profile!();
                 $($style();)+
                 weight();
             }
@@ -48,6 +52,7 @@ pub mod de {
     ///
     /// See module documentation for why.
     pub fn weight() {
+        profile!();
         escape("22m");
     }
 }
@@ -89,3 +94,422 @@ pub mod with {
         strike: strike
     ];
 }
+
+/// A combination of colors and style attributes, e.g. for a
+/// [span](crate::spans) of text.
+///
+/// `None` colors leave the current color alone.
+///
+/// Displays as the escape sequence that [applies](Style::apply) it, or
+/// nothing if it doesn't change anything.
+///
+/// ```rust
+/// # use cod::style::Style;
+/// # cod::compat::set_enabled(false);
+/// let heading = Style::new().fg(12).bold().underline();
+/// assert_eq!(heading.to_string(), "\x1b[1;4;38;5;12m");
+/// assert_ne!(heading, Style::new().fg(12).bold());
+/// assert_eq!(Style::new().to_string(), "");
+///
+/// heading.apply();
+/// cod::text("Results", 0, 0);
+/// cod::normal();
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Style {
+    /// The foreground color.
+    pub fg: Option<Color>,
+    /// The background color.
+    pub bg: Option<Color>,
+    /// Whether the text is bold.
+    pub bold: bool,
+    /// Whether the text is faint.
+    pub faint: bool,
+    /// Whether the text is italic.
+    pub italic: bool,
+    /// Whether the text is underlined.
+    pub underline: bool,
+    /// Whether the text is struck through.
+    pub strike: bool,
+}
+
+impl Style {
+    /// Create a style that changes nothing.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            fg: None,
+            bg: None,
+            bold: false,
+            faint: false,
+            italic: false,
+            underline: false,
+            strike: false,
+        }
+    }
+
+    /// Set the foreground color.
+    #[must_use]
+    pub fn fg<C: Into<Color>>(mut self, color: C) -> Self {
+        self.fg = Some(color.into());
+        self
+    }
+
+    /// Set the background color.
+    #[must_use]
+    pub fn bg<C: Into<Color>>(mut self, color: C) -> Self {
+        self.bg = Some(color.into());
+        self
+    }
+
+    /// Make the text bold.
+    #[must_use]
+    pub const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    /// Make the text faint.
+    #[must_use]
+    pub const fn faint(mut self) -> Self {
+        self.faint = true;
+        self
+    }
+
+    /// Make the text italic.
+    #[must_use]
+    pub const fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    /// Underline the text.
+    #[must_use]
+    pub const fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    /// Strike through the text.
+    #[must_use]
+    pub const fn strike(mut self) -> Self {
+        self.strike = true;
+        self
+    }
+
+    /// Returns the SGR parameters for everything the style sets, separated
+    /// by `;`, e.g. `1;38;5;196`.
+    pub(crate) fn sgr(&self) -> String {
+        // the background goes first, so high-contrast mode can pick a
+        // foreground to go with it
+        let bg = self.bg.and_then(|c| color::raw::code(c, true));
+        let fg = self.fg.and_then(|c| color::raw::code(c, false));
+
+        let attrs = [
+            (self.bold, "1"),
+            (self.faint, "2"),
+            (self.italic, "3"),
+            (self.underline, "4"),
+            (self.strike, "9"),
+        ];
+        let attrs = attrs
+            .into_iter()
+            .filter(|&(enabled, _)| enabled)
+            .map(|(_, code)| code.to_string());
+
+        attrs.chain(fg).chain(bg).collect::<Vec<_>>().join(";")
+    }
+
+    /// Set everything the style sets, on top of the current style, with a
+    /// single escape sequence.
+    ///
+    /// To undo it, use [`crate::normal`], or draw with
+    /// [`crate::pixel_styled`] and friends, which reset afterwards.
+    pub fn apply(&self) {
+        profile!();
+        let sgr = self.sgr();
+        if !sgr.is_empty() {
+            escape(format_args!("{sgr}m"));
+        }
+    }
+}
+
+/// Changes to make to a [`Style`], where anything left as `None` is kept as
+/// it was. Unlike a style, an override can turn attributes off.
+///
+/// Overrides are layered over a style to get the final look of something:
+/// [`crate::palette::resolve`] starts from a widget's default style, layers
+/// the theme's override for it on top, then the widget's own, so one-off
+/// tweaks don't need a whole new theme.
+///
+/// ```rust
+/// # use cod::style::{Style, StyleOverride};
+/// let base = Style::new().fg(1).bold();
+/// let tweak = StyleOverride::new().bg(4).bold(false);
+/// assert_eq!(tweak.over(base), Style::new().fg(1).bg(4));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StyleOverride {
+    /// The foreground color.
+    pub fg: Option<Color>,
+    /// The background color.
+    pub bg: Option<Color>,
+    /// Whether the text is bold.
+    pub bold: Option<bool>,
+    /// Whether the text is faint.
+    pub faint: Option<bool>,
+    /// Whether the text is italic.
+    pub italic: Option<bool>,
+    /// Whether the text is underlined.
+    pub underline: Option<bool>,
+    /// Whether the text is struck through.
+    pub strike: Option<bool>,
+}
+
+impl StyleOverride {
+    /// Create an override that changes nothing.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            fg: None,
+            bg: None,
+            bold: None,
+            faint: None,
+            italic: None,
+            underline: None,
+            strike: None,
+        }
+    }
+
+    /// Override the foreground color.
+    #[must_use]
+    pub fn fg<C: Into<Color>>(mut self, color: C) -> Self {
+        self.fg = Some(color.into());
+        self
+    }
+
+    /// Override the background color.
+    #[must_use]
+    pub fn bg<C: Into<Color>>(mut self, color: C) -> Self {
+        self.bg = Some(color.into());
+        self
+    }
+
+    /// Turn bold on or off.
+    #[must_use]
+    pub const fn bold(mut self, bold: bool) -> Self {
+        self.bold = Some(bold);
+        self
+    }
+
+    /// Turn faint on or off.
+    #[must_use]
+    pub const fn faint(mut self, faint: bool) -> Self {
+        self.faint = Some(faint);
+        self
+    }
+
+    /// Turn italics on or off.
+    #[must_use]
+    pub const fn italic(mut self, italic: bool) -> Self {
+        self.italic = Some(italic);
+        self
+    }
+
+    /// Turn underlining on or off.
+    #[must_use]
+    pub const fn underline(mut self, underline: bool) -> Self {
+        self.underline = Some(underline);
+        self
+    }
+
+    /// Turn striking through on or off.
+    #[must_use]
+    pub const fn strike(mut self, strike: bool) -> Self {
+        self.strike = Some(strike);
+        self
+    }
+
+    /// Returns true if the override doesn't change anything.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::new()
+    }
+
+    /// Returns a style with everything the override sets changed.
+    #[must_use]
+    pub fn over(&self, base: Style) -> Style {
+        Style {
+            fg: self.fg.or(base.fg),
+            bg: self.bg.or(base.bg),
+            bold: self.bold.unwrap_or(base.bold),
+            faint: self.faint.unwrap_or(base.faint),
+            italic: self.italic.unwrap_or(base.italic),
+            underline: self.underline.unwrap_or(base.underline),
+            strike: self.strike.unwrap_or(base.strike),
+        }
+    }
+
+    /// Returns an override that makes this one's changes, then `other`'s,
+    /// so `other` wins wherever both set something.
+    #[must_use]
+    pub fn then(&self, other: &Self) -> Self {
+        Self {
+            fg: other.fg.or(self.fg),
+            bg: other.bg.or(self.bg),
+            bold: other.bold.or(self.bold),
+            faint: other.faint.or(self.faint),
+            italic: other.italic.or(self.italic),
+            underline: other.underline.or(self.underline),
+            strike: other.strike.or(self.strike),
+        }
+    }
+}
+
+impl From<Style> for StyleOverride {
+    /// An override setting everything the style sets. Attributes the style
+    /// doesn't turn on are left alone, not turned off.
+    fn from(style: Style) -> Self {
+        let on = |enabled: bool| enabled.then_some(true);
+        Self {
+            fg: style.fg,
+            bg: style.bg,
+            bold: on(style.bold),
+            faint: on(style.faint),
+            italic: on(style.italic),
+            underline: on(style.underline),
+            strike: on(style.strike),
+        }
+    }
+}
+
+/// Returns the SGR parameters switching the terminal from one style to
+/// another, or an empty string if they look the same. Colors left as `None`
+/// are the current colors (see [`color`]).
+fn transition(from: Style, to: Style) -> String {
+    let (fg, bg) = color::current();
+    let foreground = (from.fg.or(fg), to.fg.or(fg));
+    let background = (from.bg.or(bg), to.bg.or(bg));
+
+    // bold and faint can only be turned off together
+    let weight_off = (from.bold && !to.bold) || (from.faint && !to.faint);
+    let toggles = [
+        (weight_off, "22"),
+        (to.bold && (weight_off || !from.bold), "1"),
+        (to.faint && (weight_off || !from.faint), "2"),
+        (from.italic != to.italic, if to.italic { "3" } else { "23" }),
+        (
+            from.underline != to.underline,
+            if to.underline { "4" } else { "24" },
+        ),
+        (from.strike != to.strike, if to.strike { "9" } else { "29" }),
+    ];
+    let attrs = toggles
+        .into_iter()
+        .filter(|&(toggled, _)| toggled)
+        .map(|(_, code)| code.to_string());
+
+    // the background goes first, so high-contrast mode can pick a
+    // foreground to go with it
+    let bg_changed = background.0 != background.1;
+    let bg = bg_changed
+        .then(|| {
+            if let Some(c) = background.1 {
+                color::raw::code(c, true)
+            } else {
+                crate::a11y::reset_background();
+                Some("49".to_string())
+            }
+        })
+        .flatten();
+    let fg_changed =
+        foreground.0 != foreground.1 || (bg_changed && crate::a11y::is_high_contrast());
+    let fg = fg_changed
+        .then(|| {
+            foreground
+                .1
+                .map_or_else(|| Some("39".to_string()), |c| color::raw::code(c, false))
+        })
+        .flatten();
+
+    attrs.chain(fg).chain(bg).collect::<Vec<_>>().join(";")
+}
+
+/// Keeps track of the style set on the terminal, so that switching to
+/// another only emits what's different, e.g. turning off italics without
+/// touching the colors.
+///
+/// Drawing many differently-styled pieces in a row (like cells of a
+/// [canvas](crate::canvas::Canvas)) with a tracker sends a fraction of what
+/// applying and resetting each style would.
+///
+/// Example:
+///
+/// ```
+/// # use cod::prelude::*;
+/// # compat::set_enabled(false);
+/// use cod::color::Color;
+/// use cod::style::{Style, Tracker};
+///
+/// let mut tracker = Tracker::new();
+/// let output = target::capture(|| {
+///     tracker.set(Style::new().fg(Color::Red).bold());
+///     cod::text("error:", 0, 0);
+///     tracker.set(Style::new().fg(Color::Red));
+///     cod::text(" disk full", 6, 0);
+///     tracker.reset();
+/// });
+///
+/// assert!(output.starts_with("\x1b[1;38;5;1m"));
+/// assert!(output.contains("\x1b[22m"));
+/// assert!(output.ends_with("\x1b[39m"));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tracker {
+    current: Style,
+}
+
+impl Tracker {
+    /// Create a tracker, assuming that no style is set on the terminal.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            current: Style::new(),
+        }
+    }
+
+    /// Returns the style set on the terminal.
+    #[must_use]
+    pub fn current(&self) -> Style {
+        self.current
+    }
+
+    /// Switch to a style, emitting only what changed, in a single escape
+    /// sequence.
+    pub fn set(&mut self, style: Style) {
+        profile!();
+        let sgr = transition(self.current, style);
+        if !sgr.is_empty() {
+            escape(format_args!("{sgr}m"));
+        }
+        self.current = style;
+    }
+
+    /// Switch back to no style, i.e. the current colors (see [`color`]) and
+    /// no attributes.
+    pub fn reset(&mut self) {
+        self.set(Style::new());
+    }
+}
+
+impl std::fmt::Display for Style {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sgr = self.sgr();
+        if sgr.is_empty() {
+            Ok(())
+        } else {
+            write!(f, "\x1b[{sgr}m")
+        }
+    }
+}
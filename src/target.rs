@@ -0,0 +1,532 @@
+//! Choosing where cod's output goes.
+//!
+//! By default, everything is printed to stdout. For benchmarking your drawing
+//! code without the noise of terminal I/O, use [`Target::Null`] or
+//! [`Target::Skip`].
+//!
+//! To figure out why a frame rendered wrong, use [`Target::debug`], which
+//! writes a human-readable description of each escape sequence instead of
+//! the sequence itself, e.g. `[goto 5,3][fg 196]"X"`.
+//!
+//! To draw somewhere else entirely, like a file, stderr, or a network
+//! connection, use [`Target::writer`]. To collect output in memory instead,
+//! use [`capture`].
+//!
+//! To send the same output to several places at once, like the terminal and
+//! a log file, use [`Target::fanout`]. Each sink gets its own color depth,
+//! so a recording can keep true-color while a plain log leaves colors out.
+//!
+//! Example:
+//!
+//! ```
+//! # use cod::target::{self, Target};
+//! target::with(Target::Null, || {
+//!     // formatted as usual, but never printed
+//!     cod::rect::ascii(0, 0, 10, 5).unwrap();
+//! });
+//! ```
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+use crate::color::{Color, ColorDepth};
+
+static TARGET: Mutex<Target> = Mutex::new(Target::Stdout);
+
+/// The first error from writing to a [`Target::Writer`] since the last call
+/// to [`take_error`].
+static ERROR: Mutex<Option<io::Error>> = Mutex::new(None);
+
+thread_local! {
+    /// Output captured on this thread by [`capture`], if any.
+    static CAPTURE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Where cod's output goes, set with [`set`].
+#[derive(Default)]
+pub enum Target {
+    /// Print to stdout.
+    #[default]
+    Stdout,
+    /// Format everything as usual, but discard the result.
+    Null,
+    /// Discard everything without even formatting it.
+    Skip,
+    /// Describe escape sequences in a human-readable form, and write that to
+    /// the given sink. Text is written in quotes.
+    Debug(Box<dyn Write + Send>),
+    /// Write everything to the given sink, exactly as it would be printed to
+    /// stdout.
+    ///
+    /// Errors are kept until they're checked with [`take_error`], rather
+    /// than panicking.
+    Writer(Box<dyn Write + Send>),
+    /// Send everything to each of the given targets, in order. Colors are
+    /// downgraded to each target's depth (see [`ColorDepth`]) on the way,
+    /// after being formatted at the global depth (see
+    /// [`crate::color::set_depth`]).
+    Fanout(Vec<(Target, ColorDepth)>),
+}
+
+impl Target {
+    /// Create a [`Target::Debug`] writing to the given sink.
+    pub fn debug<W: Write + Send + 'static>(sink: W) -> Self {
+        Self::Debug(Box::new(sink))
+    }
+
+    /// Create a [`Target::Writer`] writing to the given sink.
+    pub fn writer<W: Write + Send + 'static>(sink: W) -> Self {
+        Self::Writer(Box::new(sink))
+    }
+
+    /// Create a [`Target::Fanout`] sending everything to each of the given
+    /// targets, downgrading colors to each one's depth.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// # use cod::prelude::*;
+    /// # use std::sync::{Arc, Mutex};
+    /// # #[derive(Clone, Default)]
+    /// # struct Shared(Arc<Mutex<Vec<u8>>>);
+    /// # impl std::io::Write for Shared {
+    /// #     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    /// #         self.0.lock().unwrap().write(buf)
+    /// #     }
+    /// #     fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+    /// # }
+    /// use cod::color::ColorDepth;
+    /// use cod::target::{self, Target};
+    /// # compat::set_enabled(false);
+    ///
+    /// let (recording, log) = (Shared::default(), Shared::default());
+    /// let fanout = Target::fanout([
+    ///     (Target::writer(recording.clone()), ColorDepth::TrueColor),
+    ///     (Target::writer(log.clone()), ColorDepth::NoColor),
+    /// ]);
+    ///
+    /// target::with(fanout, || {
+    ///     color::tc_fg(255, 0, 0);
+    ///     cod::pixel('X', 0, 0);
+    ///     color::de::fg();
+    /// });
+    ///
+    /// let recording = String::from_utf8(recording.0.lock().unwrap().clone()).unwrap();
+    /// let log = String::from_utf8(log.0.lock().unwrap().clone()).unwrap();
+    /// assert_eq!(recording, "\x1b[38;2;255;0;0m\x1b[1;1HX\x1b[39m");
+    /// assert_eq!(log, "\x1b[1;1HX");
+    /// ```
+    pub fn fanout<I: IntoIterator<Item = (Target, ColorDepth)>>(targets: I) -> Self {
+        Self::Fanout(targets.into_iter().collect())
+    }
+
+    /// Returns true if this prints to stdout, on its own or as part of a
+    /// [`Target::Fanout`].
+    fn includes_stdout(&self) -> bool {
+        match self {
+            Self::Stdout => true,
+            Self::Fanout(targets) => targets.iter().any(|(t, _)| t.includes_stdout()),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Debug for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stdout => write!(f, "Stdout"),
+            Self::Null => write!(f, "Null"),
+            Self::Skip => write!(f, "Skip"),
+            Self::Debug(_) => write!(f, "Debug(..)"),
+            Self::Writer(_) => write!(f, "Writer(..)"),
+            Self::Fanout(targets) => f.debug_tuple("Fanout").field(targets).finish(),
+        }
+    }
+}
+
+/// Send all output from now on to a new target, returning the previous one.
+///
+/// # Panics
+///
+/// If another thread panicked while writing output, panics.
+pub fn set(target: Target) -> Target {
+    std::mem::replace(&mut *TARGET.lock().unwrap(), target)
+}
+
+/// Send all output to a target while running a function, then restore the
+/// previous target, even if the function panics.
+///
+/// ```
+/// # use cod::target::{self, Target};
+/// # std::panic::set_hook(Box::new(|_| {}));
+/// let result = std::panic::catch_unwind(|| target::with(Target::Skip, || panic!("oops")));
+/// assert!(result.is_err());
+/// assert!(matches!(target::set(Target::Stdout), Target::Stdout));
+/// ```
+///
+/// # Panics
+///
+/// If another thread panicked while writing output, panics.
+pub fn with<T>(target: Target, f: impl FnOnce() -> T) -> T {
+    /// Puts the previous target back when dropped, even while panicking.
+    struct Restore(Option<Target>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            if let Some(previous) = self.0.take() {
+                *TARGET
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner) = previous;
+            }
+        }
+    }
+
+    let _restore = Restore(Some(set(target)));
+    (f)()
+}
+
+/// Write to the current target.
+///
+/// # Panics
+///
+/// If writing to stdout fails, panics.
+pub(crate) fn write(args: fmt::Arguments) {
+    let captured = CAPTURE.with(|c| match &mut *c.borrow_mut() {
+        Some(buf) => {
+            let _ = fmt::Write::write_fmt(buf, args);
+            true
+        }
+        None => false,
+    });
+    if captured {
+        return;
+    }
+
+    write_to(&mut TARGET.lock().unwrap(), args);
+}
+
+/// Write to a specific target.
+fn write_to(target: &mut Target, args: fmt::Arguments) {
+    match target {
+        Target::Stdout => io::stdout()
+            .write_fmt(args)
+            .expect("failed to write to stdout"),
+        Target::Null => {
+            let _ = io::sink().write_fmt(args);
+        }
+        Target::Skip => {}
+        Target::Debug(sink) => {
+            let _ = sink.write_all(annotate(&args.to_string()).as_bytes());
+        }
+        Target::Writer(sink) => {
+            if let Err(e) = sink.write_fmt(args) {
+                record_error(e);
+            }
+        }
+        Target::Fanout(targets) => {
+            let output = args.to_string();
+            for (target, depth) in targets {
+                write_to(target, format_args!("{}", downgrade(&output, *depth)));
+            }
+        }
+    }
+}
+
+/// Remember an error from a [`Target::Writer`], unless there's one already.
+fn record_error(e: io::Error) {
+    ERROR.lock().unwrap().get_or_insert(e);
+}
+
+/// Returns the first error from writing to a [`Target::Writer`] since this
+/// was last called, if any, and forgets it.
+///
+/// # Panics
+///
+/// If another thread panicked while checking for errors, panics.
+pub fn take_error() -> Option<io::Error> {
+    ERROR.lock().unwrap().take()
+}
+
+/// Run a function, collecting everything it outputs on this thread instead
+/// of writing it to the target.
+///
+/// If the function panics, whatever it output is dropped, and output is
+/// written as before again.
+///
+/// Example:
+///
+/// ```
+/// # use cod::target;
+/// let output = target::capture(|| cod::pixel('X', 4, 2));
+/// assert_eq!(output, "\x1b[3;5HX");
+///
+/// # std::panic::set_hook(Box::new(|_| {}));
+/// let output = target::capture(|| {
+///     let _ = std::panic::catch_unwind(|| {
+///         target::capture(|| {
+///             cod::pixel('a', 0, 0);
+///             panic!("oops");
+///         })
+///     });
+///     cod::pixel('X', 4, 2);
+/// });
+/// assert_eq!(output, "\x1b[3;5HX");
+/// ```
+pub fn capture(f: impl FnOnce()) -> String {
+    /// Puts the previous capture back when dropped, even while panicking.
+    struct Restore(Option<String>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            let _ = CAPTURE.try_with(|c| c.replace(previous));
+        }
+    }
+
+    let restore = Restore(CAPTURE.with(|c| c.replace(Some(String::new()))));
+    (f)();
+    let output = CAPTURE.with(RefCell::take).unwrap_or_default();
+    drop(restore);
+    output
+}
+
+/// Returns true if output is going to stdout, possibly among other places
+/// (and isn't being captured).
+pub(crate) fn is_stdout() -> bool {
+    CAPTURE.with(|c| c.borrow().is_none()) && TARGET.lock().unwrap().includes_stdout()
+}
+
+/// Flush the current target.
+///
+/// # Panics
+///
+/// If flushing stdout fails, panics.
+pub(crate) fn flush() {
+    flush_target(&mut TARGET.lock().unwrap());
+}
+
+/// Flush a specific target.
+fn flush_target(target: &mut Target) {
+    match target {
+        Target::Stdout => io::stdout().flush().expect("Failed to flush stdout"),
+        Target::Null | Target::Skip => {}
+        Target::Debug(sink) => {
+            let _ = sink.write_all(b"[flush]\n");
+            let _ = sink.flush();
+        }
+        Target::Writer(sink) => {
+            if let Err(e) = sink.flush() {
+                record_error(e);
+            }
+        }
+        Target::Fanout(targets) => {
+            for (target, _) in targets {
+                flush_target(target);
+            }
+        }
+    }
+}
+
+/// Re-render the colors in some output for a lower color depth, leaving
+/// everything else as it is.
+fn downgrade(output: &str, depth: ColorDepth) -> Cow<'_, str> {
+    if depth == ColorDepth::TrueColor || !output.contains("\x1b[") {
+        return Cow::Borrowed(output);
+    }
+
+    let mut downgraded = String::with_capacity(output.len());
+    let mut rest = output;
+    while let Some(start) = rest.find("\x1b[") {
+        downgraded.push_str(&rest[..start]);
+        let seq = &rest[start + 2..];
+
+        let end = seq
+            .find(|c: char| !(' '..='?').contains(&c))
+            .unwrap_or(seq.len());
+        let (params, final_byte) = (&seq[..end], seq[end..].chars().next());
+        let len = end + final_byte.map_or(0, char::len_utf8);
+
+        if final_byte == Some('m') && !params.is_empty() {
+            let params = downgrade_sgr(params, depth);
+            // a sequence that only set colors has nothing left to say
+            if !params.is_empty() {
+                downgraded.push_str("\x1b[");
+                downgraded.push_str(&params);
+                downgraded.push('m');
+            }
+        } else {
+            downgraded.push_str(&rest[start..start + 2 + len]);
+        }
+        rest = &seq[len..];
+    }
+    downgraded.push_str(rest);
+
+    Cow::Owned(downgraded)
+}
+
+/// Re-render the colors in the parameters of a "select graphic rendition"
+/// sequence for a lower color depth.
+fn downgrade_sgr(params: &str, depth: ColorDepth) -> String {
+    let mut kept = Vec::new();
+    let mut nums = params.split(';').map(|p| p.trim().parse().unwrap_or(0u32));
+    while let Some(code) = nums.next() {
+        let color = match code {
+            38 | 48 => match nums.next() {
+                Some(5) => nums.next().map(|c| Color::Ansi(c.try_into().unwrap_or(0))),
+                Some(2) => {
+                    let mut next = || nums.next().map_or(0, |c| c.try_into().unwrap_or(u8::MAX));
+                    Some(Color::Rgb(next(), next(), next()))
+                }
+                _ => None,
+            },
+            30..=39 | 40..=49 | 90..=97 | 100..=107 if depth == ColorDepth::NoColor => continue,
+            _ => {
+                kept.push(code.to_string());
+                continue;
+            }
+        };
+
+        if let Some(code) = color.and_then(|c| crate::color::raw::code_at(c, code == 48, depth)) {
+            kept.push(code);
+        }
+    }
+
+    kept.join(";")
+}
+
+/// Describe the escape sequences in some output, quoting the text between
+/// them.
+fn annotate(output: &str) -> String {
+    use std::fmt::Write as _;
+
+    let mut annotated = String::new();
+    let mut rest = output;
+    while !rest.is_empty() {
+        let Some(start) = rest.find('\x1b') else {
+            let _ = write!(annotated, "{rest:?}");
+            break;
+        };
+
+        if start > 0 {
+            let _ = write!(annotated, "{:?}", &rest[..start]);
+        }
+        rest = &rest[start + 1..];
+
+        if let Some(osc) = rest.strip_prefix(']') {
+            let (command, after) = osc.split_once("\x1b\\").unwrap_or((osc, ""));
+            rest = after;
+            annotated.push_str(&describe_osc(command));
+            continue;
+        }
+
+        let Some(seq) = rest.strip_prefix('[') else {
+            annotated.push_str("[esc]");
+            continue;
+        };
+
+        // parameters and intermediates are in 0x20..=0x3f, then one final byte
+        let end = seq
+            .find(|c: char| !(' '..='?').contains(&c))
+            .unwrap_or(seq.len());
+        let (params, final_byte) = (&seq[..end], seq[end..].chars().next());
+        rest = &seq[(end + final_byte.map_or(0, char::len_utf8))..];
+
+        annotated.push_str(&describe(params, final_byte));
+    }
+
+    annotated
+}
+
+/// Describe a single control sequence.
+fn describe(params: &str, final_byte: Option<char>) -> String {
+    let nums: Vec<u32> = params
+        .split(';')
+        .filter_map(|p| p.trim().parse().ok())
+        .collect();
+    let n = nums.first().copied().unwrap_or(1);
+
+    match (params, final_byte) {
+        (_, Some('H')) => {
+            let y = nums.first().copied().unwrap_or(1);
+            let x = nums.get(1).copied().unwrap_or(1);
+            format!("[goto {},{}]", x.saturating_sub(1), y.saturating_sub(1))
+        }
+        (_, Some('A')) => format!("[up {n}]"),
+        (_, Some('B')) => format!("[down {n}]"),
+        (_, Some('C')) => format!("[right {n}]"),
+        (_, Some('D')) => format!("[left {n}]"),
+        ("", Some('G')) => "[start]".to_string(),
+        ("2", Some('J')) => "[clear all]".to_string(),
+        ("2", Some('K')) => "[clear line]".to_string(),
+        ("?1049", Some('h')) => "[secondary screen]".to_string(),
+        ("?1049", Some('l')) => "[primary screen]".to_string(),
+        (_, Some('q')) if params.ends_with(' ') => format!("[cursor style {n}]"),
+        (_, Some('t')) if nums.len() == 3 && nums[0] == 8 => {
+            format!("[resize {}x{}]", nums[2], nums[1])
+        }
+        (_, Some('m')) => describe_sgr(&nums),
+        _ => format!(
+            "[csi {params:?}{}]",
+            final_byte.map(String::from).unwrap_or_default()
+        ),
+    }
+}
+
+/// Describe an operating system command.
+fn describe_osc(command: &str) -> String {
+    match command
+        .strip_prefix("8;")
+        .and_then(|link| link.split_once(';'))
+    {
+        Some((_, "")) => "[unlink]".to_string(),
+        Some((_, url)) => format!("[link {url:?}]"),
+        None => format!("[osc {command:?}]"),
+    }
+}
+
+/// Describe a "select graphic rendition" sequence, i.e. colors and styles.
+fn describe_sgr(nums: &[u32]) -> String {
+    let mut described = String::new();
+    let mut nums = nums.iter().copied();
+    while let Some(code) = nums.next() {
+        let part = match code {
+            0 => "normal".to_string(),
+            1 => "bold".to_string(),
+            2 => "faint".to_string(),
+            3 => "italic".to_string(),
+            4 => "underline".to_string(),
+            9 => "strike".to_string(),
+            22 => "-weight".to_string(),
+            23 => "-italic".to_string(),
+            24 => "-underline".to_string(),
+            29 => "-strike".to_string(),
+            39 => "-fg".to_string(),
+            49 => "-bg".to_string(),
+            38 | 48 => {
+                let layer = if code == 38 { "fg" } else { "bg" };
+                match (nums.next(), nums.next()) {
+                    (Some(5), Some(c)) => format!("{layer} {c}"),
+                    (Some(2), Some(r)) => {
+                        let (g, b) = (nums.next().unwrap_or(0), nums.next().unwrap_or(0));
+                        format!("{layer} #{r:02x}{g:02x}{b:02x}")
+                    }
+                    _ => format!("{layer} ?"),
+                }
+            }
+            other => format!("sgr {other}"),
+        };
+
+        described.push('[');
+        described.push_str(&part);
+        described.push(']');
+    }
+
+    if described.is_empty() {
+        described.push_str("[normal]");
+    }
+
+    described
+}
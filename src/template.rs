@@ -0,0 +1,343 @@
+//! Screens laid out as text, with named regions to fill in at runtime.
+//!
+//! A [`Template`] is written the way the screen should look, with
+//! placeholders where the content goes:
+//!
+//! - `{name}` is a region one row high, as wide as the placeholder itself.
+//! - `{name:20x5}` is a region 20 columns wide and 5 rows high, with its
+//!   top-left corner where the placeholder starts. Leave room for it below.
+//!
+//! Write `{{` and `}}` for literal braces. Everything else is drawn as-is.
+//!
+//! Regions are filled with [`Spans`], so content can be styled, and is
+//! wrapped to fit; anything that doesn't fit is cut off.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! use cod::rect::Rect;
+//! use cod::spans::Spans;
+//! use cod::style::Style;
+//! use cod::template::Template;
+//!
+//! let mut screen = Template::new(
+//!     "\
+//! +--------------------------+
+//! | {title}                  |
+//! +--------------------------+
+//! | {body:24x3}              |
+//! |                          |
+//! |                          |
+//! +--------------------------+",
+//! )
+//! .unwrap();
+//! assert_eq!(screen.region("body"), Some(Rect::new(2, 3, 24, 3)));
+//!
+//! let mut title = Spans::new();
+//! title.push("Inbox", Style::new().bold());
+//!
+//! screen
+//!     .set("title", title)
+//!     .set("body", "No new messages. Check back later!");
+//! screen.draw(0, 0);
+//! cod::flush();
+//! ```
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::canvas::Canvas;
+use crate::rect::Rect;
+use crate::spans::{Overflow, Spans};
+use crate::width::str_width;
+
+/// A named region of a template, and what's been put in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Region {
+    name: String,
+    rect: Rect,
+    content: Spans,
+}
+
+/// A screen laid out as text, with regions to fill in. See the
+/// [module docs](self).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Template {
+    /// The text drawn around the regions, with placeholders blanked out.
+    lines: Vec<String>,
+    regions: Vec<Region>,
+}
+
+impl Template {
+    /// Parse a template. See the [module docs](self) for the syntax.
+    ///
+    /// # Errors
+    ///
+    /// If a placeholder isn't closed, has no name or an invalid size, or
+    /// uses a name already taken, returns an error.
+    pub fn new<S: AsRef<str>>(template: S) -> Result<Self, ParseError> {
+        let mut lines = Vec::new();
+        let mut regions: Vec<Region> = Vec::new();
+
+        for (y, source) in (0..).zip(template.as_ref().lines()) {
+            let mut line = String::new();
+            let mut chars = source.chars().peekable();
+            while let Some(c) = chars.next() {
+                let x = u32::try_from(str_width(&line)).unwrap_or(u32::MAX);
+                match c {
+                    '{' if chars.next_if_eq(&'{').is_some() => line.push('{'),
+                    '}' if chars.next_if_eq(&'}').is_some() => line.push('}'),
+                    '{' => {
+                        let mut placeholder = String::new();
+                        loop {
+                            match chars.next() {
+                                Some('}') => break,
+                                Some(c) => placeholder.push(c),
+                                None => return Err(ParseError::Unclosed { x, y }),
+                            }
+                        }
+
+                        let width = u32::try_from(str_width(&placeholder)).unwrap_or(0) + 2;
+                        let region = parse_placeholder(&placeholder, x, y, width)?;
+                        if regions.iter().any(|r| r.name == region.name) {
+                            return Err(ParseError::Duplicate(region.name));
+                        }
+
+                        line.extend(std::iter::repeat_n(' ', width as usize));
+                        regions.push(region);
+                    }
+                    c => line.push(c),
+                }
+            }
+
+            lines.push(line);
+        }
+
+        Ok(Self { lines, regions })
+    }
+
+    /// Load a template from a file. See [`Template::new`].
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read or isn't a valid template, returns an error.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
+        Ok(Self::new(std::fs::read_to_string(path)?)?)
+    }
+
+    /// Returns the width of the template, i.e. of its widest line.
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        let widest = self.lines.iter().map(|line| str_width(line)).max();
+        u32::try_from(widest.unwrap_or(0)).unwrap_or(u32::MAX)
+    }
+
+    /// Returns the number of lines in the template.
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        u32::try_from(self.lines.len()).unwrap_or(u32::MAX)
+    }
+
+    /// Returns the names of the regions, in the order they appear.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.regions.iter().map(|region| region.name.as_str())
+    }
+
+    /// Returns where a region is, relative to the top-left corner of the
+    /// template, or `None` if there's no region by that name. Useful for
+    /// drawing things other than text there.
+    #[must_use]
+    pub fn region(&self, name: &str) -> Option<Rect> {
+        self.regions
+            .iter()
+            .find(|region| region.name == name)
+            .map(|region| region.rect)
+    }
+
+    /// Set what's drawn in a region, replacing what was there. Names that
+    /// aren't in the template are ignored.
+    pub fn set<S: Into<Spans>>(&mut self, name: &str, content: S) -> &mut Self {
+        if let Some(region) = self.regions.iter_mut().find(|region| region.name == name) {
+            region.content = content.into();
+        }
+        self
+    }
+
+    /// Empty every region.
+    pub fn clear(&mut self) -> &mut Self {
+        for region in &mut self.regions {
+            region.content = Spans::new();
+        }
+        self
+    }
+
+    /// Returns each line of the content of a region, wrapped to fit, with
+    /// its position relative to the template.
+    fn content_lines(region: &Region) -> impl Iterator<Item = (u32, u32, Spans)> + '_ {
+        let rect = region.rect;
+        let lines = region.content.lines(rect.width as usize, Overflow::Wrap);
+        (rect.y..rect.y + rect.height)
+            .zip(lines)
+            .map(move |(y, line)| (rect.x, y, line))
+    }
+
+    /// Draw the template onto the screen, with its top-left corner at the
+    /// given position, and each region's content in it.
+    ///
+    /// Afterwards, styling is reset and the colors are restored (see
+    /// [`crate::color`]).
+    pub fn draw(&self, x: u32, y: u32) {
+        profile!();
+        for (row, line) in (y..).zip(&self.lines) {
+            crate::run(line, x, row);
+        }
+
+        for region in &self.regions {
+            let rect = region.rect;
+            let blank = " ".repeat(rect.width as usize);
+            for row in rect.y..rect.y + rect.height {
+                crate::run(&blank, x + rect.x, y + row);
+            }
+
+            let rect = Rect::new(x + rect.x, y + rect.y, rect.width, rect.height);
+            region.content.draw(rect, Overflow::Wrap);
+        }
+    }
+
+    /// Draw the template onto a canvas. See [`Template::draw`].
+    ///
+    /// The text around the regions is drawn in the canvas' current style,
+    /// and each region's content in its own styles.
+    pub fn draw_on(&self, canvas: &mut Canvas, x: u32, y: u32) {
+        for (row, line) in (y..).zip(&self.lines) {
+            canvas.text(line, x, row);
+        }
+
+        let style = canvas.style();
+        for region in &self.regions {
+            let rect = region.rect;
+            canvas.erase(Rect::new(x + rect.x, y + rect.y, rect.width, rect.height));
+
+            for (col, row, line) in Self::content_lines(region) {
+                let mut col = x + col;
+                for span in line.spans() {
+                    canvas.set_style(span.style);
+                    canvas.text(&span.text, col, y + row);
+                    col += u32::try_from(span.width()).unwrap_or(u32::MAX);
+                }
+            }
+        }
+        canvas.set_style(style);
+    }
+}
+
+/// Returns the region described by the inside of a placeholder starting
+/// at a position, `width` columns wide including the braces.
+fn parse_placeholder(placeholder: &str, x: u32, y: u32, width: u32) -> Result<Region, ParseError> {
+    let (name, size) = match placeholder.split_once(':') {
+        Some((name, size)) => {
+            let size = size
+                .split_once('x')
+                .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+                .ok_or(ParseError::InvalidSize { x, y })?;
+            (name, size)
+        }
+        None => (placeholder, (width, 1)),
+    };
+
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ParseError::Unnamed { x, y });
+    }
+
+    Ok(Region {
+        name: name.to_string(),
+        rect: Rect::new(x, y, size.0, size.1),
+        content: Spans::new(),
+    })
+}
+
+/// An error in the syntax of a template. Positions are the column and line
+/// of the placeholder at fault.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A `{` had no matching `}` on its line.
+    Unclosed {
+        /// The column of the placeholder.
+        x: u32,
+        /// The line of the placeholder.
+        y: u32,
+    },
+    /// A placeholder had no name.
+    Unnamed {
+        /// The column of the placeholder.
+        x: u32,
+        /// The line of the placeholder.
+        y: u32,
+    },
+    /// A placeholder's size wasn't of the form `WIDTHxHEIGHT`.
+    InvalidSize {
+        /// The column of the placeholder.
+        x: u32,
+        /// The line of the placeholder.
+        y: u32,
+    },
+    /// Two placeholders had the same name.
+    Duplicate(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unclosed { x, y } => write!(f, "unclosed placeholder at {x},{y}"),
+            Self::Unnamed { x, y } => write!(f, "placeholder without a name at {x},{y}"),
+            Self::InvalidSize { x, y } => {
+                write!(f, "placeholder at {x},{y} has an invalid size")
+            }
+            Self::Duplicate(name) => write!(f, "more than one placeholder named `{name}`"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// An error encountered while loading a template.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file couldn't be read.
+    Io(io::Error),
+    /// The file's contents weren't a valid template.
+    Parse(ParseError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read template: {e}"),
+            Self::Parse(e) => write!(f, "failed to parse template: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ParseError> for LoadError {
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
@@ -3,7 +3,7 @@
 //! Only enabled on feature `crossterm`.
 
 /// The style of the cursor, used with [`set_cursor_style`].
-#[derive(Default, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CursorStyle {
     /// Default cursor shape configured by the user.
     #[default]
@@ -43,9 +43,17 @@ pub fn size() -> Option<(u32, u32)> {
 #[cfg(any(feature = "crossterm", doc))]
 #[allow(clippy::must_use_candidate)]
 pub fn size_or() -> (u32, u32) {
-    crossterm::terminal::size()
-        .map(|(cols, rows)| (u32::from(cols), u32::from(rows)))
-        .unwrap_or((80, 24))
+    crossterm::terminal::size().map_or((80, 24), |(cols, rows)| (u32::from(cols), u32::from(rows)))
+}
+
+/// Returns the terminal size in columns and rows, or 80x24 if it can't be
+/// found (as it never can be, without feature `crossterm`).
+pub(crate) fn size_or_default() -> (u32, u32) {
+    #[cfg(feature = "crossterm")]
+    let size = size_or();
+    #[cfg(not(feature = "crossterm"))]
+    let size = (80, 24);
+    size
 }
 
 /// Changes the cursor style.
@@ -55,29 +63,234 @@ pub fn size_or() -> (u32, u32) {
 /// If this fails to set the cursor style, panics with the message "failed to
 /// change cursor style".
 pub fn set_cursor_style(style: CursorStyle) {
+    profile!();
     match style {
-        CursorStyle::DefaultUserShape => print!("\x1b[0 q"),
-        CursorStyle::BlinkingBlock => print!("\x1b[1 q"),
-        CursorStyle::SteadyBlock => print!("\x1b[2 q"),
-        CursorStyle::BlinkingUnderScore => print!("\x1b[3 q"),
-        CursorStyle::SteadyUnderScore => print!("\x1b[4 q"),
-        CursorStyle::BlinkingBar => print!("\x1b[5 q"),
-        CursorStyle::SteadyBar => print!("\x1b[6 q"),
+        CursorStyle::DefaultUserShape => crate::escape("0 q"),
+        CursorStyle::BlinkingBlock => crate::escape("1 q"),
+        CursorStyle::SteadyBlock => crate::escape("2 q"),
+        CursorStyle::BlinkingUnderScore => crate::escape("3 q"),
+        CursorStyle::SteadyUnderScore => crate::escape("4 q"),
+        CursorStyle::BlinkingBar => crate::escape("5 q"),
+        CursorStyle::SteadyBar => crate::escape("6 q"),
+    }
+}
+
+/// A simple utility to guarantee the cursor style is restored.
+///
+/// Terminals can't report the current cursor style, so by default this
+/// restores [`CursorStyle::DefaultUserShape`]; if you know what the style was
+/// before, use [`CursorStyleGuard::set_restoring`].
+///
+/// Restores the cursor style when dropped (e.g. on program exit, or while
+/// unwinding from a panic).
+#[must_use = "does nothing unless stored, consider `let _guard = ...`"]
+#[derive(Debug, PartialEq, Eq)]
+pub struct CursorStyleGuard {
+    previous: CursorStyle,
+}
+
+impl CursorStyleGuard {
+    /// Creates a new guard, changing the cursor style in the process.
+    pub fn set(style: CursorStyle) -> Self {
+        Self::set_restoring(style, CursorStyle::DefaultUserShape)
+    }
+
+    /// Creates a new guard, changing the cursor style in the process. When
+    /// dropped, it restores `previous` instead of the user's default.
+    pub fn set_restoring(style: CursorStyle, previous: CursorStyle) -> Self {
+        set_cursor_style(style);
+        Self { previous }
+    }
+}
+
+impl Drop for CursorStyleGuard {
+    fn drop(&mut self) {
+        set_cursor_style(self.previous);
+    }
+}
+
+/// Returns true if the terminal is likely to honor [`request_resize`].
+///
+/// Terminals can't be asked whether they allow resizing, so this is a guess
+/// based on the environment: it's false if stdout isn't a terminal, inside
+/// `tmux` or `screen`, and in terminals known to ignore the request (the
+/// Linux console, Windows Terminal, and VS Code). Some terminals that
+/// support it have it turned off by default, e.g. xterm's `allowWindowOps`.
+///
+/// If cod's output isn't going to stdout (see [`target`](crate::target)),
+/// this is always true.
+#[must_use]
+pub fn supports_resize() -> bool {
+    use std::env::var_os;
+    use std::io::IsTerminal;
+
+    if !crate::target::is_stdout() {
+        return true;
+    }
+
+    let term = std::env::var("TERM").unwrap_or_default();
+    let term_program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+
+    std::io::stdout().is_terminal()
+        && var_os("TMUX").is_none()
+        && var_os("STY").is_none()
+        && var_os("WT_SESSION").is_none()
+        && !matches!(term.as_str(), "" | "dumb" | "linux")
+        && !term.starts_with("screen")
+        && !term.starts_with("tmux")
+        && term_program != "vscode"
+}
+
+/// Why [`request_resize`] didn't send a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResizeError {
+    /// The requested size had no columns or no rows.
+    Empty,
+    /// The terminal probably doesn't allow resizing; see
+    /// [`supports_resize`].
+    Unsupported,
+}
+
+impl std::fmt::Display for ResizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "cannot resize the terminal to zero columns or rows"),
+            Self::Unsupported => write!(f, "the terminal doesn't support resizing"),
+        }
     }
 }
 
+impl std::error::Error for ResizeError {}
+
+/// Asks the terminal to resize its window to the given number of columns and
+/// rows, using the XTWINOPS escape sequence.
+///
+/// The terminal resizes in its own time, if at all, so check [`size`] or
+/// wait for a resize event rather than assuming the new size.
+///
+/// ```no_run
+/// # use cod::term::{self, ResizeError};
+/// if let Err(ResizeError::Unsupported) = term::request_resize(80, 24) {
+///     eprintln!("please resize your terminal to 80x24");
+/// }
+/// ```
+///
+/// # Errors
+///
+/// If either dimension is zero, or [`supports_resize`] returns false,
+/// returns an error without sending anything.
+pub fn request_resize(cols: u32, rows: u32) -> Result<(), ResizeError> {
+    profile!();
+    if cols == 0 || rows == 0 {
+        return Err(ResizeError::Empty);
+    }
+    if !supports_resize() {
+        return Err(ResizeError::Unsupported);
+    }
+
+    crate::escape(format!("8;{rows};{cols}t"));
+    Ok(())
+}
+
+/// Asks the terminal for the size of a character cell in pixels, as width
+/// and height. Needed to line pictures drawn with sixel or kitty graphics up
+/// with the character grid.
+///
+/// The cell size is asked for directly with XTWINOPS (`CSI 16 t`); if the
+/// terminal doesn't answer that, it's worked out from the size of the whole
+/// text area (`CSI 14 t`) and [`size`]. The queries are followed by a
+/// request every terminal answers (`CSI c`), so this doesn't wait on a
+/// terminal that ignores them.
+///
+/// Returns `None` if stdin or stdout isn't a terminal, if cod's output isn't
+/// going to stdout (see [`target`](crate::target)), or if the terminal
+/// doesn't report a size. Any input typed while waiting on the answer is
+/// lost.
+///
+/// ```no_run
+/// let (width, height) = cod::term::cell_pixel_size().unwrap_or((8, 16));
+/// println!("scale pictures to multiples of {width}x{height}");
+/// ```
+///
+/// Only enabled on feature `crossterm`.
+#[cfg(any(feature = "crossterm", doc))]
+#[must_use]
+pub fn cell_pixel_size() -> Option<(u32, u32)> {
+    use std::io::{IsTerminal, Read, Write};
+
+    if !crate::target::is_stdout()
+        || !std::io::stdin().is_terminal()
+        || !std::io::stdout().is_terminal()
+    {
+        return None;
+    }
+
+    let was_raw = crossterm::terminal::is_raw_mode_enabled().ok()?;
+    if !was_raw {
+        crossterm::terminal::enable_raw_mode().ok()?;
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    let sent = stdout
+        .write_all(b"\x1b[16t\x1b[14t\x1b[c")
+        .and_then(|()| stdout.flush());
+    drop(stdout);
+
+    // read until the answer to `CSI c`, which comes last
+    let mut reply = Vec::new();
+    if sent.is_ok() {
+        for byte in std::io::stdin().lock().bytes() {
+            let Ok(byte) = byte else { break };
+            reply.push(byte);
+            if byte == b'c' && reply.windows(3).any(|w| w == b"\x1b[?") {
+                break;
+            }
+        }
+    }
+
+    if !was_raw {
+        crossterm::terminal::disable_raw_mode().ok()?;
+    }
+
+    parse_pixel_size(&reply, 6)
+        .or_else(|| {
+            let (width, height) = parse_pixel_size(&reply, 4)?;
+            let (cols, rows) = size()?;
+            (cols > 0 && rows > 0).then(|| (width / cols, height / rows))
+        })
+        .filter(|&(width, height)| width > 0 && height > 0)
+}
+
+/// Finds the answer to an XTWINOPS size query in what the terminal sent
+/// back, as width and height in pixels. Answers start with `kind`: 6 for a
+/// cell's size, 4 for the text area's.
+#[cfg(feature = "crossterm")]
+fn parse_pixel_size(reply: &[u8], kind: u32) -> Option<(u32, u32)> {
+    let reply = String::from_utf8_lossy(reply);
+    reply.split("\x1b[").skip(1).find_map(|answer| {
+        let (params, _) = answer.split_once('t')?;
+        let params: Vec<_> = params.split(';').map(str::parse::<u32>).collect();
+        match params[..] {
+            [Ok(k), Ok(height), Ok(width)] if k == kind => Some((width, height)),
+            _ => None,
+        }
+    })
+}
+
 /// Switch to the secondary screen.
 ///
 /// Use [`primary_screen`] to swap back.
 pub fn secondary_screen() {
-    print!("\x1b[?1049h");
+    profile!();
+    crate::escape("?1049h");
 }
 
 /// Switch to the primary (default) screen.
 ///
 /// Use [`secondary_screen`] to swap back.
 pub fn primary_screen() {
-    print!("\x1b[?1049l");
+    profile!();
+    crate::escape("?1049l");
 }
 
 /// Enable raw mode for the terminal.
@@ -0,0 +1,424 @@
+//! A session object owning the terminal, for larger programs.
+//!
+//! The free functions in cod are handy for quick scripts, but a full
+//! application has to remember to enter and leave the secondary screen and
+//! raw mode, reset colors, choose where output goes, and so on. A
+//! [`Terminal`] does all of that for you: it sets everything up when it's
+//! created and puts it back when it's dropped, even while unwinding from a
+//! panic.
+//!
+//! All of the usual drawing functions are available as methods, along with
+//! an off-screen [`Canvas`] the size of the terminal for flicker-free
+//! redraws.
+//!
+//! Example:
+//!
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! use cod::color::Color;
+//! use cod::Terminal;
+//!
+//! let mut t = Terminal::new()?;
+//! t.fg(Color::Ansi(2));
+//! t.circle('o', 20, 10, 5);
+//! t.normal();
+//! t.text("press any key", 0, 0);
+//! t.flush()?;
+//! # #[cfg(feature = "crossterm")]
+//! t.key();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io;
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::rect::Rect;
+use crate::target::{self, Target};
+use crate::term::ScreenGuard;
+use crate::NonOrthogonal;
+
+/// What the terminal a [`Terminal`] draws to can do, as far as can be told
+/// from the environment.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Capabilities {
+    /// Output is going to an interactive terminal.
+    pub interactive: bool,
+    /// The terminal supports 24-bit colors, according to `COLORTERM`.
+    pub truecolor: bool,
+    /// The terminal probably honors [`Terminal::request_resize`].
+    pub resize: bool,
+}
+
+impl Capabilities {
+    /// Guess the capabilities of the terminal output is currently going to.
+    #[must_use]
+    pub fn detect() -> Self {
+        use std::io::IsTerminal;
+
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        Self {
+            interactive: target::is_stdout() && io::stdout().is_terminal(),
+            truecolor: matches!(colorterm.as_str(), "truecolor" | "24bit"),
+            resize: crate::term::supports_resize(),
+        }
+    }
+}
+
+/// A drawing session on a terminal.
+///
+/// While it exists, a terminal session owns cod's output: it sets the
+/// [`target`] when created and restores the previous one when dropped. Only
+/// one should exist at a time.
+///
+/// When dropped, it resets colors and styles, leaves the secondary screen
+/// and raw mode if it entered them, and flushes.
+#[derive(Debug)]
+pub struct Terminal {
+    size: (u32, u32),
+    capabilities: Capabilities,
+    canvas: Canvas,
+
+    /// The target to restore when dropped.
+    previous: Option<Target>,
+    screen: Option<ScreenGuard>,
+    #[cfg(feature = "crossterm")]
+    raw: Option<crate::term::RawModeGuard>,
+}
+
+impl Terminal {
+    /// Start a session on stdout, switching to the secondary screen and
+    /// clearing it. On feature `crossterm`, also enters raw mode.
+    ///
+    /// # Errors
+    ///
+    /// If raw mode can't be enabled, returns the error, leaving the terminal
+    /// as it was.
+    pub fn new() -> io::Result<Self> {
+        let mut terminal = Self::with_target(Target::Stdout);
+
+        #[cfg(feature = "crossterm")]
+        {
+            crossterm::terminal::enable_raw_mode()?;
+            terminal.raw = Some(crate::term::RawModeGuard);
+        }
+
+        terminal.screen = Some(ScreenGuard::enter());
+        terminal.clear();
+        Ok(terminal)
+    }
+
+    /// Start a session sending output to the given target, e.g. a
+    /// [`Target::writer`]. This doesn't switch screens or enter raw mode,
+    /// and assumes a size of 80x24 unless told otherwise with
+    /// [`Terminal::set_size`].
+    ///
+    /// ```rust
+    /// # use cod::target::Target;
+    /// # use cod::Terminal;
+    /// let mut t = Terminal::with_target(Target::Null);
+    /// t.text("hello", 0, 0);
+    /// t.set_size(40, 10);
+    /// assert_eq!(t.canvas().width(), 40);
+    /// ```
+    #[must_use]
+    pub fn with_target(target: Target) -> Self {
+        let stdout = matches!(target, Target::Stdout);
+        let previous = target::set(target);
+
+        #[cfg(feature = "crossterm")]
+        let size = if stdout {
+            crate::term::size_or()
+        } else {
+            (80, 24)
+        };
+        #[cfg(not(feature = "crossterm"))]
+        let size = {
+            let _ = stdout;
+            (80, 24)
+        };
+
+        Self {
+            size,
+            capabilities: Capabilities::detect(),
+            canvas: Canvas::new(size.0, size.1),
+            previous: Some(previous),
+            screen: None,
+            #[cfg(feature = "crossterm")]
+            raw: None,
+        }
+    }
+
+    /// Returns the size of the terminal in columns and rows, as of the last
+    /// time it was checked.
+    #[must_use]
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Set the size of the terminal, resizing the canvas to match if it
+    /// changed.
+    pub fn set_size(&mut self, cols: u32, rows: u32) {
+        if self.size != (cols, rows) {
+            self.size = (cols, rows);
+            self.canvas = Canvas::new(cols, rows);
+        }
+    }
+
+    /// Check the size of the terminal again, e.g. after a resize event.
+    /// Returns true if it changed.
+    ///
+    /// Only enabled on feature `crossterm`.
+    #[cfg(feature = "crossterm")]
+    pub fn refresh_size(&mut self) -> bool {
+        let before = self.size;
+        if let Some((cols, rows)) = crate::term::size() {
+            self.set_size(cols, rows);
+        }
+        self.size != before
+    }
+
+    /// Returns what the terminal can do, as detected when the session
+    /// started.
+    #[must_use]
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Returns a rectangle covering the whole terminal.
+    #[must_use]
+    pub fn area(&self) -> Rect {
+        Rect::new(0, 0, self.size.0, self.size.1)
+    }
+
+    /// Returns the off-screen canvas, the size of the terminal.
+    pub fn canvas(&mut self) -> &mut Canvas {
+        &mut self.canvas
+    }
+
+    /// Print the changes to the canvas since it was last presented.
+    pub fn present(&mut self) {
+        self.canvas.present(0, 0);
+    }
+
+    /// Clear the screen. Since this wipes out what the canvas last
+    /// presented, the next [`Terminal::present`] redraws all of it.
+    pub fn clear(&mut self) {
+        crate::clear::all();
+        self.canvas.invalidate();
+    }
+
+    /// Flush the output.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error writing to a [`Target::Writer`] since the
+    /// last flush, if any.
+    pub fn flush(&mut self) -> io::Result<()> {
+        crate::flush();
+        target::take_error().map_or(Ok(()), Err)
+    }
+
+    /// Set the foreground color. See [`Color::fg`].
+    pub fn fg<C: Into<Color>>(&mut self, color: C) {
+        color.into().fg();
+    }
+
+    /// Set the background color. See [`Color::bg`].
+    pub fn bg<C: Into<Color>>(&mut self, color: C) {
+        color.into().bg();
+    }
+
+    /// Disable all style and color attributes. See [`crate::normal`].
+    pub fn normal(&mut self) {
+        crate::normal();
+    }
+
+    /// Move the cursor. See [`crate::goto::pos`].
+    pub fn goto(&mut self, x: u32, y: u32) {
+        crate::goto::pos(x, y);
+    }
+
+    /// Draw a single character. See [`crate::pixel`].
+    pub fn pixel(&mut self, c: char, x: u32, y: u32) {
+        crate::pixel(c, x, y);
+    }
+
+    /// Draw a single character in a style. See [`crate::pixel_styled`].
+    pub fn pixel_styled(&mut self, c: char, x: u32, y: u32, style: crate::style::Style) {
+        crate::pixel_styled(c, x, y, style);
+    }
+
+    /// Draw text in a style. See [`crate::text_styled`].
+    pub fn text_styled<S: AsRef<str>>(&mut self, s: S, x: u32, y: u32, style: crate::style::Style) {
+        crate::text_styled(s, x, y, style);
+    }
+
+    /// Draw text. See [`crate::text`].
+    pub fn text<S: AsRef<str>>(&mut self, s: S, x: u32, y: u32) {
+        crate::text(s, x, y);
+    }
+
+    /// Draw a "texture". See [`crate::blit`].
+    pub fn blit<S: AsRef<str>>(&mut self, src: S, x: u32, y: u32) {
+        crate::blit(src, x, y);
+    }
+
+    /// Draw a line. See [`crate::line`].
+    pub fn line(&mut self, c: char, x1: u32, y1: u32, x2: u32, y2: u32) {
+        crate::line(c, x1, y1, x2, y2);
+    }
+
+    /// Draw a thick line. See [`crate::line_thick`].
+    pub fn line_thick(&mut self, c: char, x1: u32, y1: u32, x2: u32, y2: u32, thickness: u32) {
+        crate::line_thick(c, x1, y1, x2, y2, thickness);
+    }
+
+    /// Draw an orthogonal line. See [`crate::orth_line`].
+    ///
+    /// # Errors
+    ///
+    /// If the line isn't orthogonal, returns [`NonOrthogonal`].
+    pub fn orth_line(
+        &mut self,
+        c: char,
+        x1: u32,
+        y1: u32,
+        x2: u32,
+        y2: u32,
+    ) -> Result<(), NonOrthogonal> {
+        crate::orth_line(c, x1, y1, x2, y2)
+    }
+
+    /// Draw an orthogonal line, snapping it to the closest axis. See
+    /// [`crate::orth_line_snap`].
+    pub fn orth_line_snap(&mut self, c: char, x1: u32, y1: u32, x2: u32, y2: u32) {
+        crate::orth_line_snap(c, x1, y1, x2, y2);
+    }
+
+    /// Connect two points with an L-shaped line. See
+    /// [`crate::orth_line_elbow`].
+    pub fn orth_line_elbow(&mut self, c: char, corner: char, x1: u32, y1: u32, x2: u32, y2: u32) {
+        crate::orth_line_elbow(c, corner, x1, y1, x2, y2);
+    }
+
+    /// Draw a border around a rectangle, returning the area inside it. See
+    /// [`crate::rect::border`].
+    pub fn border(&mut self, rect: Rect) -> Rect {
+        crate::rect::border(rect)
+    }
+
+    /// Draw a route through some points with box-drawing lines. See
+    /// [`crate::rect::path`].
+    ///
+    /// # Errors
+    ///
+    /// If any segment of the path isn't orthogonal, returns an error.
+    pub fn path(
+        &mut self,
+        points: &[(u32, u32)],
+        style: crate::rect::BoxStyle,
+    ) -> Result<(), NonOrthogonal> {
+        crate::rect::path(points, style)
+    }
+
+    /// Draw a triangle. See [`crate::triangle`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn triangle(&mut self, c: char, x1: u32, y1: u32, x2: u32, y2: u32, x3: u32, y3: u32) {
+        crate::triangle(c, x1, y1, x2, y2, x3, y3);
+    }
+
+    /// Draw a filled triangle. See [`crate::triangle_fill`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn triangle_fill(&mut self, c: char, x1: u32, y1: u32, x2: u32, y2: u32, x3: u32, y3: u32) {
+        crate::triangle_fill(c, x1, y1, x2, y2, x3, y3);
+    }
+
+    /// Draw the outline of a closed shape. See [`crate::polygon`].
+    pub fn polygon(&mut self, c: char, points: &[(u32, u32)]) {
+        crate::polygon(c, points);
+    }
+
+    /// Draw a filled closed shape. See [`crate::polygon_fill`].
+    pub fn polygon_fill(&mut self, c: char, points: &[(u32, u32)]) {
+        crate::polygon_fill(c, points);
+    }
+
+    /// Draw a circle. See [`crate::circle`].
+    pub fn circle(&mut self, c: char, cx: u32, cy: u32, r: u32) {
+        crate::circle(c, cx, cy, r);
+    }
+
+    /// Draw a filled circle. See [`crate::circle_fill`].
+    pub fn circle_fill(&mut self, c: char, cx: u32, cy: u32, r: u32) {
+        crate::circle_fill(c, cx, cy, r);
+    }
+
+    /// Draw an ellipse. See [`crate::ellipse`].
+    pub fn ellipse(&mut self, c: char, cx: u32, cy: u32, rx: u32, ry: u32) {
+        crate::ellipse(c, cx, cy, rx, ry);
+    }
+
+    /// Draw a filled ellipse. See [`crate::ellipse_fill`].
+    pub fn ellipse_fill(&mut self, c: char, cx: u32, cy: u32, rx: u32, ry: u32) {
+        crate::ellipse_fill(c, cx, cy, rx, ry);
+    }
+
+    /// Ask the terminal to resize. See [`crate::term::request_resize`].
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::term::request_resize`].
+    pub fn request_resize(&mut self, cols: u32, rows: u32) -> Result<(), crate::term::ResizeError> {
+        crate::term::request_resize(cols, rows)
+    }
+
+    /// Wait for a key press. See [`crate::read::key`].
+    ///
+    /// Only enabled on feature `crossterm`.
+    #[cfg(feature = "crossterm")]
+    pub fn key(&mut self) -> Option<crate::read::KeyEvent> {
+        crate::read::key()
+    }
+
+    /// Wait for a mouse event. See [`crate::read::mouse`].
+    ///
+    /// Only enabled on feature `crossterm`.
+    #[cfg(feature = "crossterm")]
+    pub fn mouse(&mut self) -> Option<crate::read::MouseEvent> {
+        crate::read::mouse()
+    }
+
+    /// Read every event waiting, without blocking. See [`crate::read::drain`].
+    ///
+    /// Only enabled on feature `crossterm`.
+    #[cfg(feature = "crossterm")]
+    pub fn drain(&mut self) -> Vec<crate::read::Event> {
+        crate::read::drain()
+    }
+
+    /// Wait for a key press, discarding key repeats. See
+    /// [`crate::read::key_once`].
+    ///
+    /// Only enabled on feature `crossterm`.
+    #[cfg(feature = "crossterm")]
+    pub fn key_once(&mut self) -> Option<crate::read::KeyEvent> {
+        crate::read::key_once()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        crate::style::de::all();
+        crate::color::de::all();
+        self.screen.take();
+        #[cfg(feature = "crossterm")]
+        self.raw.take();
+        crate::flush();
+
+        if let Some(previous) = self.previous.take() {
+            target::set(previous);
+        }
+    }
+}
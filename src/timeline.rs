@@ -0,0 +1,287 @@
+//! Keyframed animations of position, color, and visibility.
+//!
+//! A [`Timeline`] describes how one thing on screen (a sprite, a widget, a
+//! piece of text) moves and changes over time. Each property gets keyframes
+//! at points in time, and the value between two keyframes is interpolated
+//! with the [`Easing`] of the later one. Advance the timeline with
+//! [`Timeline::tick`], then draw with its current values.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! # use std::time::Duration;
+//! use cod::color::Color;
+//! use cod::timeline::{Easing, Timeline};
+//!
+//! let secs = Duration::from_secs;
+//! let mut ship = Timeline::new();
+//! ship.position((0, 5), secs(0), Easing::Linear)
+//!     .position((40, 5), secs(2), Easing::EaseOut)
+//!     .color(Color::Rgb(255, 255, 255), secs(1), Easing::Linear)
+//!     .color(Color::Rgb(255, 0, 0), secs(2), Easing::Linear)
+//!     .visible(false, secs(3));
+//!
+//! ship.tick(secs(1));
+//! assert_eq!(ship.current_position(), Some((30, 5)));
+//!
+//! while !ship.is_finished() {
+//!     if ship.tick(Duration::from_millis(50)) && ship.is_visible() {
+//!         // redraw the ship
+//!     }
+//! #   break;
+//! }
+//! ```
+
+use std::time::Duration;
+
+use crate::color::Color;
+
+/// How a value moves between two keyframes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Easing {
+    /// At a constant speed.
+    #[default]
+    Linear,
+    /// Starting slow and speeding up.
+    EaseIn,
+    /// Starting fast and slowing down.
+    EaseOut,
+    /// Starting and ending slow.
+    EaseInOut,
+    /// Not at all until the keyframe is reached, then all at once.
+    Step,
+}
+
+impl Easing {
+    /// Map the fraction of time passed between two keyframes, from 0 to 1,
+    /// to the fraction of the way the value has moved.
+    #[must_use]
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t,
+            Self::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Self::EaseInOut if t < 0.5 => 2.0 * t * t,
+            Self::EaseInOut => 1.0 - 2.0 * (1.0 - t) * (1.0 - t),
+            Self::Step if t < 1.0 => 0.0,
+            Self::Step => 1.0,
+        }
+    }
+}
+
+/// A value that can be animated between keyframes.
+trait Lerp: Copy {
+    /// Returns the value a fraction `t` of the way from `self` to `other`.
+    fn lerp(self, other: Self, t: f64) -> Self;
+}
+
+impl Lerp for u32 {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn lerp(self, other: Self, t: f64) -> Self {
+        let (a, b) = (f64::from(self), f64::from(other));
+        (a + (b - a) * t).round().max(0.0) as u32
+    }
+}
+
+impl Lerp for (u32, u32) {
+    fn lerp(self, other: Self, t: f64) -> Self {
+        (self.0.lerp(other.0, t), self.1.lerp(other.1, t))
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(self, other: Self, t: f64) -> Self {
+        self.mix(other, t)
+    }
+}
+
+impl Lerp for bool {
+    fn lerp(self, other: Self, t: f64) -> Self {
+        if t >= 1.0 {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+/// The keyframes of a single property, sorted by time.
+#[derive(Debug, Clone, PartialEq)]
+struct Track<T> {
+    keys: Vec<(Duration, T, Easing)>,
+}
+
+impl<T> Default for Track<T> {
+    fn default() -> Self {
+        Self { keys: Vec::new() }
+    }
+}
+
+impl<T: Lerp> Track<T> {
+    /// Add a keyframe, replacing any at the same time.
+    fn insert(&mut self, at: Duration, value: T, easing: Easing) {
+        match self.keys.binary_search_by_key(&at, |&(t, _, _)| t) {
+            Ok(i) => self.keys[i] = (at, value, easing),
+            Err(i) => self.keys.insert(i, (at, value, easing)),
+        }
+    }
+
+    /// Returns the value at a point in time, if there are any keyframes.
+    fn at(&self, time: Duration) -> Option<T> {
+        let next = self.keys.partition_point(|&(t, _, _)| t <= time);
+        let Some(&(start, from, _)) = next.checked_sub(1).and_then(|i| self.keys.get(i)) else {
+            return self.keys.first().map(|&(_, v, _)| v);
+        };
+        let Some(&(end, to, easing)) = self.keys.get(next) else {
+            return Some(from);
+        };
+
+        let passed = time.saturating_sub(start).as_secs_f64();
+        let t = passed / end.saturating_sub(start).as_secs_f64();
+        Some(from.lerp(to, easing.apply(t)))
+    }
+
+    /// Returns the time of the last keyframe.
+    fn end(&self) -> Duration {
+        self.keys.last().map_or(Duration::ZERO, |&(t, _, _)| t)
+    }
+}
+
+/// A sequence of keyframed changes to one thing's position, color, and
+/// visibility.
+///
+/// Before a property's first keyframe, it has that keyframe's value; after
+/// its last, it keeps the last value. Properties without keyframes have no
+/// value, except visibility, which is visible by default.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Timeline {
+    position: Track<(u32, u32)>,
+    color: Track<Color>,
+    visible: Track<bool>,
+
+    elapsed: Duration,
+    looping: bool,
+}
+
+impl Timeline {
+    /// Create an empty timeline, at its start.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a keyframe moving to a position, replacing any position keyframe
+    /// at the same time.
+    pub fn position(&mut self, position: (u32, u32), at: Duration, easing: Easing) -> &mut Self {
+        self.position.insert(at, position, easing);
+        self
+    }
+
+    /// Add a keyframe changing to a color, replacing any color keyframe at
+    /// the same time. Colors are blended in RGB.
+    pub fn color(&mut self, color: Color, at: Duration, easing: Easing) -> &mut Self {
+        self.color.insert(at, color, easing);
+        self
+    }
+
+    /// Add a keyframe showing or hiding, replacing any visibility keyframe at
+    /// the same time. Visibility changes exactly at the keyframe.
+    pub fn visible(&mut self, visible: bool, at: Duration) -> &mut Self {
+        self.visible.insert(at, visible, Easing::Step);
+        self
+    }
+
+    /// Set whether the timeline starts over once it's finished.
+    pub fn looping(&mut self, looping: bool) -> &mut Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Returns the time of the last keyframe.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.position
+            .end()
+            .max(self.color.end())
+            .max(self.visible.end())
+    }
+
+    /// Returns how far into the timeline it is.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns true if the timeline is past its last keyframe. Looping
+    /// timelines never finish.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.elapsed >= self.duration()
+    }
+
+    /// Jump to a point in the timeline.
+    pub fn seek(&mut self, time: Duration) {
+        self.elapsed = time;
+        if self.looping && !self.duration().is_zero() {
+            let duration = self.duration().as_nanos();
+            let wrapped = self.elapsed.as_nanos() % duration;
+            self.elapsed = Duration::from_nanos(u64::try_from(wrapped).unwrap_or(0));
+        }
+    }
+
+    /// Advance the timeline, returning true if any property changed.
+    ///
+    /// With [reduced motion](crate::a11y::reduced_motion) on, jumps straight
+    /// to the last keyframe instead, and stays there even if looping.
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// use cod::timeline::{Easing, Timeline};
+    ///
+    /// cod::a11y::set_reduced_motion(true);
+    ///
+    /// let mut ship = Timeline::new();
+    /// ship.position((0, 5), Duration::ZERO, Easing::Linear)
+    ///     .position((40, 5), Duration::from_secs(2), Easing::Linear);
+    ///
+    /// ship.tick(Duration::from_millis(50));
+    /// assert_eq!(ship.current_position(), Some((40, 5)));
+    /// ```
+    pub fn tick(&mut self, dt: Duration) -> bool {
+        let before = self.state();
+        if crate::a11y::reduced_motion() {
+            self.elapsed = self.duration();
+        } else {
+            self.seek(self.elapsed.saturating_add(dt));
+        }
+        self.state() != before
+    }
+
+    fn state(&self) -> (Option<(u32, u32)>, Option<Color>, bool) {
+        (
+            self.current_position(),
+            self.current_color(),
+            self.is_visible(),
+        )
+    }
+
+    /// Returns the current position, if it has any keyframes.
+    #[must_use]
+    pub fn current_position(&self) -> Option<(u32, u32)> {
+        self.position.at(self.elapsed)
+    }
+
+    /// Returns the current color, if it has any keyframes.
+    #[must_use]
+    pub fn current_color(&self) -> Option<Color> {
+        self.color.at(self.elapsed)
+    }
+
+    /// Returns true if currently visible.
+    #[must_use]
+    pub fn is_visible(&self) -> bool {
+        self.visible.at(self.elapsed).unwrap_or(true)
+    }
+}
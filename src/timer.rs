@@ -0,0 +1,236 @@
+//! Stopwatches and countdowns that draw themselves in place.
+//!
+//! A [`Timer`] remembers where it's drawn; call [`Timer::tick`] as often as
+//! you like, and it only redraws when the displayed time changes. Colors can
+//! be set to change as the time passes thresholds, e.g. to turn a countdown
+//! red in its last ten seconds.
+//!
+//! Example:
+//!
+//! ```no_run
+//! # use cod::prelude::*;
+//! # use std::time::Duration;
+//! use cod::color::Color;
+//! use cod::timer::Timer;
+//!
+//! let mut timer = Timer::countdown(Duration::from_secs(25 * 60), 0, 0);
+//! timer
+//!     .color_at(Duration::from_secs(60), Color::Ansi(3))
+//!     .color_at(Duration::from_secs(10), Color::Ansi(1));
+//!
+//! while !timer.is_finished() {
+//!     if timer.tick() {
+//!         cod::flush();
+//!     }
+//!     std::thread::sleep(Duration::from_millis(50));
+//! }
+//! ```
+
+use std::time::{Duration, Instant};
+
+use crate::color::Color;
+use crate::rect::Rect;
+use crate::spans::{Overflow, Spans};
+use crate::style::Style;
+
+/// How a [`Timer`] displays time.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// `mm:ss`, or `h:mm:ss` past an hour.
+    #[default]
+    Clock,
+    /// `mm:ss.t`, or `h:mm:ss.t` past an hour.
+    Tenths,
+    /// `h:mm:ss`, always showing hours.
+    Hours,
+    /// Whole seconds, e.g. `42s`.
+    Seconds,
+}
+
+impl Format {
+    /// Format a duration.
+    #[must_use]
+    pub fn format(self, time: Duration) -> String {
+        let secs = time.as_secs();
+        let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+        let tenths = time.subsec_millis() / 100;
+
+        match self {
+            Self::Clock if h == 0 => format!("{m:02}:{s:02}"),
+            Self::Tenths if h == 0 => format!("{m:02}:{s:02}.{tenths}"),
+            Self::Clock | Self::Hours => format!("{h}:{m:02}:{s:02}"),
+            Self::Tenths => format!("{h}:{m:02}:{s:02}.{tenths}"),
+            Self::Seconds => format!("{secs}s"),
+        }
+    }
+}
+
+/// A stopwatch or countdown, drawn at a fixed position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timer {
+    x: u32,
+    y: u32,
+    countdown: Option<Duration>,
+    format: Format,
+    colors: Vec<(Duration, Color)>,
+
+    start: Instant,
+    paused: Option<Instant>,
+    shown: Option<(String, Option<Color>)>,
+}
+
+impl Timer {
+    fn new(countdown: Option<Duration>, x: u32, y: u32) -> Self {
+        Self {
+            x,
+            y,
+            countdown,
+            format: Format::default(),
+            colors: Vec::new(),
+            start: Instant::now(),
+            paused: None,
+            shown: None,
+        }
+    }
+
+    /// Create a stopwatch, counting up from zero, starting now.
+    #[must_use]
+    pub fn stopwatch(x: u32, y: u32) -> Self {
+        Self::new(None, x, y)
+    }
+
+    /// Create a countdown, counting down to zero, starting now.
+    #[must_use]
+    pub fn countdown(duration: Duration, x: u32, y: u32) -> Self {
+        Self::new(Some(duration), x, y)
+    }
+
+    /// Change how the time is displayed.
+    pub fn format(&mut self, format: Format) -> &mut Self {
+        self.format = format;
+        self
+    }
+
+    /// Draw in a color once the time passes a threshold: for a stopwatch,
+    /// once that much time has passed; for a countdown, once that little
+    /// time is left. If several thresholds have passed, the latest wins.
+    pub fn color_at(&mut self, threshold: Duration, color: Color) -> &mut Self {
+        self.colors.push((threshold, color));
+        self
+    }
+
+    /// Move the timer. It's drawn at the new position on the next tick;
+    /// the old position isn't cleared.
+    pub fn move_to(&mut self, x: u32, y: u32) {
+        self.x = x;
+        self.y = y;
+        self.shown = None;
+    }
+
+    /// Returns the time since the timer started, not counting pauses.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.paused
+            .unwrap_or_else(Instant::now)
+            .saturating_duration_since(self.start)
+    }
+
+    /// Returns the time left on a countdown, or `None` for a stopwatch.
+    #[must_use]
+    pub fn remaining(&self) -> Option<Duration> {
+        Some(self.countdown?.saturating_sub(self.elapsed()))
+    }
+
+    /// Returns true if the timer is a countdown that has reached zero.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.remaining() == Some(Duration::ZERO)
+    }
+
+    /// Stop the timer, until [`Timer::resume`] is called.
+    pub fn pause(&mut self) {
+        self.paused.get_or_insert_with(Instant::now);
+    }
+
+    /// Start a paused timer again.
+    pub fn resume(&mut self) {
+        if let Some(paused) = self.paused.take() {
+            self.start += paused.elapsed();
+        }
+    }
+
+    /// Returns true if the timer is paused.
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    /// Start the timer over from now, keeping it paused if it was.
+    pub fn reset(&mut self) {
+        let now = Instant::now();
+        self.start = now;
+        if self.paused.is_some() {
+            self.paused = Some(now);
+        }
+    }
+
+    /// Returns the time as it's displayed.
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.format
+            .format(self.remaining().unwrap_or_else(|| self.elapsed()))
+    }
+
+    /// Returns the color the time is currently drawn in, if any.
+    #[must_use]
+    pub fn color(&self) -> Option<Color> {
+        let elapsed = self.elapsed();
+        let remaining = self.countdown.map(|d| d.saturating_sub(elapsed));
+        let passed = |t: Duration| remaining.map_or(elapsed >= t, |r| r <= t);
+
+        let latest = self.colors.iter().filter(|&&(t, _)| passed(t));
+        if self.countdown.is_some() {
+            latest.min_by_key(|&&(t, _)| t)
+        } else {
+            latest.max_by_key(|&&(t, _)| t)
+        }
+        .map(|&(_, c)| c)
+    }
+
+    /// Redraw the timer if what it displays has changed, returning true if
+    /// it did. Like [`Spans::draw`], colors are restored afterwards.
+    pub fn tick(&mut self) -> bool {
+        profile!();
+        let shown = (self.text(), self.color());
+        if self.shown.as_ref() == Some(&shown) {
+            return false;
+        }
+
+        // pad with spaces to cover up longer text from before
+        let old_width = self
+            .shown
+            .as_ref()
+            .map_or(0, |(text, _)| text.chars().count());
+        let width = shown.0.chars().count().max(old_width);
+
+        let mut spans = Spans::new();
+        spans
+            .push(
+                shown.0.as_str(),
+                Style {
+                    fg: shown.1,
+                    ..Style::default()
+                },
+            )
+            .push(
+                " ".repeat(width - shown.0.chars().count()),
+                Style::default(),
+            );
+
+        let width = u32::try_from(width).unwrap_or(u32::MAX);
+        spans.draw(Rect::new(self.x, self.y, width, 1), Overflow::Truncate);
+
+        self.shown = Some(shown);
+        true
+    }
+}
@@ -0,0 +1,629 @@
+//! A minimal terminal emulator, turning a stream of text and escape
+//! sequences into cells on a [`Canvas`].
+//!
+//! Feed a [`Screen`] whatever a program printed (or whatever cod printed,
+//! via [`crate::target::capture`]), and it keeps track of what a terminal
+//! would show. Use [`render`] to do that in one go.
+//!
+//! Understands:
+//!
+//! - Printable text, wrapping at the right edge and scrolling at the bottom
+//! - Carriage returns, linefeeds, backspaces, and tabs
+//! - Cursor movement, saving, and restoring
+//! - Erasing, inserting, and deleting characters and lines
+//! - Scrolling, and scroll regions
+//! - SGR styling: bold, faint, italic, underline, strikethrough, reverse,
+//!   and 16, 256, and true-color colors
+//! - Hyperlinks (OSC 8) and window titles (OSC 0 and 2)
+//! - Showing and hiding the cursor, turning wrapping off, and the
+//!   alternate screen
+//!
+//! Anything else is skipped.
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! # compat::set_enabled(false);
+//! use cod::color::Color;
+//! use cod::vt::Screen;
+//!
+//! let mut screen = Screen::new(20, 3);
+//! screen.feed(b"\x1b[1;32mok\x1b[0m done\r\n");
+//! screen.feed(b"\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\");
+//!
+//! let canvas = screen.canvas();
+//! assert_eq!(canvas.get(0, 0).unwrap().style.fg, Some(Color::Green));
+//! assert_eq!(canvas.link_at(1, 1), Some("https://example.com"));
+//! assert_eq!(screen.cursor(), (4, 1));
+//!
+//! // or replay cod's own output
+//! let output = target::capture(|| cod::text("hello", 3, 1));
+//! let canvas = vt::render(&output, 20, 3);
+//! assert_eq!(canvas.get(3, 1).unwrap().c, 'h');
+//! ```
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::rect::Rect;
+use crate::style::Style;
+
+/// The longest OSC string kept; the rest is dropped.
+const MAX_OSC: usize = 4096;
+
+/// The longest CSI parameter string kept; the rest is dropped.
+const MAX_PARAMS: usize = 256;
+
+/// Where the parser is in an escape sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Ground,
+    /// After an ESC.
+    Escape,
+    /// After an ESC that takes one more byte, e.g. `ESC ( B`.
+    EscapeArg,
+    /// Inside a CSI sequence, collecting parameters.
+    Csi,
+    /// Inside an OSC string.
+    Osc,
+    /// After an ESC inside an OSC string, which ends it.
+    OscEscape,
+}
+
+/// The cursor's position and style, as saved by `ESC 7` or `CSI s`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Saved {
+    cursor: (u32, u32),
+    style: Style,
+    reverse: bool,
+}
+
+/// The screen of a terminal, updated by feeding it output.
+///
+/// Also implements [`std::io::Write`], so it can be written to like a
+/// terminal.
+#[derive(Debug, Clone)]
+pub struct Screen {
+    canvas: Canvas,
+    /// The main screen, while the alternate screen is shown.
+    main: Option<Canvas>,
+    /// The cursor's position. The column is past the right edge after
+    /// printing in the last column, until something else is printed.
+    cursor: (u32, u32),
+    saved: Option<Saved>,
+    style: Style,
+    reverse: bool,
+    link: Option<String>,
+    title: String,
+    /// The first and last rows that scroll.
+    margins: (u32, u32),
+    cursor_visible: bool,
+    wrap: bool,
+    state: State,
+    /// The parameters of a CSI sequence, or the contents of an OSC string.
+    params: Vec<u8>,
+    /// The start of a UTF-8 character split between two feeds.
+    utf8: Vec<u8>,
+}
+
+impl Screen {
+    /// Create a blank screen, with the cursor in the top-left corner.
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            canvas: Canvas::new(width, height),
+            main: None,
+            cursor: (0, 0),
+            saved: None,
+            style: Style::new(),
+            reverse: false,
+            link: None,
+            title: String::new(),
+            margins: (0, height.saturating_sub(1)),
+            cursor_visible: true,
+            wrap: true,
+            state: State::Ground,
+            params: Vec::new(),
+            utf8: Vec::new(),
+        }
+    }
+
+    /// Returns what's on the screen.
+    #[must_use]
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
+    }
+
+    /// Returns what's on the screen, consuming it.
+    #[must_use]
+    pub fn into_canvas(self) -> Canvas {
+        self.canvas
+    }
+
+    /// Returns the position of the cursor, always on the screen.
+    #[must_use]
+    pub fn cursor(&self) -> (u32, u32) {
+        let (x, y) = self.cursor;
+        (
+            x.min(self.canvas.width().saturating_sub(1)),
+            y.min(self.canvas.height().saturating_sub(1)),
+        )
+    }
+
+    /// Returns false if the cursor was hidden (with `CSI ? 25 l`).
+    #[must_use]
+    pub fn is_cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    /// Returns the window title last set, or an empty string.
+    #[must_use]
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Returns true if the alternate screen is shown, as full-screen
+    /// programs do.
+    #[must_use]
+    pub fn is_alternate(&self) -> bool {
+        self.main.is_some()
+    }
+
+    /// Change the size of the screen, keeping whatever still fits. Resets
+    /// the scroll region.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.canvas = resized(&self.canvas, width, height);
+        self.canvas.set_style(self.drawn_style());
+        self.main = self.main.as_ref().map(|main| resized(main, width, height));
+        self.margins = (0, height.saturating_sub(1));
+        self.cursor = self.cursor();
+    }
+
+    /// Interpret output, as a terminal would. Sequences and characters may
+    /// be split between calls.
+    ///
+    /// Out-of-range parameters are clamped to the screen, and overlong
+    /// sequences are cut short, so a misbehaving program can't make this
+    /// panic or use up memory.
+    ///
+    /// ```rust
+    /// use cod::vt::Screen;
+    ///
+    /// let mut screen = Screen::new(5, 1);
+    /// screen.feed(b"abc\x1b[2D\x1b[4294967295X");
+    /// assert_eq!(screen.canvas().get(0, 0).unwrap().c, 'a');
+    /// assert_eq!(screen.canvas().get(1, 0).unwrap().c, ' ');
+    /// ```
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            match self.state {
+                State::Ground => self.ground(byte),
+                State::Escape => self.escape(byte),
+                State::Csi => self.csi(byte),
+                State::Osc => match byte {
+                    0x07 => self.osc(),
+                    0x1b => self.state = State::OscEscape,
+                    _ if self.params.len() < MAX_OSC => self.params.push(byte),
+                    _ => {}
+                },
+                // ESC \ ends the string; any other ESC does too, in practice
+                State::OscEscape => self.osc(),
+                State::EscapeArg => self.state = State::Ground,
+            }
+        }
+    }
+
+    fn ground(&mut self, byte: u8) {
+        if byte >= 0x80 {
+            self.utf8.push(byte);
+            match std::str::from_utf8(&self.utf8) {
+                Ok(s) => {
+                    let c = s.chars().next().unwrap_or(char::REPLACEMENT_CHARACTER);
+                    self.utf8.clear();
+                    self.print(c);
+                }
+                Err(e) if e.error_len().is_some() => {
+                    self.utf8.clear();
+                    self.print(char::REPLACEMENT_CHARACTER);
+                }
+                // wait for the rest of the character
+                Err(_) => {}
+            }
+            return;
+        }
+
+        if !self.utf8.is_empty() {
+            self.utf8.clear();
+            self.print(char::REPLACEMENT_CHARACTER);
+        }
+
+        match byte {
+            0x1b => self.state = State::Escape,
+            b'\r' => self.cursor.0 = 0,
+            b'\n' | 0x0b | 0x0c => self.linefeed(),
+            0x08 => self.cursor.0 = self.cursor().0.saturating_sub(1),
+            b'\t' => {
+                let next = (self.cursor.0 / 8 + 1) * 8;
+                self.cursor.0 = next.min(self.canvas.width().saturating_sub(1));
+            }
+            0x20..=0x7e => self.print(char::from(byte)),
+            _ => {}
+        }
+    }
+
+    fn escape(&mut self, byte: u8) {
+        self.state = State::Ground;
+        match byte {
+            b'[' => {
+                self.params.clear();
+                self.state = State::Csi;
+            }
+            b']' => {
+                self.params.clear();
+                self.state = State::Osc;
+            }
+            b'(' | b')' | b'*' | b'+' | b'#' => self.state = State::EscapeArg,
+            b'7' => self.save(),
+            b'8' => self.restore(),
+            b'D' => self.linefeed(),
+            b'E' => {
+                self.cursor.0 = 0;
+                self.linefeed();
+            }
+            b'M' => self.reverse_linefeed(),
+            b'c' => *self = Self::new(self.canvas.width(), self.canvas.height()),
+            _ => {}
+        }
+    }
+
+    fn csi(&mut self, byte: u8) {
+        match byte {
+            0x20..=0x3f if self.params.len() < MAX_PARAMS => self.params.push(byte),
+            0x40..=0x7e => {
+                self.state = State::Ground;
+                let params = std::mem::take(&mut self.params);
+                let params = String::from_utf8_lossy(&params);
+                if let Some(params) = params.strip_prefix('?') {
+                    self.mode(byte, params);
+                } else if !params.starts_with(['<', '=', '>']) {
+                    self.dispatch(byte, &params);
+                }
+            }
+            0x1b => self.state = State::Escape,
+            _ => {}
+        }
+    }
+
+    /// Carry out an OSC string.
+    fn osc(&mut self) {
+        self.state = State::Ground;
+        let params = std::mem::take(&mut self.params);
+        let params = String::from_utf8_lossy(&params);
+        let Some((command, rest)) = params.split_once(';') else {
+            return;
+        };
+
+        match command {
+            "0" | "2" => self.title = rest.to_string(),
+            // `8;params;url`, where an empty url ends the link
+            "8" => {
+                let url = rest.split_once(';').map_or("", |(_, url)| url);
+                self.link = (!url.is_empty()).then(|| url.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    /// Set or reset DEC private modes (`CSI ? n h` and `CSI ? n l`).
+    fn mode(&mut self, action: u8, params: &str) {
+        let on = match action {
+            b'h' => true,
+            b'l' => false,
+            _ => return,
+        };
+
+        for param in params.split(';') {
+            match param {
+                "7" => self.wrap = on,
+                "25" => self.cursor_visible = on,
+                "47" | "1047" | "1049" => self.alternate(on, param == "1049"),
+                _ => {}
+            }
+        }
+    }
+
+    /// Switch to or from the alternate screen, saving and restoring the
+    /// cursor too if `save` is set.
+    fn alternate(&mut self, on: bool, save: bool) {
+        if on == self.is_alternate() {
+            return;
+        }
+
+        if on {
+            if save {
+                self.save();
+            }
+            let blank = Canvas::new(self.canvas.width(), self.canvas.height());
+            self.main = Some(std::mem::replace(&mut self.canvas, blank));
+        } else if let Some(main) = self.main.take() {
+            self.canvas = main;
+            if save {
+                self.restore();
+            }
+        }
+        self.canvas.set_style(self.drawn_style());
+    }
+
+    /// Carry out a CSI sequence.
+    fn dispatch(&mut self, action: u8, params: &str) {
+        let params: Vec<u32> = params.split(';').map(|p| p.parse().unwrap_or(0)).collect();
+        // the nth parameter, where 0 (or missing) means 1
+        let n = |i: usize| params.get(i).copied().filter(|&n| n > 0).unwrap_or(1);
+
+        let (x, y) = self.cursor();
+        let (width, height) = (self.canvas.width(), self.canvas.height());
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        match action {
+            b'A' => self.cursor = (x, y.saturating_sub(n(0))),
+            b'B' => self.cursor = (x, y.saturating_add(n(0))),
+            b'C' => self.cursor = (x.saturating_add(n(0)), y),
+            b'D' => self.cursor = (x.saturating_sub(n(0)), y),
+            b'E' => self.cursor = (0, y.saturating_add(n(0))),
+            b'F' => self.cursor = (0, y.saturating_sub(n(0))),
+            b'G' | b'`' => self.cursor = (n(0) - 1, y),
+            b'd' => self.cursor = (x, n(0) - 1),
+            b'H' | b'f' => self.cursor = (n(1) - 1, n(0) - 1),
+            b'J' => match params[0] {
+                0 => {
+                    self.erase(Rect::new(x, y, width - x, 1));
+                    self.erase(Rect::new(0, y + 1, width, height - y - 1));
+                }
+                1 => {
+                    self.erase(Rect::new(0, 0, width, y));
+                    self.erase(Rect::new(0, y, x + 1, 1));
+                }
+                _ => self.erase(self.canvas.bounds()),
+            },
+            b'K' => match params[0] {
+                0 => self.erase(Rect::new(x, y, width - x, 1)),
+                1 => self.erase(Rect::new(0, y, x + 1, 1)),
+                _ => self.erase(Rect::new(0, y, width, 1)),
+            },
+            b'X' => self.erase(Rect::new(x, y, n(0).min(width - x), 1)),
+            b'@' | b'P' => {
+                let count = n(0).min(width - x);
+                let rest = width - x - count;
+                if action == b'@' {
+                    self.canvas
+                        .copy_region(Rect::new(x, y, rest, 1), (x + count, y));
+                    self.erase(Rect::new(x, y, count, 1));
+                } else {
+                    self.canvas
+                        .copy_region(Rect::new(x + count, y, rest, 1), (x, y));
+                    self.erase(Rect::new(x + rest, y, count, 1));
+                }
+            }
+            b'L' | b'M' => {
+                let (top, bottom) = self.margins;
+                if (top..=bottom).contains(&y) {
+                    let region = Rect::new(0, y, width, bottom - y + 1);
+                    let rows = i32::try_from(n(0)).unwrap_or(i32::MAX);
+                    self.scroll(region, if action == b'L' { rows } else { -rows });
+                    self.cursor.0 = 0;
+                }
+            }
+            b'S' => self.scroll(self.region(), -i32::try_from(n(0)).unwrap_or(i32::MAX)),
+            b'T' => self.scroll(self.region(), i32::try_from(n(0)).unwrap_or(i32::MAX)),
+            b'r' => {
+                let bottom = params.get(1).copied().filter(|&n| n > 0).unwrap_or(height);
+                let (top, bottom) = (n(0) - 1, bottom.min(height) - 1);
+                if top < bottom {
+                    self.margins = (top, bottom);
+                    self.cursor = (0, 0);
+                }
+            }
+            b's' => self.save(),
+            b'u' => self.restore(),
+            b'm' => self.sgr(&params),
+            _ => {}
+        }
+
+        let (x, y) = self.cursor;
+        self.cursor = (x.min(width - 1), y.min(height - 1));
+    }
+
+    /// Apply SGR parameters to the current style.
+    fn sgr(&mut self, params: &[u32]) {
+        let mut params = params.iter().copied();
+        while let Some(param) = params.next() {
+            let style = &mut self.style;
+            match param {
+                0 => {
+                    *style = Style::new();
+                    self.reverse = false;
+                }
+                1 => style.bold = true,
+                2 => style.faint = true,
+                3 => style.italic = true,
+                4 => style.underline = true,
+                7 => self.reverse = true,
+                9 => style.strike = true,
+                22 => (style.bold, style.faint) = (false, false),
+                23 => style.italic = false,
+                24 => style.underline = false,
+                27 => self.reverse = false,
+                29 => style.strike = false,
+                30..=37 => style.fg = Some(ansi(param - 30)),
+                38 => style.fg = extended(&mut params),
+                39 => style.fg = None,
+                40..=47 => style.bg = Some(ansi(param - 40)),
+                48 => style.bg = extended(&mut params),
+                49 => style.bg = None,
+                90..=97 => style.fg = Some(ansi(param - 90 + 8)),
+                100..=107 => style.bg = Some(ansi(param - 100 + 8)),
+                _ => {}
+            }
+        }
+        self.canvas.set_style(self.drawn_style());
+    }
+
+    /// Returns the style text is drawn in, with reverse video applied.
+    fn drawn_style(&self) -> Style {
+        if self.reverse {
+            Style {
+                fg: Some(self.style.bg.unwrap_or(Color::Black)),
+                bg: Some(self.style.fg.unwrap_or(Color::White)),
+                ..self.style
+            }
+        } else {
+            self.style
+        }
+    }
+
+    fn save(&mut self) {
+        self.saved = Some(Saved {
+            cursor: self.cursor(),
+            style: self.style,
+            reverse: self.reverse,
+        });
+    }
+
+    fn restore(&mut self) {
+        let saved = self.saved.unwrap_or(Saved {
+            cursor: (0, 0),
+            style: Style::new(),
+            reverse: false,
+        });
+        (self.style, self.reverse) = (saved.style, saved.reverse);
+        self.cursor = saved.cursor;
+        self.cursor = self.cursor();
+        self.canvas.set_style(self.drawn_style());
+    }
+
+    /// Draw a character at the cursor, and move the cursor past it.
+    fn print(&mut self, c: char) {
+        let width = crate::width::columns(c);
+        if width == 0 || self.canvas.width() < width {
+            return;
+        }
+
+        if self.cursor.0 + width > self.canvas.width() {
+            if self.wrap {
+                self.cursor.0 = 0;
+                self.linefeed();
+            } else {
+                self.cursor.0 = self.canvas.width() - width;
+            }
+        }
+
+        let (x, y) = self.cursor;
+        self.canvas.pixel(c, x, y);
+        let cell = Rect::new(x, y, width, 1);
+        match &self.link {
+            Some(url) => self.canvas.link(cell, url),
+            None => self.canvas.unlink(cell),
+        }
+        self.cursor.0 += width;
+    }
+
+    /// Returns the rows that scroll.
+    fn region(&self) -> Rect {
+        let (top, bottom) = self.margins;
+        Rect::new(0, top, self.canvas.width(), bottom + 1 - top)
+    }
+
+    /// Move the cursor down a row, scrolling if it's at the bottom of the
+    /// scroll region.
+    fn linefeed(&mut self) {
+        let y = self.cursor.1;
+        if y == self.margins.1 {
+            self.scroll(self.region(), -1);
+        } else if y + 1 < self.canvas.height() {
+            self.cursor.1 += 1;
+        }
+    }
+
+    /// Move the cursor up a row, scrolling if it's at the top of the scroll
+    /// region.
+    fn reverse_linefeed(&mut self) {
+        let y = self.cursor.1;
+        if y == self.margins.0 {
+            self.scroll(self.region(), 1);
+        } else if y > 0 {
+            self.cursor.1 -= 1;
+        }
+    }
+
+    /// Scroll part of the screen, filling in blank rows in the current
+    /// background.
+    fn scroll(&mut self, region: Rect, dy: i32) {
+        let region = region.intersect(self.canvas.bounds());
+        self.canvas.scroll_region(region, dy);
+
+        let rows = dy.unsigned_abs().min(region.height);
+        let top = if dy > 0 {
+            region.y
+        } else {
+            region.y + region.height - rows
+        };
+        self.erase(Rect::new(0, top, region.width, rows));
+    }
+
+    /// Fill a region with spaces in the current background, and remove any
+    /// links from it.
+    fn erase(&mut self, rect: Rect) {
+        self.canvas.set_style(Style {
+            bg: self.drawn_style().bg,
+            ..Style::new()
+        });
+        self.canvas.fill(' ', rect);
+        self.canvas.unlink(rect);
+        self.canvas.set_style(self.drawn_style());
+    }
+}
+
+impl std::io::Write for Screen {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.feed(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Interpret output on a blank screen of the given size, returning what it
+/// shows. See [`Screen`].
+#[must_use]
+pub fn render(output: &str, width: u32, height: u32) -> Canvas {
+    let mut screen = Screen::new(width, height);
+    screen.feed(output.as_bytes());
+    screen.into_canvas()
+}
+
+/// Returns a copy of a canvas at a new size, keeping whatever still fits.
+fn resized(canvas: &Canvas, width: u32, height: u32) -> Canvas {
+    let mut new = Canvas::new(width, height);
+    new.stamp(canvas, 0, 0);
+    new
+}
+
+/// Returns one of the 16 basic colors.
+fn ansi(index: u32) -> Color {
+    Color::Ansi(u8::try_from(index).unwrap_or(0))
+}
+
+/// Reads the rest of a 256-color (`5;n`) or true-color (`2;r;g;b`) SGR
+/// color.
+fn extended(params: &mut impl Iterator<Item = u32>) -> Option<Color> {
+    let mut next = || u8::try_from(params.next()?).ok();
+    match next()? {
+        5 => next().map(Color::Ansi),
+        2 => Some(Color::Rgb(next()?, next()?, next()?)),
+        _ => None,
+    }
+}
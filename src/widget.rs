@@ -0,0 +1,471 @@
+//! A retained tree of widgets, laid out automatically and redrawn only when
+//! they change.
+//!
+//! Each node in a [`Tree`] has a [`Constraint`] describing how much of its
+//! parent's area it takes, and optionally a [`Widget`] that draws into that
+//! area. A node's children split its area in its [`Direction`].
+//!
+//! Events are passed to widgets with [`Tree::update`]; any widget that says
+//! it changed is marked dirty, and the next [`Tree::render`] redraws only it
+//! (and whatever is inside it) onto a [`Canvas`].
+//!
+//! Example:
+//!
+//! ```rust
+//! # use cod::prelude::*;
+//! # use cod::canvas::Canvas;
+//! # use cod::rect::Rect;
+//! # use cod::widget::{Constraint, Direction, Tree, Widget};
+//! struct Counter(u32);
+//!
+//! impl Widget<char> for Counter {
+//!     fn update(&mut self, event: &char) -> bool {
+//!         if *event == '+' {
+//!             self.0 += 1;
+//!             true
+//!         } else {
+//!             false
+//!         }
+//!     }
+//!
+//!     fn render(&self, canvas: &mut Canvas, area: Rect) {
+//!         canvas.text(self.0.to_string(), area.x, area.y);
+//!     }
+//! }
+//!
+//! let mut tree = Tree::new(Direction::Horizontal);
+//! let root = tree.root();
+//! tree.add(root, Constraint::Length(10), Counter(0));
+//! tree.add(root, Constraint::Fill(1), Counter(100));
+//!
+//! let mut canvas = Canvas::new(40, 1);
+//! tree.render(&mut canvas);
+//!
+//! tree.update(&'+');
+//! for area in tree.render(&mut canvas) {
+//!     canvas.draw_region(area, 0, 0);
+//! }
+//! # assert_eq!(canvas.snapshot(Rect::new(10, 0, 3, 1)), {
+//! #     let mut c = Canvas::new(40, 1);
+//! #     c.text("101", 10, 0);
+//! #     c.snapshot(Rect::new(10, 0, 3, 1))
+//! # });
+//! ```
+
+use crate::canvas::Canvas;
+use crate::rect::Rect;
+
+/// Something that can be drawn in a [`Tree`], reacting to events of type
+/// `E`.
+pub trait Widget<E> {
+    /// Handle an event, returning true if the widget needs to be redrawn.
+    ///
+    /// By default, ignores every event.
+    fn update(&mut self, event: &E) -> bool {
+        let _ = event;
+        false
+    }
+
+    /// Called when the widget gains or loses focus (see [`crate::focus`]),
+    /// returning true if the widget needs to be redrawn.
+    ///
+    /// By default, does nothing.
+    fn set_focused(&mut self, focused: bool) -> bool {
+        let _ = focused;
+        false
+    }
+
+    /// Draw the widget onto a canvas. The area has already been cleared;
+    /// widgets shouldn't draw outside of it.
+    fn render(&self, canvas: &mut Canvas, area: Rect);
+
+    /// Draw the widget onto a new canvas of the given size, to be
+    /// [stamped](Canvas::stamp) wherever it's needed without rendering it
+    /// again.
+    fn prerender(&self, width: u32, height: u32) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        let area = canvas.bounds();
+        self.render(&mut canvas, area);
+        canvas
+    }
+}
+
+/// How much of its parent's area a node takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Constraint {
+    /// A fixed number of columns or rows.
+    Length(u32),
+    /// A percentage of the parent's area.
+    Percent(u32),
+    /// A share of whatever's left after every [`Constraint::Length`] and
+    /// [`Constraint::Percent`], proportional to the given weight.
+    Fill(u32),
+}
+
+/// Which way a node's children are laid out.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Left to right.
+    Horizontal,
+    /// Top to bottom.
+    #[default]
+    Vertical,
+}
+
+/// Identifies a node in a [`Tree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+
+struct Node<E> {
+    widget: Option<Box<dyn Widget<E>>>,
+    constraint: Constraint,
+    direction: Direction,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    area: Rect,
+    dirty: bool,
+}
+
+/// A tree of widgets. See the [module-level documentation](self).
+pub struct Tree<E> {
+    nodes: Vec<Node<E>>,
+    bounds: Option<Rect>,
+}
+
+impl<E> std::fmt::Debug for Tree<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tree")
+            .field("nodes", &self.nodes.len())
+            .field("bounds", &self.bounds)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<E> Tree<E> {
+    /// Create a tree with an empty root node, which fills the whole canvas
+    /// and lays out its children in the given direction.
+    #[must_use]
+    pub fn new(direction: Direction) -> Self {
+        Self {
+            nodes: vec![Node {
+                widget: None,
+                constraint: Constraint::Fill(1),
+                direction,
+                parent: None,
+                children: Vec::new(),
+                area: Rect::default(),
+                dirty: true,
+            }],
+            bounds: None,
+        }
+    }
+
+    /// Returns the root node.
+    #[must_use]
+    pub const fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    fn push(&mut self, parent: NodeId, node: Node<E>) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(node);
+        self.nodes[parent.0].children.push(id);
+        self.bounds = None;
+        id
+    }
+
+    /// Add a widget as the last child of a node. Its own children are laid
+    /// out top to bottom; see [`Tree::set_direction`].
+    ///
+    /// # Panics
+    ///
+    /// If `parent` isn't from this tree, panics.
+    pub fn add<W: Widget<E> + 'static>(
+        &mut self,
+        parent: NodeId,
+        constraint: Constraint,
+        widget: W,
+    ) -> NodeId {
+        self.push(
+            parent,
+            Node {
+                widget: Some(Box::new(widget)),
+                constraint,
+                direction: Direction::default(),
+                parent: Some(parent),
+                children: Vec::new(),
+                area: Rect::default(),
+                dirty: true,
+            },
+        )
+    }
+
+    /// Add an empty node as the last child of a node, to group other nodes.
+    ///
+    /// # Panics
+    ///
+    /// If `parent` isn't from this tree, panics.
+    pub fn add_layout(
+        &mut self,
+        parent: NodeId,
+        constraint: Constraint,
+        direction: Direction,
+    ) -> NodeId {
+        self.push(
+            parent,
+            Node {
+                widget: None,
+                constraint,
+                direction,
+                parent: Some(parent),
+                children: Vec::new(),
+                area: Rect::default(),
+                dirty: true,
+            },
+        )
+    }
+
+    /// Change which way a node's children are laid out.
+    ///
+    /// # Panics
+    ///
+    /// If `id` isn't from this tree, panics.
+    pub fn set_direction(&mut self, id: NodeId, direction: Direction) {
+        self.nodes[id.0].direction = direction;
+        self.bounds = None;
+    }
+
+    /// Change how much of its parent's area a node takes.
+    ///
+    /// # Panics
+    ///
+    /// If `id` isn't from this tree, panics.
+    pub fn set_constraint(&mut self, id: NodeId, constraint: Constraint) {
+        self.nodes[id.0].constraint = constraint;
+        self.bounds = None;
+    }
+
+    /// Returns the parent of a node, or `None` for the root.
+    ///
+    /// # Panics
+    ///
+    /// If `id` isn't from this tree, panics.
+    #[must_use]
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id.0].parent
+    }
+
+    /// Returns the children of a node, in order.
+    ///
+    /// # Panics
+    ///
+    /// If `id` isn't from this tree, panics.
+    #[must_use]
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.nodes[id.0].children
+    }
+
+    /// Returns the area a node was given by the last [`Tree::render`].
+    ///
+    /// # Panics
+    ///
+    /// If `id` isn't from this tree, panics.
+    #[must_use]
+    pub fn area(&self, id: NodeId) -> Rect {
+        self.nodes[id.0].area
+    }
+
+    /// Returns a node's widget, if it has one.
+    ///
+    /// # Panics
+    ///
+    /// If `id` isn't from this tree, panics.
+    #[must_use]
+    pub fn widget(&self, id: NodeId) -> Option<&dyn Widget<E>> {
+        self.nodes[id.0].widget.as_deref()
+    }
+
+    /// Returns a node's widget, if it has one, and marks it dirty.
+    ///
+    /// # Panics
+    ///
+    /// If `id` isn't from this tree, panics.
+    pub fn widget_mut(&mut self, id: NodeId) -> Option<&mut (dyn Widget<E> + 'static)> {
+        let node = &mut self.nodes[id.0];
+        node.dirty = true;
+        node.widget.as_deref_mut()
+    }
+
+    /// Mark a node to be redrawn by the next [`Tree::render`].
+    ///
+    /// # Panics
+    ///
+    /// If `id` isn't from this tree, panics.
+    pub fn mark_dirty(&mut self, id: NodeId) {
+        self.nodes[id.0].dirty = true;
+    }
+
+    /// Returns true if a node will be redrawn by the next [`Tree::render`].
+    ///
+    /// # Panics
+    ///
+    /// If `id` isn't from this tree, panics.
+    #[must_use]
+    pub fn is_dirty(&self, id: NodeId) -> bool {
+        self.nodes[id.0].dirty
+    }
+
+    /// Pass an event to every widget, marking those that changed dirty.
+    pub fn update(&mut self, event: &E) {
+        for node in &mut self.nodes {
+            if let Some(widget) = &mut node.widget {
+                node.dirty |= widget.update(event);
+            }
+        }
+    }
+
+    /// Pass an event to a single widget, marking it dirty if it changed.
+    /// Returns true if it changed.
+    ///
+    /// # Panics
+    ///
+    /// If `id` isn't from this tree, panics.
+    pub fn update_node(&mut self, id: NodeId, event: &E) -> bool {
+        let node = &mut self.nodes[id.0];
+        let changed = node
+            .widget
+            .as_mut()
+            .is_some_and(|widget| widget.update(event));
+        node.dirty |= changed;
+        changed
+    }
+
+    /// Tell a node's widget it gained or lost focus, marking it dirty if it
+    /// wants to be redrawn.
+    pub(crate) fn set_focused(&mut self, id: NodeId, focused: bool) {
+        let node = &mut self.nodes[id.0];
+        if let Some(widget) = &mut node.widget {
+            node.dirty |= widget.set_focused(focused);
+        }
+    }
+
+    /// Redraw every dirty node, and everything inside them, onto a canvas.
+    /// Returns the areas of the canvas that were redrawn.
+    ///
+    /// The root node fills the whole canvas. If the canvas' size or the
+    /// tree's layout changed since the last render, everything is redrawn.
+    pub fn render(&mut self, canvas: &mut Canvas) -> Vec<Rect> {
+        profile!();
+        if self.bounds != Some(canvas.bounds()) {
+            self.bounds = Some(canvas.bounds());
+            self.layout(self.root(), canvas.bounds());
+            self.nodes[0].dirty = true;
+        }
+
+        let mut redrawn = Vec::new();
+        let mut stack = vec![self.root()];
+        while let Some(id) = stack.pop() {
+            if self.nodes[id.0].dirty {
+                let area = self.nodes[id.0].area;
+                canvas.erase(area);
+                self.draw(id, canvas);
+                redrawn.push(area);
+            } else {
+                stack.extend(self.nodes[id.0].children.iter().rev());
+            }
+        }
+
+        redrawn
+    }
+
+    /// Draw a node and everything inside it, clearing their dirty flags.
+    fn draw(&mut self, id: NodeId, canvas: &mut Canvas) {
+        let node = &mut self.nodes[id.0];
+        node.dirty = false;
+        if let Some(widget) = &node.widget {
+            widget.render(canvas, node.area);
+        }
+
+        for i in 0..self.nodes[id.0].children.len() {
+            let child = self.nodes[id.0].children[i];
+            self.draw(child, canvas);
+        }
+    }
+
+    /// Give a node an area, and split it among its children.
+    fn layout(&mut self, id: NodeId, area: Rect) {
+        self.nodes[id.0].area = area;
+
+        let direction = self.nodes[id.0].direction;
+        let total = match direction {
+            Direction::Horizontal => area.width,
+            Direction::Vertical => area.height,
+        };
+
+        let children = self.nodes[id.0].children.clone();
+        let constraints: Vec<_> = children
+            .iter()
+            .map(|child| self.nodes[child.0].constraint)
+            .collect();
+
+        let mut offset = 0;
+        for (child, size) in children.into_iter().zip(split(total, &constraints)) {
+            let area = match direction {
+                Direction::Horizontal => Rect::new(area.x + offset, area.y, size, area.height),
+                Direction::Vertical => Rect::new(area.x, area.y + offset, area.width, size),
+            };
+            offset += size;
+            self.layout(child, area);
+        }
+    }
+}
+
+/// Splits a length according to some constraints. Fixed sizes are handed out
+/// first, in order, until the length runs out; the rest is shared between
+/// [`Constraint::Fill`]s, with any remainder going to the last one.
+fn split(total: u32, constraints: &[Constraint]) -> Vec<u32> {
+    let mut left = total;
+    let mut sizes: Vec<u32> = constraints
+        .iter()
+        .map(|c| {
+            let size = match *c {
+                Constraint::Length(n) => n,
+                Constraint::Percent(p) => {
+                    u32::try_from(u64::from(total) * u64::from(p) / 100).unwrap_or(u32::MAX)
+                }
+                Constraint::Fill(_) => 0,
+            }
+            .min(left);
+            left -= size;
+            size
+        })
+        .collect();
+
+    let weights: u64 = constraints
+        .iter()
+        .map(|c| match *c {
+            Constraint::Fill(w) => u64::from(w),
+            _ => 0,
+        })
+        .sum();
+    if weights == 0 {
+        return sizes;
+    }
+
+    let mut last = None;
+    let mut shared = 0;
+    for (i, c) in constraints.iter().enumerate() {
+        if let Constraint::Fill(w) = *c {
+            let size = u64::from(left) * u64::from(w) / weights;
+            sizes[i] = u32::try_from(size).unwrap_or(u32::MAX);
+            shared += sizes[i];
+            last = Some(i);
+        }
+    }
+
+    if let Some(last) = last {
+        sizes[last] += left - shared;
+    }
+
+    sizes
+}
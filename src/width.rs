@@ -0,0 +1,269 @@
+//! How many columns of the terminal text takes up.
+//!
+//! Most characters take up one column, but CJK characters, fullwidth forms,
+//! and most emoji take up two, and combining marks (like the accent in
+//! `e\u{301}`) and other invisible characters take up none. Everything cod
+//! draws is laid out by these widths, so text after a wide character still
+//! lines up.
+//!
+//! Widths follow Unicode's East Asian Width property, and are built into
+//! cod rather than looked up, so they're close to what terminals do, but
+//! not exact for every character; terminals don't all agree anyway.
+//!
+//! With feature `graphemes`, text is split into grapheme clusters (see
+//! [`graphemes`]) rather than characters, so an emoji joined with ZWJs, or a
+//! letter with its accents, is drawn and measured as a single unit.
+//!
+//! Example:
+//!
+//! ```
+//! # use cod::prelude::*;
+//! assert_eq!(width::char_width('a'), 1);
+//! assert_eq!(width::char_width('猫'), 2);
+//! assert_eq!(width::char_width('\u{301}'), 0);
+//! assert_eq!(width::str_width("日本語 text"), 11);
+//! ```
+
+/// Ranges of characters two columns wide.
+const WIDE: &[(u32, u32)] = &[
+    (0x1100, 0x115f),
+    (0x231a, 0x231b),
+    (0x2329, 0x232a),
+    (0x23e9, 0x23ec),
+    (0x23f0, 0x23f0),
+    (0x23f3, 0x23f3),
+    (0x25fd, 0x25fe),
+    (0x2614, 0x2615),
+    (0x2648, 0x2653),
+    (0x267f, 0x267f),
+    (0x2693, 0x2693),
+    (0x26a1, 0x26a1),
+    (0x26aa, 0x26ab),
+    (0x26bd, 0x26be),
+    (0x26c4, 0x26c5),
+    (0x26ce, 0x26ce),
+    (0x26d4, 0x26d4),
+    (0x26ea, 0x26ea),
+    (0x26f2, 0x26f3),
+    (0x26f5, 0x26f5),
+    (0x26fa, 0x26fa),
+    (0x26fd, 0x26fd),
+    (0x2705, 0x2705),
+    (0x270a, 0x270b),
+    (0x2728, 0x2728),
+    (0x274c, 0x274c),
+    (0x274e, 0x274e),
+    (0x2753, 0x2755),
+    (0x2757, 0x2757),
+    (0x2795, 0x2797),
+    (0x27b0, 0x27b0),
+    (0x27bf, 0x27bf),
+    (0x2b1b, 0x2b1c),
+    (0x2b50, 0x2b50),
+    (0x2b55, 0x2b55),
+    (0x2e80, 0x3029),
+    (0x302e, 0x303e),
+    (0x3041, 0x3098),
+    (0x309b, 0x33ff),
+    (0x3400, 0x4dbf),
+    (0x4e00, 0xa4cf),
+    (0xa960, 0xa97f),
+    (0xac00, 0xd7a3),
+    (0xf900, 0xfaff),
+    (0xfe10, 0xfe19),
+    (0xfe30, 0xfe6f),
+    (0xff00, 0xff60),
+    (0xffe0, 0xffe6),
+    (0x16fe0, 0x16fe4),
+    (0x17000, 0x18cff),
+    (0x1b000, 0x1b2ff),
+    (0x1f004, 0x1f004),
+    (0x1f0cf, 0x1f0cf),
+    (0x1f18e, 0x1f18e),
+    (0x1f191, 0x1f19a),
+    (0x1f200, 0x1f251),
+    (0x1f300, 0x1f320),
+    (0x1f32d, 0x1f335),
+    (0x1f337, 0x1f37c),
+    (0x1f37e, 0x1f393),
+    (0x1f3a0, 0x1f3ca),
+    (0x1f3cf, 0x1f3d3),
+    (0x1f3e0, 0x1f3f0),
+    (0x1f3f4, 0x1f3f4),
+    (0x1f3f8, 0x1f43e),
+    (0x1f440, 0x1f440),
+    (0x1f442, 0x1f4fc),
+    (0x1f4ff, 0x1f53d),
+    (0x1f54b, 0x1f54e),
+    (0x1f550, 0x1f567),
+    (0x1f57a, 0x1f57a),
+    (0x1f595, 0x1f596),
+    (0x1f5a4, 0x1f5a4),
+    (0x1f5fb, 0x1f64f),
+    (0x1f680, 0x1f6c5),
+    (0x1f6cc, 0x1f6cc),
+    (0x1f6d0, 0x1f6d2),
+    (0x1f6d5, 0x1f6d7),
+    (0x1f6dc, 0x1f6df),
+    (0x1f6eb, 0x1f6ec),
+    (0x1f6f4, 0x1f6fc),
+    (0x1f7e0, 0x1f7eb),
+    (0x1f7f0, 0x1f7f0),
+    (0x1f90c, 0x1f93a),
+    (0x1f93c, 0x1f945),
+    (0x1f947, 0x1f9ff),
+    (0x1fa70, 0x1faff),
+    (0x20000, 0x2fffd),
+    (0x30000, 0x3fffd),
+];
+
+/// Ranges of characters that take up no columns: combining marks, joiners,
+/// variation selectors, and the like.
+const ZERO: &[(u32, u32)] = &[
+    (0x0300, 0x036f),
+    (0x0483, 0x0489),
+    (0x0591, 0x05bd),
+    (0x05bf, 0x05bf),
+    (0x05c1, 0x05c2),
+    (0x05c4, 0x05c5),
+    (0x05c7, 0x05c7),
+    (0x0610, 0x061a),
+    (0x064b, 0x065f),
+    (0x0670, 0x0670),
+    (0x06d6, 0x06dc),
+    (0x06df, 0x06e4),
+    (0x0900, 0x0902),
+    (0x093a, 0x093a),
+    (0x093c, 0x093c),
+    (0x0941, 0x0948),
+    (0x094d, 0x094d),
+    (0x0e31, 0x0e31),
+    (0x0e34, 0x0e3a),
+    (0x0e47, 0x0e4e),
+    (0x1160, 0x11ff),
+    (0x1ab0, 0x1aff),
+    (0x1dc0, 0x1dff),
+    (0x200b, 0x200f),
+    (0x2028, 0x202e),
+    (0x2060, 0x2064),
+    (0x20d0, 0x20ff),
+    (0x302a, 0x302d),
+    (0x3099, 0x309a),
+    (0xfe00, 0xfe0f),
+    (0xfe20, 0xfe2f),
+    (0xfeff, 0xfeff),
+    (0x1f3fb, 0x1f3ff),
+    (0xe0000, 0xe0fff),
+];
+
+/// Returns true if a character is in one of a sorted list of ranges.
+fn in_ranges(c: char, ranges: &[(u32, u32)]) -> bool {
+    let c = u32::from(c);
+    ranges
+        .binary_search_by(|&(start, end)| {
+            if end < c {
+                std::cmp::Ordering::Less
+            } else if start > c {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// Returns how many columns a character takes up: 0, 1, or 2. Control
+/// characters take up none.
+#[must_use]
+pub fn char_width(c: char) -> usize {
+    usize::from(width_of(c))
+}
+
+/// Returns [`char_width`] as a `u32`, for positions on screen.
+pub(crate) fn columns(c: char) -> u32 {
+    u32::from(width_of(c))
+}
+
+fn width_of(c: char) -> u8 {
+    if c.is_ascii() {
+        u8::from(!c.is_ascii_control())
+    } else if c.is_control() || in_ranges(c, ZERO) {
+        0
+    } else if in_ranges(c, WIDE) {
+        2
+    } else {
+        1
+    }
+}
+
+/// Split a string into the units cod draws it in: grapheme clusters with
+/// feature `graphemes`, or single characters without it.
+///
+/// A grapheme cluster is what a reader sees as one character, even if it's
+/// made of several, like `e\u{301}` (an `e` and a combining accent) or a
+/// family emoji made of people joined with ZWJs.
+///
+/// ```
+/// # use cod::prelude::*;
+/// let units: Vec<&str> = width::graphemes("e\u{301}!").collect();
+/// # #[cfg(feature = "graphemes")]
+/// assert_eq!(units, ["e\u{301}", "!"]);
+/// # #[cfg(not(feature = "graphemes"))]
+/// # assert_eq!(units, ["e", "\u{301}", "!"]);
+/// ```
+pub fn graphemes(s: &str) -> impl Iterator<Item = &str> {
+    #[cfg(feature = "graphemes")]
+    let units = unicode_segmentation::UnicodeSegmentation::graphemes(s, true);
+    #[cfg(not(feature = "graphemes"))]
+    let units = s.char_indices().map(|(i, c)| &s[i..i + c.len_utf8()]);
+    units
+}
+
+/// Returns how many columns a grapheme cluster takes up: the width of its
+/// first character that takes up any, since the rest are drawn on top of
+/// it.
+///
+/// ```
+/// # use cod::prelude::*;
+/// assert_eq!(width::grapheme_width("e\u{301}"), 1);
+/// assert_eq!(width::grapheme_width("👨\u{200d}👩\u{200d}👧"), 2);
+/// ```
+#[must_use]
+pub fn grapheme_width(g: &str) -> usize {
+    g.chars().map(char_width).find(|&w| w > 0).unwrap_or(0)
+}
+
+/// Returns [`grapheme_width`] as a `u32`, for positions on screen.
+pub(crate) fn grapheme_columns(g: &str) -> u32 {
+    g.chars().map(columns).find(|&w| w > 0).unwrap_or(0)
+}
+
+/// Returns how many columns a string takes up, i.e. the sum of the widths
+/// of its characters (or grapheme clusters, with feature `graphemes`).
+/// Doesn't take linebreaks into account.
+#[must_use]
+pub fn str_width(s: &str) -> usize {
+    graphemes(s).map(grapheme_width).sum()
+}
+
+/// Returns the longest start of a string that fits in `width` columns.
+/// A wide character that would only half fit is left out. With feature
+/// `graphemes`, grapheme clusters are never split.
+///
+/// ```
+/// # use cod::prelude::*;
+/// assert_eq!(width::truncate("hello", 3), "hel");
+/// assert_eq!(width::truncate("日本語", 5), "日本");
+/// ```
+#[must_use]
+pub fn truncate(s: &str, width: usize) -> &str {
+    let (mut used, mut end) = (0, 0);
+    for g in graphemes(s) {
+        used += grapheme_width(g);
+        if used > width {
+            break;
+        }
+        end += g.len();
+    }
+    &s[..end]
+}